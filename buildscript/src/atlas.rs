@@ -0,0 +1,83 @@
+use crate::models::Texture;
+
+/// A packed atlas, with the position of every texture in it.
+pub struct Atlas {
+    pub size: u32,
+    pub positions: Vec<(u32, u32)>,
+    pub rgba: Vec<u8>,
+}
+
+/// Packs `textures` into the smallest square power of two atlas that fits them, up to
+/// `max_size`, with the same skyline packer as `gooseboy::atlas::TextureAtlas::pack`.
+pub fn pack(textures: &[&Texture], padding: u32, max_size: u32) -> Option<Atlas> {
+    let area: u32 = textures
+        .iter()
+        .map(|texture| (texture.width + padding) * (texture.height + padding))
+        .sum();
+    let widest = textures
+        .iter()
+        .map(|texture| texture.width.max(texture.height) + padding)
+        .max()
+        .unwrap_or(1);
+
+    let mut order: Vec<usize> = (0..textures.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse((textures[i].height, textures[i].width)));
+
+    let mut size = widest.max(area.isqrt()).next_power_of_two();
+    while size <= max_size {
+        if let Some(positions) = place(textures, &order, padding, size) {
+            let mut rgba = vec![0; (size * size * 4) as usize];
+            for (texture, &(x, y)) in textures.iter().zip(&positions) {
+                let row = (texture.width * 4) as usize;
+                for (line, source) in texture.rgba.chunks_exact(row.max(1)).enumerate() {
+                    let start = (((y as usize + line) * size as usize) + x as usize) * 4;
+                    rgba[start..start + row].copy_from_slice(source);
+                }
+            }
+            return Some(Atlas {
+                size,
+                positions,
+                rgba,
+            });
+        }
+        size *= 2;
+    }
+    None
+}
+
+/// Places every texture in a `size`x`size` atlas, or returns `None` if they don't fit.
+fn place(
+    textures: &[&Texture],
+    order: &[usize],
+    padding: u32,
+    size: u32,
+) -> Option<Vec<(u32, u32)>> {
+    let mut skyline = vec![0; size as usize];
+    let mut positions = vec![(0, 0); textures.len()];
+    for &i in order {
+        let (width, height) = (textures[i].width as usize, textures[i].height as usize);
+        if width == 0 || height == 0 {
+            continue;
+        }
+        if width > size as usize {
+            return None;
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        for x in 0..=size as usize - width {
+            if x > 0 && skyline[x - 1] == skyline[x] {
+                continue;
+            }
+            let y = skyline[x..x + width].iter().copied().max().unwrap_or(0);
+            if y + height <= size as usize && best.is_none_or(|(_, best)| y < best) {
+                best = Some((x, y));
+            }
+        }
+
+        let (x, y) = best?;
+        let end = (x + width + padding as usize).min(size as usize);
+        skyline[x..end].fill(y + height + padding as usize);
+        positions[i] = (x as u32, y as u32);
+    }
+    Some(positions)
+}
@@ -1,8 +1,14 @@
 use std::fs;
 use std::fs::File;
-use std::io::{BufReader, Write};
+use std::io::{Cursor, Write};
+use std::path::Path;
 use std::process::Command;
 
+use gooseboy::texture::EncodedTexture;
+
+mod atlas;
+mod models;
+
 /// # Panics
 /// Panics if FFMPEG fails to convert the audio file
 pub fn convert_audio() {
@@ -44,6 +50,56 @@ pub fn convert_audio() {
 /// # Panics
 /// Panics if the image color type is unsupported
 pub fn convert_images() {
+    convert_images_ex(&ImageOptions::default());
+}
+
+/// Options for [`convert_images_ex`].
+#[derive(Clone, Copy, Debug)]
+pub struct ImageOptions {
+    /// Packs every image into a single `ATLAS` `gooseboy::atlas::TextureAtlas` instead of
+    /// a sprite per image, with an `AtlasRegion` const per image and an `ATLAS_REGIONS`
+    /// table of them by name.
+    pub atlas: bool,
+    /// The amount of empty pixels between the images in the atlas.
+    pub atlas_padding: u32,
+    /// The maximum width and height of the atlas.
+    pub max_atlas_size: u32,
+    /// How the images are compressed, see [`TextureCompression`].
+    pub compression: TextureCompression,
+}
+
+/// How [`convert_images_ex`] compresses the images.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureCompression {
+    /// Images are embedded as RGBA.
+    #[default]
+    None,
+    /// Images are embedded as a `gooseboy::texture::EncodedTexture` in the smallest format
+    /// that keeps every pixel, with a `{NAME}_TEXTURE` static next to every sprite.
+    Lossless,
+    /// Like [`TextureCompression::Lossless`], but RGB565 and RGBA4444 are used even if
+    /// they lose some precision.
+    Lossy,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            atlas: false,
+            atlas_padding: 1,
+            max_atlas_size: 4096,
+            compression: TextureCompression::default(),
+        }
+    }
+}
+
+/// Converts the PNGs in the images folder into RGBA, and generates `src/generated/sprites.rs`
+/// with a `LazyLock<Sprite>` for each of them, or a single atlas of them if
+/// [`ImageOptions::atlas`] is set.
+///
+/// # Panics
+/// Panics if the image color type is unsupported, or if the images don't fit in the atlas
+pub fn convert_images_ex(options: &ImageOptions) {
     println!("cargo:rerun-if-changed=images/");
 
     let img_dir = "images";
@@ -54,53 +110,280 @@ pub fn convert_images() {
     let mut f = File::create(format!("{gen_dir}/sprites.rs")).unwrap();
     writeln!(f, "// Auto-generated").unwrap();
     writeln!(f, "use std::sync::LazyLock;").unwrap();
-    writeln!(f, "use gooseboy::sprite::Sprite;\n").unwrap();
+    // A compressed atlas is only ever decoded into a sprite, never named.
+    if !options.atlas || options.compression == TextureCompression::None {
+        writeln!(f, "use gooseboy::sprite::Sprite;").unwrap();
+    }
+    if options.atlas {
+        writeln!(f, "use gooseboy::atlas::{{AtlasRegion, TextureAtlas}};").unwrap();
+    }
+    if options.compression != TextureCompression::None {
+        writeln!(f, "use gooseboy::texture::EncodedTexture;").unwrap();
+    }
+    writeln!(f).unwrap();
 
-    for entry in fs::read_dir(img_dir).unwrap() {
-        let path = entry.unwrap().path();
-        if path.extension().and_then(|s| s.to_str()) == Some("png") {
-            let file_stem = path.file_stem().unwrap().to_str().unwrap();
-            let const_name = file_stem.to_uppercase();
-            let out_dir = std::env::var("OUT_DIR").unwrap();
-            let out_bin = format!("{out_dir}/{file_stem}.bin");
-
-            let file = File::open(&path).unwrap();
-            let decoder = png::Decoder::new(BufReader::new(file));
-            let mut reader = decoder.read_info().unwrap();
-
-            let info = reader.info();
-            let width = info.width;
-            let height = info.height;
-            let color = info.color_type;
-
-            let mut buf = vec![0; reader.output_buffer_size().unwrap()];
-            let frame_info = reader.next_frame(&mut buf).unwrap();
-
-            let pixels = match color {
-                png::ColorType::Rgb => {
-                    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
-                    for chunk in buf[..frame_info.buffer_size()].chunks_exact(3) {
-                        rgba.extend_from_slice(&[chunk[0], chunk[1], chunk[2], 255]);
-                    }
-                    rgba
-                }
-                png::ColorType::Rgba => buf[..frame_info.buffer_size()].to_vec(),
-                _ => panic!("unsupported color type: {color:?}"),
-            };
-
-            std::fs::write(out_bin, pixels).unwrap();
+    let mut paths: Vec<_> = fs::read_dir(img_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("png"))
+        .collect();
+    if options.atlas {
+        paths.sort();
+        write_atlas(&mut f, &paths, options);
+        return;
+    }
+
+    for path in paths {
+        let file_stem = path.file_stem().unwrap().to_str().unwrap();
+        let const_name = file_stem.to_uppercase();
+        let texture = decode_png(&fs::read(&path).unwrap());
+        let sprite = write_texture(&mut f, &const_name, file_stem, &texture, options);
+
+        writeln!(f, "#[allow(dead_code)]").unwrap();
+        writeln!(
+            f,
+            "pub static {const_name}: LazyLock<Sprite> = LazyLock::new(|| {{\n{sprite}\n}});\n"
+        )
+        .unwrap();
+    }
+}
+
+/// Writes a texture into `OUT_DIR`, compressed as the options say, returning the code of a
+/// block making a `Sprite` out of it.
+///
+/// Compressed textures also get a `{const_name}_TEXTURE` static.
+fn write_texture(
+    f: &mut File,
+    const_name: &str,
+    file_stem: &str,
+    texture: &models::Texture,
+    options: &ImageOptions,
+) -> String {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let lossy = match options.compression {
+        TextureCompression::None => {
+            std::fs::write(
+                Path::new(&out_dir).join(format!("{file_stem}.bin")),
+                &texture.rgba,
+            )
+            .unwrap();
+            return format!(
+                "    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_stem}.bin\")); 
+    Sprite::new_blended({}, {}, data)",
+                texture.width, texture.height
+            );
+        }
+        TextureCompression::Lossless => false,
+        TextureCompression::Lossy => true,
+    };
+
+    let encoded = EncodedTexture::encode_best(texture.width, texture.height, &texture.rgba, lossy)
+        .unwrap_or_else(|e| panic!("failed to encode {file_stem}: {e}"));
+    std::fs::write(
+        Path::new(&out_dir).join(format!("{file_stem}.tex")),
+        encoded.to_bytes(),
+    )
+    .unwrap();
+    writeln!(f, "#[allow(dead_code)]").unwrap();
+    writeln!(
+        f,
+        "pub static {const_name}_TEXTURE: LazyLock<EncodedTexture> = LazyLock::new(|| {{
+    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_stem}.tex\"));
+    EncodedTexture::from_bytes(data).expect(\"{file_stem}.tex is malformed\")
+}});\n"
+    )
+    .unwrap();
+    format!("    {const_name}_TEXTURE.to_sprite().expect(\"{file_stem}.tex is malformed\")")
+}
+
+/// Packs the images into `atlas.bin` or `atlas.tex`, and writes the atlas and its' regions.
+fn write_atlas(f: &mut File, paths: &[std::path::PathBuf], options: &ImageOptions) {
+    let textures: Vec<_> = paths
+        .iter()
+        .map(|path| decode_png(&fs::read(path).unwrap()))
+        .collect();
+    let atlas = atlas::pack(
+        &textures.iter().collect::<Vec<_>>(),
+        options.atlas_padding,
+        options.max_atlas_size,
+    )
+    .unwrap_or_else(|| {
+        panic!(
+            "the images don't fit in a {0}x{0} atlas",
+            options.max_atlas_size
+        )
+    });
+
+    let mut table = String::new();
+    for ((path, texture), (x, y)) in paths.iter().zip(&textures).zip(atlas.positions) {
+        let file_stem = path.file_stem().unwrap().to_str().unwrap();
+        let const_name = file_stem.to_uppercase();
+        assert!(
+            !["ATLAS", "ATLAS_REGIONS", "ATLAS_TEXTURE"].contains(&const_name.as_str()),
+            "{} collides with the generated atlas",
+            path.display()
+        );
+
+        writeln!(f, "#[allow(dead_code)]").unwrap();
+        writeln!(
+            f,
+            "pub const {const_name}: AtlasRegion = AtlasRegion::new({x}, {y}, {}, {});\n",
+            texture.width, texture.height
+        )
+        .unwrap();
+        table.push_str(&format!("    (\"{file_stem}\", {const_name}),\n"));
+    }
+
+    writeln!(f, "#[allow(dead_code)]").unwrap();
+    writeln!(
+        f,
+        "pub const ATLAS_REGIONS: &[(&str, AtlasRegion)] = &[\n{table}];\n"
+    )
+    .unwrap();
+    let texture = models::Texture {
+        width: atlas.size,
+        height: atlas.size,
+        rgba: atlas.rgba,
+    };
+    let sprite = write_texture(f, "ATLAS", "atlas", &texture, options);
+    writeln!(f, "#[allow(dead_code)]").unwrap();
+    writeln!(
+        f,
+        "pub static ATLAS: LazyLock<TextureAtlas> = LazyLock::new(|| {{
+    let sprite = {{\n{sprite}\n    }};
+    let regions: Vec<AtlasRegion> = ATLAS_REGIONS.iter().map(|(_, region)| *region).collect();
+    TextureAtlas::from_regions(sprite, &regions)
+}});"
+    )
+    .unwrap();
+}
+
+/// Decodes a PNG into RGBA
+///
+/// # Panics
+/// Panics if the PNG is malformed or the image color type is unsupported
+fn decode_png(bytes: &[u8]) -> models::Texture {
+    let decoder = png::Decoder::new(Cursor::new(bytes));
+    let mut reader = decoder.read_info().unwrap();
+
+    let info = reader.info();
+    let width = info.width;
+    let height = info.height;
+    let color = info.color_type;
+
+    let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+    let frame_info = reader.next_frame(&mut buf).unwrap();
+
+    let rgba = match color {
+        png::ColorType::Rgb => {
+            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+            for chunk in buf[..frame_info.buffer_size()].as_chunks::<3>().0 {
+                rgba.extend_from_slice(&[chunk[0], chunk[1], chunk[2], 255]);
+            }
+            rgba
+        }
+        png::ColorType::Rgba => buf[..frame_info.buffer_size()].to_vec(),
+        _ => panic!("unsupported color type: {color:?}"),
+    };
+
+    models::Texture {
+        width,
+        height,
+        rgba,
+    }
+}
+
+/// Converts the OBJ (with MTL materials) and glTF files in the models folder into
+/// `gooseboy::mesh`'s binary mesh format, and generates `src/generated/models.rs`
+/// with a `LazyLock<Mesh>` for each of them.
+///
+/// Every model is merged into a single mesh, material colors become vertex colors,
+/// and the texture of the first textured material becomes a `<NAME>_TEXTURE` sprite.
+/// Skinned glTF models also get a `<NAME>_SKIN` `LazyLock<SkinnedMesh>`, with their
+/// skeleton and animations.
+///
+/// # Panics
+/// Panics if a model or one of its' textures fails to load
+pub fn convert_models() {
+    println!("cargo:rerun-if-changed=models/");
+
+    let model_dir = "models";
+    let gen_dir = "src/generated";
+    fs::create_dir_all(model_dir).unwrap();
+    fs::create_dir_all(gen_dir).unwrap();
+
+    let mut f = File::create(format!("{gen_dir}/models.rs")).unwrap();
+    writeln!(f, "// Auto-generated").unwrap();
+    writeln!(f, "use std::sync::LazyLock;").unwrap();
+    writeln!(f, "use gooseboy::mesh::Mesh;\n").unwrap();
+
+    let mut paths: Vec<_> = fs::read_dir(model_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let model = match path.extension().and_then(|s| s.to_str()) {
+            Some("obj") => models::load_obj(&path),
+            Some("gltf" | "glb") => models::load_gltf(&path),
+            _ => continue,
+        };
+
+        let file_stem = path.file_stem().unwrap().to_str().unwrap();
+        let const_name = file_stem.to_uppercase().replace(['-', ' ', '.'], "_");
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let mesh = model.to_mesh();
+        std::fs::write(
+            Path::new(&out_dir).join(format!("{file_stem}.mesh")),
+            mesh.to_bytes(),
+        )
+        .unwrap();
+
+        writeln!(f, "#[allow(dead_code)]").unwrap();
+        writeln!(
+            f,
+            "pub static {const_name}: LazyLock<Mesh> = LazyLock::new(|| {{
+    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_stem}.mesh\"));
+    Mesh::from_bytes(data).expect(\"{file_stem}.mesh is malformed\")
+}});\n"
+        )
+        .unwrap();
+
+        if let Some(skin) = model.skin {
+            std::fs::write(
+                Path::new(&out_dir).join(format!("{file_stem}.anim")),
+                skin.into_skinned_mesh(mesh).to_bytes(),
+            )
+            .unwrap();
+
+            writeln!(f, "#[allow(dead_code)]").unwrap();
+            writeln!(
+                f,
+                "pub static {const_name}_SKIN: LazyLock<gooseboy::animation::SkinnedMesh> = LazyLock::new(|| {{
+    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_stem}.anim\"));
+    gooseboy::animation::SkinnedMesh::from_bytes({const_name}.clone(), data)
+        .expect(\"{file_stem}.anim is malformed\")
+}});\n"
+            )
+            .unwrap();
+        }
+
+        if let Some(texture) = model.texture {
+            std::fs::write(
+                Path::new(&out_dir).join(format!("{file_stem}_texture.bin")),
+                texture.rgba,
+            )
+            .unwrap();
 
             writeln!(f, "#[allow(dead_code)]").unwrap();
             writeln!(
                 f,
-                "pub static {}: LazyLock<Sprite> = LazyLock::new(|| {{
-    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\")); 
-    Sprite::new_blended({}, {}, data)
+                "pub static {const_name}_TEXTURE: LazyLock<gooseboy::sprite::Sprite> = LazyLock::new(|| {{
+    let data = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_stem}_texture.bin\"));
+    gooseboy::sprite::Sprite::new_blended({}, {}, data)
 }});\n",
-                const_name,
-                format_args!("{}.bin", file_stem),
-                width,
-                height
+                texture.width, texture.height
             )
             .unwrap();
         }
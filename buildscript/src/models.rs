@@ -0,0 +1,565 @@
+use std::fs;
+use std::path::Path;
+
+use gooseboy::animation::{
+    self, Animation, Easing, Interpolate, Keyframe, SkinnedMesh, Track, TransformTrack,
+};
+use gooseboy::color::Color;
+use gooseboy::mesh::Mesh;
+use gooseboy::vek::{Quaternion, Transform};
+use gooseboy::{Mat4, Vec2, Vec3};
+
+use crate::decode_png;
+
+/// An RGBA texture, with its' width and height.
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// A triangulated model, with the texture of its' first textured material.
+#[derive(Default)]
+pub struct Model {
+    pub positions: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    pub colors: Option<Vec<[u8; 4]>>,
+    pub indices: Vec<u32>,
+    pub texture: Option<Texture>,
+    pub skin: Option<Skin>,
+}
+
+/// The skeleton, per-vertex weights and animations of a skinned model.
+pub struct Skin {
+    pub skeleton: animation::Skeleton,
+    pub vertex_joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+    pub animations: Vec<Animation>,
+}
+
+impl Skin {
+    /// Binds the skin to the mesh of its' model.
+    pub fn into_skinned_mesh(self, mesh: Mesh) -> SkinnedMesh {
+        SkinnedMesh {
+            mesh,
+            joints: self.vertex_joints,
+            weights: self.weights,
+            skeleton: self.skeleton,
+            animations: self.animations,
+        }
+    }
+}
+
+/// The 4 joints and weights of every vertex of a skinned part.
+type Weights = (Vec<[u16; 4]>, Vec<[f32; 4]>);
+
+/// A part of a model, before it's merged into the others.
+struct Part {
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    normals: Option<Vec<[f32; 3]>>,
+    colors: Option<Vec<[u8; 4]>>,
+    skin: Option<Weights>,
+    indices: Vec<u32>,
+}
+
+impl Model {
+    fn from_parts(parts: Vec<Part>, texture: Option<Texture>, skin: Option<Skin>) -> Self {
+        let has_normals = parts.iter().all(|part| part.normals.is_some());
+        let has_colors = parts.iter().any(|part| part.colors.is_some());
+        let mut model = Self {
+            normals: has_normals.then(Vec::new),
+            colors: has_colors.then(Vec::new),
+            texture,
+            skin,
+            ..Self::default()
+        };
+
+        for part in parts {
+            let offset = model.positions.len() as u32;
+            let count = part.positions.len();
+            model.positions.extend(part.positions);
+            model.uvs.extend(part.uvs);
+            if let (Some(normals), Some(part)) = (&mut model.normals, part.normals) {
+                normals.extend(part);
+            }
+            if let Some(colors) = &mut model.colors {
+                colors.extend(part.colors.unwrap_or_else(|| vec![[255; 4]; count]));
+            }
+            // Parts that aren't skinned get no weights, so they're never moved.
+            if let Some(skin) = &mut model.skin {
+                let (joints, weights) = part
+                    .skin
+                    .unwrap_or_else(|| (vec![[0; 4]; count], vec![[0.0; 4]; count]));
+                skin.vertex_joints.extend(joints);
+                skin.weights.extend(weights);
+            }
+            model
+                .indices
+                .extend(part.indices.iter().map(|index| index + offset));
+        }
+        model
+    }
+
+    /// Returns the model as a `Mesh`, to be encoded with `Mesh::to_bytes`.
+    pub fn to_mesh(&self) -> Mesh {
+        Mesh {
+            positions: self.positions.iter().copied().map(Vec3::from).collect(),
+            uvs: self.uvs.iter().copied().map(Vec2::from).collect(),
+            normals: self
+                .normals
+                .as_ref()
+                .map(|normals| normals.iter().copied().map(Vec3::from).collect()),
+            colors: self.colors.as_ref().map(|colors| {
+                colors
+                    .iter()
+                    .map(|&[r, g, b, a]| Color::new(r, g, b, a))
+                    .collect()
+            }),
+            indices: self.indices.clone(),
+        }
+    }
+}
+
+fn color_to_u8(color: [f32; 4]) -> [u8; 4] {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// Loads an OBJ file and its' MTL materials.
+///
+/// # Panics
+/// Panics if the OBJ file is malformed, or a material's texture isn't a PNG
+pub fn load_obj(path: &Path) -> Model {
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+        .unwrap_or_else(|e| panic!("failed to load {}: {e}", path.display()));
+    let materials = materials.unwrap_or_default();
+
+    let texture = materials
+        .iter()
+        .find_map(|material| material.diffuse_texture.as_ref())
+        .map(|texture| {
+            let texture_path = path.parent().unwrap().join(texture.replace('\\', "/"));
+            println!("cargo:rerun-if-changed={}", texture_path.display());
+            decode_png(&fs::read(&texture_path).unwrap())
+        });
+
+    let parts = models
+        .into_iter()
+        .map(|model| {
+            let mesh = model.mesh;
+            let count = mesh.positions.len() / 3;
+            let material = mesh.material_id.and_then(|id| materials.get(id));
+
+            let colors = if mesh.vertex_color.is_empty() {
+                material.and_then(|material| {
+                    let [r, g, b] = material.diffuse?;
+                    let a = material.dissolve.unwrap_or(1.0);
+                    Some(vec![color_to_u8([r, g, b, a]); count])
+                })
+            } else {
+                Some(
+                    mesh.vertex_color
+                        .as_chunks::<3>()
+                        .0
+                        .iter()
+                        .map(|&[r, g, b]| color_to_u8([r, g, b, 1.0]))
+                        .collect(),
+                )
+            };
+
+            Part {
+                positions: mesh.positions.as_chunks::<3>().0.to_vec(),
+                // OBJ UVs start at the bottom left, textures start at the top left.
+                uvs: if mesh.texcoords.is_empty() {
+                    vec![[0.0; 2]; count]
+                } else {
+                    mesh.texcoords
+                        .as_chunks::<2>()
+                        .0
+                        .iter()
+                        .map(|&[u, v]| [u, 1.0 - v])
+                        .collect()
+                },
+                normals: (!mesh.normals.is_empty())
+                    .then(|| mesh.normals.as_chunks::<3>().0.to_vec()),
+                colors,
+                skin: None,
+                indices: mesh.indices,
+            }
+        })
+        .collect();
+
+    Model::from_parts(parts, texture, None)
+}
+
+pub type Matrix = [[f32; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [[0.0; 4]; 4];
+    for (col, out) in out.iter_mut().enumerate() {
+        for (row, value) in out.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    out
+}
+
+fn transform(m: &Matrix, [x, y, z]: [f32; 3], w: f32) -> [f32; 3] {
+    let mut out = [0.0; 3];
+    for (row, value) in out.iter_mut().enumerate() {
+        *value = m[0][row] * x + m[1][row] * y + m[2][row] * z + m[3][row] * w;
+    }
+    out
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let len = (x * x + y * y + z * z).sqrt();
+    if len > 0.0 {
+        [x / len, y / len, z / len]
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Loads a glTF or GLB file's default scene, baking the node transforms into the vertices.
+///
+/// If the file has a skin, the meshes using it are kept in their bind pose along with
+/// the skeleton, their weights and the animations of the joints.
+///
+/// # Panics
+/// Panics if the file is malformed, uses embedded `data:` URIs or a texture isn't a PNG
+pub fn load_gltf(path: &Path) -> Model {
+    let gltf =
+        gltf::Gltf::open(path).unwrap_or_else(|e| panic!("failed to load {}: {e}", path.display()));
+    let dir = path.parent().unwrap();
+    let read_uri = |uri: &str| {
+        assert!(
+            !uri.starts_with("data:"),
+            "{}: embedded data URIs are unsupported",
+            path.display()
+        );
+        let uri_path = dir.join(uri);
+        println!("cargo:rerun-if-changed={}", uri_path.display());
+        fs::read(uri_path).unwrap()
+    };
+
+    let buffers: Vec<Vec<u8>> = gltf
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Bin => gltf.blob.clone().unwrap_or_default(),
+            gltf::buffer::Source::Uri(uri) => read_uri(uri),
+        })
+        .collect();
+
+    let skeleton = load_skeleton(&gltf, &buffers, path);
+    let mut parts = Vec::new();
+    let mut texture = None;
+    let mut stack: Vec<(gltf::Node, Matrix)> = gltf
+        .default_scene()
+        .or_else(|| gltf.scenes().next())
+        .map(|scene| {
+            scene
+                .nodes()
+                .map(|node| {
+                    let matrix = node.transform().matrix();
+                    (node, matrix)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    while let Some((node, matrix)) = stack.pop() {
+        for child in node.children() {
+            let child_matrix = mul(&matrix, &child.transform().matrix());
+            stack.push((child, child_matrix));
+        }
+        let Some(mesh) = node.mesh() else {
+            continue;
+        };
+        // Skinned meshes ignore their node's transform, the joints place them instead.
+        let skinned = skeleton.as_ref().is_some_and(|skeleton| {
+            node.skin()
+                .is_some_and(|skin| skin.index() == skeleton.skin)
+        });
+        let matrix = if skinned { IDENTITY } else { matrix };
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                println!(
+                    "cargo:warning={}: skipping a primitive that isn't triangles",
+                    path.display()
+                );
+                continue;
+            }
+
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let positions: Vec<[f32; 3]> = positions.map(|p| transform(&matrix, p, 1.0)).collect();
+            let count = positions.len();
+
+            let material = primitive.material();
+            let pbr = material.pbr_metallic_roughness();
+            let factor = pbr.base_color_factor();
+            let colors = reader.read_colors(0).map_or_else(
+                || (factor != [1.0; 4]).then(|| vec![color_to_u8(factor); count]),
+                |colors| {
+                    Some(
+                        colors
+                            .into_rgba_f32()
+                            .map(|c| color_to_u8([0, 1, 2, 3].map(|i| c[i] * factor[i])))
+                            .collect(),
+                    )
+                },
+            );
+
+            if texture.is_none()
+                && let Some(info) = pbr.base_color_texture()
+            {
+                let png = match info.texture().source().source() {
+                    gltf::image::Source::View { view, .. } => {
+                        let buffer = &buffers[view.buffer().index()];
+                        buffer[view.offset()..view.offset() + view.length()].to_vec()
+                    }
+                    gltf::image::Source::Uri { uri, .. } => read_uri(uri),
+                };
+                texture = Some(decode_png(&png));
+            }
+
+            parts.push(Part {
+                positions,
+                uvs: reader
+                    .read_tex_coords(0)
+                    .map_or_else(|| vec![[0.0; 2]; count], |uvs| uvs.into_f32().collect()),
+                normals: reader.read_normals().map(|normals| {
+                    normals
+                        .map(|n| normalize(transform(&matrix, n, 0.0)))
+                        .collect()
+                }),
+                colors,
+                skin: skeleton.as_ref().filter(|_| skinned).and_then(|skeleton| {
+                    let joints = reader.read_joints(0)?.into_u16().map(|joints| {
+                        joints.map(|joint| {
+                            skeleton.remap.get(usize::from(joint)).copied().unwrap_or(0)
+                        })
+                    });
+                    let weights = reader.read_weights(0)?.into_f32();
+                    Some((joints.collect(), weights.collect()))
+                }),
+                indices: reader.read_indices().map_or_else(
+                    || (0..count as u32).collect(),
+                    |indices| indices.into_u32().collect(),
+                ),
+            });
+        }
+    }
+
+    let skin = skeleton.map(|skeleton| Skin {
+        animations: load_animations(&gltf, &buffers, &skeleton, path),
+        skeleton: animation::Skeleton {
+            joints: skeleton.joints,
+            root: skeleton.root,
+        },
+        vertex_joints: Vec::new(),
+        weights: Vec::new(),
+    });
+    if skin.is_none() && gltf.animations().next().is_some() {
+        println!(
+            "cargo:warning={}: skipping the animations of a model without a skin",
+            path.display()
+        );
+    }
+    Model::from_parts(parts, texture, skin)
+}
+
+/// The skeleton of a glTF file's first skin.
+struct Skeleton {
+    /// The index of the skin.
+    skin: usize,
+    joints: Vec<animation::Joint>,
+    root: Mat4<f32>,
+    /// The joint of every joint in the skin, sorted so parents come first.
+    remap: Vec<u16>,
+    /// The node index of every joint.
+    nodes: Vec<usize>,
+}
+
+/// Loads the first skin of a glTF file, sorting its' joints so parents come before children.
+fn load_skeleton(gltf: &gltf::Gltf, buffers: &[Vec<u8>], path: &Path) -> Option<Skeleton> {
+    let skin = gltf.skins().next()?;
+    if gltf.skins().count() > 1 {
+        println!(
+            "cargo:warning={}: only the first skin is used, the others are static",
+            path.display()
+        );
+    }
+
+    let mut parents = vec![None; gltf.nodes().count()];
+    for node in gltf.nodes() {
+        for child in node.children() {
+            parents[child.index()] = Some(node.index());
+        }
+    }
+    let locals: Vec<Matrix> = gltf.nodes().map(|node| node.transform().matrix()).collect();
+    let ancestors = |node: usize| std::iter::successors(parents[node], |&node| parents[node]);
+
+    let nodes: Vec<gltf::Node> = skin.joints().collect();
+    let is_joint = |node: usize| nodes.iter().position(|joint| joint.index() == node);
+    let depth = |node: usize| ancestors(node).filter(|&a| is_joint(a).is_some()).count();
+
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    order.sort_by_key(|&i| depth(nodes[i].index()));
+    let mut remap = vec![0; nodes.len()];
+    for (joint, &i) in order.iter().enumerate() {
+        remap[i] = joint as u16;
+    }
+
+    let inverse_binds: Vec<Matrix> = skin
+        .reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice))
+        .read_inverse_bind_matrices()
+        .map_or_else(|| vec![IDENTITY; nodes.len()], Iterator::collect);
+
+    let joints = order
+        .iter()
+        .map(|&i| {
+            let node = &nodes[i];
+            let (t, r, s) = node.transform().decomposed();
+            animation::Joint {
+                name: node.name().unwrap_or_default().to_owned(),
+                parent: ancestors(node.index())
+                    .find_map(is_joint)
+                    .map(|parent| usize::from(remap[parent])),
+                rest: Transform {
+                    position: Vec3::from(t),
+                    orientation: Quaternion::from_xyzw(r[0], r[1], r[2], r[3]),
+                    scale: Vec3::from(s),
+                },
+                inverse_bind: Mat4::from_col_arrays(
+                    inverse_binds.get(i).copied().unwrap_or(IDENTITY),
+                ),
+            }
+        })
+        .collect();
+
+    // The nodes above the root joints, which the animations never move.
+    let root = order
+        .first()
+        .and_then(|&i| parents[nodes[i].index()])
+        .map_or(IDENTITY, |parent| {
+            std::iter::once(parent)
+                .chain(ancestors(parent))
+                .fold(IDENTITY, |child, node| mul(&locals[node], &child))
+        });
+
+    Some(Skeleton {
+        skin: skin.index(),
+        joints,
+        root: Mat4::from_col_arrays(root),
+        nodes: order.iter().map(|&i| nodes[i].index()).collect(),
+        remap,
+    })
+}
+
+/// Loads the animations of a glTF file's joints.
+fn load_animations(
+    gltf: &gltf::Gltf,
+    buffers: &[Vec<u8>],
+    skeleton: &Skeleton,
+    path: &Path,
+) -> Vec<Animation> {
+    use gltf::animation::{Interpolation, util::ReadOutputs};
+
+    let mut animations = Vec::new();
+    for (index, animation) in gltf.animations().enumerate() {
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let node = channel.target().node().index();
+            let Some(target) = skeleton.nodes.iter().position(|&joint| joint == node) else {
+                println!(
+                    "cargo:warning={}: skipping an animation channel of a node that isn't a joint",
+                    path.display()
+                );
+                continue;
+            };
+
+            let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let (Some(times), Some(outputs)) = (reader.read_inputs(), reader.read_outputs()) else {
+                continue;
+            };
+            let (property, values): (u8, Vec<f32>) = match outputs {
+                ReadOutputs::Translations(values) => (0, values.flatten().collect()),
+                ReadOutputs::Rotations(values) => (1, values.into_f32().flatten().collect()),
+                ReadOutputs::Scales(values) => (2, values.flatten().collect()),
+                ReadOutputs::MorphTargetWeights(_) => {
+                    println!(
+                        "cargo:warning={}: skipping an unsupported morph target animation",
+                        path.display()
+                    );
+                    continue;
+                }
+            };
+
+            let interpolation = channel.sampler().interpolation();
+            let values = if interpolation == Interpolation::CubicSpline {
+                // Keep the values, and drop the in and out tangents around them.
+                let size = if property == 1 { 4 } else { 3 };
+                values
+                    .chunks(size * 3)
+                    .flat_map(|key| key[size..size * 2].to_vec())
+                    .collect()
+            } else {
+                values
+            };
+            let easing = if interpolation == Interpolation::Step {
+                Easing::Step
+            } else {
+                Easing::Linear
+            };
+            let times: Vec<f32> = times.collect();
+            let mut track = TransformTrack::default();
+            match property {
+                0 => track.translation = keyframes(&times, &values, easing, Vec3::from),
+                1 => {
+                    track.rotation = keyframes(&times, &values, easing, |[x, y, z, w]| {
+                        Quaternion::from_xyzw(x, y, z, w)
+                    });
+                }
+                _ => track.scale = keyframes(&times, &values, easing, Vec3::from),
+            }
+            channels.push(animation::Channel { target, track });
+        }
+
+        animations.push(Animation::new(
+            animation
+                .name()
+                .map_or_else(|| format!("animation{index}"), str::to_owned),
+            channels,
+        ));
+    }
+    animations
+}
+
+/// Pairs the times of a channel with its' values, `N` floats each.
+fn keyframes<T: Interpolate, const N: usize>(
+    times: &[f32],
+    values: &[f32],
+    easing: Easing,
+    value: impl Fn([f32; N]) -> T,
+) -> Track<T> {
+    Track::new(
+        times
+            .iter()
+            .zip(values.as_chunks::<N>().0)
+            .map(|(&time, &values)| Keyframe::eased(time, value(values), easing))
+            .collect(),
+    )
+}
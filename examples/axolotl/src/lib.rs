@@ -4,15 +4,15 @@
 
 use std::sync::{LazyLock, Mutex};
 
-use glam::{Mat3, Vec2};
+use gooseboy::Vec2;
 use gooseboy::color::Color;
 use gooseboy::framebuffer::init_fb;
+use gooseboy::sprite::{BlitOptions, Filter};
 use gooseboy::text::{get_text_height, get_text_width};
 
-use crate::renderer::Command;
+use crate::renderer::{Command, Transform};
 
 pub mod renderer;
-pub mod transformer;
 
 mod sprites {
     include!("generated/sprites.rs");
@@ -36,13 +36,22 @@ fn main() {
     }
 }
 
-fn make_transform_for_object(angle: f32, pos: Vec2, size: Vec2) -> Mat3 {
-    let center = Vec2::new(size.x / 2.0, size.y / 2.0);
-    let t1 = Mat3::from_translation(-center);
-    let r = Mat3::from_angle(angle);
-    let t2 = Mat3::from_translation(center);
-    let translation = Mat3::from_translation(pos);
-    translation * (t2 * r * t1)
+fn make_transform_for_object(
+    angle: f32,
+    pos: Vec2<f32>,
+    size: Vec2<f32>,
+    filter: Filter,
+) -> Transform {
+    let center = size / 2.0;
+    Transform {
+        position: (pos + center).round().as_(),
+        options: BlitOptions {
+            rotation: angle,
+            origin: center,
+            filter,
+            ..BlitOptions::default()
+        },
+    }
 }
 
 #[gooseboy::update]
@@ -60,44 +69,55 @@ fn update(nano_time: i64) {
     r.clear(Color::BLACK);
     r.group("on top", -100, |r| {
         let sz = Vec2::new(100.0, 100.0);
-        let tx = make_transform_for_object(unsafe { ANGLE }, Vec2::new(50.0, 50.0), sz);
+        let tx =
+            make_transform_for_object(unsafe { ANGLE }, Vec2::new(50.0, 50.0), sz, Filter::Nearest);
         r.command(Command::Rect {
             transform: tx,
             size: sz,
             color: Color::GREEN,
-            resampling: transformer::Resample::Nearest,
         });
     });
     r.group("text sprite rect", 0, |r| {
         let text = "Hello, world!";
         #[allow(clippy::cast_precision_loss)]
         let text_sz = Vec2::new(get_text_width(text) as f32, get_text_height(text) as f32);
-        let tx = make_transform_for_object(unsafe { ANGLE }, Vec2::new(50.0, 50.0), text_sz);
+        let tx = make_transform_for_object(
+            unsafe { ANGLE },
+            Vec2::new(50.0, 50.0),
+            text_sz,
+            Filter::Nearest,
+        );
         r.command(Command::Text {
             transform: tx,
             text: text.to_owned(),
             color: Color::RED,
             max_width: None,
-            resampling: transformer::Resample::Nearest,
         });
 
         let sprite_sz = Vec2::new(100.0, 100.0);
-        let sprite_tx =
-            make_transform_for_object(unsafe { ANGLE }, Vec2::new(50.0, 50.0), sprite_sz);
+        let sprite_tx = make_transform_for_object(
+            unsafe { ANGLE },
+            Vec2::new(50.0, 50.0),
+            sprite_sz,
+            Filter::Bilinear,
+        );
         r.command(Command::Sprite {
             transform: sprite_tx,
             id: unsafe { SPRITE_ID },
             color: Color::WHITE,
-            resampling: transformer::Resample::Bilinear,
         });
 
         let rect_sz = Vec2::new(50.0, 50.0);
-        let rect_tx = make_transform_for_object(unsafe { ANGLE }, Vec2::new(50.0, 50.0), rect_sz);
+        let rect_tx = make_transform_for_object(
+            unsafe { ANGLE },
+            Vec2::new(50.0, 50.0),
+            rect_sz,
+            Filter::Nearest,
+        );
         r.command(Command::Rect {
             transform: rect_tx,
             size: rect_sz,
             color: Color::BLUE,
-            resampling: transformer::Resample::Nearest,
         });
     });
     r.flush();
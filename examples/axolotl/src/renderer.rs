@@ -1,21 +1,24 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_sign_loss)]
-use glam::{Mat3, Vec2};
 use gooseboy::{
+    Vec2,
     color::Color,
     framebuffer::{
         Surface, clear_surface, get_framebuffer_height, get_framebuffer_ptr,
         get_framebuffer_ptr_mut, get_framebuffer_width,
     },
     log, mem,
-    sprite::Sprite,
+    sprite::{BlitOptions, Sprite},
     text::{draw_text_wrapped_ex, get_text_height, get_text_width},
     unsafe_casts,
 };
 
-use crate::transformer::{self, Resample};
-
-type Transform = Mat3;
+/// Where a command is drawn, and how it's scaled, rotated and sampled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Transform {
+    pub position: Vec2<i32>,
+    pub options: BlitOptions,
+}
 
 pub enum Command {
     Clear {
@@ -26,19 +29,16 @@ pub enum Command {
         text: String,
         color: Color,
         max_width: Option<usize>,
-        resampling: Resample,
     },
     Sprite {
         transform: Transform,
         id: usize,
         color: Color,
-        resampling: Resample,
     },
     Rect {
         transform: Transform,
-        size: Vec2,
+        size: Vec2<f32>,
         color: Color,
-        resampling: Resample,
     },
     BeginGroup {
         label: Option<String>,
@@ -49,7 +49,7 @@ pub enum Command {
 
 pub struct AtlasEntry {
     id: usize,
-    surface: Surface,
+    sprite: Sprite,
 }
 
 #[derive(Default)]
@@ -73,9 +73,11 @@ impl Renderer {
 
     pub fn upload_sprite(&mut self, sprite: &Sprite) -> usize {
         let id = self.next_atlas_id();
+        let mut rgba = sprite.rgba.clone();
+        premultiply_rgba_inplace(&mut rgba);
         self.atlas.push(AtlasEntry {
             id,
-            surface: Surface::new(sprite.width, sprite.height, sprite.rgba.clone()),
+            sprite: Sprite::new_blended(sprite.width, sprite.height, &rgba),
         });
         id
     }
@@ -132,73 +134,60 @@ impl Renderer {
                 text,
                 color,
                 max_width,
-                resampling,
             } => {
                 let width = get_text_width(text.clone());
                 let height = get_text_height(text.clone());
                 let mut text_surface = Surface::new_empty(width, height);
                 draw_text_wrapped_ex(&mut text_surface, 0, 0, text, color, max_width);
-                let (out_width, out_height, off_x, off_y, transformed) =
-                    transformer::transform_rgba(
-                        &text_surface.rgba,
-                        width,
-                        height,
-                        transform,
-                        resampling,
-                        true,
-                    );
-                surface.blit_premultiplied_clipped(
-                    off_x,
-                    off_y,
-                    out_width,
-                    out_height,
-                    &transformed,
-                    true,
+                let text_sprite = Sprite {
+                    width,
+                    height,
+                    rgba: text_surface.rgba,
+                    blend: true,
+                };
+                text_sprite.blit_ex_to(
+                    surface,
+                    transform.position.x,
+                    transform.position.y,
+                    &transform.options,
                 );
             }
             Command::Sprite {
                 transform,
                 id,
                 color,
-                resampling,
             } => {
                 let entry = self.atlas.iter().find(|p| p.id == id).unwrap();
-                let (out_width, out_height, off_x, off_y, mut transformed) =
-                    transformer::transform_rgba(
-                        &entry.surface.rgba,
-                        entry.surface.width,
-                        entry.surface.height,
-                        transform,
-                        resampling,
-                        true,
-                    );
-                transformer::tint_rgba(&mut transformed, color);
-                surface.blit_premultiplied_clipped(
-                    off_x,
-                    off_y,
-                    out_width,
-                    out_height,
-                    &transformed,
-                    true,
+                let options = BlitOptions {
+                    tint: color,
+                    ..transform.options
+                };
+                entry.sprite.blit_ex_to(
+                    surface,
+                    transform.position.x,
+                    transform.position.y,
+                    &options,
                 );
             }
             Command::Rect {
                 transform,
                 size,
                 color,
-                resampling,
             } => {
-                let mut rect_surface = Surface::new_empty(size.x as usize, size.y as usize);
-                rect_surface.clear(color);
-                let (out_w, out_h, off_x, off_y, transformed) = transformer::transform_rgba(
-                    &rect_surface.rgba,
-                    rect_surface.width,
-                    rect_surface.height,
-                    transform,
-                    resampling,
-                    true,
+                // A single pixel, scaled up to the size of the rect.
+                let mut rgba = [color.r, color.g, color.b, color.a];
+                premultiply_rgba_inplace(&mut rgba);
+                let options = BlitOptions {
+                    scale: transform.options.scale * size,
+                    origin: transform.options.origin / size,
+                    ..transform.options
+                };
+                Sprite::new_blended(1, 1, &rgba).blit_ex_to(
+                    surface,
+                    transform.position.x,
+                    transform.position.y,
+                    &options,
                 );
-                surface.blit_premultiplied_clipped(off_x, off_y, out_w, out_h, &transformed, true);
             }
             Command::BeginGroup { label, layer } => {
                 if let Some(text) = label {
@@ -252,3 +241,18 @@ impl Renderer {
         surface
     }
 }
+
+fn premultiply_rgba_inplace(pixels: &mut [u8]) {
+    let chunks = pixels.as_chunks_mut::<4>().0;
+    for px in chunks {
+        let a = px[3];
+        if a == 255 {
+            continue;
+        }
+
+        let a32 = u32::from(a);
+        px[0] = ((u32::from(px[0]) * a32 + 127) / 255) as u8;
+        px[1] = ((u32::from(px[1]) * a32 + 127) / 255) as u8;
+        px[2] = ((u32::from(px[2]) * a32 + 127) / 255) as u8;
+    }
+}
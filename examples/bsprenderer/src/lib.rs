@@ -3,10 +3,13 @@
 use std::sync::LazyLock;
 
 use gooseboy::{
+    atlas::{AtlasRegion, DEFAULT_PADDING, TextureAtlas},
     color::Color,
     framebuffer::{clear_framebuffer, init_fb},
     gpu::{GpuCommandBuffer, PrimitiveType, gpu_read_value},
     input::grab_mouse,
+    log,
+    sprite::Sprite,
     text::draw_text_formatted,
 };
 
@@ -15,8 +18,6 @@ mod sprites {
 }
 
 static mut GLOBAL_BUFFER: LazyLock<GpuCommandBuffer> = LazyLock::new(GpuCommandBuffer::new);
-static mut ATLAS_BYTES: LazyLock<Vec<u8>> = LazyLock::new(Vec::new);
-static mut ATLAS_SIZE: LazyLock<(u32, u32)> = LazyLock::new(|| (0, 0));
 
 #[gooseboy::main]
 fn main() {
@@ -81,121 +82,93 @@ fn gpu_main() {
         embedded
     };
 
-    let atlas_w: u32 = embedded
+    let sprites: Vec<Sprite> = embedded
         .iter()
-        .map(|(_, img)| img.width())
-        .max()
-        .unwrap_or(1);
-    let atlas_h: u32 = embedded
-        .iter()
-        .map(|(_, img)| img.height())
-        .sum::<u32>()
-        .max(1);
-
-    let mut atlas_rgba: Vec<u8> = vec![0u8; (atlas_w * atlas_h * 4) as usize];
-    let mut atlas_positions: HashMap<String, (u32, u32, u32, u32)> = HashMap::new();
-
-    let mut cursor_y: u32 = 0;
-    for (name, img) in embedded {
-        let w = img.width();
-        let h = img.height();
-
-        for (x, y, pixel) in img.enumerate_pixels() {
-            let dst_x = x;
-            let dst_y = cursor_y + y;
-            let dst_idx = ((dst_y * atlas_w + dst_x) * 4) as usize;
-            atlas_rgba[dst_idx] = pixel[0];
-            atlas_rgba[dst_idx + 1] = pixel[1];
-            atlas_rgba[dst_idx + 2] = pixel[2];
-            atlas_rgba[dst_idx + 3] = 255u8;
-        }
-
-        atlas_positions.insert(name.clone(), (0, cursor_y, w, h));
-        cursor_y += h;
-    }
-
-    unsafe {
-        *ATLAS_BYTES = atlas_rgba;
-        *ATLAS_SIZE = (atlas_w, atlas_h);
-    }
+        .map(|(_, img)| {
+            let rgba: Vec<u8> = img
+                .pixels()
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+                .collect();
+            Sprite::new(img.width() as usize, img.height() as usize, &rgba)
+        })
+        .collect();
+    let Some((atlas, regions)) =
+        TextureAtlas::pack(&sprites.iter().collect::<Vec<_>>(), DEFAULT_PADDING, 4096)
+    else {
+        log!("the map's textures don't fit in an atlas");
+        return;
+    };
+    let atlas_positions: HashMap<String, AtlasRegion> = embedded
+        .into_iter()
+        .map(|(name, _)| name)
+        .zip(regions)
+        .collect();
 
-    let mut texidx_to_placement: HashMap<i32, (u32, u32, u32, u32)> = HashMap::new();
+    let mut texidx_to_placement: HashMap<i32, AtlasRegion> = HashMap::new();
     let fallback = atlas_positions
         .values()
         .next()
         .copied()
-        .unwrap_or((0u32, 0u32, 1u32, 1u32));
+        .unwrap_or(AtlasRegion::new(0, 0, 1, 1));
 
     for (i, opt_tex) in bsp.textures.iter().enumerate() {
         if let Some(tex) = opt_tex {
             let name = tex.header.name.to_string();
-            if let Some(&(px, py, w, h)) = atlas_positions.get(&name) {
-                texidx_to_placement.insert(i as i32, (px, py, w, h));
-            } else {
-                texidx_to_placement.insert(i as i32, fallback);
-            }
+            let region = atlas_positions.get(&name).copied().unwrap_or(fallback);
+            texidx_to_placement.insert(i as i32, region);
         }
     }
 
     let mut buffer = GpuCommandBuffer::new();
+    let texture = atlas.register(&mut buffer);
 
-    unsafe {
-        let atlas_ref: &Vec<u8> = &ATLAS_BYTES;
-        let (w, h) = *ATLAS_SIZE;
-        buffer.insert(&GpuCommand::RegisterTexture {
-            rgba: atlas_ref.as_slice(),
-            w,
-            h,
-        });
-    }
-
-    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Triangles));
-
-    for face in &bsp.faces {
-        let verts: Vec<qbsp::glam::Vec3> = face.vertices(&bsp).collect();
-        if verts.len() < 3 {
-            continue;
-        }
+    let map = buffer.record(PrimitiveType::Triangles, |buffer| {
+        for face in &bsp.faces {
+            let verts: Vec<qbsp::glam::Vec3> = face.vertices(&bsp).collect();
+            if verts.len() < 3 {
+                continue;
+            }
 
-        let tex_info = &bsp.tex_info[face.texture_info_idx.0 as usize];
-        let tex_idx = tex_info.texture_idx.0.unwrap_or(0).cast_signed();
+            let tex_info = &bsp.tex_info[face.texture_info_idx.0 as usize];
+            let tex_idx = tex_info.texture_idx.0.unwrap_or(0).cast_signed();
 
-        let (px, py, tw, th) = texidx_to_placement
-            .get(&tex_idx)
-            .copied()
-            .unwrap_or(fallback);
-        let tex_w = tw.max(1);
-        let tex_h = th.max(1);
+            let region = texidx_to_placement
+                .get(&tex_idx)
+                .copied()
+                .unwrap_or(fallback);
+            let tex_w = region.width.max(1);
+            let tex_h = region.height.max(1);
 
-        let proj = tex_info.projection;
+            let proj = tex_info.projection;
 
-        for tri in triangulate_fan(verts) {
-            for v in &tri {
-                let p = *v;
-                let uv_world = proj.project(p);
-                let u_world = uv_world.x;
-                let v_world = uv_world.y;
+            for tri in triangulate_fan(verts) {
+                for v in &tri {
+                    let p = *v;
+                    let uv_world = proj.project(p);
+                    let u_world = uv_world.x;
+                    let v_world = uv_world.y;
 
-                let u_tiled = fract_positive(u_world / (tex_w as f32));
-                let v_tiled = fract_positive(v_world / (tex_h as f32));
+                    let u_tiled = fract_positive(u_world / (tex_w as f32));
+                    let v_tiled = fract_positive(v_world / (tex_h as f32));
 
-                let atlas_u = (px as f32 + u_tiled * (tw as f32)) / (atlas_w as f32);
-                let atlas_v = (py as f32 + v_tiled * (th as f32)) / (atlas_h as f32);
+                    let (atlas_u, atlas_v) =
+                        region.map_uv(atlas.width(), atlas.height(), u_tiled, v_tiled);
 
-                buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
-                    v.x, v.y, v.z, atlas_u, atlas_v,
-                )));
+                    buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
+                        v.x, v.y, v.z, atlas_u, atlas_v,
+                    )));
+                }
             }
         }
+    });
+    if let Err(e) = buffer.upload() {
+        log!("failed to upload the map: {e}");
     }
 
-    buffer.insert(&GpuCommand::PopRecord);
-    let _ = buffer.upload();
-
     unsafe {
         GLOBAL_BUFFER.insert(&GpuCommand::Push);
-        GLOBAL_BUFFER.insert(&GpuCommand::BindTexture(0));
-        GLOBAL_BUFFER.insert(&GpuCommand::DrawRecorded(0));
+        GLOBAL_BUFFER.bind_texture(texture);
+        GLOBAL_BUFFER.draw_recorded(map);
         GLOBAL_BUFFER.insert(&GpuCommand::Pop);
     }
 }
@@ -221,6 +194,6 @@ fn update() {
     );
 
     unsafe {
-        let _ = GLOBAL_BUFFER.upload();
+        let _ = GLOBAL_BUFFER.submit();
     }
 }
@@ -148,9 +148,9 @@ fn cast_ray(angle: f64) -> (f64, bool, f64, u8) {
         };
 
         let mut wall_x = if side {
-            PLAYER_X + perp_wall_dist * ray_dir_x
+            perp_wall_dist.mul_add(ray_dir_x, PLAYER_X)
         } else {
-            PLAYER_Y + perp_wall_dist * ray_dir_y
+            perp_wall_dist.mul_add(ray_dir_y, PLAYER_Y)
         };
         wall_x -= wall_x.floor();
 
@@ -216,7 +216,7 @@ fn handle_input(dt: f64) {
         let move_speed = MOVE_SPEED * dt;
         let rot_speed = ROT_SPEED * dt;
 
-        PLAYER_ANGLE += get_mouse_accumulated_dx() * 0.003;
+        PLAYER_ANGLE = get_mouse_accumulated_dx().mul_add(0.003, PLAYER_ANGLE);
         PLAYER_ANGLE %= 2.0 * PI;
 
         if is_key_down(KEY_LEFT) {
@@ -228,7 +228,7 @@ fn handle_input(dt: f64) {
 
         PLAYER_ANGLE %= 2.0 * PI;
         if PLAYER_ANGLE < 0.0 {
-            PLAYER_ANGLE += 2.0 * PI;
+            PLAYER_ANGLE = 2.0f64.mul_add(PI, PLAYER_ANGLE);
         }
 
         let move_x = PLAYER_ANGLE.cos() * move_speed;
@@ -1,3 +1,4 @@
 fn main() {
     gooseboy_buildscript::convert_images();
+    gooseboy_buildscript::convert_models();
 }
@@ -0,0 +1,10 @@
+// Auto-generated
+use std::sync::LazyLock;
+use gooseboy::mesh::Mesh;
+
+#[allow(dead_code)]
+pub static TEAPOT: LazyLock<Mesh> = LazyLock::new(|| {
+    let data = include_bytes!(concat!(env!("OUT_DIR"), "/teapot.mesh"));
+    Mesh::from_bytes(data).expect("teapot.mesh is malformed")
+});
+
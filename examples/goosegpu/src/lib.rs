@@ -1,21 +1,38 @@
 #![no_main]
 
+use std::sync::Mutex;
+
 use gooseboy::{
-    camera::{get_camera_pitch, get_camera_x, get_camera_y, get_camera_yaw, get_camera_z},
+    Vec3,
+    camera::{Camera, get_camera_pitch, get_camera_x, get_camera_y, get_camera_yaw, get_camera_z},
     color::Color,
     framebuffer::{clear_framebuffer, init_fb},
-    gpu::{GpuCommand, GpuCommandBuffer, PrimitiveType, Vertex, gpu_read_value, load_obj},
+    gpu::{GpuCommand, GpuCommandBuffer, PrimitiveType, Vertex, gpu_read_value},
     input::{grab_mouse, is_key_down},
     keys::{KEY_F, KEY_G},
+    log,
+    scene::{Node, NodeId, Scene},
     system::convert_nano_time_to_seconds,
     text::draw_text_formatted,
-    vek::Wrap,
+    vek::{Quaternion, Wrap},
 };
 
 mod sprites {
     include!("generated/sprites.rs");
 }
 
+mod models {
+    include!("generated/models.rs");
+}
+
+struct Nodes {
+    teapot2: NodeId,
+    quad: NodeId,
+}
+
+static SCENE: Mutex<Scene> = Mutex::new(Scene::new());
+static NODES: Mutex<Option<Nodes>> = Mutex::new(None);
+
 #[gooseboy::main]
 fn main() {
     init_fb();
@@ -25,19 +42,24 @@ fn main() {
 fn gpu_main() {
     grab_mouse();
 
-    let vertices = load_obj(include_str!("../teapot.obj"), true);
-    let vertices2 = load_obj(include_str!("../teapot.obj"), true);
     let mut buffer = GpuCommandBuffer::new();
-    buffer.insert_register_sprite(&sprites::CAT);
-    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Triangles));
-    buffer.insert(&GpuCommand::BindTexture(0));
-    buffer.insert(&GpuCommand::EmitVertices(vertices.into()));
-    buffer.insert(&GpuCommand::PopRecord);
-
-    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Triangles));
-    buffer.insert(&GpuCommand::BindTexture(0));
-    buffer.insert(&GpuCommand::EmitVertices(vertices2.into()));
-    buffer.insert(&GpuCommand::PopRecord);
+    let cat = buffer.register_sprite(&sprites::CAT);
+    let teapot = buffer.record_ex(
+        PrimitiveType::Triangles,
+        models::TEAPOT.vertex_format(),
+        |r| {
+            r.bind_texture(cat);
+            models::TEAPOT.emit(r);
+        },
+    );
+    let teapot2 = buffer.record_ex(
+        PrimitiveType::Triangles,
+        models::TEAPOT.vertex_format(),
+        |r| {
+            r.bind_texture(cat);
+            models::TEAPOT.emit(r);
+        },
+    );
 
     let quad_vertices = [
         Vertex::new(-0.5, -0.5, 0.0, 0.0, 0.0),
@@ -46,13 +68,30 @@ fn gpu_main() {
         Vertex::new(-0.5, 0.5, 0.0, 0.0, 1.0),
     ];
 
-    buffer.insert_register_sprite(&sprites::ICON);
-    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Quads));
-    buffer.insert(&GpuCommand::BindTexture(1));
-    buffer.insert(&GpuCommand::EmitVertices(quad_vertices.into()));
-    buffer.insert(&GpuCommand::PopRecord);
+    let icon = buffer.register_sprite(&sprites::ICON);
+    let quad = buffer.record(PrimitiveType::Quads, |r| {
+        r.bind_texture(icon);
+        r.insert(&GpuCommand::EmitVertices(quad_vertices.into()));
+    });
+
+    if let Err(e) = buffer.upload() {
+        log!("failed to upload: {e}");
+    }
 
-    let _ = buffer.upload();
+    let mut scene = SCENE.lock().unwrap();
+    let bounds = models::TEAPOT.bounds();
+    let mut teapot = Node::with_record(teapot, bounds);
+    teapot.transform.orientation = Quaternion::rotation_x(180.0_f32.to_radians());
+    scene.add(teapot);
+
+    let mut teapot2 = Node::with_record(teapot2, bounds);
+    teapot2.transform.position = Vec3::new(10.0, 0.0, 0.0);
+    let mut quad = Node::with_record(quad, None);
+    quad.transform.position = Vec3::new(5.0, 0.0, 0.0);
+    *NODES.lock().unwrap() = Some(Nodes {
+        teapot2: scene.add(teapot2),
+        quad: scene.add(quad),
+    });
 }
 
 #[gooseboy::update]
@@ -81,38 +120,18 @@ fn update(nano_time: i64) {
         Color::RED,
     );
 
-    let mut buffer = GpuCommandBuffer::new();
-
-    buffer.insert(&GpuCommand::Push);
-    buffer.insert(&GpuCommand::RotateAxis {
-        x: 1.0,
-        y: 0.0,
-        z: 0.0,
-        angle: (180.0_f32).to_radians(),
-    });
-    buffer.insert(&GpuCommand::DrawRecorded(0));
-    buffer.insert(&GpuCommand::Pop);
-
-    if is_key_down(KEY_F) {
-        buffer.insert(&GpuCommand::Push);
-        buffer.insert(&GpuCommand::Translate {
-            x: 10.0,
-            y: 0.0,
-            z: 0.0,
-        });
-        buffer.insert(&GpuCommand::DrawRecorded(1));
-        buffer.insert(&GpuCommand::Pop);
-    }
-    if is_key_down(KEY_G) {
-        buffer.insert(&GpuCommand::Push);
-        buffer.insert(&GpuCommand::Translate {
-            x: 5.0,
-            y: 0.0,
-            z: 0.0,
-        });
-        buffer.insert(&GpuCommand::DrawRecorded(2));
-        buffer.insert(&GpuCommand::Pop);
+    let Some(nodes) = &*NODES.lock().unwrap() else {
+        return;
+    };
+    let mut scene = SCENE.lock().unwrap();
+    for (node, key) in [(nodes.teapot2, KEY_F), (nodes.quad, KEY_G)] {
+        if let Some(node) = scene.node_mut(node) {
+            node.visible = is_key_down(key);
+        }
     }
 
-    let _ = buffer.upload();
+    let mut buffer = GpuCommandBuffer::new();
+    scene.render(&Camera::read(), &mut buffer);
+    drop(scene);
+    let _ = buffer.submit();
 }
@@ -0,0 +1,10 @@
+use gooseboy_buildscript::{ImageOptions, TextureCompression};
+
+fn main() {
+    gooseboy_buildscript::convert_images_ex(&ImageOptions {
+        atlas: true,
+        compression: TextureCompression::Lossless,
+        ..ImageOptions::default()
+    });
+    gooseboy_buildscript::convert_models();
+}
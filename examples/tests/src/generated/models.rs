@@ -0,0 +1,17 @@
+// Auto-generated
+use std::sync::LazyLock;
+use gooseboy::mesh::Mesh;
+
+#[allow(dead_code)]
+pub static RIG: LazyLock<Mesh> = LazyLock::new(|| {
+    let data = include_bytes!(concat!(env!("OUT_DIR"), "/rig.mesh"));
+    Mesh::from_bytes(data).expect("rig.mesh is malformed")
+});
+
+#[allow(dead_code)]
+pub static RIG_SKIN: LazyLock<gooseboy::animation::SkinnedMesh> = LazyLock::new(|| {
+    let data = include_bytes!(concat!(env!("OUT_DIR"), "/rig.anim"));
+    gooseboy::animation::SkinnedMesh::from_bytes(RIG.clone(), data)
+        .expect("rig.anim is malformed")
+});
+
@@ -0,0 +1,31 @@
+// Auto-generated
+use std::sync::LazyLock;
+use gooseboy::atlas::{AtlasRegion, TextureAtlas};
+use gooseboy::texture::EncodedTexture;
+
+#[allow(dead_code)]
+pub const RED: AtlasRegion = AtlasRegion::new(0, 0, 4, 4);
+
+#[allow(dead_code)]
+pub const STRIPES: AtlasRegion = AtlasRegion::new(5, 0, 3, 2);
+
+#[allow(dead_code)]
+pub const ATLAS_REGIONS: &[(&str, AtlasRegion)] = &[
+    ("red", RED),
+    ("stripes", STRIPES),
+];
+
+#[allow(dead_code)]
+pub static ATLAS_TEXTURE: LazyLock<EncodedTexture> = LazyLock::new(|| {
+    let data = include_bytes!(concat!(env!("OUT_DIR"), "/atlas.tex"));
+    EncodedTexture::from_bytes(data).expect("atlas.tex is malformed")
+});
+
+#[allow(dead_code)]
+pub static ATLAS: LazyLock<TextureAtlas> = LazyLock::new(|| {
+    let sprite = {
+    ATLAS_TEXTURE.to_sprite().expect("atlas.tex is malformed")
+    };
+    let regions: Vec<AtlasRegion> = ATLAS_REGIONS.iter().map(|(_, region)| *region).collect();
+    TextureAtlas::from_regions(sprite, &regions)
+});
@@ -1,6 +1,25 @@
 use crate::tests::{
-    color::test_color, framebuffer::test_framebuffer, mem::test_mem, sprite::test_sprite,
-    storage::test_storage, text::test_text, timer::test_timer,
+    animation::{test_animation, test_animation_bytes, test_animation_skinning},
+    atlas::test_atlas,
+    buildscript::test_buildscript,
+    camera::{test_camera, test_camera_controllers, test_camera_first_person},
+    color::test_color,
+    framebuffer::{test_framebuffer, test_framebuffer_shapes, test_framebuffer_views},
+    gpu::{
+        test_gpu, test_gpu_decode, test_gpu_golden, test_gpu_handles, test_gpu_instanced,
+        test_gpu_lifecycle, test_gpu_lines, test_gpu_protocol, test_gpu_random_roundtrip,
+        test_gpu_validate, test_gpu_vertex_decode, test_gpu_vertex_formats,
+    },
+    mem::test_mem,
+    mesh::{test_mesh, test_mesh_bytes, test_mesh_record},
+    scene::test_scene,
+    sprite::{test_sprite, test_sprite_blit_ex},
+    storage::test_storage,
+    text::test_text,
+    texture::test_texture,
+    timer::test_timer,
+    vector::test_vector,
+    voxel::{test_voxel, test_voxel_world},
 };
 
 pub struct TestResult {
@@ -28,8 +47,39 @@ pub fn run_tests() {
     test_storage();
     test_color();
     test_framebuffer();
+    test_framebuffer_shapes();
+    test_framebuffer_views();
+    test_vector();
+    test_camera();
+    test_camera_controllers();
+    test_camera_first_person();
+    test_gpu();
+    test_gpu_decode();
+    test_gpu_random_roundtrip();
+    test_gpu_protocol();
+    test_gpu_golden();
+    test_gpu_validate();
+    test_gpu_handles();
+    test_gpu_lifecycle();
+    test_gpu_vertex_formats();
+    test_gpu_vertex_decode();
+    test_gpu_lines();
+    test_gpu_instanced();
+    test_mesh();
+    test_mesh_record();
+    test_mesh_bytes();
+    test_animation();
+    test_animation_skinning();
+    test_animation_bytes();
+    test_scene();
+    test_atlas();
+    test_buildscript();
+    test_texture();
+    test_voxel();
+    test_voxel_world();
     test_mem();
     test_sprite();
+    test_sprite_blit_ex();
     test_text();
     test_timer();
 }
@@ -0,0 +1,210 @@
+use std::f32::consts::FRAC_PI_2;
+
+use gooseboy::{
+    Mat4, Vec2, Vec3,
+    animation::{
+        Animation, AnimationDecodeError, Channel, Easing, Joint, Keyframe, Skeleton, SkinnedMesh,
+        Track, TransformTrack, transform_matrix,
+    },
+    mesh::Mesh,
+    vek::{Quaternion, Transform},
+};
+
+use crate::{test, tests::helpers::close};
+
+/// A 1x2 quad, the bottom bound to a root joint and the top to a joint 1 unit above it.
+fn arm() -> SkinnedMesh {
+    let mut mesh = Mesh::new();
+    for position in [
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Vec3::new(1.0, 2.0, 0.0),
+    ] {
+        mesh.push_vertex(position, Vec2::zero());
+    }
+    mesh.push_triangle(0, 1, 2).push_triangle(1, 3, 2);
+
+    let bend = Track::new(vec![
+        Keyframe::new(0.0, Quaternion::identity()),
+        Keyframe::new(1.0, Quaternion::rotation_z(FRAC_PI_2)),
+    ]);
+    SkinnedMesh {
+        mesh,
+        joints: vec![[0; 4], [0; 4], [1, 0, 0, 0], [1, 0, 0, 0]],
+        weights: vec![[1.0, 0.0, 0.0, 0.0]; 4],
+        skeleton: Skeleton {
+            joints: vec![
+                Joint {
+                    name: "lower".to_owned(),
+                    parent: None,
+                    rest: Transform::default(),
+                    inverse_bind: Mat4::identity(),
+                },
+                Joint {
+                    name: "upper".to_owned(),
+                    parent: Some(0),
+                    rest: Transform {
+                        position: Vec3::unit_y(),
+                        ..Transform::default()
+                    },
+                    inverse_bind: Mat4::translation_3d(-Vec3::unit_y()),
+                },
+            ],
+            root: Mat4::translation_3d(Vec3::new(5.0, 0.0, 0.0)),
+        },
+        animations: vec![Animation::new(
+            "bend",
+            vec![Channel {
+                target: 1,
+                track: TransformTrack {
+                    rotation: bend,
+                    ..TransformTrack::default()
+                },
+            }],
+        )],
+    }
+}
+
+pub fn test_animation() {
+    test!(
+        "animation:easing",
+        [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::SineInOut,
+        ]
+        .iter()
+        .all(|easing| easing.apply(0.0).abs() < 1e-6 && (easing.apply(1.0) - 1.0).abs() < 1e-6)
+            && Easing::Step.apply(0.99) < 1e-6
+            && Easing::EaseIn.apply(0.5) < 0.5
+            && Easing::EaseOut.apply(0.5) > 0.5
+            && (Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6
+            && (0..=5).all(|repr| Easing::from_repr(repr).is_some_and(|e| e.repr() == repr))
+    );
+
+    let track = Track::new(vec![
+        Keyframe::new(2.0, 10.0),
+        Keyframe::eased(0.0, 0.0, Easing::Step),
+        Keyframe::new(1.0, 5.0),
+    ]);
+    test!(
+        "animation:track",
+        (track.duration() - 2.0).abs() < 1e-6
+            && track.sample(-1.0) == Some(0.0)
+            && track.sample(0.5) == Some(0.0)
+            && track.sample(1.0) == Some(5.0)
+            && track.sample(1.5) == Some(7.5)
+            && track.sample(3.0) == Some(10.0)
+            && Track::<f32>::default().sample(0.0).is_none()
+    );
+
+    let mut transform = Transform::default();
+    let channel = TransformTrack {
+        translation: Track::new(vec![
+            Keyframe::new(0.0, Vec3::zero()),
+            Keyframe::new(1.0, Vec3::new(2.0, 0.0, 0.0)),
+        ]),
+        rotation: Track::new(vec![
+            Keyframe::new(0.0, Quaternion::identity()),
+            Keyframe::new(1.0, Quaternion::rotation_y(FRAC_PI_2)),
+        ]),
+        ..TransformTrack::default()
+    };
+    channel.sample(0.5, &mut transform);
+    test!(
+        "animation:transform_track",
+        close(transform.position, Vec3::new(1.0, 0.0, 0.0))
+            && close(
+                transform.orientation * Vec3::unit_x(),
+                Quaternion::rotation_y(FRAC_PI_2 / 2.0) * Vec3::unit_x()
+            )
+            && transform.scale == Vec3::one()
+    );
+    test!(
+        "animation:transform_matrix",
+        close(
+            transform_matrix(&transform).mul_point(Vec3::zero()),
+            transform.position
+        )
+    );
+}
+
+pub fn test_animation_skinning() {
+    let arm = arm();
+    test!(
+        "animation:duration",
+        arm.animation("bend")
+            .is_some_and(|bend| (bend.duration - 1.0).abs() < 1e-6)
+            && arm.animation("walk").is_none()
+            && arm.skeleton.find("upper") == Some(1)
+    );
+
+    let mut pose = arm.skeleton.rest_pose();
+    let world = arm.skeleton.world_matrices(&pose);
+    test!(
+        "animation:world_matrices",
+        close(world[1].mul_point(Vec3::zero()), Vec3::new(5.0, 1.0, 0.0))
+    );
+    let rest = arm.skin(&arm.skeleton.joint_matrices(&pose));
+    test!(
+        "animation:skin_rest",
+        rest.positions
+            .iter()
+            .zip(&arm.mesh.positions)
+            .all(|(&a, &b)| close(a, b + Vec3::new(5.0, 0.0, 0.0)))
+    );
+
+    arm.animations[0].sample(1.0, &mut pose);
+    let bent = arm.skin(&arm.skeleton.joint_matrices(&pose));
+    test!(
+        "animation:skin_bent",
+        close(bent.positions[0], Vec3::new(5.0, 0.0, 0.0))
+            && close(bent.positions[2], Vec3::new(4.0, 1.0, 0.0))
+            && close(bent.positions[3], Vec3::new(4.0, 2.0, 0.0))
+    );
+
+    let mut blended = arm.clone();
+    blended.weights[3] = [0.5, 0.5, 0.0, 0.0];
+    blended.weights[2] = [0.0; 4];
+    let blended = blended.skin(&arm.skeleton.joint_matrices(&pose));
+    test!(
+        "animation:skin_weights",
+        close(blended.positions[2], arm.mesh.positions[2])
+            && close(blended.positions[3], Vec3::new(5.0, 2.0, 0.0))
+    );
+}
+
+pub fn test_animation_bytes() {
+    let arm = arm();
+    let bytes = arm.to_bytes();
+    test!(
+        "animation:bytes_roundtrip",
+        SkinnedMesh::from_bytes(arm.mesh.clone(), &bytes).is_ok_and(|decoded| decoded == arm)
+    );
+    test!(
+        "animation:bytes_magic",
+        SkinnedMesh::from_bytes(Mesh::new(), b"GMSH\x01\x00")
+            == Err(AnimationDecodeError::BadMagic)
+    );
+    test!(
+        "animation:bytes_truncated",
+        SkinnedMesh::from_bytes(arm.mesh.clone(), &bytes[..bytes.len() - 1])
+            == Err(AnimationDecodeError::UnexpectedEnd)
+    );
+    test!(
+        "animation:bytes_vertex_count",
+        SkinnedMesh::from_bytes(Mesh::cube(1.0), &bytes)
+            == Err(AnimationDecodeError::VertexCountMismatch { skin: 4, mesh: 24 })
+    );
+
+    let mut unordered = arm.clone();
+    unordered.skeleton.joints[0].parent = Some(1);
+    test!(
+        "animation:bytes_joint_order",
+        SkinnedMesh::from_bytes(arm.mesh, &unordered.to_bytes())
+            == Err(AnimationDecodeError::BadJoint { joint: 1 })
+    );
+}
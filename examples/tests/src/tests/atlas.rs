@@ -0,0 +1,83 @@
+use gooseboy::{
+    atlas::{AtlasRegion, TextureAtlas},
+    sprite::Sprite,
+};
+
+use crate::test;
+
+fn solid(width: usize, height: usize, value: u8) -> Sprite {
+    Sprite::new(width, height, &vec![value; width * height * 4])
+}
+
+const fn overlaps(a: &AtlasRegion, b: &AtlasRegion) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn is_packed(atlas: &TextureAtlas, regions: &[AtlasRegion]) -> bool {
+    regions.iter().enumerate().all(|(i, a)| {
+        a.x + a.width <= atlas.width()
+            && a.y + a.height <= atlas.height()
+            && regions[i + 1..].iter().all(|b| !overlaps(a, b))
+    })
+}
+
+pub fn test_atlas() {
+    let mut atlas = TextureAtlas::new(32, 32);
+    let first = atlas.insert(&solid(16, 8, 1));
+    let second = atlas.insert(&solid(8, 8, 2));
+    let third = atlas.insert(&solid(8, 16, 3));
+    test!(
+        "atlas:insert",
+        first == Some(AtlasRegion::new(0, 0, 16, 8))
+            && second == Some(AtlasRegion::new(17, 0, 8, 8))
+            && third.is_some_and(|region| region.y == 9)
+            && is_packed(&atlas, atlas.regions())
+    );
+
+    let region = second.unwrap_or_default();
+    let copy = atlas.region_sprite(&region);
+    test!(
+        "atlas:pixels",
+        copy.width == 8
+            && copy.rgba.iter().all(|&value| value == 2)
+            && atlas.sprite.rgba[(16 * 4)..(17 * 4)] == [0; 4]
+    );
+
+    let uv = atlas.uv(&region);
+    let (u, v) = region.map_uv(32, 32, 1.0, 1.0);
+    test!(
+        "atlas:uv",
+        (uv.x - 17.0 / 32.0).abs() < 1e-6
+            && (uv.w - 0.25).abs() < 1e-6
+            && (u - 25.0 / 32.0).abs() < 1e-6
+            && (v - 0.25).abs() < 1e-6
+    );
+
+    test!(
+        "atlas:full",
+        atlas.insert(&solid(33, 1, 0)).is_none() && atlas.insert(&solid(32, 32, 0)).is_none()
+    );
+
+    let sprites: Vec<Sprite> = (1..=12).map(|i| solid(i * 2, 20 - i, 0)).collect();
+    let packed = TextureAtlas::pack(&sprites.iter().collect::<Vec<_>>(), 1, 256);
+    test!(
+        "atlas:pack",
+        packed.as_ref().is_some_and(|(atlas, regions)| {
+            atlas.width() == 64
+                && is_packed(atlas, regions)
+                && regions
+                    .iter()
+                    .zip(&sprites)
+                    .all(|(region, sprite)| region.width == sprite.width)
+        }) && TextureAtlas::pack(&[&solid(64, 64, 0)], 1, 32).is_none()
+    );
+
+    let mut restored = TextureAtlas::from_regions(atlas.sprite.clone(), atlas.regions());
+    let below = restored.insert(&solid(32, 4, 4));
+    test!(
+        "atlas:from_regions",
+        restored.regions().len() == 4
+            && below.is_some_and(|region| region.y == 26)
+            && is_packed(&restored, restored.regions())
+    );
+}
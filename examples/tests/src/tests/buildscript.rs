@@ -0,0 +1,49 @@
+use gooseboy::{Vec3, texture::TextureFormat, vek::Quaternion};
+
+use crate::{test, tests::helpers::close};
+
+mod sprites {
+    include!("../generated/sprites.rs");
+}
+
+mod models {
+    include!("../generated/models.rs");
+}
+
+pub fn test_buildscript() {
+    let atlas = &*sprites::ATLAS;
+    let stripes = atlas.region_sprite(&sprites::STRIPES);
+    test!(
+        "buildscript:atlas",
+        sprites::ATLAS_TEXTURE.format != TextureFormat::Rgba8
+            && atlas.regions() == [sprites::RED, sprites::STRIPES]
+            && atlas.region_sprite(&sprites::RED).rgba == [255, 0, 0, 255].repeat(16)
+            && stripes.rgba[..12] == [10, 20, 30, 255, 40, 50, 60, 255, 70, 80, 90, 255]
+    );
+
+    let skin = &*models::RIG_SKIN;
+    let joints = &skin.skeleton.joints;
+    test!(
+        "buildscript:skin",
+        models::RIG.vertex_count() == 4
+            && joints.len() == 2
+            && joints[0].name == "root"
+            && joints[1].parent == Some(0)
+            && skin.joints[0][0] == 0
+            && skin.joints[2][0] == 1
+    );
+
+    let mut pose = skin.skeleton.rest_pose();
+    let wave = skin.animation("wave");
+    if let Some(wave) = wave {
+        wave.sample(1.0, &mut pose);
+    }
+    let quarter = Quaternion::rotation_z(std::f32::consts::FRAC_PI_2);
+    test!(
+        "buildscript:animation",
+        wave.is_some_and(|wave| (wave.duration - 1.0).abs() < 1e-6 && wave.channels.len() == 2)
+            && close(pose[0].position, Vec3::unit_x())
+            && close(pose[1].position, Vec3::unit_y())
+            && pose[1].orientation.dot(quarter) > 0.9999
+    );
+}
@@ -0,0 +1,225 @@
+use std::f32::consts::FRAC_PI_2;
+
+use gooseboy::{
+    Aabb, Vec2, Vec3,
+    camera::{
+        Camera, CameraTransform,
+        controller::{
+            CameraController, ControllerInput, FirstPersonController, FollowController,
+            OrbitController, SplineController,
+        },
+    },
+    gpu::software::SoftwareGpu,
+};
+
+use crate::{test, tests::helpers::close};
+
+pub fn test_camera() {
+    let size = Vec2::new(320.0, 240.0);
+    let mut camera = Camera::from_transform(CameraTransform {
+        z: 5.0,
+        ..CameraTransform::default()
+    });
+
+    test!(
+        "camera:axes",
+        close(camera.forward(), -Vec3::unit_z())
+            && close(camera.right(), Vec3::unit_x())
+            && close(camera.up(), Vec3::unit_y())
+    );
+    test!(
+        "camera:view_matrix",
+        close(
+            (camera.view_matrix() * Vec3::zero().with_w(1.0)).xyz(),
+            Vec3::new(0.0, 0.0, -5.0)
+        )
+    );
+    test!(
+        "camera:world_to_screen",
+        camera
+            .world_to_screen(Vec3::zero(), size)
+            .is_some_and(|p| p.distance(Vec2::new(160.0, 120.0)) < 1e-3)
+            && camera
+                .world_to_screen(Vec3::new(1.0, 0.0, 0.0), size)
+                .is_some_and(|p| p.x > 160.0)
+            && camera
+                .world_to_screen(Vec3::new(0.0, 0.0, 10.0), size)
+                .is_none()
+    );
+
+    let ray = camera.screen_to_ray(Vec2::new(160.0, 120.0), size);
+    test!(
+        "camera:screen_to_ray",
+        close(ray.direction, camera.forward()) && ray.origin.distance(camera.position()) < 0.1
+    );
+    let picked = camera.screen_to_ray(Vec2::new(40.0, 200.0), size);
+    test!(
+        "camera:ray_roundtrip",
+        camera
+            .world_to_screen(picked.origin + picked.direction * 10.0, size)
+            .is_some_and(|p| p.distance(Vec2::new(40.0, 200.0)) < 1e-2)
+    );
+
+    camera.roll = FRAC_PI_2;
+    test!(
+        "camera:roll",
+        close(camera.up(), -Vec3::unit_x())
+            && camera
+                .world_to_screen(Vec3::new(1.0, 0.0, 0.0), size)
+                .is_some_and(|p| p.y > 120.0 && (p.x - 160.0).abs() < 1e-3)
+    );
+    camera.roll = 0.0;
+
+    let frustum = camera.frustum(size.x / size.y);
+    test!(
+        "camera:frustum",
+        frustum.contains_point(Vec3::zero())
+            && !frustum.contains_point(Vec3::new(0.0, 0.0, 10.0))
+            && !frustum.contains_point(Vec3::new(100.0, 0.0, 0.0))
+            && frustum.intersects_sphere(Vec3::new(0.0, 0.0, 6.0), 2.0)
+            && !frustum.intersects_sphere(Vec3::new(0.0, 0.0, 10.0), 2.0)
+            && frustum.intersects_aabb(&Aabb {
+                min: Vec3::new(-100.0, -1.0, -1.0),
+                max: Vec3::new(100.0, 1.0, 1.0),
+            })
+            && !frustum.intersects_aabb(&Aabb {
+                min: Vec3::new(50.0, -1.0, -1.0),
+                max: Vec3::new(60.0, 1.0, 1.0),
+            })
+    );
+
+    let mut gpu = SoftwareGpu::new();
+    gpu.camera = camera.transform;
+    gpu.roll = 0.3;
+    camera.roll = 0.3;
+    test!(
+        "camera:software_gpu",
+        gpu.full_camera() == camera
+            && gpu.view_projection(320, 240) == camera.view_projection(size.x / size.y)
+    );
+}
+
+const fn input(mouse_delta: Vec2<f32>, movement: Vec3<f32>) -> ControllerInput {
+    ControllerInput {
+        mouse_delta,
+        movement,
+    }
+}
+
+const fn position(transform: CameraTransform) -> Vec3<f32> {
+    Vec3::new(transform.x, transform.y, transform.z)
+}
+
+pub fn test_camera_controllers() {
+    let idle = ControllerInput::default();
+    let target = Vec3::new(1.0, 2.0, 3.0);
+
+    let looking = CameraTransform::looking_at(Vec3::new(0.0, 5.0, 5.0), Vec3::zero());
+    test!(
+        "camera:looking_at",
+        close(looking.forward(), Vec3::new(0.0, -1.0, -1.0).normalized())
+    );
+
+    let mut orbit = OrbitController::new(target, 4.0);
+    let transform = orbit.update(&idle, 0.05);
+    test!(
+        "camera:orbit",
+        close(position(transform), target + Vec3::unit_z() * 4.0)
+            && close(transform.forward(), -Vec3::unit_z())
+    );
+    let transform = orbit.update(&input(Vec2::new(0.0, -100_000.0), Vec3::zero()), 0.05);
+    test!(
+        "camera:orbit_pitch_clamp",
+        orbit.pitch <= orbit.max_pitch && position(transform).distance(target) < 4.001
+    );
+    orbit.min_distance = 3.0;
+    orbit.update(&input(Vec2::zero(), Vec3::unit_z()), 10.0);
+    test!("camera:orbit_zoom", (orbit.distance - 3.0).abs() < 1e-6);
+
+    let mut smooth = OrbitController::new(target, 4.0);
+    smooth.smoothing = 0.5;
+    smooth.yaw = 1.0;
+    let first = smooth.update(&idle, 0.05);
+    let second = smooth.update(&idle, 0.05);
+    test!(
+        "camera:orbit_smoothing",
+        first.yaw > 0.0 && first.yaw < second.yaw && second.yaw < 1.0
+    );
+
+    let mut follow = FollowController::new(Vec3::zero(), 5.0);
+    follow.pitch = 0.0;
+    let transform = follow.update(&idle, 0.05);
+    let pivot = follow.offset;
+    test!(
+        "camera:follow",
+        close(position(transform), pivot + Vec3::unit_z() * 5.0)
+            && close(transform.forward(), -Vec3::unit_z())
+    );
+    follow.target = Vec3::new(0.0, 0.0, -10.0);
+    let lagging = position(follow.update(&idle, 0.05));
+    test!("camera:follow_lag", lagging.z > -5.0 && lagging.z < 5.0);
+    follow.probe = Some(|_, length| length * 0.5);
+    let probed = position(follow.update(&idle, 0.05));
+    test!(
+        "camera:follow_probe",
+        probed.distance(follow.target + pivot) < 2.501
+    );
+}
+
+pub fn test_camera_first_person() {
+    let forward = input(Vec2::zero(), Vec3::unit_z());
+
+    let mut flying = FirstPersonController::new(Vec3::zero());
+    flying.smoothing = 0.0;
+    let transform = flying.update(&forward, 1.0);
+    test!(
+        "camera:first_person_fly",
+        close(flying.position, Vec3::new(0.0, 0.0, -flying.speed))
+            && (transform.y - flying.eye_height).abs() < 1e-6
+    );
+    flying.update(&input(Vec2::new(0.0, 100_000.0), Vec3::unit_y()), 1.0);
+    test!(
+        "camera:first_person_pitch_clamp",
+        (flying.pitch - flying.min_pitch).abs() < 1e-6 && flying.position.y > 0.0
+    );
+
+    let mut walking = FirstPersonController::new(Vec3::new(0.0, 3.0, 0.0));
+    walking.ground = Some(|_| Some(1.0));
+    for _ in 0..40 {
+        walking.update(&ControllerInput::default(), 0.05);
+    }
+    test!(
+        "camera:first_person_gravity",
+        walking.is_on_ground() && (walking.position.y - 1.0).abs() < 1e-6
+    );
+    walking.update(&input(Vec2::zero(), Vec3::unit_y()), 0.05);
+    test!(
+        "camera:first_person_jump",
+        !walking.is_on_ground() && walking.position.y > 1.0
+    );
+
+    let points = vec![
+        Vec3::zero(),
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(10.0, 0.0, 10.0),
+    ];
+    let mut spline = SplineController::new(points, 2.0);
+    test!(
+        "camera:spline_points",
+        close(spline.position_at(0.0), Vec3::zero())
+            && close(spline.position_at(0.5), Vec3::new(10.0, 0.0, 0.0))
+            && close(spline.position_at(1.0), Vec3::new(10.0, 0.0, 10.0))
+    );
+    let transform = spline.update(&ControllerInput::default(), 0.0);
+    test!("camera:spline_tangent", transform.forward().x > 0.9);
+    spline.look_at = Some(Vec3::new(10.0, 0.0, -10.0));
+    spline.update(&ControllerInput::default(), 5.0);
+    test!("camera:spline_finish", spline.is_finished());
+    spline.looping = true;
+    spline.restart();
+    spline.update(&ControllerInput::default(), 5.0);
+    test!(
+        "camera:spline_loop",
+        !spline.is_finished() && (spline.time() - 1.0).abs() < 1e-4
+    );
+}
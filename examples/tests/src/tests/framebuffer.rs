@@ -1,13 +1,16 @@
 use gooseboy::{
+    Rect, Vec2,
     color::Color,
     framebuffer::{
-        get_framebuffer_height, get_framebuffer_ptr, get_framebuffer_width, get_pixel_index,
-        set_pixel,
+        Surface, get_framebuffer_height, get_framebuffer_ptr, get_framebuffer_width,
+        get_pixel_index, set_pixel,
     },
+    sprite::Sprite,
+    text::{draw_text_background_ex, draw_text_ex},
     unsafe_casts,
 };
 
-use crate::test;
+use crate::{test, tests::helpers::pixel};
 
 pub fn test_framebuffer() {
     let w = get_framebuffer_width();
@@ -40,3 +43,240 @@ pub fn test_framebuffer() {
         }
     }
 }
+
+/// Returns the amount of pixels that were drawn to on a surface that started out empty.
+fn drawn(surface: &Surface) -> usize {
+    surface
+        .rgba
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .filter(|pixel| pixel[3] != 0)
+        .count()
+}
+
+pub fn test_framebuffer_shapes() {
+    let mut surface = Surface::new_empty(16, 16);
+    surface.draw_line(Vec2::new(1, 1), Vec2::new(6, 1), 1, Color::WHITE, false);
+    surface.draw_line(Vec2::new(0, 3), Vec2::new(4, 7), 1, Color::WHITE, false);
+    test!(
+        "fb:draw_line",
+        drawn(&surface) == 11 && pixel(&surface, 2, 5) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_line(
+        Vec2::new(-40, -40),
+        Vec2::new(40, 40),
+        1,
+        Color::WHITE,
+        false,
+    );
+    surface.fill_circle(Vec2::new(-100, 8), 104, Color::RED, true);
+    test!("fb:clipped", pixel(&surface, 15, 15) == [255; 4]);
+
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_line(
+        Vec2::new(0, 0),
+        Vec2::new(i32::MAX, 5),
+        1,
+        Color::WHITE,
+        false,
+    );
+    surface.draw_line(
+        Vec2::new(i32::MIN, i32::MIN),
+        Vec2::new(i32::MAX, i32::MAX),
+        1,
+        Color::WHITE,
+        false,
+    );
+    test!(
+        "fb:huge_line",
+        drawn(&surface) == 31
+            && pixel(&surface, 15, 0) == [255; 4]
+            && pixel(&surface, 15, 15) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_line(Vec2::new(2, 4), Vec2::new(9, 4), 3, Color::WHITE, false);
+    test!(
+        "fb:thick_line",
+        drawn(&surface) == 30 && pixel(&surface, 1, 3) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.fill_circle(Vec2::new(5, 5), 2, Color::WHITE, false);
+    let filled = drawn(&surface);
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_circle(Vec2::new(5, 5), 2, Color::WHITE, false);
+    test!(
+        "fb:circle",
+        filled == 21 && drawn(&surface) == 12 && pixel(&surface, 5, 5) == [0; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.fill_ellipse(Vec2::new(8, 8), Vec2::new(6, 1), Color::WHITE, false);
+    test!(
+        "fb:ellipse",
+        pixel(&surface, 2, 8) == [255; 4] && pixel(&surface, 8, 6) == [0; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.fill_rounded_rect(Rect::new(1, 1, 10, 6), 2, Color::WHITE, false);
+    let rounded = drawn(&surface);
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_rounded_rect(Rect::new(1, 1, 10, 6), 0, Color::WHITE, false);
+    test!(
+        "fb:rounded_rect",
+        rounded == 56 && drawn(&surface) == 28 && pixel(&surface, 5, 3) == [0; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_rect(-4, 12, 8, 8, Color::WHITE, false);
+    surface.draw_rect(20, 0, 4, 4, Color::WHITE, false);
+    test!(
+        "fb:draw_rect",
+        drawn(&surface) == 16 && pixel(&surface, 3, 15) == [255; 4]
+    );
+
+    surface.clear(Color::BLACK);
+    surface.draw_rect(2, 2, 4, 4, Color::new(255, 0, 0, 128), true);
+    test!(
+        "fb:draw_rect_blend",
+        pixel(&surface, 5, 5) == [128, 0, 0, 255] && pixel(&surface, 6, 6) == [0, 0, 0, 255]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    draw_text_background_ex(&mut surface, 0, 0, "i", Color::WHITE, Color::BLUE);
+    test!(
+        "fb:text_background",
+        drawn(&surface) == 64 && pixel(&surface, 0, 0) == [0, 0, 255, 255]
+    );
+
+    test_framebuffer_polygons();
+}
+
+fn test_framebuffer_polygons() {
+    let mut surface = Surface::new_empty(16, 16);
+    let square = [
+        Vec2::new(0, 0),
+        Vec2::new(4, 0),
+        Vec2::new(4, 4),
+        Vec2::new(0, 4),
+    ];
+    surface.fill_polygon(&square, Color::WHITE, false);
+    let filled = drawn(&surface);
+    surface.clear(Color::TRANSPARENT);
+    surface.draw_polygon(&square, Color::new(255, 255, 255, 128), true);
+    test!(
+        "fb:polygon",
+        filled == 16
+            && drawn(&surface) == 16
+            && pixel(&surface, 4, 0) == pixel(&surface, 0, 0)
+            && pixel(&surface, 2, 2) == [0; 4]
+    );
+
+    // Triangles sharing an edge don't blend over each other.
+    let half = Color::new(255, 0, 0, 128);
+    surface.clear(Color::BLACK);
+    surface.fill_triangle(
+        [Vec2::new(0, 0), Vec2::new(7, 0), Vec2::new(7, 7)],
+        half,
+        true,
+    );
+    surface.fill_triangle(
+        [Vec2::new(0, 0), Vec2::new(7, 7), Vec2::new(0, 7)],
+        half,
+        true,
+    );
+    let blended = [128, 0, 0, 255];
+    let covered = (0..7).all(|y| (0..7).all(|x| pixel(&surface, x, y) == blended));
+    test!(
+        "fb:fill_triangle",
+        covered && pixel(&surface, 7, 7) == [0, 0, 0, 255]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    surface.fill_triangle_colored(
+        [Vec2::new(0, 0), Vec2::new(12, 0), Vec2::new(0, 12)],
+        [Color::RED, Color::GREEN, Color::BLUE],
+        false,
+    );
+    let middle = pixel(&surface, 4, 4);
+    test!(
+        "fb:fill_triangle_colored",
+        pixel(&surface, 0, 0) == [255, 0, 0, 255]
+            && middle[0] == 85
+            && middle[1] == 85
+            && middle[2] == 85
+    );
+
+    surface.clear(Color::BLACK);
+    surface.draw_pixel(3, 3, half, true);
+    surface.draw_pixel(-1, 3, Color::WHITE, false);
+    test!("fb:blend", pixel(&surface, 3, 3) == [128, 0, 0, 255]);
+}
+
+pub fn test_framebuffer_views() {
+    let mut surface = Surface::new_empty(16, 16);
+    let mut view = surface.view_mut(Rect::new(4, 4, 8, 8));
+    view.draw_rect(0, 0, 100, 100, Color::WHITE, false);
+    view.view_mut(Rect::new(6, 6, 8, 8)).clear(Color::RED);
+    test!(
+        "fb:view",
+        drawn(&surface) == 64
+            && pixel(&surface, 4, 4) == [255; 4]
+            && pixel(&surface, 3, 3) == [0; 4]
+            && pixel(&surface, 11, 11) == [255, 0, 0, 255]
+            && pixel(&surface, 9, 9) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let mut view = surface.view_mut(Rect::new(0, 0, 16, 16));
+    view.push_clip(Rect::new(0, 0, 8, 8));
+    view.push_clip(Rect::new(4, 4, 8, 8));
+    view.fill_circle(Vec2::new(8, 8), 8, Color::WHITE, false);
+    let inner = view.pop_clip();
+    view.draw_pixel(0, 0, Color::WHITE, false);
+    view.draw_pixel(15, 15, Color::WHITE, false);
+    view.pop_clip();
+    let restored = view.clip_rect();
+    test!(
+        "fb:clip_stack",
+        drawn(&surface) == 17
+            && inner == Some(Rect::new(4, 4, 4, 4))
+            && restored == Rect::new(0, 0, 16, 16)
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let mut view = surface.view_mut(Rect::new(4, 4, 8, 8));
+    view.fill_circle(Vec2::new(0, 0), 20, Color::WHITE, false);
+    test!(
+        "fb:view_shapes",
+        drawn(&surface) == 64
+            && pixel(&surface, 4, 4) == [255; 4]
+            && pixel(&surface, 12, 12) == [0; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let mut view = surface.view_mut(Rect::new(4, 4, 8, 8));
+    view.draw_line(Vec2::new(-5, 2), Vec2::new(20, 2), 1, Color::WHITE, false);
+    test!(
+        "fb:view_line",
+        drawn(&surface) == 8 && pixel(&surface, 4, 6) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let sprite = Sprite::new(4, 4, &[255; 64]);
+    sprite.blit_to(&mut surface.view_mut(Rect::new(2, 2, 2, 2)), -1, -1);
+    test!(
+        "fb:view_blit",
+        drawn(&surface) == 4 && pixel(&surface, 3, 3) == [255; 4]
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let mut view = surface.view_mut(Rect::new(0, 0, 4, 8));
+    draw_text_ex(&mut view, 0, 0, "WW", Color::WHITE);
+    let spilled = (4..16).any(|x| (0..16).any(|y| pixel(&surface, x, y) != [0; 4]));
+    test!("fb:view_text", drawn(&surface) > 0 && !spilled);
+}
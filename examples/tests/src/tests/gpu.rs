@@ -0,0 +1,1163 @@
+use gooseboy::{
+    Aabb, Mat4, Vec2, Vec3,
+    color::Color,
+    error::GooseboyError,
+    framebuffer::Surface,
+    gpu::{
+        GB_GPU_MATRIX_DEPTH, GB_GPU_RECORD_ID, GB_GPU_STATUS, GB_GPU_TEXTURE_ID,
+        GB_STATUS_BAD_TEXTURE, GB_STATUS_BAD_TEXTURE_SIZE, GB_STATUS_BAD_VERTEX_FORMAT,
+        GB_STATUS_MATRIX_TOO_BIG, GB_STATUS_MATRIX_TOO_SMALL, GB_STATUS_NOT_RECORDING,
+        GB_STATUS_OK, GB_STATUS_UNKNOWN_RECORD, GpuCommand, GpuCommandBuffer, GpuCommandReader,
+        GpuDecodeError, GpuStatus, GpuValidationError, PrimitiveType, RecordId, TextureId, Vertex,
+        VertexColor, VertexData, VertexFormat, VertexLit,
+        debug_draw::DebugDraw,
+        protocol::{self, HEADER_SIZE, Header, OPCODES},
+        software::SoftwareGpu,
+    },
+    rand::Rng,
+    texture::TextureFormat,
+};
+
+use crate::test;
+
+pub fn test_gpu() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+
+    buffer.insert(&GpuCommand::RegisterTexture {
+        w: 1,
+        h: 1,
+        rgba: &[255, 0, 0, 255],
+    });
+    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Quads));
+    buffer.insert(&GpuCommand::BindTexture(0));
+    buffer.insert(&GpuCommand::EmitVertices(
+        [
+            Vertex::new(-1.0, -1.0, 0.0, 0.0, 0.0),
+            Vertex::new(1.0, -1.0, 0.0, 1.0, 0.0),
+            Vertex::new(1.0, 1.0, 0.0, 1.0, 1.0),
+            Vertex::new(-1.0, 1.0, 0.0, 0.0, 1.0),
+        ]
+        .into(),
+    ));
+    buffer.insert(&GpuCommand::PopRecord);
+    buffer.insert(&GpuCommand::Push);
+
+    test!(
+        "gpu:execute",
+        gpu.execute(buffer.as_bytes(), &mut target).is_ok()
+    );
+    test!(
+        "gpu:ids",
+        gpu.read_value(GB_GPU_RECORD_ID) == 0 && gpu.read_value(GB_GPU_TEXTURE_ID) == 0
+    );
+    test!("gpu:matrix_depth", gpu.read_value(GB_GPU_MATRIX_DEPTH) == 1);
+
+    gpu.begin_frame(&mut target, Color::BLACK);
+    gpu.camera.z = 2.0;
+    buffer.clear();
+    buffer.insert(&GpuCommand::DrawRecorded(0));
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    let center = (8 * 16 + 8) * 4;
+    test!(
+        "gpu:draw",
+        target.rgba[center..center + 4] == [255, 0, 0, 255]
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::Pop);
+    buffer.insert(&GpuCommand::Pop);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:matrix_too_small",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_MATRIX_TOO_SMALL
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
+        0.0, 0.0, 0.0, 0.0, 0.0,
+    )));
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:not_recording",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_NOT_RECORDING
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::RegisterTexture {
+        w: 0,
+        h: 0,
+        rgba: &[],
+    });
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:bad_texture_size",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_BAD_TEXTURE_SIZE
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::DrawRecorded(0));
+    let mut empty = [Surface::new_empty(0, 16), Surface::new_empty(16, 0)];
+    test!(
+        "gpu:empty_target",
+        empty
+            .iter_mut()
+            .all(|target| gpu.execute(buffer.as_bytes(), target).is_ok())
+    );
+}
+
+pub fn test_gpu_decode() {
+    let commands = [
+        GpuCommand::Push,
+        GpuCommand::PushRecord(PrimitiveType::Quads),
+        GpuCommand::BindTexture(3),
+        GpuCommand::EmitVertex(Vertex::new(1.0, 2.0, 3.0, 0.5, 0.25)),
+        GpuCommand::EmitVertices([Vertex::new(0.0, 1.0, 0.0, 1.0, 1.0)].into()),
+        GpuCommand::PopRecord,
+        GpuCommand::RegisterTexture {
+            w: 1,
+            h: 2,
+            rgba: &[1, 2, 3, 4, 5, 6, 7, 8],
+        },
+        GpuCommand::Translate {
+            x: 1.0,
+            y: -2.0,
+            z: 3.5,
+        },
+        GpuCommand::RotateAxis {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+            angle: 1.5,
+        },
+        GpuCommand::RotateEuler {
+            yaw: 0.1,
+            pitch: 0.2,
+            roll: 0.3,
+        },
+        GpuCommand::Scale {
+            x: 2.0,
+            y: 2.0,
+            z: 2.0,
+        },
+        GpuCommand::LoadMatrix([1.0; 16]),
+        GpuCommand::MulMatrix([2.0; 16]),
+        GpuCommand::Identity,
+        GpuCommand::DrawRecorded(7),
+        GpuCommand::Pop,
+        GpuCommand::ReRecord {
+            id: 7,
+            primitive: PrimitiveType::Triangles,
+        },
+        GpuCommand::PopRecord,
+        GpuCommand::ReRecordEx {
+            id: 7,
+            primitive: PrimitiveType::Lines,
+            format: VertexFormat::Lit,
+        },
+        GpuCommand::PopRecord,
+        GpuCommand::UpdateTexture {
+            id: 1,
+            x: 2,
+            y: 3,
+            w: 1,
+            h: 1,
+            rgba: &[9, 8, 7, 6],
+        },
+        GpuCommand::DeleteRecord(7),
+        GpuCommand::DeleteTexture(1),
+        GpuCommand::RegisterTextureEx {
+            w: 2,
+            h: 1,
+            format: TextureFormat::Rgb565,
+            rle: true,
+            data: &[0x81, 0x1F, 0x00],
+        },
+    ];
+
+    let mut buffer = GpuCommandBuffer::new();
+    for command in &commands {
+        buffer.insert(command);
+    }
+
+    let decoded: Result<Vec<GpuCommand>, GpuDecodeError> = buffer.commands().collect();
+    test!(
+        "gpu:decode_roundtrip",
+        decoded.is_ok_and(|decoded| decoded == commands)
+    );
+
+    let (command, len) = GpuCommand::deserialize(&buffer.as_bytes()[HEADER_SIZE + 1..])
+        .unwrap_or((GpuCommand::Pop, 0));
+    test!(
+        "gpu:deserialize",
+        command == GpuCommand::PushRecord(PrimitiveType::Quads) && len == 2
+    );
+
+    let truncated = &buffer.as_bytes()[HEADER_SIZE..buffer.as_bytes().len() - 3];
+    test!(
+        "gpu:decode_truncated",
+        GpuCommandReader::new(truncated)
+            .last()
+            .is_some_and(|last| matches!(last, Err(GpuDecodeError::UnexpectedEnd { .. })))
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Triangles));
+    buffer.insert(&GpuCommand::DrawRecorded(2));
+    test!(
+        "gpu:disassemble",
+        buffer.to_string()
+            == "; protocol v1, 2 commands\n000000  push_record triangles\n000002  draw_recorded 2\n"
+    );
+}
+
+/// Returns a random index below `len`.
+fn random_index(rng: &mut Rng, len: usize) -> usize {
+    usize::try_from(rng.next_u64() % len as u64).unwrap_or(0)
+}
+
+fn random_f32s<const N: usize>(rng: &mut Rng) -> [f32; N] {
+    core::array::from_fn(|_| rng.gen_range_f32(-1000.0..1000.0))
+}
+
+const fn random_color(rng: &mut Rng) -> Color {
+    let [r, g, b, a, ..] = rng.next_u64().to_le_bytes();
+    Color::new(r, g, b, a)
+}
+
+fn random_vertex(rng: &mut Rng) -> Vertex {
+    let position = random_f32s::<5>(rng);
+    Vertex::new(
+        position[0],
+        position[1],
+        position[2],
+        position[3],
+        position[4],
+    )
+}
+
+fn random_primitive(rng: &mut Rng) -> PrimitiveType {
+    [
+        PrimitiveType::Triangles,
+        PrimitiveType::Quads,
+        PrimitiveType::Lines,
+        PrimitiveType::LineStrip,
+        PrimitiveType::Points,
+    ][random_index(rng, 5)]
+}
+
+fn random_format(rng: &mut Rng) -> VertexFormat {
+    [
+        VertexFormat::Standard,
+        VertexFormat::Color,
+        VertexFormat::Lit,
+    ][random_index(rng, 3)]
+}
+
+fn random_vertex_data(rng: &mut Rng) -> VertexData {
+    let len = random_index(rng, 4);
+    match random_format(rng) {
+        VertexFormat::Standard => {
+            VertexData::Standard((0..len).map(|_| random_vertex(rng)).collect())
+        }
+        VertexFormat::Color => VertexData::Color(
+            (0..len)
+                .map(|_| {
+                    let Vertex { x, y, z, u, v } = random_vertex(rng);
+                    VertexColor::new(x, y, z, u, v, random_color(rng))
+                })
+                .collect(),
+        ),
+        VertexFormat::Lit => VertexData::Lit(
+            (0..len)
+                .map(|_| {
+                    VertexLit::new(
+                        random_f32s::<3>(rng).into(),
+                        random_f32s::<2>(rng).into(),
+                        random_f32s::<3>(rng).into(),
+                        random_color(rng),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Returns a random texture size and its' RGBA pixels, borrowed from `bytes`.
+fn random_texture<'a>(rng: &mut Rng, bytes: &'a [u8]) -> (u32, u32, &'a [u8]) {
+    let (w, h) = (
+        rng.gen_range_u32_inclusive(1..=4),
+        rng.gen_range_u32_inclusive(1..=4),
+    );
+    (w, h, &bytes[..(w * h * 4) as usize])
+}
+
+/// Returns a random command with the following opcode, borrowing texture data from `bytes`.
+fn random_command<'a>(rng: &mut Rng, opcode: u8, bytes: &'a [u8]) -> GpuCommand<'a> {
+    let id = rng.gen_range_u32(0..1000);
+    match opcode {
+        0x01 => GpuCommand::Pop,
+        0x02 => GpuCommand::PushRecord(random_primitive(rng)),
+        0x03 => GpuCommand::PopRecord,
+        0x04 => GpuCommand::DrawRecorded(id),
+        0x05 => GpuCommand::EmitVertex(random_vertex(rng)),
+        0x06 => GpuCommand::BindTexture(id),
+        0x07 => {
+            let (w, h, rgba) = random_texture(rng, bytes);
+            GpuCommand::RegisterTexture { w, h, rgba }
+        }
+        0x08 => {
+            let [x, y, z] = random_f32s(rng);
+            GpuCommand::Translate { x, y, z }
+        }
+        0x09 => {
+            let [x, y, z, angle] = random_f32s(rng);
+            GpuCommand::RotateAxis { x, y, z, angle }
+        }
+        0x0A => {
+            let [yaw, pitch, roll] = random_f32s(rng);
+            GpuCommand::RotateEuler { yaw, pitch, roll }
+        }
+        0x0B => {
+            let [x, y, z] = random_f32s(rng);
+            GpuCommand::Scale { x, y, z }
+        }
+        0x0C => GpuCommand::LoadMatrix(random_f32s(rng)),
+        0x0D => GpuCommand::MulMatrix(random_f32s(rng)),
+        0x0E => GpuCommand::Identity,
+        0x0F => GpuCommand::EmitVertices(
+            (0..random_index(rng, 4))
+                .map(|_| random_vertex(rng))
+                .collect(),
+        ),
+        0x10 => GpuCommand::DeleteRecord(id),
+        0x11 => GpuCommand::DeleteTexture(id),
+        0x12 => {
+            let (x, y) = (rng.gen_range_u32(0..16), rng.gen_range_u32(0..16));
+            let (w, h, rgba) = random_texture(rng, bytes);
+            GpuCommand::UpdateTexture {
+                id,
+                x,
+                y,
+                w,
+                h,
+                rgba,
+            }
+        }
+        0x13 => GpuCommand::ReRecord {
+            id,
+            primitive: random_primitive(rng),
+        },
+        0x14 => GpuCommand::PushRecordEx {
+            primitive: random_primitive(rng),
+            format: random_format(rng),
+        },
+        0x15 => GpuCommand::EmitVerticesEx(random_vertex_data(rng)),
+        0x16 => GpuCommand::LineWidth(rng.gen_range_f32(0.0..16.0)),
+        0x17 => GpuCommand::DrawRecordedInstanced {
+            id,
+            transforms: (0..random_index(rng, 3))
+                .map(|_| random_f32s(rng))
+                .collect(),
+        },
+        0x18 => GpuCommand::DrawRecordedTranslated {
+            id,
+            offsets: (0..random_index(rng, 3))
+                .map(|_| random_f32s(rng))
+                .collect(),
+        },
+        0x19 => GpuCommand::RegisterTextureEx {
+            w: rng.gen_range_u32_inclusive(1..=4),
+            h: rng.gen_range_u32_inclusive(1..=4),
+            format: [
+                TextureFormat::Rgba8,
+                TextureFormat::Rgb565,
+                TextureFormat::Rgba4444,
+                TextureFormat::Palette8,
+            ][random_index(rng, 4)],
+            rle: rng.next_u64().is_multiple_of(2),
+            data: &bytes[..random_index(rng, bytes.len())],
+        },
+        0x1A => GpuCommand::ReRecordEx {
+            id,
+            primitive: random_primitive(rng),
+            format: random_format(rng),
+        },
+        _ => GpuCommand::Push,
+    }
+}
+
+pub fn test_gpu_random_roundtrip() {
+    let mut rng = Rng::new(0x600D_B00F);
+    let bytes: Vec<u8> = (0..256).map(|_| rng.next_u64().to_le_bytes()[0]).collect();
+    let mut seen = [false; OPCODES.len()];
+    let mut roundtrips = true;
+
+    for _ in 0..32 {
+        let commands: Vec<GpuCommand> = (0..32)
+            .map(|_| {
+                let opcode = OPCODES[random_index(&mut rng, OPCODES.len())].code;
+                seen[usize::from(opcode)] = true;
+                random_command(&mut rng, opcode, &bytes)
+            })
+            .collect();
+
+        let mut buffer = GpuCommandBuffer::new();
+        for command in &commands {
+            buffer.insert(command);
+        }
+        let decoded: Result<Vec<GpuCommand>, GpuDecodeError> = buffer.commands().collect();
+        roundtrips &= decoded.is_ok_and(|decoded| decoded == commands);
+    }
+
+    test!("gpu:random_every_opcode", seen.iter().all(|seen| *seen));
+    test!("gpu:random_roundtrip", roundtrips);
+}
+
+pub fn test_gpu_protocol() {
+    let mut buffer = GpuCommandBuffer::new();
+    test!(
+        "protocol:empty",
+        buffer.as_bytes() == [b'G', b'G', b'P', b'U', 1, 0, 0, 0, 0, 0, 0, 0]
+    );
+
+    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Quads));
+    buffer.insert(&GpuCommand::EmitVertices(
+        [Vertex::new(1.0, 0.0, 0.0, 0.0, 2.0)].into(),
+    ));
+    #[rustfmt::skip]
+    let golden: &[u8] = &[
+        b'G', b'G', b'P', b'U', 1, 0, 0, 0, 2, 0, 0, 0,
+        0x02, 1,
+        0x0F, 1, 0, 0, 0,
+        0, 0, 0x80, 0x3F, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x40,
+    ];
+    test!("protocol:golden", buffer.as_bytes() == golden);
+
+    test!(
+        "protocol:header",
+        protocol::split_stream(golden).is_ok_and(|(header, commands)| {
+            header == Header::new(2) && commands.len() == golden.len() - HEADER_SIZE
+        })
+    );
+
+    let mut newer = golden.to_vec();
+    newer[4] = 2;
+    test!(
+        "protocol:version_mismatch",
+        Header::parse(&newer) == Err(GpuDecodeError::UnsupportedVersion { version: 2 })
+    );
+
+    let mut older = golden.to_vec();
+    older[4] = 0;
+    test!(
+        "protocol:older_version",
+        Header::parse(&older).is_ok_and(|header| header.version == 0)
+    );
+
+    let sizes_match = payload_commands().iter().all(|command| {
+        let mut bytes = Vec::new();
+        command.serialize(&mut bytes);
+        let payload = OPCODES[command.repr() as usize].payload;
+        payload.size(&bytes[1..]) == Some(bytes.len() - 1)
+    });
+    test!("protocol:payload_sizes", sizes_match);
+}
+
+/// Every opcode, in order, with the bytes it must serialize to.
+#[rustfmt::skip]
+fn golden_commands() -> Vec<(GpuCommand<'static>, Vec<u8>)> {
+    const ONE: &[u8] = &[0, 0, 0x80, 0x3F];
+    const TWO: &[u8] = &[0, 0, 0, 0x40];
+    const ZERO: &[u8] = &[0; 4];
+    vec![
+        (GpuCommand::Push, vec![0x00]),
+        (GpuCommand::Pop, vec![0x01]),
+        (GpuCommand::PushRecord(PrimitiveType::Quads), vec![0x02, 1]),
+        (GpuCommand::PopRecord, vec![0x03]),
+        (GpuCommand::DrawRecorded(0x0403_0201), vec![0x04, 1, 2, 3, 4]),
+        (
+            GpuCommand::EmitVertex(Vertex::new(1.0, 2.0, 0.0, 0.0, 1.0)),
+            [&[0x05], ONE, TWO, ZERO, ZERO, ONE].concat(),
+        ),
+        (GpuCommand::BindTexture(2), vec![0x06, 2, 0, 0, 0]),
+        (
+            GpuCommand::RegisterTexture { w: 1, h: 1, rgba: &[1, 2, 3, 4] },
+            vec![0x07, 1, 0, 0, 0, 1, 0, 0, 0, 1, 2, 3, 4],
+        ),
+        (
+            GpuCommand::Translate { x: 1.0, y: 2.0, z: 0.0 },
+            [&[0x08], ONE, TWO, ZERO].concat(),
+        ),
+        (
+            GpuCommand::RotateAxis { x: 0.0, y: 1.0, z: 0.0, angle: 2.0 },
+            [&[0x09], ZERO, ONE, ZERO, TWO].concat(),
+        ),
+        (
+            GpuCommand::RotateEuler { yaw: 1.0, pitch: 0.0, roll: 2.0 },
+            [&[0x0A], ONE, ZERO, TWO].concat(),
+        ),
+        (
+            GpuCommand::Scale { x: 2.0, y: 2.0, z: 1.0 },
+            [&[0x0B], TWO, TWO, ONE].concat(),
+        ),
+        (GpuCommand::LoadMatrix([1.0; 16]), [&[0x0C], &ONE.repeat(16)[..]].concat()),
+        (GpuCommand::MulMatrix([0.0; 16]), [&[0x0D], &[0; 64][..]].concat()),
+        (GpuCommand::Identity, vec![0x0E]),
+        (
+            GpuCommand::EmitVertices([Vertex::new(0.0, 0.0, 0.0, 1.0, 0.0)].into()),
+            [&[0x0F, 1, 0, 0, 0], ZERO, ZERO, ZERO, ONE, ZERO].concat(),
+        ),
+        (GpuCommand::DeleteRecord(3), vec![0x10, 3, 0, 0, 0]),
+        (GpuCommand::DeleteTexture(4), vec![0x11, 4, 0, 0, 0]),
+        (
+            GpuCommand::UpdateTexture { id: 1, x: 2, y: 3, w: 1, h: 1, rgba: &[9, 8, 7, 6] },
+            vec![0x12, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 9, 8, 7, 6],
+        ),
+        (
+            GpuCommand::ReRecord { id: 5, primitive: PrimitiveType::Lines },
+            vec![0x13, 5, 0, 0, 0, 2],
+        ),
+        (
+            GpuCommand::PushRecordEx { primitive: PrimitiveType::Triangles, format: VertexFormat::Color },
+            vec![0x14, 0, 1],
+        ),
+        (
+            GpuCommand::EmitVerticesEx(VertexData::Color(
+                [VertexColor::new(0.0, 1.0, 0.0, 0.0, 0.0, Color::new(1, 2, 3, 4))].into(),
+            )),
+            [&[0x15, 1, 1, 0, 0, 0], ZERO, ONE, ZERO, ZERO, ZERO, &[1, 2, 3, 4]].concat(),
+        ),
+        (GpuCommand::LineWidth(2.0), [&[0x16], TWO].concat()),
+        (
+            GpuCommand::DrawRecordedInstanced { id: 1, transforms: vec![[0.0; 16]].into() },
+            [&[0x17, 1, 0, 0, 0, 1, 0, 0, 0], &[0; 64][..]].concat(),
+        ),
+        (
+            GpuCommand::DrawRecordedTranslated { id: 2, offsets: vec![[1.0, 0.0, 2.0]].into() },
+            [&[0x18, 2, 0, 0, 0, 1, 0, 0, 0], ONE, ZERO, TWO].concat(),
+        ),
+        (
+            GpuCommand::RegisterTextureEx {
+                w: 2, h: 1, format: TextureFormat::Rgb565, rle: true, data: &[0x81, 0x1F, 0x00],
+            },
+            vec![0x19, 2, 0, 0, 0, 1, 0, 0, 0, 0x81, 3, 0, 0, 0, 0x81, 0x1F, 0x00],
+        ),
+        (
+            GpuCommand::ReRecordEx { id: 6, primitive: PrimitiveType::Points, format: VertexFormat::Lit },
+            vec![0x1A, 6, 0, 0, 0, 4, 2],
+        ),
+    ]
+}
+
+pub fn test_gpu_golden() {
+    let golden = golden_commands();
+    test!(
+        "protocol:golden_every_opcode",
+        golden.len() == OPCODES.len()
+            && golden
+                .iter()
+                .zip(&OPCODES)
+                .all(|((command, _), opcode)| command.repr() == opcode.code)
+    );
+    for (command, bytes) in &golden {
+        let mut serialized = Vec::new();
+        command.serialize(&mut serialized);
+        test!(
+            format!("protocol:golden_{}", OPCODES[command.repr() as usize].name),
+            serialized == *bytes
+                && GpuCommand::deserialize(bytes)
+                    .is_ok_and(|decoded| decoded == (command.clone(), bytes.len()))
+        );
+    }
+}
+
+/// One command of every payload shape, for checking the opcode table against serialization.
+fn payload_commands() -> Vec<GpuCommand<'static>> {
+    vec![
+        GpuCommand::Push,
+        GpuCommand::PushRecord(PrimitiveType::Triangles),
+        GpuCommand::DrawRecorded(1),
+        GpuCommand::EmitVertex(Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0)),
+        GpuCommand::RegisterTexture {
+            w: 2,
+            h: 1,
+            rgba: &[0; 8],
+        },
+        GpuCommand::RotateAxis {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+            angle: 0.0,
+        },
+        GpuCommand::LoadMatrix([0.0; 16]),
+        GpuCommand::EmitVertices([Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0); 3].into()),
+        GpuCommand::DeleteRecord(1),
+        GpuCommand::UpdateTexture {
+            id: 0,
+            x: 1,
+            y: 1,
+            w: 1,
+            h: 2,
+            rgba: &[0; 8],
+        },
+        GpuCommand::ReRecord {
+            id: 1,
+            primitive: PrimitiveType::Quads,
+        },
+        GpuCommand::PushRecordEx {
+            primitive: PrimitiveType::Quads,
+            format: VertexFormat::Color,
+        },
+        GpuCommand::EmitVerticesEx(VertexData::Standard(
+            [Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0); 2].into(),
+        )),
+        GpuCommand::EmitVerticesEx(VertexData::Color(
+            [VertexColor::new(0.0, 0.0, 0.0, 0.0, 0.0, Color::WHITE); 3].into(),
+        )),
+        GpuCommand::EmitVerticesEx(VertexData::Lit(
+            [VertexLit::new(Vec3::zero(), Vec2::zero(), Vec3::unit_z(), Color::WHITE); 2].into(),
+        )),
+        GpuCommand::LineWidth(2.0),
+        GpuCommand::DrawRecordedInstanced {
+            id: 1,
+            transforms: vec![[0.5; 16]; 2].into(),
+        },
+        GpuCommand::DrawRecordedTranslated {
+            id: 1,
+            offsets: vec![[1.0, 2.0, 3.0]; 3].into(),
+        },
+        GpuCommand::RegisterTextureEx {
+            w: 2,
+            h: 2,
+            format: TextureFormat::Palette8,
+            rle: false,
+            data: &[0, 255, 255, 255, 255, 0, 0, 0, 0],
+        },
+        GpuCommand::ReRecordEx {
+            id: 1,
+            primitive: PrimitiveType::Triangles,
+            format: VertexFormat::Color,
+        },
+    ]
+}
+
+pub fn test_gpu_validate() {
+    let mut buffer = GpuCommandBuffer::new();
+    buffer.insert(&GpuCommand::PushRecord(PrimitiveType::Triangles));
+    buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
+        0.0, 0.0, 0.0, 0.0, 0.0,
+    )));
+    buffer.insert(&GpuCommand::PopRecord);
+    buffer.insert(&GpuCommand::Push);
+    buffer.insert(&GpuCommand::DrawRecorded(0));
+    buffer.insert(&GpuCommand::Pop);
+    test!("validate:ok", buffer.validate().is_ok());
+
+    buffer.insert(&GpuCommand::Pop);
+    test!(
+        "validate:matrix_too_small",
+        buffer.validate() == Err(GpuValidationError::MatrixTooSmall { index: 6 })
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::Push);
+    test!(
+        "validate:unbalanced_push",
+        buffer.validate() == Err(GpuValidationError::UnbalancedPush { index: 0 })
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
+        0.0, 0.0, 0.0, 0.0, 0.0,
+    )));
+    test!(
+        "validate:not_recording",
+        buffer.validate() == Err(GpuValidationError::NotRecording { index: 0 })
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::Identity);
+    buffer.insert(&GpuCommand::DrawRecorded(5));
+    buffer.insert(&GpuCommand::BindTexture(0));
+    test!(
+        "validate:unknown_record",
+        buffer.validate() == Err(GpuValidationError::UnknownRecord { index: 1, id: 5 })
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::RegisterTexture {
+        w: 0,
+        h: 4,
+        rgba: &[],
+    });
+    test!(
+        "validate:bad_texture_size",
+        buffer.validate().is_err_and(
+            |e| matches!(e, GpuValidationError::BadTextureSize { .. }) && e.index() == 0
+        )
+    );
+}
+
+pub fn test_gpu_handles() {
+    let mut buffer = GpuCommandBuffer::new();
+    let first = buffer.register_texture(1, 1, &[0; 4]);
+    let second = buffer.register_texture(1, 1, &[0; 4]);
+    test!(
+        "gpu:texture_ids",
+        first == TextureId(0) && second == TextureId(1)
+    );
+
+    let quad = buffer.record(PrimitiveType::Quads, |r| {
+        r.bind_texture(second);
+    });
+    let tri = buffer.record(PrimitiveType::Triangles, |_| {});
+    test!("gpu:record_ids", quad == RecordId(0) && tri == RecordId(1));
+    buffer.draw_recorded(tri);
+    test!("gpu:handles_validate", buffer.validate().is_ok());
+
+    test!(
+        "gpu:status_raw",
+        GpuStatus::from_raw(GB_STATUS_MATRIX_TOO_BIG) == GpuStatus::MatrixTooBig
+            && GpuStatus::from_raw(42).raw() == 42
+    );
+    test!(
+        "gpu:status_result",
+        GpuStatus::Ok.into_result().is_ok()
+            && matches!(
+                GpuStatus::NotRecording.into_result(),
+                Err(GooseboyError::Gpu(GpuStatus::NotRecording))
+            )
+    );
+}
+
+pub fn test_gpu_lifecycle() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+    let quad = [
+        Vertex::new(-1.0, -1.0, 0.0, 0.0, 0.0),
+        Vertex::new(1.0, -1.0, 0.0, 1.0, 0.0),
+        Vertex::new(1.0, 1.0, 0.0, 1.0, 1.0),
+        Vertex::new(-1.0, 1.0, 0.0, 0.0, 1.0),
+    ];
+
+    let texture = buffer.register_texture(2, 2, &[255; 16]);
+    let record = buffer.record(PrimitiveType::Quads, |r| {
+        r.bind_texture(texture);
+        r.insert(&GpuCommand::EmitVertices(quad.into()));
+    });
+    buffer.update_texture(texture, 0, 0, 2, 2, &[0, 0, 255, 255].repeat(4));
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+
+    gpu.camera.z = 2.0;
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(record);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    let center = (8 * 16 + 8) * 4;
+    test!(
+        "gpu:update_texture",
+        target.rgba[center..center + 4] == [0, 0, 255, 255]
+    );
+
+    buffer.clear();
+    buffer.rerecord(
+        record,
+        PrimitiveType::Triangles,
+        VertexFormat::Standard,
+        |r| {
+            r.insert(&GpuCommand::EmitVertices(quad[..3].into()));
+        },
+    );
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:rerecord",
+        gpu.read_value(GB_GPU_RECORD_ID) == record.0 && gpu.record_count() == 1
+    );
+
+    let outside = [(1, 1), (u32::MAX, u32::MAX)].iter().all(|&(x, y)| {
+        buffer.clear();
+        buffer.update_texture(texture, x, y, 2, 2, &[0; 16]);
+        let _ = gpu.execute(buffer.as_bytes(), &mut target);
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_BAD_TEXTURE_SIZE
+    });
+    test!("gpu:update_texture_bounds", outside);
+
+    buffer.clear();
+    buffer.delete_record(record);
+    buffer.delete_texture(texture);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:delete",
+        gpu.record_count() == 0 && gpu.texture_count() == 0
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::DrawRecorded(record.0));
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:deleted_record",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_UNKNOWN_RECORD
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::BindTexture(texture.0));
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:deleted_texture",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_BAD_TEXTURE
+    );
+
+    buffer.clear();
+    let record = buffer.record(PrimitiveType::Triangles, |_| {});
+    buffer.rerecord(record, PrimitiveType::Quads, VertexFormat::Standard, |_| {});
+    test!("validate:rerecord", buffer.validate().is_ok());
+    buffer.delete_record(record);
+    buffer.draw_recorded(record);
+    test!(
+        "validate:deleted_record",
+        buffer.validate()
+            == Err(GpuValidationError::UnknownRecord {
+                index: 5,
+                id: record.0
+            })
+    );
+
+    buffer.clear();
+    let record = buffer.record(PrimitiveType::Triangles, |_| {});
+    buffer.rerecord(record, PrimitiveType::Triangles, VertexFormat::Color, |r| {
+        r.insert(&GpuCommand::EmitVertex(Vertex::new(
+            0.0, 0.0, 0.0, 0.0, 0.0,
+        )));
+    });
+    test!(
+        "validate:rerecord_format",
+        buffer.validate()
+            == Err(GpuValidationError::VertexFormatMismatch {
+                index: 3,
+                expected: VertexFormat::Color,
+                actual: VertexFormat::Standard,
+            })
+    );
+}
+
+pub fn test_gpu_vertex_formats() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+    let center = (8 * 16 + 8) * 4;
+    let quad = |color| {
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| VertexLit::new(Vec3::new(x, y, 0.0), Vec2::zero(), Vec3::unit_z(), color))
+    };
+
+    let colored = buffer.record_ex(PrimitiveType::Quads, VertexFormat::Color, |r| {
+        let vertices =
+            quad(Color::GREEN).map(|v| VertexColor::new(v.x, v.y, v.z, v.u, v.v, v.color));
+        r.insert(&GpuCommand::EmitVerticesEx(VertexData::Color(
+            vertices.into(),
+        )));
+    });
+    let lit = buffer.record_ex(PrimitiveType::Quads, VertexFormat::Lit, |r| {
+        r.insert(&GpuCommand::EmitVerticesEx(VertexData::Lit(
+            quad(Color::WHITE).into(),
+        )));
+    });
+    test!("validate:vertex_formats", buffer.validate().is_ok());
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+
+    gpu.camera.z = 2.0;
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(colored);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:vertex_color",
+        target.rgba[center..center + 4] == [0, 255, 0, 255]
+    );
+
+    gpu.light_direction = -Vec3::unit_z();
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(lit);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:vertex_lit",
+        target.rgba[center..center + 4] == [255, 255, 255, 255]
+    );
+
+    gpu.light_direction = Vec3::unit_z();
+    gpu.ambient = 0.5;
+    gpu.begin_frame(&mut target, Color::BLACK);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:vertex_ambient",
+        target.rgba[center..center + 4] == [128, 128, 128, 255]
+    );
+
+    gpu.ambient = 1.0;
+    buffer.clear();
+    buffer.rerecord(colored, PrimitiveType::Quads, VertexFormat::Lit, |r| {
+        r.insert(&GpuCommand::EmitVerticesEx(VertexData::Lit(
+            quad(Color::BLUE).into(),
+        )));
+    });
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(colored);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:rerecord_format",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_OK
+            && target.rgba[center..center + 4] == [0, 0, 255, 255]
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::PushRecordEx {
+        primitive: PrimitiveType::Triangles,
+        format: VertexFormat::Color,
+    });
+    buffer.insert(&GpuCommand::EmitVertex(Vertex::new(
+        0.0, 0.0, 0.0, 0.0, 0.0,
+    )));
+    test!(
+        "validate:vertex_format_mismatch",
+        buffer.validate()
+            == Err(GpuValidationError::VertexFormatMismatch {
+                index: 1,
+                expected: VertexFormat::Color,
+                actual: VertexFormat::Standard,
+            })
+    );
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:bad_vertex_format",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_BAD_VERTEX_FORMAT
+            && GpuStatus::from_raw(GB_STATUS_BAD_VERTEX_FORMAT) == GpuStatus::BadVertexFormat
+    );
+}
+
+pub fn test_gpu_vertex_decode() {
+    let commands = [
+        GpuCommand::PushRecordEx {
+            primitive: PrimitiveType::Triangles,
+            format: VertexFormat::Lit,
+        },
+        GpuCommand::EmitVerticesEx(VertexData::Lit(
+            [VertexLit::new(
+                Vec3::new(1.0, 2.0, 3.0),
+                Vec2::new(0.5, 0.25),
+                Vec3::unit_y(),
+                Color::RED,
+            )]
+            .into(),
+        )),
+        GpuCommand::EmitVerticesEx(VertexData::Color(
+            [VertexColor::new(1.0, 2.0, 3.0, 0.5, 0.25, Color::BLUE)].into(),
+        )),
+        GpuCommand::PopRecord,
+        GpuCommand::PushRecord(PrimitiveType::LineStrip),
+        GpuCommand::LineWidth(2.5),
+        GpuCommand::DrawRecordedInstanced {
+            id: 4,
+            transforms: vec![Mat4::<f32>::identity().into_col_array()].into(),
+        },
+        GpuCommand::DrawRecordedTranslated {
+            id: 4,
+            offsets: (&[[0.5, -1.0, 2.0]][..]).into(),
+        },
+    ];
+    let mut buffer = GpuCommandBuffer::new();
+    for command in &commands {
+        buffer.insert(command);
+    }
+    let decoded: Result<Vec<GpuCommand>, GpuDecodeError> = buffer.commands().collect();
+    test!(
+        "gpu:decode_vertex_formats",
+        decoded.is_ok_and(|decoded| decoded == commands)
+    );
+    test!(
+        "gpu:decode_vertex_format",
+        GpuCommand::deserialize(&[0x14, 0, 9])
+            == Err(GpuDecodeError::UnknownVertexFormat { offset: 0, repr: 9 })
+    );
+}
+
+pub fn test_gpu_lines() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+    let pixel = |target: &Surface, x: usize, y: usize| {
+        let i = (y * 16 + x) * 4;
+        [target.rgba[i], target.rgba[i + 1], target.rgba[i + 2]]
+    };
+
+    let line = buffer.record_ex(PrimitiveType::Lines, VertexFormat::Color, |r| {
+        r.insert(&GpuCommand::LineWidth(3.0));
+        r.insert(&GpuCommand::EmitVerticesEx(VertexData::Color(
+            [
+                VertexColor::new(-1.0, 0.0, 0.0, 0.0, 0.0, Color::RED),
+                VertexColor::new(1.0, 0.0, 0.0, 0.0, 0.0, Color::RED),
+            ]
+            .into(),
+        )));
+    });
+    let point = buffer.record(PrimitiveType::Points, |r| {
+        r.insert(&GpuCommand::LineWidth(4.0));
+        r.insert(&GpuCommand::EmitVertex(Vertex::new(
+            0.0, 0.0, 0.0, 0.0, 0.0,
+        )));
+    });
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+
+    gpu.camera.z = 2.0;
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(line);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:lines",
+        pixel(&target, 2, 8) == [255, 0, 0]
+            && pixel(&target, 8, 7) == [255, 0, 0]
+            && pixel(&target, 8, 4) == [0, 0, 0]
+    );
+
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded(point);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:points",
+        pixel(&target, 7, 7) == [255, 255, 255]
+            && pixel(&target, 9, 9) == [255, 255, 255]
+            && pixel(&target, 3, 8) == [0, 0, 0]
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::LineWidth(2.0));
+    test!(
+        "validate:line_width",
+        buffer.validate() == Err(GpuValidationError::NotRecording { index: 0 })
+    );
+
+    let mut debug = DebugDraw::new();
+    debug.aabb(&Aabb::new_empty(Vec3::zero()), Color::GREEN);
+    debug.sphere(Vec3::zero(), 1.0, Color::RED);
+    debug.grid(Vec3::zero(), 4.0, 2, Color::GRAY);
+    debug.axes(Vec3::zero(), 1.0);
+    test!("debug_draw:lines", debug.line_count() == 12 + 72 + 6 + 3);
+
+    buffer.clear();
+    let first = debug.flush(&mut buffer);
+    debug.ray(Vec3::zero(), Vec3::unit_y(), Color::YELLOW);
+    let second = debug.flush(&mut buffer);
+    test!(
+        "debug_draw:flush",
+        first.is_some()
+            && second.is_some()
+            && first == second
+            && debug.is_empty()
+            && debug.flush(&mut buffer).is_none()
+            && buffer.validate().is_ok()
+            && buffer
+                .commands()
+                .all(|command| !matches!(command, Ok(GpuCommand::DeleteRecord(_))))
+    );
+}
+
+pub fn test_gpu_instanced() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+    let pixel = |target: &Surface, x: usize, y: usize| {
+        let i = (y * 16 + x) * 4;
+        [target.rgba[i], target.rgba[i + 1], target.rgba[i + 2]]
+    };
+
+    let quad = buffer.record(PrimitiveType::Quads, |r| {
+        r.insert(&GpuCommand::EmitVertices(
+            [
+                Vertex::new(-0.2, -0.2, 0.0, 0.0, 0.0),
+                Vertex::new(0.2, -0.2, 0.0, 0.0, 0.0),
+                Vertex::new(0.2, 0.2, 0.0, 0.0, 0.0),
+                Vertex::new(-0.2, 0.2, 0.0, 0.0, 0.0),
+            ]
+            .into(),
+        ));
+    });
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    gpu.camera.z = 2.0;
+
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.draw_recorded_translated(quad, &[Vec3::new(-0.8, 0.0, 0.0), Vec3::new(0.8, 0.0, 0.0)]);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:draw_translated",
+        pixel(&target, 3, 8) == [255; 3]
+            && pixel(&target, 12, 8) == [255; 3]
+            && pixel(&target, 8, 8) == [0; 3]
+    );
+
+    gpu.begin_frame(&mut target, Color::BLACK);
+    buffer.clear();
+    buffer.insert(&GpuCommand::Push);
+    buffer.insert(&GpuCommand::Translate {
+        x: 0.0,
+        y: 0.8,
+        z: 0.0,
+    });
+    buffer.draw_recorded_instanced(
+        quad,
+        &[
+            Mat4::identity(),
+            Mat4::translation_3d(Vec3::new(0.0, -1.6, 0.0)),
+        ],
+    );
+    buffer.insert(&GpuCommand::Pop);
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:draw_instanced",
+        pixel(&target, 8, 3) == [255; 3]
+            && pixel(&target, 8, 12) == [255; 3]
+            && pixel(&target, 8, 8) == [0; 3]
+    );
+
+    buffer.clear();
+    buffer.draw_recorded_translated(RecordId(quad.0 + 1), &[Vec3::zero()]);
+    test!(
+        "validate:instanced_record",
+        buffer.validate()
+            == Err(GpuValidationError::UnknownRecord {
+                index: 0,
+                id: quad.0 + 1
+            })
+    );
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:instanced_unknown_record",
+        gpu.read_value(GB_GPU_STATUS) == GB_STATUS_UNKNOWN_RECORD
+    );
+
+    let transforms = [Mat4::translation_3d(Vec3::new(1.0, 2.0, 3.0)); 100];
+    buffer.clear();
+    buffer.draw_recorded_instanced(quad, &transforms);
+    let instanced = buffer.as_bytes().len();
+    buffer.clear();
+    buffer.draw_recorded_translated(quad, &[Vec3::new(1.0, 2.0, 3.0); 100]);
+    let translated = buffer.as_bytes().len();
+    buffer.clear();
+    for transform in transforms {
+        buffer.insert(&GpuCommand::Push);
+        buffer.insert(&GpuCommand::MulMatrix(transform.into_col_array()));
+        buffer.draw_recorded(quad);
+        buffer.insert(&GpuCommand::Pop);
+    }
+    test!(
+        "gpu:instanced_size",
+        instanced < buffer.as_bytes().len() && translated * 5 < buffer.as_bytes().len()
+    );
+}
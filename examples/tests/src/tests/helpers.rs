@@ -0,0 +1,15 @@
+//! Helpers shared between the tests.
+use gooseboy::{Vec3, framebuffer::Surface};
+
+/// Returns the RGBA of a pixel of the surface, or zeroes if it's outside of it.
+pub fn pixel(surface: &Surface, x: usize, y: usize) -> [u8; 4] {
+    surface
+        .get_pixel_index(x, y)
+        .and_then(|index| surface.rgba[index..].first_chunk::<4>().copied())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if two points are within a rounding error of each other.
+pub fn close(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+    a.distance(b) < 1e-4
+}
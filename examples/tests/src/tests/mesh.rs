@@ -0,0 +1,145 @@
+use gooseboy::{
+    Mat4, Vec3,
+    gpu::{GpuCommand, GpuCommandBuffer, RecordId, Vertex, VertexData, VertexFormat},
+    mesh::{MESH_MAGIC, Mesh, MeshDecodeError},
+};
+
+use crate::test;
+
+pub fn test_mesh() {
+    let quad = [
+        Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0),
+        Vertex::new(1.0, 0.0, 0.0, 1.0, 0.0),
+        Vertex::new(1.0, 1.0, 0.0, 1.0, 1.0),
+        Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0),
+        Vertex::new(1.0, 1.0, 0.0, 1.0, 1.0),
+        Vertex::new(0.0, 1.0, 0.0, 0.0, 1.0),
+    ];
+    let mesh = Mesh::from_vertices(&quad);
+    test!(
+        "mesh:dedup",
+        mesh.vertex_count() == 4 && mesh.triangle_count() == 2
+    );
+    test!("mesh:to_vertices", mesh.to_vertices() == quad);
+
+    let cube = Mesh::cube(2.0);
+    test!(
+        "mesh:cube",
+        cube.vertex_count() == 24 && cube.triangle_count() == 12
+    );
+    test!(
+        "mesh:bounds",
+        cube.bounds().is_some_and(|bounds| {
+            bounds.min == Vec3::broadcast(-1.0) && bounds.max == Vec3::broadcast(1.0)
+        }) && Mesh::new().bounds().is_none()
+    );
+    test!(
+        "mesh:winding",
+        cube.indices
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .enumerate()
+            .all(|(i, tri)| {
+                let [a, b, c] = tri.map(|i| cube.positions[i as usize]);
+                let normal = cube
+                    .normals
+                    .as_ref()
+                    .map_or_else(Vec3::zero, |n| n[i / 2 * 4]);
+                (b - a).cross(c - a).dot(normal) > 0.0
+            })
+    );
+
+    let mut moved = Mesh::cube(2.0);
+    moved
+        .translate(Vec3::new(1.0, 0.0, 0.0))
+        .transform(&Mat4::scaling_3d(Vec3::broadcast(2.0)));
+    test!(
+        "mesh:transform",
+        moved.bounds().is_some_and(|bounds| {
+            bounds.min == Vec3::new(0.0, -2.0, -2.0) && bounds.max == Vec3::new(4.0, 2.0, 2.0)
+        }) && moved.normals == cube.normals
+    );
+
+    let grid = Mesh::grid(4.0, 4.0, 4, 2);
+    test!(
+        "mesh:grid",
+        grid.vertex_count() == 15 && grid.triangle_count() == 16
+    );
+
+    let sphere = Mesh::uv_sphere(1.0, 8, 4);
+    test!(
+        "mesh:sphere",
+        sphere.triangle_count() == 8 * 2 + 8 * 2 * 2
+            && sphere
+                .positions
+                .iter()
+                .all(|p| (p.magnitude() - 1.0).abs() < 1e-5)
+    );
+
+    let cylinder = Mesh::cylinder(1.0, 2.0, 6);
+    test!(
+        "mesh:cylinder",
+        cylinder.triangle_count() == 6 * 4
+            && cylinder
+                .bounds()
+                .is_some_and(|bounds| (bounds.max.y - bounds.min.y - 2.0).abs() < 1e-5)
+    );
+
+    let mut smooth = Mesh::from_vertices(&quad);
+    smooth.compute_normals();
+    test!(
+        "mesh:compute_normals",
+        smooth
+            .normals
+            .is_some_and(|normals| normals.iter().all(|n| *n == Vec3::unit_z()))
+    );
+
+    let mut buffer = GpuCommandBuffer::new();
+    let record = mesh.record(&mut buffer);
+    test!(
+        "mesh:record",
+        record == RecordId(0) && buffer.command_count() == 3 && buffer.validate().is_ok()
+    );
+
+    test!(
+        "mesh:vertex_format",
+        mesh.vertex_format() == VertexFormat::Standard
+            && sphere.vertex_format() == VertexFormat::Lit
+            && matches!(sphere.to_vertex_data(), VertexData::Lit(v) if v.len() == sphere.indices.len())
+    );
+}
+
+pub fn test_mesh_record() {
+    let sphere = Mesh::uv_sphere(1.0, 8, 4);
+    let mut buffer = GpuCommandBuffer::new();
+    let lit = sphere.record(&mut buffer);
+    test!("mesh:record_lit", buffer.validate().is_ok());
+
+    let over = sphere.record_over(&mut buffer, Some(lit));
+    test!(
+        "mesh:record_over",
+        over == lit
+            && buffer
+                .commands()
+                .any(|c| c.is_ok_and(|c| matches!(c, GpuCommand::ReRecordEx { .. })))
+            && buffer.validate().is_ok()
+    );
+}
+
+pub fn test_mesh_bytes() {
+    let sphere = Mesh::uv_sphere(1.0, 8, 4);
+    let bytes = sphere.to_bytes();
+    test!(
+        "mesh:bytes_roundtrip",
+        bytes[..4] == MESH_MAGIC && Mesh::from_bytes(&bytes).is_ok_and(|decoded| decoded == sphere)
+    );
+    test!(
+        "mesh:bytes_truncated",
+        Mesh::from_bytes(&bytes[..bytes.len() - 1]) == Err(MeshDecodeError::UnexpectedEnd)
+    );
+    test!(
+        "mesh:bytes_magic",
+        Mesh::from_bytes(&[0; 16]) == Err(MeshDecodeError::BadMagic)
+    );
+}
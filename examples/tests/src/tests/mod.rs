@@ -1,7 +1,18 @@
+pub mod animation;
+pub mod atlas;
+pub mod buildscript;
+pub mod camera;
 pub mod color;
 pub mod framebuffer;
+pub mod gpu;
+pub mod helpers;
 pub mod mem;
+pub mod mesh;
+pub mod scene;
 pub mod sprite;
 pub mod storage;
 pub mod text;
+pub mod texture;
 pub mod timer;
+pub mod vector;
+pub mod voxel;
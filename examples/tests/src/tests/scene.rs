@@ -0,0 +1,136 @@
+use gooseboy::{
+    Aabb, Mat4, Vec3,
+    camera::{Camera, CameraTransform},
+    gpu::{GpuCommand, GpuCommandBuffer, RecordId},
+    scene::{Node, RenderStats, Scene},
+};
+
+use crate::test;
+
+const UNIT_BOX: Option<Aabb<f32>> = Some(Aabb {
+    min: Vec3::new(-0.5, -0.5, -0.5),
+    max: Vec3::new(0.5, 0.5, 0.5),
+});
+
+fn render(scene: &Scene, camera: &Camera) -> (RenderStats, GpuCommandBuffer) {
+    let mut buffer = GpuCommandBuffer::new();
+    let stats = scene.render_frustum(&camera.frustum(4.0 / 3.0), &mut buffer);
+    (stats, buffer)
+}
+
+fn decode(buffer: &GpuCommandBuffer) -> Vec<GpuCommand<'_>> {
+    buffer
+        .commands()
+        .collect::<Result<_, _>>()
+        .unwrap_or_default()
+}
+
+pub fn test_scene() {
+    let mut scene = Scene::new();
+    let root = scene.add(Node::new());
+    let child = scene.add_child(root, Node::with_record(RecordId(1), UNIT_BOX));
+    let grandchild = scene.add_child(child, Node::with_record(RecordId(2), UNIT_BOX));
+    scene.node_mut(root).unwrap().transform.position = Vec3::new(0.0, 0.0, -5.0);
+    scene.node_mut(child).unwrap().transform.scale = Vec3::broadcast(2.0);
+    scene.node_mut(grandchild).unwrap().transform.position = Vec3::new(1.0, 0.0, 0.0);
+
+    test!(
+        "scene:hierarchy",
+        scene.len() == 3
+            && scene.roots() == [root]
+            && scene.node(grandchild).unwrap().parent() == Some(child)
+            && scene.node(root).unwrap().children() == [child]
+            && scene.ancestors(grandchild).eq([grandchild, child, root])
+    );
+    test!(
+        "scene:world_matrix",
+        scene.world_matrix(grandchild).is_some_and(|m| m
+            .mul_point(Vec3::zero())
+            .distance(Vec3::new(2.0, 0.0, -5.0))
+            < 1e-5)
+    );
+    test!(
+        "scene:set_parent_cycle",
+        !scene.set_parent(root, Some(grandchild)) && !scene.set_parent(child, Some(child))
+    );
+
+    let camera = Camera::new();
+    let (stats, buffer) = render(&scene, &camera);
+    let commands = decode(&buffer);
+    let root_matrix = scene.node(root).unwrap().local_matrix().into_col_array();
+    test!(
+        "scene:render",
+        stats
+            == RenderStats {
+                drawn: 2,
+                culled: 0
+            }
+            && commands.len() == 11
+            && commands[..2] == [GpuCommand::Push, GpuCommand::MulMatrix(root_matrix)]
+            && commands[4] == GpuCommand::DrawRecorded(1)
+            && commands[7..]
+                == [
+                    GpuCommand::DrawRecorded(2),
+                    GpuCommand::Pop,
+                    GpuCommand::Pop,
+                    GpuCommand::Pop
+                ]
+    );
+
+    let behind = Camera::from_transform(CameraTransform {
+        yaw: std::f32::consts::PI,
+        ..CameraTransform::default()
+    });
+    let (stats, buffer) = render(&scene, &behind);
+    test!(
+        "scene:cull",
+        stats
+            == RenderStats {
+                drawn: 0,
+                culled: 2
+            }
+            && buffer.command_count() == 0
+    );
+
+    scene.node_mut(child).unwrap().record = None;
+    scene.node_mut(grandchild).unwrap().transform.position = Vec3::new(100.0, 0.0, 0.0);
+    let (stats, buffer) = render(&scene, &camera);
+    test!(
+        "scene:cull_subtree",
+        stats
+            == RenderStats {
+                drawn: 0,
+                culled: 1
+            }
+            && buffer.command_count() == 0
+    );
+
+    let free = scene.add(Node::with_record(RecordId(3), None));
+    let (stats, buffer) = render(&scene, &behind);
+    test!(
+        "scene:identity_unculled",
+        stats.drawn == 1 && decode(&buffer) == [GpuCommand::DrawRecorded(3)]
+    );
+
+    scene.node_mut(free).unwrap().visible = false;
+    test!(
+        "scene:invisible",
+        render(&scene, &camera).1.command_count() == 0
+    );
+
+    test!(
+        "scene:set_parent",
+        scene.set_parent(grandchild, None)
+            && scene.roots() == [root, free, grandchild]
+            && scene.node(child).unwrap().children().is_empty()
+            && scene.world_matrix(grandchild)
+                == Some(Mat4::translation_3d(Vec3::new(100.0, 0.0, 0.0)))
+    );
+    test!(
+        "scene:remove",
+        scene.remove(root).is_some()
+            && scene.node(child).is_none()
+            && scene.len() == 2
+            && scene.remove(root).is_none()
+    );
+}
@@ -1,12 +1,15 @@
+use std::f32::consts::FRAC_PI_2;
+
 use gooseboy::{
+    Vec2,
     bindings::Pointer,
     color::Color,
-    framebuffer::{clear_framebuffer, get_framebuffer_ptr, get_pixel_index},
-    sprite::Sprite,
+    framebuffer::{Surface, clear_framebuffer, get_framebuffer_ptr, get_pixel_index},
+    sprite::{BlitOptions, Filter, Sprite},
     unsafe_casts,
 };
 
-use crate::test;
+use crate::{test, tests::helpers::pixel};
 
 unsafe fn read_pixel_rgba(fb_ptr: Pointer, x: usize, y: usize) -> Option<[u8; 4]> {
     get_pixel_index(x, y).map(|idx| {
@@ -64,3 +67,71 @@ pub fn test_sprite() {
 
     test!("sprite:blit_all_pixels_opaque", ok);
 }
+
+pub fn test_sprite_blit_ex() {
+    let (a, b, c, d) = ([40, 0, 0, 255], [0, 80, 0, 255], [0, 0, 120, 255], [160; 4]);
+    let sprite = Sprite::new(2, 2, &[a, b, c, d].concat());
+    let mut surface = Surface::new_empty(16, 16);
+
+    let options = BlitOptions {
+        scale: Vec2::broadcast(2.0),
+        flip_x: true,
+        ..BlitOptions::default()
+    };
+    sprite.blit_ex_to(&mut surface, 0, 0, &options);
+    test!(
+        "sprite:blit_ex_scaled",
+        pixel(&surface, 1, 1) == b && pixel(&surface, 2, 0) == a && pixel(&surface, 0, 3) == d
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let options = BlitOptions {
+        origin: Vec2::new(1.0, 1.0),
+        ..BlitOptions::default()
+    };
+    sprite.blit_ex_to(&mut surface, 5, 5, &options);
+    test!(
+        "sprite:blit_ex_origin",
+        pixel(&surface, 4, 4) == a && pixel(&surface, 5, 5) == d
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let options = BlitOptions {
+        rotation: FRAC_PI_2,
+        origin: Vec2::new(1.0, 1.0),
+        ..BlitOptions::default()
+    };
+    sprite.blit_ex_to(&mut surface, 8, 8, &options);
+    test!(
+        "sprite:blit_ex_rotated",
+        pixel(&surface, 8, 7) == a
+            && pixel(&surface, 7, 7) == c
+            && pixel(&surface, 8, 8) == b
+            && pixel(&surface, 7, 8) == d
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    let options = BlitOptions {
+        scale: Vec2::broadcast(1.5),
+        filter: Filter::Bilinear,
+        ..BlitOptions::default()
+    };
+    sprite.blit_ex_to(&mut surface, 0, 0, &options);
+    test!(
+        "sprite:blit_ex_bilinear",
+        pixel(&surface, 1, 1) == [50, 60, 70, 231] && pixel(&surface, 3, 3) == [0; 4]
+    );
+
+    surface.clear(Color::BLACK);
+    let white = Sprite::new(1, 1, &[255; 4]);
+    let options = BlitOptions {
+        tint: Color::new_opaque(255, 0, 255),
+        alpha: 128,
+        ..BlitOptions::default()
+    };
+    white.blit_ex_to(&mut surface, 0, 0, &options);
+    test!(
+        "sprite:blit_ex_tint",
+        pixel(&surface, 0, 0) == [128, 0, 128, 255]
+    );
+}
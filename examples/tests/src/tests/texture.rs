@@ -0,0 +1,201 @@
+use gooseboy::{
+    framebuffer::Surface,
+    gpu::{
+        GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE, GB_STATUS_OK, GpuCommand, GpuCommandBuffer,
+        GpuValidationError, software::SoftwareGpu,
+    },
+    texture::{self, EncodedTexture, TEXTURE_HEADER_SIZE, TextureError, TextureFormat},
+};
+
+use crate::test;
+
+/// A 4x4 image with a few colors, a run of transparent pixels and exact 4 bit channels.
+fn image() -> Vec<u8> {
+    let mut rgba = [0x11, 0x22, 0x33, 0xFF].repeat(6);
+    rgba.extend([0, 0, 0, 0].repeat(6));
+    rgba.extend([0xFF, 0x88, 0x00, 0x77].repeat(4));
+    rgba
+}
+
+/// A 16x17 opaque gradient, with too many colors for a palette.
+fn gradient() -> Vec<u8> {
+    (0..=255u8)
+        .flat_map(|i| [i, 255 - i, i & 0xF8, 255])
+        .chain((1..=16u8).flat_map(|i| [i, 2, 3, 255]))
+        .collect()
+}
+
+fn roundtrips(rgba: &[u8], width: u32, height: u32, format: TextureFormat) -> bool {
+    [false, true].iter().all(|&rle| {
+        EncodedTexture::encode(width, height, rgba, format, rle)
+            .and_then(|texture| texture.decode())
+            .is_ok_and(|decoded| decoded == rgba)
+    })
+}
+
+pub fn test_texture() {
+    let rgba = image();
+    test!(
+        "texture:roundtrip",
+        roundtrips(&rgba, 4, 4, TextureFormat::Rgba8)
+            && roundtrips(&rgba, 4, 4, TextureFormat::Rgba4444)
+            && roundtrips(&rgba, 4, 4, TextureFormat::Palette8)
+            && roundtrips(&rgba, 2, 8, TextureFormat::Palette8)
+    );
+
+    let palette = EncodedTexture::encode(4, 4, &rgba, TextureFormat::Palette8, true);
+    test!(
+        "texture:palette",
+        palette.as_ref().is_ok_and(|texture| {
+            // 3 colors, then a run of 6, a run of 6 and a run of 4 indices.
+            texture.data.len() == 1 + 3 * 4 + 3 * 2 && texture.data[0] == 2
+        })
+    );
+
+    let opaque = [255, 0, 255, 255].repeat(4);
+    let rgb565 = EncodedTexture::encode(2, 2, &opaque, TextureFormat::Rgb565, false);
+    test!(
+        "texture:rgb565",
+        rgb565.as_ref().is_ok_and(|texture| {
+            texture.data == [0x1F, 0xF8].repeat(4)
+                && texture.decode().is_ok_and(|decoded| decoded == opaque)
+        })
+    );
+
+    let gradient = gradient();
+    let lossy = EncodedTexture::encode_best(16, 17, &gradient, true);
+    test!(
+        "texture:choose_format",
+        texture::choose_format(&rgba, false) == TextureFormat::Palette8
+            && texture::choose_format(&gradient, false) == TextureFormat::Rgba8
+            && texture::choose_format(&gradient, true) == TextureFormat::Rgb565
+            && lossy.is_ok_and(|texture| {
+                texture.format == TextureFormat::Rgb565 && texture.data.len() <= 16 * 17 * 2
+            })
+    );
+
+    let best = EncodedTexture::encode_best(4, 4, &rgba, false);
+    test!(
+        "texture:bytes",
+        best.as_ref().is_ok_and(|texture| {
+            let bytes = texture.to_bytes();
+            texture.rle
+                && bytes.len() == TEXTURE_HEADER_SIZE + texture.data.len()
+                && EncodedTexture::from_bytes(&bytes).as_ref() == Ok(texture)
+                && texture.to_sprite().is_ok_and(|sprite| sprite.rgba == rgba)
+        })
+    );
+
+    test_texture_errors();
+    test_texture_gpu();
+}
+
+fn test_texture_errors() {
+    let rgba = image();
+    let mut bytes = EncodedTexture::encode(4, 4, &rgba, TextureFormat::Rgba8, false)
+        .map(|texture| texture.to_bytes())
+        .unwrap_or_default();
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    let mut version = bytes.clone();
+    version[4] = 2;
+    let mut format = bytes.clone();
+    format[14] = 9;
+    let mut huge = bytes.clone();
+    huge[6..14].fill(0xFF);
+    test!(
+        "texture:bad_header",
+        EncodedTexture::from_bytes(&magic) == Err(TextureError::BadMagic)
+            && EncodedTexture::from_bytes(&version)
+                == Err(TextureError::UnsupportedVersion { version: 2 })
+            && EncodedTexture::from_bytes(&format) == Err(TextureError::UnknownFormat { repr: 9 })
+            && EncodedTexture::from_bytes(&bytes[..10]) == Err(TextureError::UnexpectedEnd)
+    );
+    test!(
+        "texture:huge_size",
+        EncodedTexture::from_bytes(&huge).and_then(|texture| texture.decode())
+            == Err(TextureError::BadSize {
+                len: rgba.len(),
+                expected: usize::MAX
+            })
+            && EncodedTexture::encode(u32::MAX, u32::MAX, &rgba, TextureFormat::Rgba8, false)
+                .is_err_and(|error| matches!(error, TextureError::BadSize { .. }))
+    );
+
+    bytes.push(0);
+    test!(
+        "texture:bad_data",
+        EncodedTexture::from_bytes(&bytes).and_then(|texture| texture.decode())
+            == Err(TextureError::TrailingBytes { len: 1 })
+            && texture::decode(2, 1, TextureFormat::Rgb565, true, &[0x82, 0, 0])
+                == Err(TextureError::BadRun)
+            && texture::decode(2, 1, TextureFormat::Rgb565, true, &[0x81, 0])
+                == Err(TextureError::UnexpectedEnd)
+            && texture::decode(1, 1, TextureFormat::Palette8, false, &[0, 1, 2, 3, 4, 1])
+                == Err(TextureError::BadPaletteIndex { index: 1 })
+    );
+
+    let colors: Vec<u8> = (0..=255u8)
+        .flat_map(|i| [i, 0, 0, 255])
+        .chain([0, 1, 0, 255])
+        .collect();
+    test!(
+        "texture:too_many_colors",
+        EncodedTexture::encode(257, 1, &colors, TextureFormat::Palette8, false)
+            == Err(TextureError::TooManyColors)
+            && EncodedTexture::encode(2, 2, &colors, TextureFormat::Rgba8, false)
+                == Err(TextureError::BadSize {
+                    len: 257 * 4,
+                    expected: 16
+                })
+    );
+}
+
+fn test_texture_gpu() {
+    let mut gpu = SoftwareGpu::new();
+    let mut target = Surface::new_empty(16, 16);
+    let mut buffer = GpuCommandBuffer::new();
+
+    let rgba = image();
+    let Ok(encoded) = EncodedTexture::encode_best(4, 4, &rgba, false) else {
+        test!("gpu:register_encoded", false);
+        return;
+    };
+    let raw = buffer.register_texture(4, 4, &rgba);
+    let id = buffer.register_encoded(&encoded);
+    test!(
+        "gpu:register_encoded",
+        id.0 == raw.0 + 1
+            && buffer.validate().is_ok()
+            && buffer
+                .to_string()
+                .contains("register_texture_ex 4x4 palette8 rle")
+    );
+
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:register_encoded_execute",
+        gpu.texture_count() == 2 && gpu.read_value(GB_GPU_STATUS) == GB_STATUS_OK
+    );
+
+    buffer.clear();
+    buffer.insert(&GpuCommand::RegisterTextureEx {
+        w: 4,
+        h: 4,
+        format: TextureFormat::Rgb565,
+        rle: true,
+        data: &[0x90, 0, 0],
+    });
+    let invalid = buffer.validate();
+    let _ = gpu.execute(buffer.as_bytes(), &mut target);
+    test!(
+        "gpu:register_encoded_invalid",
+        invalid
+            == Err(GpuValidationError::BadEncodedTexture {
+                index: 0,
+                error: TextureError::BadRun
+            })
+            && gpu.read_value(GB_GPU_STATUS) == GB_STATUS_BAD_TEXTURE
+            && gpu.texture_count() == 2
+    );
+}
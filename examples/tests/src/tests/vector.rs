@@ -0,0 +1,250 @@
+use gooseboy::{
+    Mat3, Rect, Vec2,
+    color::Color,
+    framebuffer::Surface,
+    vector::{
+        FillRule, GradientStop, LineCap, LineJoin, Paint, Path, Stroke, fill_path, stroke_path,
+    },
+};
+
+use crate::{test, tests::helpers::pixel};
+
+const WHITE: Paint = Paint::Solid(Color::WHITE);
+
+/// Returns the total alpha of the surface, 255 per fully covered pixel.
+fn total_alpha(surface: &Surface) -> u32 {
+    surface
+        .rgba
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .map(|pixel| u32::from(pixel[3]))
+        .sum()
+}
+
+fn stroked(path: &Path, stroke: &Stroke) -> Surface {
+    let mut surface = Surface::new_empty(16, 16);
+    stroke_path(&mut surface, path, &WHITE, stroke);
+    surface
+}
+
+pub fn test_vector() {
+    let mut surface = Surface::new_empty(16, 16);
+    fill_path(
+        &mut surface,
+        &Path::rect(Rect::new(2.0, 2.0, 4.0, 4.0)),
+        &WHITE,
+        FillRule::NonZero,
+    );
+    test!(
+        "vector:fill_rect",
+        total_alpha(&surface) == 16 * 255
+            && pixel(&surface, 2, 2)[3] == 255
+            && pixel(&surface, 6, 2)[3] == 0
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    fill_path(
+        &mut surface,
+        &Path::rect(Rect::new(2.5, 2.0, 4.0, 4.0)),
+        &WHITE,
+        FillRule::NonZero,
+    );
+    test!(
+        "vector:antialiased",
+        (120..=136).contains(&pixel(&surface, 2, 3)[3])
+            && (120..=136).contains(&pixel(&surface, 6, 3)[3])
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    fill_path(
+        &mut surface,
+        &Path::circle(Vec2::new(8.0, 8.0), 5.0),
+        &WHITE,
+        FillRule::NonZero,
+    );
+    test!(
+        "vector:circle_area",
+        (76 * 255..=80 * 255).contains(&total_alpha(&surface))
+    );
+
+    let mut nested = Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+    nested
+        .move_to(Vec2::new(3.0, 3.0))
+        .line_to(Vec2::new(7.0, 3.0))
+        .line_to(Vec2::new(7.0, 7.0))
+        .line_to(Vec2::new(3.0, 7.0))
+        .close();
+    surface.clear(Color::TRANSPARENT);
+    fill_path(&mut surface, &nested, &WHITE, FillRule::NonZero);
+    let non_zero = pixel(&surface, 5, 5)[3];
+    surface.clear(Color::TRANSPARENT);
+    fill_path(&mut surface, &nested, &WHITE, FillRule::EvenOdd);
+    test!(
+        "vector:fill_rule",
+        non_zero == 255 && pixel(&surface, 5, 5)[3] == 0 && pixel(&surface, 1, 1)[3] == 255
+    );
+
+    surface.clear(Color::TRANSPARENT);
+    fill_path(
+        &mut surface,
+        &Path::rect(Rect::new(-5.0, -5.0, 10.0, 10.0)),
+        &WHITE,
+        FillRule::NonZero,
+    );
+    fill_path(
+        &mut surface,
+        &Path::circle(Vec2::new(-100.0, 300.0), 50.0),
+        &WHITE,
+        FillRule::NonZero,
+    );
+    test!(
+        "vector:clipped",
+        total_alpha(&surface) == 25 * 255 && pixel(&surface, 5, 5)[3] == 0
+    );
+
+    test_vector_paths();
+    test_vector_strokes();
+}
+
+fn test_vector_paths() {
+    let contours = Path::circle(Vec2::new(0.0, 0.0), 10.0).flatten();
+    test!(
+        "vector:flatten",
+        contours.len() == 1
+            && contours[0].closed
+            && contours[0].points.len() > 8
+            && contours[0]
+                .points
+                .iter()
+                .all(|point| (9.7..10.05).contains(&point.magnitude()))
+    );
+
+    // Without a subpath, curves start at their first control point.
+    let mut curve = Path::new();
+    curve.quad_to(Vec2::new(5.0, 10.0), Vec2::new(10.0, 0.0));
+    let quad = curve.flatten();
+
+    // This cubic peaks at 3.75 halfway through.
+    let mut curve = Path::new();
+    curve.move_to(Vec2::new(10.0, 0.0)).cubic_to(
+        Vec2::new(10.0, 5.0),
+        Vec2::new(0.0, 5.0),
+        Vec2::new(0.0, 0.0),
+    );
+    let cubic = curve.flatten();
+    let peak = cubic.first().map_or(0.0, |contour| {
+        contour
+            .points
+            .iter()
+            .fold(0.0, |peak: f32, point| peak.max(point.y))
+    });
+    test!(
+        "vector:curves",
+        quad.len() == 1
+            && !quad[0].closed
+            && quad[0].points.first() == Some(&Vec2::new(5.0, 10.0))
+            && quad[0].points.last() == Some(&Vec2::new(10.0, 0.0))
+            && cubic[0].points.last() == Some(&Vec2::new(0.0, 0.0))
+            && (3.5..=3.7501).contains(&peak)
+    );
+
+    let mut moved = Path::rect(Rect::new(0.0, 0.0, 2.0, 2.0));
+    moved.transform(&Mat3::translation_2d(Vec2::new(3.0, 4.0)));
+    test!(
+        "vector:transform",
+        moved.flatten()[0].points[2] == Vec2::new(5.0, 6.0)
+    );
+
+    let gradient = Paint::LinearGradient {
+        start: Vec2::new(0.0, 0.0),
+        end: Vec2::new(16.0, 0.0),
+        stops: vec![
+            GradientStop::new(0.0, Color::BLACK),
+            GradientStop::new(1.0, Color::WHITE),
+        ],
+    };
+    let radial = Paint::RadialGradient {
+        center: Vec2::new(8.0, 8.0),
+        radius: 4.0,
+        stops: vec![
+            GradientStop::new(0.0, Color::RED),
+            GradientStop::new(0.5, Color::BLUE),
+        ],
+    };
+    test!(
+        "vector:gradient",
+        gradient.color_at(Vec2::new(-4.0, 3.0)) == Color::BLACK
+            && gradient.color_at(Vec2::new(8.0, 3.0)) == Color::new(128, 128, 128, 255)
+            && gradient.color_at(Vec2::new(20.0, 0.0)) == Color::WHITE
+            && radial.color_at(Vec2::new(8.0, 8.0)) == Color::RED
+            && radial.color_at(Vec2::new(8.0, 15.0)) == Color::BLUE
+    );
+}
+
+fn test_vector_strokes() {
+    let mut line = Path::new();
+    line.move_to(Vec2::new(2.0, 8.0))
+        .line_to(Vec2::new(12.0, 8.0));
+    let butt = stroked(&line, &Stroke::new(2.0));
+    let square = stroked(
+        &line,
+        &Stroke {
+            cap: LineCap::Square,
+            ..Stroke::new(2.0)
+        },
+    );
+    let round = stroked(
+        &line,
+        &Stroke {
+            cap: LineCap::Round,
+            ..Stroke::new(2.0)
+        },
+    );
+    test!(
+        "vector:stroke_caps",
+        total_alpha(&butt) == 20 * 255
+            && pixel(&butt, 1, 8)[3] == 0
+            && pixel(&square, 1, 8)[3] == 255
+            && (1..255).contains(&pixel(&round, 1, 8)[3])
+    );
+
+    let mut corner = Path::new();
+    corner
+        .move_to(Vec2::new(2.0, 2.0))
+        .line_to(Vec2::new(10.0, 2.0))
+        .line_to(Vec2::new(10.0, 10.0));
+    let miter = stroked(&corner, &Stroke::new(2.0));
+    let bevel = stroked(
+        &corner,
+        &Stroke {
+            join: LineJoin::Bevel,
+            ..Stroke::new(2.0)
+        },
+    );
+    let limited = stroked(
+        &corner,
+        &Stroke {
+            miter_limit: 1.0,
+            ..Stroke::new(2.0)
+        },
+    );
+    test!(
+        "vector:stroke_joins",
+        pixel(&miter, 10, 1)[3] == 255
+            && (100..=156).contains(&pixel(&bevel, 10, 1)[3])
+            && pixel(&limited, 10, 1)[3] == pixel(&bevel, 10, 1)[3]
+            && pixel(&miter, 9, 2)[3] == 255
+    );
+
+    let closed = stroked(
+        &Path::rect(Rect::new(3.0, 3.0, 8.0, 8.0)),
+        &Stroke::new(2.0),
+    );
+    test!(
+        "vector:stroke_closed",
+        pixel(&closed, 2, 2)[3] == 255
+            && pixel(&closed, 7, 7)[3] == 0
+            && pixel(&closed, 11, 11)[3] == 255
+    );
+}
@@ -0,0 +1,159 @@
+use gooseboy::{
+    Vec2, Vec3,
+    gpu::GpuCommandBuffer,
+    mesh::Mesh,
+    voxel::{AIR, Block, CHUNK_SIZE, Chunk, Face, Mesher, VoxelWorld},
+};
+
+use crate::test;
+
+const STONE: Block = 1;
+const GRASS: Block = 2;
+/// The size of a chunk in the world.
+const SIZE: f32 = 16.0;
+
+const fn textures(block: Block, face: Face) -> u32 {
+    match (block, face) {
+        (GRASS, Face::Up) => 0,
+        (GRASS, Face::Down) => 2,
+        (GRASS, _) => 1,
+        _ => 3,
+    }
+}
+
+const fn quads(mesh: &Mesh) -> usize {
+    mesh.indices.len() / 6
+}
+
+fn is_wound_outwards(mesh: &Mesh) -> bool {
+    let normals = mesh.normals.as_deref().unwrap_or_default();
+    mesh.indices.chunks(3).all(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+        (b - a).cross(c - a).dot(normals[triangle[0] as usize]) > 0.0
+    })
+}
+
+pub fn test_voxel() {
+    let mesher = Mesher::new(Vec2::new(4, 4), textures);
+    let mut world = VoxelWorld::new(mesher);
+    world.set_block(Vec3::new(1, 2, 3), STONE);
+    let single = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    test!(
+        "voxel:single_block",
+        quads(&single) == 6
+            && single.positions.len() == 24
+            && single
+                .bounds()
+                .is_some_and(|bounds| bounds.min == Vec3::new(1.0, 2.0, 3.0)
+                    && bounds.max == Vec3::new(2.0, 3.0, 4.0))
+            && is_wound_outwards(&single)
+    );
+
+    world.set_block(Vec3::new(2, 2, 3), STONE);
+    world.set_block(Vec3::new(3, 2, 3), STONE);
+    let row = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    world.mesher.greedy = false;
+    let row_faces = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    test!(
+        "voxel:greedy",
+        quads(&row) == 6 && quads(&row_faces) == 14 && is_wound_outwards(&row)
+    );
+
+    world.mesher.greedy = true;
+    world.insert_chunk(Vec3::zero(), Chunk::filled(GRASS));
+    let full = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    world.mesher.greedy = false;
+    let full_faces = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    world.mesher.greedy = true;
+    test!(
+        "voxel:full_chunk",
+        quads(&full) == 6 && quads(&full_faces) == 6 * CHUNK_SIZE * CHUNK_SIZE
+    );
+
+    let (top_min, top_max) = mesher.tile_uvs(0);
+    let (side_min, side_max) = mesher.tile_uvs(1);
+    let uvs_of = |normal: Vec3<f32>| {
+        let normals = full.normals.as_deref().unwrap_or_default();
+        (0..full.uvs.len())
+            .filter(move |&i| normals[i] == normal)
+            .map(|i| (full.positions[i], full.uvs[i]))
+            .collect::<Vec<_>>()
+    };
+    test!(
+        "voxel:uvs",
+        top_min == Vec2::zero()
+            && top_max == Vec2::broadcast(0.25)
+            && side_min == Vec2::new(0.25, 0.0)
+            && uvs_of(Vec3::unit_y())
+                .iter()
+                .all(|&(_, uv)| uv.x.max(uv.y) <= top_max.x)
+            && uvs_of(Vec3::unit_x()).iter().all(|&(position, uv)| {
+                let top = position.y > 0.0;
+                (uv.y - if top { side_min.y } else { side_max.y }).abs() < 1e-6
+            })
+    );
+}
+
+pub fn test_voxel_world() {
+    let mut world = VoxelWorld::new(Mesher::new(Vec2::new(4, 4), textures));
+    world.insert_chunk(Vec3::zero(), Chunk::filled(GRASS));
+    world.insert_chunk(Vec3::unit_x(), Chunk::filled(STONE));
+    let left = world.mesh_chunk(Vec3::zero()).unwrap_or_default();
+    let right = world.mesh_chunk(Vec3::unit_x()).unwrap_or_default();
+    test!(
+        "voxel:neighbour_culling",
+        quads(&left) == 5
+            && quads(&right) == 5
+            && left.positions.iter().all(|p| p.x <= SIZE)
+            && right
+                .positions
+                .iter()
+                .all(|p| p.x >= SIZE && p.x <= 2.0 * SIZE)
+    );
+
+    let mut buffer = GpuCommandBuffer::new();
+    let first = world.dirty_count();
+    let partial = world.remesh(&mut buffer, 1);
+    let rest = world.remesh(&mut buffer, 8);
+    test!(
+        "voxel:remesh",
+        first == 2
+            && partial == 1
+            && rest == 1
+            && world.dirty_count() == 0
+            && world.record(Vec3::zero()).is_some()
+            && world.record(Vec3::unit_x()).is_some()
+    );
+
+    world.set_block(Vec3::new(5, 5, 5), AIR);
+    let interior = world.dirty_count();
+    world.remesh(&mut buffer, 8);
+    world.set_block(Vec3::new(15, 5, 5), AIR);
+    let edge = world.dirty_count();
+    world.remesh(&mut buffer, 8);
+    let unchanged = world.set_block(Vec3::new(15, 5, 5), AIR);
+    test!(
+        "voxel:dirty",
+        interior == 1 && edge == 2 && unchanged == AIR && world.dirty_count() == 0
+    );
+
+    test!(
+        "voxel:world_blocks",
+        world.block(Vec3::new(-1, 0, 0)) == AIR
+            && world.block(Vec3::new(0, 0, 0)) == GRASS
+            && world.block(Vec3::new(16, 0, 0)) == STONE
+            && VoxelWorld::split_position(Vec3::new(-1, 17, 0))
+                == (Vec3::new(-1, 1, 0), Vec3::new(15, 1, 0))
+    );
+
+    let removed = world.remove_chunk(Vec3::unit_x()).is_some();
+    world.insert_chunk(Vec3::new(0, 1, 0), Chunk::new());
+    world.remesh(&mut buffer, 8);
+    test!(
+        "voxel:remove",
+        removed
+            && world.len() == 2
+            && world.record(Vec3::new(0, 1, 0)).is_none()
+            && world.draw(&mut buffer, None) == 1
+    );
+}
@@ -0,0 +1,809 @@
+//! This is used to animate transforms with keyframes, and skinned meshes with skeletons.
+//!
+//! Example:
+//! ```rs
+//! let mut pose = models::FOX_SKIN.skeleton.rest_pose();
+//! let walk = models::FOX_SKIN.animation("Walk").unwrap();
+//! walk.sample(time % walk.duration, &mut pose);
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! record = Some(models::FOX_SKIN.record(&mut buffer, &pose, record));
+//! buffer.draw_recorded(record.unwrap());
+//! ```
+//!
+//! Skinned meshes are stored in the compact binary mesh format of [`crate::mesh`], next to an
+//! animation file with the skeleton, the skin weights and the animations, which is what the
+//! buildscript's `convert_models` generates for skinned glTF models. All values are
+//! little-endian, strings are a `u16` length followed by UTF-8, and transforms are
+//! a translation, a rotation (x, y, z, w) and a scale, as 10 `f32`s:
+//!
+//! | Size               | Field                                                           |
+//! |--------------------|-----------------------------------------------------------------|
+//! | 4                  | [`ANIMATION_MAGIC`]                                             |
+//! | 2                  | [`ANIMATION_VERSION`]                                           |
+//! | 2                  | reserved flags, always 0                                        |
+//! | 4                  | vertex count `u32`, matching the mesh                           |
+//! | 4                  | joint count `u32`                                               |
+//! | 4                  | animation count `u32`                                           |
+//! | 64                 | the root matrix, column-major `f32`s                            |
+//! | per joint          | name, parent `u16` ([`NO_PARENT`] for roots), rest transform,   |
+//! |                    | inverse bind matrix, column-major `f32`s                        |
+//! | `vertices * 8`     | joints, 4 `u16` per vertex                                      |
+//! | `vertices * 16`    | weights, 4 `f32` per vertex                                     |
+//! | per animation      | name, channel count `u32`, then every channel                   |
+//! | per channel        | target joint `u16`, property `u8` (translation 0, rotation 1,   |
+//! |                    | scale 2), [`Easing`] `u8`, keyframe count `u32`, the times      |
+//! |                    | `f32`, then the values, 3 or 4 `f32` per keyframe               |
+#![allow(clippy::cast_possible_truncation)]
+use std::f32::consts::PI;
+
+use thiserror::Error;
+
+use crate::{
+    Mat4, Vec2, Vec3, Vec4,
+    gpu::{GpuCommandBuffer, RecordId},
+    mesh::Mesh,
+    vek::{Quaternion, Transform},
+};
+
+/// The magic bytes every binary animation file starts with.
+pub const ANIMATION_MAGIC: [u8; 4] = *b"GANM";
+/// The current version of the binary animation format.
+pub const ANIMATION_VERSION: u16 = 1;
+/// The parent of root joints in the binary animation format.
+pub const NO_PARENT: u16 = u16::MAX;
+
+/// An error returned when decoding a malformed binary animation file.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AnimationDecodeError {
+    /// The data doesn't start with [`ANIMATION_MAGIC`].
+    #[error("missing animation header")]
+    BadMagic,
+    /// The file was written with another format version.
+    #[error("unsupported animation version {version}")]
+    UnsupportedVersion {
+        /// The version in the header.
+        version: u16,
+    },
+    /// The data ended before everything was read.
+    #[error("unexpected end of animation data")]
+    UnexpectedEnd,
+    /// The vertex count doesn't match the mesh.
+    #[error("the skin has {skin} vertices, but the mesh has {mesh}")]
+    VertexCountMismatch {
+        /// The vertex count of the skin.
+        skin: u32,
+        /// The vertex count of the mesh.
+        mesh: u32,
+    },
+    /// A joint's parent doesn't come before it, or a vertex or channel uses a missing joint.
+    #[error("joint {joint} is out of bounds or out of order")]
+    BadJoint {
+        /// The joint index.
+        joint: u16,
+    },
+    /// A channel has an unknown property or easing, or a string isn't UTF-8.
+    #[error("invalid value at offset {offset}")]
+    InvalidValue {
+        /// The offset of the value.
+        offset: usize,
+    },
+}
+
+/// An easing function, shaping the interpolation between two keyframes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Easing {
+    /// Holds the value of the first keyframe until the next.
+    Step = 0,
+    /// Interpolates at a constant speed.
+    #[default]
+    Linear = 1,
+    /// Starts slow and speeds up, as a cubic.
+    EaseIn = 2,
+    /// Starts fast and slows down, as a cubic.
+    EaseOut = 3,
+    /// Starts and ends slow, as a cubic.
+    EaseInOut = 4,
+    /// Starts and ends slow, as a half sine wave.
+    SineInOut = 5,
+}
+
+impl Easing {
+    /// Returns the representation of the easing.
+    #[must_use]
+    pub const fn repr(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Returns the easing with a representation, or `None` if there's none.
+    #[must_use]
+    pub const fn from_repr(repr: u8) -> Option<Self> {
+        Some(match repr {
+            0 => Self::Step,
+            1 => Self::Linear,
+            2 => Self::EaseIn,
+            3 => Self::EaseOut,
+            4 => Self::EaseInOut,
+            5 => Self::SineInOut,
+            _ => return None,
+        })
+    }
+
+    /// Eases `t` from 0 to 1.
+    #[must_use]
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0f32).mul_add(t, 2.0).powi(3) / 2.0
+                }
+            }
+            Self::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+        }
+    }
+}
+
+/// A value that can be interpolated between keyframes.
+pub trait Interpolate: Copy {
+    /// Interpolates from `self` at 0 to `other` at 1.
+    #[must_use]
+    fn interpolate(self, other: Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        (other - self).mul_add(t, self)
+    }
+}
+
+impl Interpolate for Vec2<f32> {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        Self::lerp_unclamped(self, other, t)
+    }
+}
+
+impl Interpolate for Vec3<f32> {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        Self::lerp_unclamped(self, other, t)
+    }
+}
+
+impl Interpolate for Vec4<f32> {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        Self::lerp_unclamped(self, other, t)
+    }
+}
+
+impl Interpolate for Quaternion<f32> {
+    /// Interpolates along the shortest arc.
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        Self::slerp_unclamped(self, other, t).normalized()
+    }
+}
+
+/// A value at a point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    /// The time in seconds.
+    pub time: f32,
+    /// The value.
+    pub value: T,
+    /// The easing towards the next keyframe.
+    pub easing: Easing,
+}
+
+impl<T> Keyframe<T> {
+    /// Creates a new [`Keyframe`], eased linearly towards the next one.
+    pub const fn new(time: f32, value: T) -> Self {
+        Self {
+            time,
+            value,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Creates a new [`Keyframe`], eased with `easing` towards the next one.
+    pub const fn eased(time: f32, value: T, easing: Easing) -> Self {
+        Self {
+            time,
+            value,
+            easing,
+        }
+    }
+}
+
+/// A list of keyframes, sorted by time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    /// The keyframes, sorted by time.
+    pub keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Track<T> {
+    /// Creates a new [`Track`], sorting the keyframes by time.
+    #[must_use]
+    pub fn new(mut keyframes: Vec<Keyframe<T>>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    /// Returns `true` if the track has no keyframes.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Returns the time of the last keyframe, or 0 if there are none.
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// Returns the value at `time`, holding the first and last values outside of the keyframes,
+    /// or `None` if there are none.
+    #[must_use]
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let (Some(a), Some(b)) = (
+            self.keyframes.get(next.wrapping_sub(1)),
+            self.keyframes.get(next),
+        ) else {
+            return self
+                .keyframes
+                .get(next.saturating_sub(1))
+                .map(|keyframe| keyframe.value);
+        };
+
+        let t = (time - a.time) / (b.time - a.time);
+        Some(a.value.interpolate(b.value, a.easing.apply(t)))
+    }
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+        }
+    }
+}
+
+/// The translation, rotation and scale tracks of a transform.
+/// Empty tracks leave their part of the transform as it is.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransformTrack {
+    /// The translation track.
+    pub translation: Track<Vec3<f32>>,
+    /// The rotation track.
+    pub rotation: Track<Quaternion<f32>>,
+    /// The scale track.
+    pub scale: Track<Vec3<f32>>,
+}
+
+impl TransformTrack {
+    /// Returns the time of the last keyframe of every track.
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.translation
+            .duration()
+            .max(self.rotation.duration())
+            .max(self.scale.duration())
+    }
+
+    /// Writes the values at `time` into `transform`.
+    pub fn sample(&self, time: f32, transform: &mut Transform<f32, f32, f32>) {
+        if let Some(translation) = self.translation.sample(time) {
+            transform.position = translation;
+        }
+        if let Some(rotation) = self.rotation.sample(time) {
+            transform.orientation = rotation;
+        }
+        if let Some(scale) = self.scale.sample(time) {
+            transform.scale = scale;
+        }
+    }
+}
+
+/// The tracks of a single transform in an [`Animation`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Channel {
+    /// The index of the transform animated, e.g. a joint of a [`Skeleton`].
+    pub target: usize,
+    /// The tracks.
+    pub track: TransformTrack,
+}
+
+/// A named list of channels, animating a list of transforms together.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Animation {
+    /// The name.
+    pub name: String,
+    /// The time of the last keyframe in seconds.
+    pub duration: f32,
+    /// The channels.
+    pub channels: Vec<Channel>,
+}
+
+impl Animation {
+    /// Creates a new [`Animation`], lasting until the last keyframe of the channels.
+    #[must_use]
+    pub fn new(name: impl Into<String>, channels: Vec<Channel>) -> Self {
+        Self {
+            name: name.into(),
+            duration: channels
+                .iter()
+                .map(|channel| channel.track.duration())
+                .fold(0.0, f32::max),
+            channels,
+        }
+    }
+
+    /// Writes the values at `time` into the transforms of `pose`,
+    /// skipping channels targeting transforms past its' end.
+    pub fn sample(&self, time: f32, pose: &mut [Transform<f32, f32, f32>]) {
+        for channel in &self.channels {
+            if let Some(transform) = pose.get_mut(channel.target) {
+                channel.track.sample(time, transform);
+            }
+        }
+    }
+}
+
+/// Returns a transform as a matrix, translating, rotating then scaling.
+#[must_use]
+pub fn transform_matrix(transform: &Transform<f32, f32, f32>) -> Mat4<f32> {
+    Mat4::<f32>::translation_3d(transform.position)
+        * Mat4::from(transform.orientation)
+        * Mat4::<f32>::scaling_3d(transform.scale)
+}
+
+/// A joint of a [`Skeleton`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    /// The name.
+    pub name: String,
+    /// The parent joint, which always comes before this one, or `None` for roots.
+    pub parent: Option<usize>,
+    /// The transform relative to the parent when not animated.
+    pub rest: Transform<f32, f32, f32>,
+    /// The matrix bringing the mesh from its' bind pose into the joint's space.
+    pub inverse_bind: Mat4<f32>,
+}
+
+/// A hierarchy of joints, every parent coming before its' children.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+    /// The joints.
+    pub joints: Vec<Joint>,
+    /// The transform of every root joint's parent.
+    pub root: Mat4<f32>,
+}
+
+impl Skeleton {
+    /// Returns the rest transform of every joint, to be animated.
+    #[must_use]
+    pub fn rest_pose(&self) -> Vec<Transform<f32, f32, f32>> {
+        self.joints.iter().map(|joint| joint.rest).collect()
+    }
+
+    /// Returns the index of the joint with a name.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    /// Returns the transform of every joint relative to the model, for a pose with
+    /// a transform per joint. Joints missing from the pose use their rest transform.
+    #[must_use]
+    pub fn world_matrices(&self, pose: &[Transform<f32, f32, f32>]) -> Vec<Mat4<f32>> {
+        let mut world: Vec<Mat4<f32>> = Vec::with_capacity(self.joints.len());
+        for (i, joint) in self.joints.iter().enumerate() {
+            let parent = joint
+                .parent
+                .and_then(|parent| world.get(parent))
+                .unwrap_or(&self.root);
+            world.push(*parent * transform_matrix(pose.get(i).unwrap_or(&joint.rest)));
+        }
+        world
+    }
+
+    /// Returns the skinning matrix of every joint for a pose, bringing the bind pose into it.
+    #[must_use]
+    pub fn joint_matrices(&self, pose: &[Transform<f32, f32, f32>]) -> Vec<Mat4<f32>> {
+        self.world_matrices(pose)
+            .into_iter()
+            .zip(&self.joints)
+            .map(|(world, joint)| world * joint.inverse_bind)
+            .collect()
+    }
+}
+
+/// A mesh bound to a skeleton, with the animations of the skeleton.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkinnedMesh {
+    /// The mesh in its' bind pose.
+    pub mesh: Mesh,
+    /// The 4 joints of every vertex.
+    pub joints: Vec<[u16; 4]>,
+    /// The weights of the 4 joints of every vertex. Vertices with no weight aren't moved.
+    pub weights: Vec<[f32; 4]>,
+    /// The skeleton.
+    pub skeleton: Skeleton,
+    /// The animations of the skeleton.
+    pub animations: Vec<Animation>,
+}
+
+impl SkinnedMesh {
+    /// Returns the animation with a name.
+    #[must_use]
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        self.animations
+            .iter()
+            .find(|animation| animation.name == name)
+    }
+
+    /// Returns the mesh deformed by the skinning matrices of [`Skeleton::joint_matrices`].
+    #[must_use]
+    pub fn skin(&self, matrices: &[Mat4<f32>]) -> Mesh {
+        let mut mesh = self.mesh.clone();
+        for (i, (joints, weights)) in self.joints.iter().zip(&self.weights).enumerate() {
+            let mut skin = Mat4::zero();
+            let mut total = 0.0;
+            for (&joint, &weight) in joints.iter().zip(weights) {
+                if let Some(matrix) = matrices.get(usize::from(joint))
+                    && weight > 0.0
+                {
+                    skin += *matrix * weight;
+                    total += weight;
+                }
+            }
+            if total <= 0.0 {
+                continue;
+            }
+            let skin = skin / total;
+
+            if let Some(position) = mesh.positions.get_mut(i) {
+                *position = skin.mul_point(*position);
+            }
+            if let Some(normal) = mesh.normals.as_mut().and_then(|normals| normals.get_mut(i)) {
+                *normal = skin
+                    .mul_direction(*normal)
+                    .try_normalized()
+                    .unwrap_or(*normal);
+            }
+        }
+        mesh
+    }
+
+    /// Skins the mesh in a pose and records it over `record` if there's one,
+    /// e.g. the record of the previous frame, see [`Mesh::record_over`].
+    pub fn record(
+        &self,
+        buffer: &mut GpuCommandBuffer,
+        pose: &[Transform<f32, f32, f32>],
+        record: Option<RecordId>,
+    ) -> RecordId {
+        self.skin(&self.skeleton.joint_matrices(pose))
+            .record_over(buffer, record)
+    }
+
+    /// Decodes the skeleton, skin and animations of `mesh` from the binary animation format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed, or doesn't match the mesh.
+    pub fn from_bytes(mesh: Mesh, bytes: &[u8]) -> Result<Self, AnimationDecodeError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != ANIMATION_MAGIC {
+            return Err(AnimationDecodeError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != ANIMATION_VERSION {
+            return Err(AnimationDecodeError::UnsupportedVersion { version });
+        }
+        reader.u16()?;
+
+        let vertex_count = reader.u32()?;
+        if vertex_count as usize != mesh.vertex_count() {
+            return Err(AnimationDecodeError::VertexCountMismatch {
+                skin: vertex_count,
+                mesh: mesh.vertex_count() as u32,
+            });
+        }
+        let joint_count = reader.u32()? as usize;
+        let animation_count = reader.u32()? as usize;
+        let root = reader.matrix()?;
+
+        let mut joints = Vec::with_capacity(joint_count.min(bytes.len()));
+        for i in 0..joint_count {
+            let name = reader.string()?;
+            let parent = reader.u16()?;
+            if parent != NO_PARENT && usize::from(parent) >= i {
+                return Err(AnimationDecodeError::BadJoint { joint: parent });
+            }
+            joints.push(Joint {
+                name,
+                parent: (parent != NO_PARENT).then_some(usize::from(parent)),
+                rest: reader.transform()?,
+                inverse_bind: reader.matrix()?,
+            });
+        }
+
+        let vertices = vertex_count as usize;
+        let mut skin_joints = Vec::with_capacity(vertices);
+        for _ in 0..vertices {
+            let indices = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+            if let Some(&joint) = indices.iter().find(|&&j| usize::from(j) >= joint_count) {
+                return Err(AnimationDecodeError::BadJoint { joint });
+            }
+            skin_joints.push(indices);
+        }
+        let mut weights = Vec::with_capacity(vertices);
+        for _ in 0..vertices {
+            weights.push([reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?]);
+        }
+
+        let mut animations = Vec::with_capacity(animation_count.min(bytes.len()));
+        for _ in 0..animation_count {
+            animations.push(reader.animation(joint_count)?);
+        }
+
+        Ok(Self {
+            mesh,
+            joints: skin_joints,
+            weights,
+            skeleton: Skeleton { joints, root },
+            animations,
+        })
+    }
+
+    /// Encodes the skeleton, skin and animations in the binary animation format.
+    ///
+    /// The mesh isn't included, encode it with [`Mesh::to_bytes`].
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes.extend_from_slice(&ANIMATION_MAGIC);
+        writer.u16(ANIMATION_VERSION);
+        writer.u16(0);
+        writer.u32(self.mesh.vertex_count() as u32);
+        writer.u32(self.skeleton.joints.len() as u32);
+        writer.u32(self.animations.len() as u32);
+        writer.floats(&self.skeleton.root.into_col_array());
+
+        for joint in &self.skeleton.joints {
+            writer.string(&joint.name);
+            writer.u16(joint.parent.map_or(NO_PARENT, |parent| parent as u16));
+            writer.transform(&joint.rest);
+            writer.floats(&joint.inverse_bind.into_col_array());
+        }
+        for joints in &self.joints {
+            for &joint in joints {
+                writer.u16(joint);
+            }
+        }
+        for weights in &self.weights {
+            writer.floats(weights);
+        }
+
+        for animation in &self.animations {
+            let tracks = animation.channels.iter().map(|channel| {
+                let track = &channel.track;
+                [
+                    track.translation.is_empty(),
+                    track.rotation.is_empty(),
+                    track.scale.is_empty(),
+                ]
+                .iter()
+                .filter(|&&empty| !empty)
+                .count()
+            });
+            writer.string(&animation.name);
+            writer.u32(tracks.sum::<usize>() as u32);
+            for channel in &animation.channels {
+                let track = &channel.track;
+                writer.track(channel.target, 0, &track.translation, Vec3::into_array);
+                writer.track(channel.target, 1, &track.rotation, |q| {
+                    q.into_vec4().into_array()
+                });
+                writer.track(channel.target, 2, &track.scale, Vec3::into_array);
+            }
+        }
+        writer.bytes
+    }
+}
+
+/// Reads the binary animation format.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AnimationDecodeError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(AnimationDecodeError::UnexpectedEnd)?;
+        let taken = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AnimationDecodeError> {
+        Ok(self.take(N)?.try_into().unwrap_or([0; N]))
+    }
+
+    fn u8(&mut self) -> Result<u8, AnimationDecodeError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AnimationDecodeError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, AnimationDecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, AnimationDecodeError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn floats<const N: usize>(&mut self) -> Result<[f32; N], AnimationDecodeError> {
+        let mut floats = [0.0; N];
+        for float in &mut floats {
+            *float = self.f32()?;
+        }
+        Ok(floats)
+    }
+
+    fn string(&mut self) -> Result<String, AnimationDecodeError> {
+        let len = self.u16()?;
+        let offset = self.offset;
+        String::from_utf8(self.take(usize::from(len))?.to_vec())
+            .map_err(|_| AnimationDecodeError::InvalidValue { offset })
+    }
+
+    fn matrix(&mut self) -> Result<Mat4<f32>, AnimationDecodeError> {
+        Ok(Mat4::from_col_array(self.floats()?))
+    }
+
+    fn transform(&mut self) -> Result<Transform<f32, f32, f32>, AnimationDecodeError> {
+        Ok(Transform {
+            position: Vec3::from(self.floats::<3>()?),
+            orientation: Quaternion::from_vec4(Vec4::from(self.floats::<4>()?)),
+            scale: Vec3::from(self.floats::<3>()?),
+        })
+    }
+
+    fn animation(&mut self, joint_count: usize) -> Result<Animation, AnimationDecodeError> {
+        let name = self.string()?;
+        let channel_count = self.u32()?;
+        let mut channels: Vec<Channel> = Vec::new();
+        for _ in 0..channel_count {
+            let target = self.u16()?;
+            if usize::from(target) >= joint_count {
+                return Err(AnimationDecodeError::BadJoint { joint: target });
+            }
+            let target = usize::from(target);
+            let index = channels
+                .iter()
+                .position(|channel| channel.target == target)
+                .unwrap_or_else(|| {
+                    channels.push(Channel {
+                        target,
+                        track: TransformTrack::default(),
+                    });
+                    channels.len() - 1
+                });
+            let track = &mut channels[index].track;
+
+            let offset = self.offset;
+            let property = self.u8()?;
+            let easing = Easing::from_repr(self.u8()?)
+                .ok_or(AnimationDecodeError::InvalidValue { offset: offset + 1 })?;
+            match property {
+                0 => track.translation = self.track(easing, |f: [f32; 3]| Vec3::from(f))?,
+                1 => {
+                    track.rotation =
+                        self.track(easing, |f: [f32; 4]| Quaternion::from_vec4(Vec4::from(f)))?;
+                }
+                2 => track.scale = self.track(easing, |f: [f32; 3]| Vec3::from(f))?,
+                _ => return Err(AnimationDecodeError::InvalidValue { offset }),
+            }
+        }
+        Ok(Animation::new(name, channels))
+    }
+
+    fn track<T, const N: usize>(
+        &mut self,
+        easing: Easing,
+        value: impl Fn([f32; N]) -> T,
+    ) -> Result<Track<T>, AnimationDecodeError> {
+        let count = self.u32()? as usize;
+        let mut times = Vec::with_capacity(count.min(self.bytes.len()));
+        for _ in 0..count {
+            times.push(self.f32()?);
+        }
+        let mut keyframes = Vec::with_capacity(times.len());
+        for time in times {
+            keyframes.push(Keyframe::eased(time, value(self.floats()?), easing));
+        }
+        Ok(Track { keyframes })
+    }
+}
+
+/// Writes the binary animation format.
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn floats(&mut self, values: &[f32]) {
+        for value in values {
+            self.bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        let len = value.len().min(usize::from(u16::MAX));
+        self.u16(len as u16);
+        self.bytes.extend_from_slice(&value.as_bytes()[..len]);
+    }
+
+    fn transform(&mut self, transform: &Transform<f32, f32, f32>) {
+        self.floats(&transform.position.into_array());
+        self.floats(&transform.orientation.into_vec4().into_array());
+        self.floats(&transform.scale.into_array());
+    }
+
+    /// Writes a track as a channel, skipping it if it's empty.
+    /// Every keyframe is written with the easing of the first.
+    fn track<T: Copy, const N: usize>(
+        &mut self,
+        target: usize,
+        property: u8,
+        track: &Track<T>,
+        values: impl Fn(T) -> [f32; N],
+    ) {
+        let Some(first) = track.keyframes.first() else {
+            return;
+        };
+        self.u16(target as u16);
+        self.bytes.push(property);
+        self.bytes.push(first.easing.repr());
+        self.u32(track.keyframes.len() as u32);
+        for keyframe in &track.keyframes {
+            self.floats(&[keyframe.time]);
+        }
+        for keyframe in &track.keyframes {
+            self.floats(&values(keyframe.value));
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! This is used to pack many sprites into a single sprite, so they can be registered as one
+//! texture, which is a lot cheaper than registering a texture per sprite.
+//!
+//! Sprites are packed with a skyline packer, which places every sprite as low as it can go,
+//! and each packed sprite gets an [`AtlasRegion`] with its' pixel and UV rectangles.
+//!
+//! Example:
+//! ```rs
+//! let mut atlas = TextureAtlas::new(256, 256);
+//! let grass = atlas.insert(&sprites::GRASS).unwrap();
+//! let stone = atlas.insert(&sprites::STONE).unwrap();
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! let texture = atlas.register(&mut buffer);
+//! let uv = atlas.uv(&grass);
+//! ```
+//!
+//! The buildscript can also pack the images folder into an atlas at build time, see
+//! `gooseboy_buildscript::convert_images_ex`.
+#![allow(clippy::cast_precision_loss)]
+use crate::{Rect, sprite::Sprite};
+
+/// The padding of a new [`TextureAtlas`].
+pub const DEFAULT_PADDING: usize = 1;
+
+/// A rectangle of pixels in a [`TextureAtlas`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AtlasRegion {
+    /// The X position of the left edge.
+    pub x: usize,
+    /// The Y position of the top edge.
+    pub y: usize,
+    /// The width.
+    pub width: usize,
+    /// The height.
+    pub height: usize,
+}
+
+impl AtlasRegion {
+    /// Creates a new [`AtlasRegion`].
+    #[must_use]
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the UV rectangle of the region in an atlas of `atlas_width`x`atlas_height`,
+    /// with the top left corner at its' position.
+    #[must_use]
+    pub fn uv(&self, atlas_width: usize, atlas_height: usize) -> Rect<f32, f32> {
+        let (width, height) = (atlas_width.max(1) as f32, atlas_height.max(1) as f32);
+        Rect::new(
+            self.x as f32 / width,
+            self.y as f32 / height,
+            self.width as f32 / width,
+            self.height as f32 / height,
+        )
+    }
+
+    /// Maps a UV of the region, from 0 to 1 on both axes, to a UV of the atlas.
+    #[must_use]
+    pub fn map_uv(&self, atlas_width: usize, atlas_height: usize, u: f32, v: f32) -> (f32, f32) {
+        let uv = self.uv(atlas_width, atlas_height);
+        (u.mul_add(uv.w, uv.x), v.mul_add(uv.h, uv.y))
+    }
+}
+
+/// A sprite with other sprites packed into it.
+#[derive(Clone, Debug)]
+pub struct TextureAtlas {
+    /// The packed sprites.
+    pub sprite: Sprite,
+    /// The amount of empty pixels to the right and bottom of every packed sprite,
+    /// keeping filtered textures from bleeding into each other.
+    pub padding: usize,
+    /// The height of the lowest free pixel of every column.
+    skyline: Vec<usize>,
+    regions: Vec<AtlasRegion>,
+}
+
+impl TextureAtlas {
+    /// Creates a new empty [`TextureAtlas`] of `width`x`height`, with the [`DEFAULT_PADDING`].
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            sprite: Sprite::new_blended(width, height, &vec![0; width * height * 4]),
+            padding: DEFAULT_PADDING,
+            skyline: vec![0; width],
+            regions: Vec::new(),
+        }
+    }
+
+    /// Creates a [`TextureAtlas`] out of an already packed sprite and its' regions, e.g. one
+    /// generated by the buildscript. New sprites are packed below the existing regions.
+    #[must_use]
+    pub fn from_regions(sprite: Sprite, regions: &[AtlasRegion]) -> Self {
+        let mut skyline = vec![0; sprite.width];
+        for region in regions {
+            let end = (region.x + region.width + DEFAULT_PADDING).min(sprite.width);
+            for height in &mut skyline[region.x.min(end)..end] {
+                *height = (*height).max(region.y + region.height + DEFAULT_PADDING);
+            }
+        }
+
+        Self {
+            sprite,
+            padding: DEFAULT_PADDING,
+            skyline,
+            regions: regions.to_vec(),
+        }
+    }
+
+    /// Packs `sprites` into the smallest square power of two atlas that fits them, up to
+    /// `max_size`, returning the atlas and the region of every sprite, in the same order.
+    ///
+    /// Returns `None` if they don't fit into a `max_size`x`max_size` atlas.
+    #[must_use]
+    pub fn pack(
+        sprites: &[&Sprite],
+        padding: usize,
+        max_size: usize,
+    ) -> Option<(Self, Vec<AtlasRegion>)> {
+        let area: usize = sprites
+            .iter()
+            .map(|sprite| (sprite.width + padding) * (sprite.height + padding))
+            .sum();
+        let widest = sprites
+            .iter()
+            .map(|sprite| sprite.width.max(sprite.height) + padding)
+            .max()
+            .unwrap_or(1);
+
+        // Taller sprites first leave less gaps under the skyline.
+        let mut order: Vec<usize> = (0..sprites.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse((sprites[i].height, sprites[i].width)));
+
+        let mut size = widest.max(area.isqrt()).next_power_of_two();
+        while size <= max_size {
+            let mut atlas = Self::new(size, size);
+            atlas.padding = padding;
+            let mut regions = vec![AtlasRegion::default(); sprites.len()];
+            if order.iter().all(|&i| {
+                atlas
+                    .insert(sprites[i])
+                    .map(|region| regions[i] = region)
+                    .is_some()
+            }) {
+                return Some((atlas, regions));
+            }
+            size *= 2;
+        }
+        None
+    }
+
+    /// Returns the width of the atlas.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.sprite.width
+    }
+
+    /// Returns the height of the atlas.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.sprite.height
+    }
+
+    /// Returns the regions of the packed sprites, in the order they were packed.
+    #[must_use]
+    pub fn regions(&self) -> &[AtlasRegion] {
+        &self.regions
+    }
+
+    /// Returns the UV rectangle of a region of this atlas.
+    #[must_use]
+    pub fn uv(&self, region: &AtlasRegion) -> Rect<f32, f32> {
+        region.uv(self.width(), self.height())
+    }
+
+    /// Packs a sprite, returning its' region, or `None` if there's no room left for it.
+    pub fn insert(&mut self, sprite: &Sprite) -> Option<AtlasRegion> {
+        let (x, y) = self.find(sprite.width, sprite.height)?;
+        let region = AtlasRegion::new(x, y, sprite.width, sprite.height);
+
+        let row = sprite.width * 4;
+        for (line, source) in sprite.rgba.chunks_exact(row.max(1)).enumerate() {
+            let start = ((y + line) * self.width() + x) * 4;
+            self.sprite.rgba[start..start + row].copy_from_slice(source);
+        }
+
+        let end = (x + sprite.width + self.padding).min(self.width());
+        self.skyline[x..end].fill(y + sprite.height + self.padding);
+        self.regions.push(region);
+        Some(region)
+    }
+
+    /// Finds the lowest, then leftmost, position a `width`x`height` sprite fits at.
+    fn find(&self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if width > self.width() || height > self.height() {
+            return None;
+        }
+        if width == 0 || height == 0 {
+            return Some((0, 0));
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        for x in 0..=self.width() - width {
+            // Only the starts of the skyline's segments are tried, like most skyline packers.
+            if x > 0 && self.skyline[x - 1] == self.skyline[x] {
+                continue;
+            }
+
+            let y = self.skyline[x..x + width]
+                .iter()
+                .copied()
+                .max()
+                .unwrap_or(0);
+            if y + height <= self.height() && best.is_none_or(|(_, best)| y < best) {
+                best = Some((x, y));
+            }
+        }
+        best
+    }
+
+    /// Copies a region of the atlas into a new sprite.
+    #[must_use]
+    pub fn region_sprite(&self, region: &AtlasRegion) -> Sprite {
+        let mut rgba = Vec::with_capacity(region.width * region.height * 4);
+        for y in region.y..region.y + region.height {
+            let start = (y * self.width() + region.x) * 4;
+            rgba.extend_from_slice(&self.sprite.rgba[start..start + region.width * 4]);
+        }
+        Sprite {
+            rgba,
+            width: region.width,
+            height: region.height,
+            blend: self.sprite.blend,
+        }
+    }
+
+    /// Registers the atlas as a texture, returning the id it will get.
+    #[cfg(feature = "gpu")]
+    pub fn register(&self, buffer: &mut crate::gpu::GpuCommandBuffer) -> crate::gpu::TextureId {
+        buffer.register_sprite(&self.sprite)
+    }
+}
@@ -6,6 +6,10 @@ pub type Pointer = *const u8;
 /// WASM mutable pointer
 pub type PointerMut = *mut u8;
 
+#[cfg(feature = "mock-host")]
+pub use crate::mock::bindings::*;
+
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "console")]
 unsafe extern "C" {
     /// Writes to the console.
@@ -13,6 +17,7 @@ unsafe extern "C" {
 }
 
 #[cfg(feature = "framebuffer")]
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "framebuffer")]
 unsafe extern "C" {
     /// Returns the framebuffer width.
@@ -39,8 +44,22 @@ unsafe extern "C" {
         src_ptr: Pointer,
         blend: bool,
     );
+    /// Fills a `w`x`h` rectangle of a Surface with `color`, clipped to the Surface,
+    /// with optional blending.
+    pub fn fill_rect_clipped(
+        dest_ptr: Pointer,
+        dest_w: usize,
+        dest_h: usize,
+        x: i32,
+        y: i32,
+        w: usize,
+        h: usize,
+        color: i32,
+        blend: bool,
+    );
 }
 
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "memory")]
 unsafe extern "C" {
     /// Fills a region of memory.
@@ -50,6 +69,7 @@ unsafe extern "C" {
 }
 
 #[cfg(feature = "input")]
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "input")]
 unsafe extern "C" {
     /// Returns the current key being held down.
@@ -75,6 +95,7 @@ unsafe extern "C" {
 }
 
 #[cfg(feature = "audio")]
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "audio")]
 unsafe extern "C" {
     /// Plays an audio, with format being `AudioFormat::repr`, returning the audio instance id.
@@ -92,6 +113,7 @@ unsafe extern "C" {
 }
 
 #[cfg(feature = "storage")]
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "storage")]
 unsafe extern "C" {
     /// Reads from the crate storage, and returns the amount of read bytes.
@@ -104,6 +126,7 @@ unsafe extern "C" {
     pub fn storage_clear();
 }
 
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "system")]
 unsafe extern "C" {
     /// Returns the time in nanoseconds since the Unix Epoch.
@@ -115,12 +138,21 @@ unsafe extern "C" {
 }
 
 #[cfg(feature = "gpu")]
+#[cfg(not(feature = "mock-host"))]
 #[link(wasm_import_module = "gpu")]
 unsafe extern "C" {
     /// Gets the current camera transform, and returns true if successful.
     pub fn get_camera_transform(ptr: PointerMut) -> bool;
     /// Sets the current camera transform, and returns true if successful.
     pub fn set_camera_transform(x: f32, y: f32, z: f32, yaw: f32, pitch: f32) -> bool;
+    /// Returns the current camera field of view in degrees.
+    pub fn get_camera_fov() -> f32;
+    /// Sets the current camera field of view in degrees, and returns true if successful.
+    pub fn set_camera_fov(fov: f32) -> bool;
+    /// Returns the current camera roll in radians.
+    pub fn get_camera_roll() -> f32;
+    /// Sets the current camera roll in radians, and returns true if successful.
+    pub fn set_camera_roll(roll: f32) -> bool;
     /// Submits a group of GPU commands, and returns true if successful.
     pub fn submit_gpu_commands(ptr: Pointer, count: i32) -> bool;
     /// Defers until the queued GPU commands run.
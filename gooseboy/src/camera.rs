@@ -3,12 +3,28 @@
 //! Example:
 //! ```rs
 //! set_camera_y(get_camera_y() + 0.01);
+//!
+//! // Or, with the full camera:
+//! let mut camera = Camera::read();
+//! camera.roll += 0.01;
+//! camera.apply();
+//! let ray = camera.mouse_ray();
 //! ```
-use crate::{Vec2, Vec3, bindings};
+use crate::{Aabb, Mat4, Ray, Vec2, Vec3, Vec4, bindings};
+
+#[cfg(feature = "input")]
+pub mod controller;
+
+/// The default vertical field of view in degrees, matching Minecraft's default.
+pub const DEFAULT_FOV: f32 = 70.0;
+/// The default near plane distance.
+pub const DEFAULT_NEAR: f32 = 0.05;
+/// The default far plane distance.
+pub const DEFAULT_FAR: f32 = 1024.0;
 
 /// A camera transform, including the position and rotations in yaw/pitch.
 #[repr(C)]
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct CameraTransform {
     /// The X position of the camera in world space.
     pub x: f32,
@@ -22,6 +38,308 @@ pub struct CameraTransform {
     pub pitch: f32,
 }
 
+impl CameraTransform {
+    /// Creates a new [`CameraTransform`] at `position`, looking towards `target`.
+    #[must_use]
+    pub fn looking_at(position: Vec3<f32>, target: Vec3<f32>) -> Self {
+        let direction = (target - position)
+            .try_normalized()
+            .unwrap_or_else(|| -Vec3::unit_z());
+        Self {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            yaw: (-direction.x).atan2(-direction.z),
+            pitch: direction.y.clamp(-1.0, 1.0).asin(),
+        }
+    }
+
+    /// Returns the forward vector of this [`CameraTransform`].
+    #[must_use]
+    pub fn forward(&self) -> Vec3<f32> {
+        let cos_pitch = self.pitch.cos();
+        let sin_pitch = self.pitch.sin();
+        let cos_yaw = self.yaw.cos();
+        let sin_yaw = self.yaw.sin();
+
+        Vec3 {
+            x: -sin_yaw * cos_pitch,
+            y: sin_pitch,
+            z: -cos_yaw * cos_pitch,
+        }
+    }
+}
+
+/// A full camera, with a [`CameraTransform`], roll and projection.
+///
+/// The matrices match the ones the `GooseGPU` renders with, so they can be used to
+/// project points onto the screen and pick objects with the mouse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// The position and yaw/pitch rotation of the camera.
+    pub transform: CameraTransform,
+    /// The roll rotation around the forward vector in radians.
+    pub roll: f32,
+    /// The vertical field of view in degrees.
+    pub fov: f32,
+    /// The near plane distance.
+    pub near: f32,
+    /// The far plane distance.
+    pub far: f32,
+}
+
+impl Camera {
+    /// Creates a new [`Camera`] at the origin with the default projection.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::from_transform(CameraTransform {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            yaw: 0.0,
+            pitch: 0.0,
+        })
+    }
+
+    /// Creates a new [`Camera`] from a [`CameraTransform`] with the default projection.
+    #[must_use]
+    pub const fn from_transform(transform: CameraTransform) -> Self {
+        Self {
+            transform,
+            roll: 0.0,
+            fov: DEFAULT_FOV,
+            near: DEFAULT_NEAR,
+            far: DEFAULT_FAR,
+        }
+    }
+
+    /// Returns the current camera from the GPU, the near and far planes are the defaults.
+    /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+    #[must_use]
+    pub fn read() -> Self {
+        Self {
+            roll: get_camera_roll(),
+            fov: get_camera_fov(),
+            ..Self::from_transform(get_camera_transform())
+        }
+    }
+
+    /// Sends the transform, roll and field of view of this camera to the GPU.
+    /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+    pub fn apply(&self) {
+        set_camera_transform(self.transform);
+        set_camera_roll(self.roll);
+        set_camera_fov(self.fov);
+    }
+
+    /// Returns the position of the camera.
+    #[must_use]
+    pub const fn position(&self) -> Vec3<f32> {
+        Vec3::new(self.transform.x, self.transform.y, self.transform.z)
+    }
+
+    /// Returns the forward vector of the camera.
+    #[must_use]
+    pub fn forward(&self) -> Vec3<f32> {
+        self.transform.forward()
+    }
+
+    /// Returns the right vector of the camera, including the roll.
+    #[must_use]
+    pub fn right(&self) -> Vec3<f32> {
+        let (right, up) = self.unrolled_axes();
+        let (sin, cos) = self.roll.sin_cos();
+        right * cos + up * sin
+    }
+
+    /// Returns the up vector of the camera, including the roll.
+    #[must_use]
+    pub fn up(&self) -> Vec3<f32> {
+        let (right, up) = self.unrolled_axes();
+        let (sin, cos) = self.roll.sin_cos();
+        up * cos - right * sin
+    }
+
+    /// Returns the right and up vectors of the camera without the roll.
+    fn unrolled_axes(&self) -> (Vec3<f32>, Vec3<f32>) {
+        let forward = self.forward();
+        let right = forward
+            .cross(Vec3::unit_y())
+            .try_normalized()
+            .unwrap_or_else(Vec3::unit_x);
+        (right, right.cross(forward))
+    }
+
+    /// Returns the view matrix, transforming world space into camera space.
+    #[must_use]
+    pub fn view_matrix(&self) -> Mat4<f32> {
+        let eye = self.position();
+        Mat4::look_at_rh(eye, eye + self.forward(), self.up())
+    }
+
+    /// Returns the projection matrix for a target with an aspect ratio of `aspect`,
+    /// transforming camera space into OpenGL-style clip space.
+    #[must_use]
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4<f32> {
+        Mat4::perspective_rh_no(
+            self.fov.to_radians(),
+            aspect.max(f32::EPSILON),
+            self.near,
+            self.far,
+        )
+    }
+
+    /// Returns the projection matrix multiplied with the view matrix.
+    #[must_use]
+    pub fn view_projection(&self, aspect: f32) -> Mat4<f32> {
+        self.projection_matrix(aspect) * self.view_matrix()
+    }
+
+    /// Projects a world space point onto a screen of `size` pixels, with Y pointing down.
+    ///
+    /// Returns `None` if the point is behind the near plane.
+    #[must_use]
+    pub fn world_to_screen(&self, point: Vec3<f32>, size: Vec2<f32>) -> Option<Vec2<f32>> {
+        let clip = self.view_projection(size.x / size.y) * Vec4::from_point(point);
+        if clip.z < -clip.w || clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = Vec2::new(clip.x, clip.y) / clip.w;
+        Some(Vec2::new(ndc.x.mul_add(0.5, 0.5), ndc.y.mul_add(-0.5, 0.5)) * size)
+    }
+
+    /// Returns the ray from the camera through a point on a screen of `size` pixels,
+    /// with Y pointing down.
+    #[must_use]
+    pub fn screen_to_ray(&self, point: Vec2<f32>, size: Vec2<f32>) -> Ray<f32> {
+        let ndc = Vec2::new(
+            (point.x / size.x).mul_add(2.0, -1.0),
+            (point.y / size.y).mul_add(-2.0, 1.0),
+        );
+        let inverse = self.view_projection(size.x / size.y).inverted();
+        let near = inverse * Vec4::new(ndc.x, ndc.y, -1.0, 1.0);
+        let far = inverse * Vec4::new(ndc.x, ndc.y, 1.0, 1.0);
+        let near = near.xyz() / near.w;
+        let far = far.xyz() / far.w;
+
+        Ray::new(near, (far - near).normalized())
+    }
+
+    /// Returns the ray from the camera through the mouse cursor on the framebuffer.
+    ///
+    /// Requires the following permissions:
+    /// [`Gpu`](crate::system::Permission::Gpu),
+    /// [`InputMousePos`](crate::system::Permission::InputMousePos)
+    #[cfg(feature = "input")]
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mouse_ray(&self) -> Ray<f32> {
+        use crate::{
+            framebuffer::{get_framebuffer_height, get_framebuffer_width},
+            input::{get_mouse_x, get_mouse_y},
+        };
+
+        self.screen_to_ray(
+            Vec2::new(get_mouse_x() as f32 + 0.5, get_mouse_y() as f32 + 0.5),
+            Vec2::new(
+                get_framebuffer_width() as f32,
+                get_framebuffer_height() as f32,
+            ),
+        )
+    }
+
+    /// Returns the view frustum for a target with an aspect ratio of `aspect`.
+    #[must_use]
+    pub fn frustum(&self, aspect: f32) -> Frustum {
+        Frustum::from_matrix(&self.view_projection(aspect))
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A view frustum, as 6 planes facing inwards.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// The left, right, bottom, top, near and far planes, as a normal in XYZ and the
+    /// distance in W. Points inside have a positive distance to every plane.
+    pub planes: [Vec4<f32>; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum of a view-projection matrix.
+    #[must_use]
+    pub fn from_matrix(m: &Mat4<f32>) -> Self {
+        let rows = m.into_row_arrays().map(Vec4::from);
+        let planes = [
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] + rows[1],
+            rows[3] - rows[1],
+            rows[3] + rows[2],
+            rows[3] - rows[2],
+        ]
+        .map(|plane| {
+            let length = plane.xyz().magnitude();
+            if length > 0.0 { plane / length } else { plane }
+        });
+
+        Self { planes }
+    }
+
+    fn distance(plane: Vec4<f32>, point: Vec3<f32>) -> f32 {
+        plane.xyz().dot(point) + plane.w
+    }
+
+    /// Returns `true` if `point` is inside the frustum.
+    #[must_use]
+    pub fn contains_point(&self, point: Vec3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|&plane| Self::distance(plane, point) >= 0.0)
+    }
+
+    /// Returns `true` if a sphere is at least partially inside the frustum.
+    #[must_use]
+    pub fn intersects_sphere(&self, center: Vec3<f32>, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|&plane| Self::distance(plane, center) >= -radius)
+    }
+
+    /// Returns `true` if a box is at least partially inside the frustum.
+    ///
+    /// Do note; boxes near the frustum's corners may be reported as inside when they aren't.
+    #[must_use]
+    pub fn intersects_aabb(&self, aabb: &Aabb<f32>) -> bool {
+        self.planes.iter().all(|&plane| {
+            // The corner furthest along the plane's normal.
+            let corner = Vec3::new(
+                if plane.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            Self::distance(plane, corner) >= 0.0
+        })
+    }
+}
+
 /// Returns the current camera transform from the GPU.
 /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
 #[must_use]
@@ -170,26 +488,45 @@ pub fn set_camera_rotation(rotation: Vec2<f32>) {
     set_camera_transform(transform);
 }
 
-/// Returns the global camera transform's forward vector.
+/// Returns the global camera field of view in degrees.
 /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
 #[must_use]
-pub fn get_camera_forward_vector() -> Vec3<f32> {
-    let transform = get_camera_transform();
-    let yaw = transform.yaw;
-    let pitch = transform.pitch;
+pub fn get_camera_fov() -> f32 {
+    unsafe { bindings::get_camera_fov() }
+}
 
-    let cos_pitch = pitch.cos();
-    let sin_pitch = pitch.sin();
-    let cos_yaw = yaw.cos();
-    let sin_yaw = yaw.sin();
+/// Sets the global camera field of view in degrees.
+/// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+pub fn set_camera_fov(fov: f32) {
+    unsafe {
+        // The error here is ignored for performance's sake.
+        bindings::set_camera_fov(fov);
+    }
+}
 
-    Vec3 {
-        x: -sin_yaw * cos_pitch,
-        y: sin_pitch,
-        z: -cos_yaw * cos_pitch,
+/// Returns the global camera roll rotation in radians.
+/// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+#[must_use]
+pub fn get_camera_roll() -> f32 {
+    unsafe { bindings::get_camera_roll() }
+}
+
+/// Sets the global camera roll rotation in radians.
+/// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+pub fn set_camera_roll(roll: f32) {
+    unsafe {
+        // The error here is ignored for performance's sake.
+        bindings::set_camera_roll(roll);
     }
 }
 
+/// Returns the global camera transform's forward vector.
+/// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+#[must_use]
+pub fn get_camera_forward_vector() -> Vec3<f32> {
+    get_camera_transform().forward()
+}
+
 /// Returns the global camera transform's right vector.
 /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
 #[must_use]
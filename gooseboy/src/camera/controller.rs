@@ -0,0 +1,528 @@
+//! This is used to move the camera with reusable controllers, each holding its' own state
+//! and writing the camera transform once per frame.
+//!
+//! Example:
+//! ```rs
+//! static CONTROLLER: Mutex<OrbitController> = Mutex::new(OrbitController::new(Vec3::new(0.0, 0.0, 0.0), 5.0));
+//!
+//! #[gooseboy::update]
+//! fn update(nano_time: i64) {
+//!     CONTROLLER.lock().unwrap().tick(&Bindings::new(), 1.0 / 20.0);
+//! }
+//! ```
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::cast_possible_wrap)]
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    Ray, Vec2, Vec3,
+    camera::{CameraTransform, set_camera_transform},
+    input::{
+        get_mouse_accumulated_dx, get_mouse_accumulated_dy, grab_mouse, is_key_down,
+        is_key_just_pressed, release_mouse,
+    },
+    keys::{KEY_A, KEY_D, KEY_G, KEY_LEFT_SHIFT, KEY_R, KEY_S, KEY_SPACE, KEY_W, Key},
+};
+
+/// The default pitch limit, just short of looking straight up or down.
+pub const DEFAULT_PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+/// The default mouse sensitivity in radians per pixel.
+pub const DEFAULT_SENSITIVITY: f32 = 0.005;
+
+/// The keys controllers read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    /// Moves forward, or zooms in.
+    pub forward: Key,
+    /// Moves back, or zooms out.
+    pub back: Key,
+    /// Moves left.
+    pub left: Key,
+    /// Moves right.
+    pub right: Key,
+    /// Moves up, or jumps.
+    pub up: Key,
+    /// Moves down.
+    pub down: Key,
+    /// Grabs the mouse when pressed.
+    pub grab: Option<Key>,
+    /// Releases the mouse when pressed.
+    pub release: Option<Key>,
+}
+
+impl Bindings {
+    /// Creates the default [`Bindings`], WASD to move, space and left shift to move up and down,
+    /// G and R to grab and release the mouse.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            forward: KEY_W,
+            back: KEY_S,
+            left: KEY_A,
+            right: KEY_D,
+            up: KEY_SPACE,
+            down: KEY_LEFT_SHIFT,
+            grab: Some(KEY_G),
+            release: Some(KEY_R),
+        }
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The input of a single frame, as read by [`ControllerInput::read`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ControllerInput {
+    /// The mouse movement in pixels since the last frame.
+    pub mouse_delta: Vec2<f32>,
+    /// The movement keys held, from -1 to 1, as right in X, up in Y and forward in Z.
+    pub movement: Vec3<f32>,
+}
+
+impl ControllerInput {
+    /// Reads the input of this frame, grabbing or releasing the mouse if their keys were pressed.
+    ///
+    /// Requires the following permissions:
+    /// [`InputMousePos`](crate::system::Permission::InputMousePos),
+    /// [`InputGrabMouse`](crate::system::Permission::InputGrabMouse),
+    /// [`InputKeyboard`](crate::system::Permission::InputKeyboard)
+    #[must_use]
+    pub fn read(bindings: &Bindings) -> Self {
+        if bindings.grab.is_some_and(is_key_just_pressed) {
+            grab_mouse();
+        }
+        if bindings.release.is_some_and(is_key_just_pressed) {
+            release_mouse();
+        }
+
+        let axis = |positive: Key, negative: Key| {
+            f32::from(i8::from(is_key_down(positive)) - i8::from(is_key_down(negative)))
+        };
+        Self {
+            mouse_delta: Vec2::new(
+                get_mouse_accumulated_dx() as f32,
+                get_mouse_accumulated_dy() as f32,
+            ),
+            movement: Vec3::new(
+                axis(bindings.right, bindings.left),
+                axis(bindings.up, bindings.down),
+                axis(bindings.forward, bindings.back),
+            ),
+        }
+    }
+}
+
+/// A camera controller, holding its' own state between frames.
+pub trait CameraController {
+    /// Advances the controller by `dt` seconds, returning the new camera transform.
+    fn update(&mut self, input: &ControllerInput, dt: f32) -> CameraTransform;
+
+    /// Reads the input, advances the controller by `dt` seconds and sends the new
+    /// camera transform to the GPU.
+    ///
+    /// Requires the [`Gpu`](crate::system::Permission::Gpu) permission, and the permissions of
+    /// [`ControllerInput::read`].
+    fn tick(&mut self, bindings: &Bindings, dt: f32) -> CameraTransform {
+        let transform = self.update(&ControllerInput::read(bindings), dt);
+        set_camera_transform(transform);
+        transform
+    }
+}
+
+/// Returns how far to move towards a target this frame, for a smoothing time in seconds.
+/// A smoothing of 0 moves all the way.
+fn smoothing_factor(smoothing: f32, dt: f32) -> f32 {
+    if smoothing <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-dt / smoothing).exp()
+    }
+}
+
+const fn transform_at(position: Vec3<f32>, yaw: f32, pitch: f32) -> CameraTransform {
+    CameraTransform {
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        yaw,
+        pitch,
+    }
+}
+
+/// Orbits around a target with the mouse, zooming with forward and back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitController {
+    /// The point orbited around.
+    pub target: Vec3<f32>,
+    /// The distance from the target.
+    pub distance: f32,
+    /// The minimum distance from the target.
+    pub min_distance: f32,
+    /// The maximum distance from the target.
+    pub max_distance: f32,
+    /// The zoom speed in units per second.
+    pub zoom_speed: f32,
+    /// The yaw rotation in radians.
+    pub yaw: f32,
+    /// The pitch rotation in radians.
+    pub pitch: f32,
+    /// The minimum pitch in radians.
+    pub min_pitch: f32,
+    /// The maximum pitch in radians.
+    pub max_pitch: f32,
+    /// The mouse sensitivity in radians per pixel.
+    pub sensitivity: f32,
+    /// The time in seconds it takes to mostly catch up with the rotation and distance.
+    pub smoothing: f32,
+    current: Vec3<f32>,
+}
+
+impl OrbitController {
+    /// Creates a new [`OrbitController`] orbiting `target` from `distance` away.
+    #[must_use]
+    pub const fn new(target: Vec3<f32>, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            min_distance: 0.5,
+            max_distance: 100.0,
+            zoom_speed: 10.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_pitch: -DEFAULT_PITCH_LIMIT,
+            max_pitch: DEFAULT_PITCH_LIMIT,
+            sensitivity: DEFAULT_SENSITIVITY,
+            smoothing: 0.0,
+            current: Vec3::new(0.0, 0.0, distance),
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, input: &ControllerInput, dt: f32) -> CameraTransform {
+        self.yaw = input.mouse_delta.x.mul_add(-self.sensitivity, self.yaw);
+        self.pitch = (input.mouse_delta.y)
+            .mul_add(-self.sensitivity, self.pitch)
+            .clamp(self.min_pitch, self.max_pitch);
+        self.distance = (input.movement.z * dt)
+            .mul_add(-self.zoom_speed, self.distance)
+            .clamp(self.min_distance, self.max_distance);
+
+        let target = Vec3::new(self.yaw, self.pitch, self.distance);
+        self.current += (target - self.current) * smoothing_factor(self.smoothing, dt);
+
+        let Vec3 {
+            x: yaw,
+            y: pitch,
+            z: distance,
+        } = self.current;
+        let forward = transform_at(Vec3::zero(), yaw, pitch).forward();
+        transform_at(self.target - forward * distance, yaw, pitch)
+    }
+}
+
+/// Follows a target from behind with a spring arm, rotating around it with the mouse.
+#[derive(Clone, Copy, Debug)]
+pub struct FollowController {
+    /// The point followed, update it every frame.
+    pub target: Vec3<f32>,
+    /// The offset from the target the arm starts at, e.g. the height of the shoulders.
+    pub offset: Vec3<f32>,
+    /// The length of the arm.
+    pub arm_length: f32,
+    /// The yaw rotation in radians.
+    pub yaw: f32,
+    /// The pitch rotation in radians.
+    pub pitch: f32,
+    /// The minimum pitch in radians.
+    pub min_pitch: f32,
+    /// The maximum pitch in radians.
+    pub max_pitch: f32,
+    /// The mouse sensitivity in radians per pixel.
+    pub sensitivity: f32,
+    /// The time in seconds it takes the camera to mostly catch up with the target.
+    pub smoothing: f32,
+    /// Returns how far the arm can reach along a ray before hitting something,
+    /// up to the given length. The arm never collides without it.
+    pub probe: Option<fn(Ray<f32>, f32) -> f32>,
+    position: Option<Vec3<f32>>,
+}
+
+impl FollowController {
+    /// Creates a new [`FollowController`] following `target` from `arm_length` away.
+    #[must_use]
+    pub const fn new(target: Vec3<f32>, arm_length: f32) -> Self {
+        Self {
+            target,
+            offset: Vec3::new(0.0, 1.5, 0.0),
+            arm_length,
+            yaw: 0.0,
+            pitch: -0.3,
+            min_pitch: -DEFAULT_PITCH_LIMIT,
+            max_pitch: DEFAULT_PITCH_LIMIT,
+            sensitivity: DEFAULT_SENSITIVITY,
+            smoothing: 0.1,
+            probe: None,
+            position: None,
+        }
+    }
+}
+
+impl CameraController for FollowController {
+    fn update(&mut self, input: &ControllerInput, dt: f32) -> CameraTransform {
+        self.yaw = input.mouse_delta.x.mul_add(-self.sensitivity, self.yaw);
+        self.pitch = (input.mouse_delta.y)
+            .mul_add(-self.sensitivity, self.pitch)
+            .clamp(self.min_pitch, self.max_pitch);
+
+        let pivot = self.target + self.offset;
+        let back = -transform_at(Vec3::zero(), self.yaw, self.pitch).forward();
+        let length = self.probe.map_or(self.arm_length, |probe| {
+            probe(Ray::new(pivot, back), self.arm_length).clamp(0.0, self.arm_length)
+        });
+        let desired = pivot + back * length;
+
+        let mut position = self.position.map_or(desired, |position| {
+            position + (desired - position) * smoothing_factor(self.smoothing, dt)
+        });
+        // Never lag behind into whatever the arm hit.
+        if length < self.arm_length && position.distance(pivot) > length {
+            position = desired;
+        }
+        self.position = Some(position);
+
+        CameraTransform::looking_at(position, pivot)
+    }
+}
+
+/// Walks on the ground with the mouse to look around, or flies without a ground.
+#[derive(Clone, Copy, Debug)]
+pub struct FirstPersonController {
+    /// The position of the feet.
+    pub position: Vec3<f32>,
+    /// The velocity in units per second.
+    pub velocity: Vec3<f32>,
+    /// The height of the eyes above the feet.
+    pub eye_height: f32,
+    /// The walking speed in units per second.
+    pub speed: f32,
+    /// The vertical speed of a jump in units per second.
+    pub jump_speed: f32,
+    /// The gravity in units per second squared.
+    pub gravity: f32,
+    /// The yaw rotation in radians.
+    pub yaw: f32,
+    /// The pitch rotation in radians.
+    pub pitch: f32,
+    /// The minimum pitch in radians.
+    pub min_pitch: f32,
+    /// The maximum pitch in radians.
+    pub max_pitch: f32,
+    /// The mouse sensitivity in radians per pixel.
+    pub sensitivity: f32,
+    /// The time in seconds it takes to mostly reach the walking speed, or stop.
+    pub smoothing: f32,
+    /// Returns the height of the ground under a position, or `None` if there's no ground.
+    /// The controller flies without gravity without it.
+    pub ground: Option<fn(Vec3<f32>) -> Option<f32>>,
+    on_ground: bool,
+}
+
+impl FirstPersonController {
+    /// Creates a new [`FirstPersonController`] standing at `position`.
+    #[must_use]
+    pub const fn new(position: Vec3<f32>) -> Self {
+        Self {
+            position,
+            velocity: Vec3::new(0.0, 0.0, 0.0),
+            eye_height: 1.62,
+            speed: 4.3,
+            jump_speed: 8.0,
+            gravity: 25.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_pitch: -DEFAULT_PITCH_LIMIT,
+            max_pitch: DEFAULT_PITCH_LIMIT,
+            sensitivity: DEFAULT_SENSITIVITY,
+            smoothing: 0.05,
+            ground: None,
+            on_ground: false,
+        }
+    }
+
+    /// Returns `true` if the controller was standing on the ground after the last update.
+    #[must_use]
+    pub const fn is_on_ground(&self) -> bool {
+        self.on_ground
+    }
+}
+
+impl CameraController for FirstPersonController {
+    fn update(&mut self, input: &ControllerInput, dt: f32) -> CameraTransform {
+        self.yaw = input.mouse_delta.x.mul_add(-self.sensitivity, self.yaw);
+        self.pitch = (input.mouse_delta.y)
+            .mul_add(-self.sensitivity, self.pitch)
+            .clamp(self.min_pitch, self.max_pitch);
+
+        let (sin, cos) = self.yaw.sin_cos();
+        let forward = Vec3::new(-sin, 0.0, -cos);
+        let right = Vec3::new(cos, 0.0, -sin);
+        let mut wish = forward * input.movement.z + right * input.movement.x;
+        if wish.magnitude_squared() > 1.0 {
+            wish.normalize();
+        }
+        if self.ground.is_none() {
+            wish.y = input.movement.y;
+        }
+
+        let factor = smoothing_factor(self.smoothing, dt);
+        let wish = wish * self.speed;
+        self.velocity.x = (wish.x - self.velocity.x).mul_add(factor, self.velocity.x);
+        self.velocity.z = (wish.z - self.velocity.z).mul_add(factor, self.velocity.z);
+
+        if let Some(ground) = self.ground {
+            self.velocity.y = self.gravity.mul_add(-dt, self.velocity.y);
+            if self.on_ground && input.movement.y > 0.0 {
+                self.velocity.y = self.jump_speed;
+            }
+            self.position += self.velocity * dt;
+
+            self.on_ground = false;
+            if let Some(height) = ground(self.position)
+                && self.position.y <= height
+            {
+                self.position.y = height;
+                self.velocity.y = self.velocity.y.max(0.0);
+                self.on_ground = true;
+            }
+        } else {
+            self.velocity.y = (wish.y - self.velocity.y).mul_add(factor, self.velocity.y);
+            self.position += self.velocity * dt;
+        }
+
+        transform_at(
+            self.position + Vec3::unit_y() * self.eye_height,
+            self.yaw,
+            self.pitch,
+        )
+    }
+}
+
+/// Moves along a Catmull-Rom spline through a list of points, ignoring the input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplineController {
+    /// The points the spline goes through.
+    pub points: Vec<Vec3<f32>>,
+    /// The point to look at, or `None` to look along the spline.
+    pub look_at: Option<Vec3<f32>>,
+    /// The time in seconds it takes to go through every point.
+    pub duration: f32,
+    /// Whether the spline goes back to the first point and starts over.
+    pub looping: bool,
+    time: f32,
+}
+
+impl SplineController {
+    /// Creates a new [`SplineController`] going through `points` in `duration` seconds.
+    #[must_use]
+    pub const fn new(points: Vec<Vec3<f32>>, duration: f32) -> Self {
+        Self {
+            points,
+            look_at: None,
+            duration,
+            looping: false,
+            time: 0.0,
+        }
+    }
+
+    /// Returns the time in seconds since the start.
+    #[must_use]
+    pub const fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Goes back to the first point.
+    pub const fn restart(&mut self) {
+        self.time = 0.0;
+    }
+
+    /// Returns `true` if the controller reached the last point and isn't looping.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.time >= self.duration
+    }
+
+    /// Returns the position on the spline, from 0 at the first point to 1 at the end.
+    #[must_use]
+    pub fn position_at(&self, t: f32) -> Vec3<f32> {
+        let count = self.points.len();
+        if count < 2 {
+            return self.points.first().copied().unwrap_or_default();
+        }
+
+        let segments = if self.looping { count } else { count - 1 };
+        let progress = if self.looping {
+            t.rem_euclid(1.0)
+        } else {
+            t.clamp(0.0, 1.0)
+        } * segments as f32;
+        let segment = (progress.floor() as usize).min(segments - 1);
+        let local = progress - segment as f32;
+
+        let point = |index: isize| {
+            let index = if self.looping {
+                index.rem_euclid(count as isize)
+            } else {
+                index.clamp(0, count as isize - 1)
+            };
+            self.points[index as usize]
+        };
+        let segment = segment as isize;
+        let [p0, p1, p2, p3] = [
+            point(segment - 1),
+            point(segment),
+            point(segment + 1),
+            point(segment + 2),
+        ];
+
+        let linear = p2 - p0;
+        let quadratic = p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3;
+        let cubic = p1 * 3.0 - p0 - p2 * 3.0 + p3;
+        (p1 * 2.0 + linear * local + quadratic * (local * local) + cubic * (local * local * local))
+            * 0.5
+    }
+}
+
+impl CameraController for SplineController {
+    fn update(&mut self, _input: &ControllerInput, dt: f32) -> CameraTransform {
+        self.time += dt;
+        if self.looping && self.duration > 0.0 {
+            self.time = self.time.rem_euclid(self.duration);
+        } else {
+            self.time = self.time.min(self.duration);
+        }
+
+        let t = if self.duration > 0.0 {
+            self.time / self.duration
+        } else {
+            1.0
+        };
+        let position = self.position_at(t);
+        let target = self.look_at.unwrap_or_else(|| {
+            // Look a little further along the spline, or back at the end of it.
+            let ahead = self.position_at(t + 0.01);
+            if ahead.distance_squared(position) > f32::EPSILON {
+                ahead
+            } else {
+                position * 2.0 - self.position_at(t - 0.01)
+            }
+        });
+        CameraTransform::looking_at(position, target)
+    }
+}
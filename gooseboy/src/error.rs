@@ -1,10 +1,25 @@
 //! This is used to hold the Gooseboy host's errors.
 use thiserror::Error;
 
+#[cfg(feature = "gpu")]
+use crate::gpu::{GpuStatus, GpuValidationError};
+
 /// An error returned from the Gooseboy host.
 #[derive(Error, Debug)]
 pub enum GooseboyError {
     /// The crate is unauthorized.
     #[error("Unauthorized")]
     Unauthorized,
+    /// The GPU commands failed validation.
+    #[cfg(feature = "gpu")]
+    #[error("Invalid GPU commands: {0}")]
+    InvalidGpuCommands(#[from] GpuValidationError),
+    /// The GPU commands ran, but failed with a status.
+    #[cfg(feature = "gpu")]
+    #[error("GPU error: {0}")]
+    Gpu(GpuStatus),
+    /// The GPU gave a record or texture different ids than the ones that were returned for them.
+    #[cfg(feature = "gpu")]
+    #[error("GPU ids are out of sync with the host")]
+    GpuIdsOutOfSync,
 }
@@ -2,16 +2,31 @@
 //!
 //! Example:
 //! ```rs
-//! get_framebuffer_surface_mut().clear(Color::GREEN);
+//! let surface = get_framebuffer_surface_mut();
+//! surface.clear(Color::GREEN);
+//! surface.draw_line(Vec2::new(0, 0), Vec2::new(31, 15), 2, Color::RED, false);
+//! surface.fill_circle(Vec2::new(16, 16), 8, Color::new(0, 0, 255, 128), true);
+//!
+//! // Draws a panel, nothing drawn onto it can spill outside of it
+//! let mut panel = surface.view_mut(Rect::new(8, 8, 64, 32));
+//! panel.clear(Color::BLACK);
+//! draw_text_ex(&mut panel, 2, 2, "Hello!", Color::WHITE);
 //! ```
 use std::sync::{Mutex, MutexGuard};
 
 use crate::{
+    Rect,
     bindings::{self},
     color::Color,
     unsafe_casts,
 };
 
+pub mod raster;
+mod shapes;
+mod view;
+
+pub use view::{DrawTarget, SurfaceView};
+
 /// The global framebuffer surface.
 pub static FRAMEBUFFER_SURFACE: Mutex<Option<Surface>> = Mutex::new(None);
 
@@ -121,20 +136,20 @@ pub fn clear_framebuffer(color: Color) {
 /// # Safety
 /// This expects ptr to be a pointer to an RGBA buffer (check Surface's rgba)
 pub unsafe fn clear_surface(ptr: RawFramebufferPointer, size: usize, color: Color) {
-    let color_val = (usize::from(color.a) << 24)
-        | (usize::from(color.b) << 16)
-        | (usize::from(color.g) << 8)
-        | usize::from(color.r);
-
     unsafe {
         bindings::clear_surface(
             unsafe_casts::as_const_pointer(ptr),
             unsafe_casts::usize_as_i32(size),
-            unsafe_casts::usize_as_i32(color_val),
+            pack_color(color),
         );
     };
 }
 
+/// Packs a color the way the host expects it, as little endian RGBA.
+const fn pack_color(color: Color) -> i32 {
+    i32::from_le_bytes([color.r, color.g, color.b, color.a])
+}
+
 /// A surface to render on.
 #[derive(Clone)]
 pub struct Surface {
@@ -202,6 +217,8 @@ impl Surface {
 
     /// Draws a rectangle to the following position with the following dimensions, to the following `color`,
     /// with optional blending.
+    ///
+    /// The rectangle is clipped to the surface, and blending treats `color` as straight alpha.
     pub fn draw_rect(
         &mut self,
         x: i32,
@@ -211,9 +228,7 @@ impl Surface {
         color: Color,
         blend: bool,
     ) {
-        let mut surf = Self::new_empty(width, height);
-        surf.clear(color);
-        self.blit_premultiplied_clipped(x, y, width, height, &surf.rgba, blend);
+        self.as_view().draw_rect(x, y, width, height, color, blend);
     }
 
     /// Blits a surface to the following position with the following dimensions, to the following `color`,
@@ -227,18 +242,18 @@ impl Surface {
         src_rgba: &[u8],
         blend: bool,
     ) {
-        unsafe {
-            bindings::blit_premultiplied_clipped(
-                self.rgba.as_ptr(),
-                self.width,
-                self.height,
-                dest_x,
-                dest_y,
-                src_w,
-                src_h,
-                src_rgba.as_ptr(),
-                blend,
-            );
-        }
+        let dest = Rect::new(
+            dest_x,
+            dest_y,
+            i32::try_from(src_w).unwrap_or(i32::MAX),
+            i32::try_from(src_h).unwrap_or(i32::MAX),
+        );
+        let bounds = Rect::new(
+            0,
+            0,
+            i32::try_from(self.width).unwrap_or(i32::MAX),
+            i32::try_from(self.height).unwrap_or(i32::MAX),
+        );
+        view::blit_clipped(self, bounds, dest, src_rgba, blend);
     }
 }
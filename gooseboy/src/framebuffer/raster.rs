@@ -0,0 +1,102 @@
+//! Pure Rust implementations of the framebuffer host functions, working on RGBA byte buffers.
+//!
+//! These are what the headless host and the mock host run for the `framebuffer` imports, so they
+//! behave exactly like the mod's implementations of them.
+#![allow(
+    clippy::too_many_arguments,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+
+/// Clears `size` bytes of an RGBA buffer with a packed `color`.
+pub fn clear_rgba(rgba: &mut [u8], size: usize, color: i32) {
+    let color = color.to_le_bytes();
+    let size = size.min(rgba.len());
+    for pixel in rgba[..size].as_chunks_mut::<4>().0 {
+        *pixel = color;
+    }
+}
+
+/// Blits premultiplied `src` RGBA pixels onto `dest`, clipped to the destination.
+pub fn blit_rgba(
+    dest: &mut [u8],
+    dest_w: usize,
+    dest_h: usize,
+    dest_x: i32,
+    dest_y: i32,
+    src: &[u8],
+    src_w: usize,
+    src_h: usize,
+    blend: bool,
+) {
+    let x0 = i64::from(dest_x).max(0);
+    let y0 = i64::from(dest_y).max(0);
+    let x1 = (i64::from(dest_x) + src_w as i64).min(dest_w as i64);
+    let y1 = (i64::from(dest_y) + src_h as i64).min(dest_h as i64);
+
+    for y in y0..y1 {
+        let sy = (y - i64::from(dest_y)) as usize;
+        for x in x0..x1 {
+            let sx = (x - i64::from(dest_x)) as usize;
+            let si = (sy * src_w + sx) * 4;
+            let di = (y as usize * dest_w + x as usize) * 4;
+            let (Some(s), Some(_)) = (src.get(si..si + 4), dest.get(di..di + 4)) else {
+                continue;
+            };
+
+            if blend {
+                let inv = 255 - u32::from(s[3]);
+                for c in 0..4 {
+                    let d = u32::from(dest[di + c]);
+                    dest[di + c] = (u32::from(s[c]) + (d * inv + 127) / 255).min(255) as u8;
+                }
+            } else {
+                dest[di..di + 4].copy_from_slice(s);
+            }
+        }
+    }
+}
+
+/// Fills a `width`x`height` rectangle of `dest` with a packed straight-alpha `color`, clipped to the
+/// destination.
+pub fn fill_rect_rgba(
+    dest: &mut [u8],
+    dest_w: usize,
+    dest_h: usize,
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    color: i32,
+    blend: bool,
+) {
+    let color = color.to_le_bytes();
+    let x0 = i64::from(x).max(0) as usize;
+    let y0 = i64::from(y).max(0) as usize;
+    let x1 = (i64::from(x) + width as i64).clamp(0, dest_w as i64) as usize;
+    let y1 = (i64::from(y) + height as i64).clamp(0, dest_h as i64) as usize;
+    if x0 >= x1 {
+        return;
+    }
+
+    let alpha = u32::from(color[3]);
+    let inv = 255 - alpha;
+    for y in y0..y1 {
+        let Some(row) = dest.get_mut((y * dest_w + x0) * 4..(y * dest_w + x1) * 4) else {
+            continue;
+        };
+
+        for pixel in row.as_chunks_mut::<4>().0 {
+            if blend {
+                for (dest, &source) in pixel.iter_mut().zip(&color[..3]) {
+                    *dest =
+                        ((u32::from(source) * alpha + u32::from(*dest) * inv + 127) / 255) as u8;
+                }
+                pixel[3] = (alpha + (u32::from(pixel[3]) * inv + 127) / 255) as u8;
+            } else {
+                *pixel = color;
+            }
+        }
+    }
+}
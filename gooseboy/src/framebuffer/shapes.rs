@@ -0,0 +1,572 @@
+//! Shape drawing for [`Surface`] and [`SurfaceView`].
+//!
+//! Every shape is clipped to the view it's drawn onto, and is either written over it, or blended
+//! over it with the color's alpha if `blend` is set. Positions are pixels, so a polygon's
+//! vertices are the centers of pixels, and a pixel is filled if its' center is inside.
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+use std::ops::Range;
+
+use crate::{
+    Rect, Vec2,
+    color::Color,
+    framebuffer::{DrawTarget, Surface, SurfaceView},
+};
+
+/// Writes `color` over an RGBA pixel, or blends it over it with its' alpha.
+pub(super) fn put(pixel: &mut [u8; 4], color: Color, blend: bool) {
+    if !blend {
+        *pixel = [color.r, color.g, color.b, color.a];
+        return;
+    }
+
+    let alpha = u32::from(color.a);
+    let inv = 255 - alpha;
+    for (dest, source) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+        *dest = ((u32::from(source) * alpha + u32::from(*dest) * inv + 127) / 255) as u8;
+    }
+    pixel[3] = (alpha + (u32::from(pixel[3]) * inv + 127) / 255) as u8;
+}
+
+/// Returns the range of steps from `start` by `step` that land between `lo` and `hi`, inclusive.
+const fn steps_between(start: i64, step: i64, lo: i64, hi: i64) -> (i64, i64) {
+    if step < 0 {
+        (start - hi, start - lo)
+    } else {
+        (lo - start, hi - start)
+    }
+}
+
+/// Calls `f` with every pixel of a line that's inside of `clip`, with Bresenham's algorithm.
+///
+/// Only the part of the line inside of `clip` is walked, so the length of the line doesn't
+/// matter.
+fn for_line(from: Vec2<i32>, to: Vec2<i32>, clip: Rect<i32, i32>, mut f: impl FnMut(Vec2<i32>)) {
+    // Everything is done along the major axis `x` and the minor axis `y`, swapping them back
+    // for each pixel.
+    let delta = to.as_::<i64>() - from.as_::<i64>();
+    let x_major = delta.x.abs() >= delta.y.abs();
+    let swap = |v: Vec2<i64>| if x_major { v } else { Vec2::new(v.y, v.x) };
+    let (delta, start) = (swap(delta), swap(from.as_()));
+    let lo = swap(Vec2::new(clip.x, clip.y).as_());
+    let hi = swap(Vec2::new(
+        i64::from(clip.x) + i64::from(clip.w) - 1,
+        i64::from(clip.y) + i64::from(clip.h) - 1,
+    ));
+    let (major, minor) = (delta.x.abs(), delta.y.abs());
+    let step = delta.map(i64::signum);
+
+    // After `i` steps along the major axis, the line has taken
+    // `(2 * minor * i + major) / (2 * major)` steps along the minor axis.
+    let (first, last) = steps_between(start.x, step.x, lo.x, hi.x);
+    let (mut first, mut last) = (first.max(0), last.min(major));
+    let (first_minor, last_minor) = steps_between(start.y, step.y, lo.y, hi.y);
+    let (major2, minor2) = (i128::from(major) * 2, i128::from(minor) * 2);
+    if minor == 0 {
+        if first_minor > 0 || last_minor < 0 {
+            return;
+        }
+    } else {
+        if first_minor > 0 {
+            let before = (i128::from(major) - major2 * i128::from(first_minor)).div_euclid(minor2);
+            first = first.max(-before as i64);
+        }
+        let after = (major2 * i128::from(last_minor) + i128::from(major) - 1).div_euclid(minor2);
+        last = last.min(after.clamp(-1, i128::from(major)) as i64);
+    }
+    if first > last {
+        return;
+    }
+
+    let taken = minor2 * i128::from(first) + i128::from(major);
+    let divisor = major2.max(1);
+    let (mut j, mut error) = ((taken / divisor) as i64, (taken % divisor) as i64);
+    for i in first..=last {
+        let point = swap(Vec2::new(start.x + step.x * i, start.y + step.y * j));
+        f(point.as_());
+        error += minor * 2;
+        if error >= major * 2 {
+            error -= major * 2;
+            j += 1;
+        }
+    }
+}
+
+/// Returns the half width of the row `dy` rows away from an ellipse's center, or `None` if
+/// the row is outside of it.
+fn ellipse_half_width(radii: Vec2<usize>, dy: i32) -> Option<i32> {
+    // Half a pixel more than the radii rounds the ellipse, like the midpoint algorithm does.
+    let (rx, ry) = (radii.x as f32 + 0.5, radii.y as f32 + 0.5);
+    let t = dy as f32 / ry;
+    (t.abs() < 1.0).then(|| (rx * t.mul_add(-t, 1.0).sqrt()).floor() as i32)
+}
+
+/// Returns the barycentric edge function of `point` against the edge from `a` to `b`.
+const fn edge(a: Vec2<i64>, b: Vec2<i64>, point: Vec2<i64>) -> i64 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
+}
+
+/// Returns `true` if the edge from `a` to `b` of a clockwise triangle is a top or left edge,
+/// which own the pixels exactly on them, so triangles sharing an edge don't overlap.
+const fn is_top_left(a: Vec2<i64>, b: Vec2<i64>) -> bool {
+    (a.y == b.y && b.x > a.x) || b.y < a.y
+}
+
+impl SurfaceView<'_> {
+    /// Draws a horizontal run of pixels from `left` to `right`, inclusive.
+    fn draw_span(&mut self, y: i32, left: i32, right: i32, color: Color, blend: bool) {
+        let clip = self.clip_rect();
+        if y < clip.y || y - clip.y >= clip.h {
+            return;
+        }
+        let (left, right) = (left.max(clip.x), right.min(clip.x + clip.w - 1));
+        if left > right {
+            return;
+        }
+
+        // The clip rect is inside of the surface, so the span is too.
+        let origin = self.origin();
+        let row = (y + origin.y) as usize * self.surface.width;
+        let (left, right) = ((left + origin.x) as usize, (right + origin.x) as usize);
+        let span = (row + left) * 4..(row + right + 1) * 4;
+        if let Some(pixels) = self.surface.rgba.get_mut(span) {
+            for pixel in pixels.as_chunks_mut::<4>().0 {
+                put(pixel, color, blend);
+            }
+        }
+    }
+
+    /// Draws a shape made of a horizontal run of pixels per row, with `span` returning the
+    /// first and last column of a row, or `None` if the row is outside of the shape.
+    ///
+    /// Outlines only draw the pixels next to a pixel outside of the shape.
+    fn draw_spans(
+        &mut self,
+        rows: Range<i32>,
+        span: impl Fn(i32) -> Option<(i32, i32)>,
+        fill: bool,
+        color: Color,
+        blend: bool,
+    ) {
+        let clip = self.clip_rect();
+        let visible = rows.start.max(clip.y)..rows.end.min(clip.y + clip.h);
+        for y in visible {
+            let Some((left, right)) = span(y) else {
+                continue;
+            };
+            if fill {
+                self.draw_span(y, left, right, color, blend);
+                continue;
+            }
+
+            let inside = span(y - 1).zip(span(y + 1)).map(|(above, below)| {
+                (
+                    (left + 1).max(above.0).max(below.0),
+                    (right - 1).min(above.1).min(below.1),
+                )
+            });
+            match inside {
+                Some((inner_left, inner_right)) if inner_left <= inner_right => {
+                    self.draw_span(y, left, inner_left - 1, color, blend);
+                    self.draw_span(y, inner_right + 1, right, color, blend);
+                }
+                _ => self.draw_span(y, left, right, color, blend),
+            }
+        }
+    }
+
+    /// Draws a line from `from` to `to`, both inclusive, `thickness` pixels thick.
+    ///
+    /// Lines thicker than a pixel have square ends, reaching half the thickness past the ends.
+    pub fn draw_line(
+        &mut self,
+        from: Vec2<i32>,
+        to: Vec2<i32>,
+        thickness: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        if thickness <= 1 {
+            let clip = self.clip_rect();
+            for_line(from, to, clip, |point| {
+                self.draw_pixel(point.x, point.y, color, blend);
+            });
+            return;
+        }
+
+        let (from, to) = (from.as_::<f32>(), to.as_::<f32>());
+        let length = from.distance(to);
+        let direction = if length > f32::EPSILON {
+            (to - from) / length
+        } else {
+            Vec2::unit_x()
+        };
+        let along = direction * (thickness as f32 / 2.0);
+        let across = Vec2::new(-along.y, along.x);
+        self.fill_polygon_f32(
+            &[
+                from - along - across,
+                to + along - across,
+                to + along + across,
+                from - along + across,
+            ],
+            color,
+            blend,
+        );
+    }
+
+    /// Draws the outline of a circle.
+    pub fn draw_circle(&mut self, center: Vec2<i32>, radius: usize, color: Color, blend: bool) {
+        self.draw_ellipse(center, Vec2::broadcast(radius), color, blend);
+    }
+
+    /// Fills a circle.
+    pub fn fill_circle(&mut self, center: Vec2<i32>, radius: usize, color: Color, blend: bool) {
+        self.fill_ellipse(center, Vec2::broadcast(radius), color, blend);
+    }
+
+    /// Draws the outline of an ellipse with the horizontal and vertical `radii`.
+    pub fn draw_ellipse(
+        &mut self,
+        center: Vec2<i32>,
+        radii: Vec2<usize>,
+        color: Color,
+        blend: bool,
+    ) {
+        self.draw_ellipse_spans(center, radii, false, color, blend);
+    }
+
+    /// Fills an ellipse with the horizontal and vertical `radii`.
+    pub fn fill_ellipse(
+        &mut self,
+        center: Vec2<i32>,
+        radii: Vec2<usize>,
+        color: Color,
+        blend: bool,
+    ) {
+        self.draw_ellipse_spans(center, radii, true, color, blend);
+    }
+
+    fn draw_ellipse_spans(
+        &mut self,
+        center: Vec2<i32>,
+        radii: Vec2<usize>,
+        fill: bool,
+        color: Color,
+        blend: bool,
+    ) {
+        let ry = radii.y.min(i32::MAX as usize / 2) as i32;
+        self.draw_spans(
+            center.y.saturating_sub(ry)..center.y.saturating_add(ry).saturating_add(1),
+            |y| {
+                ellipse_half_width(radii, y.saturating_sub(center.y))
+                    .map(|half| (center.x.saturating_sub(half), center.x.saturating_add(half)))
+            },
+            fill,
+            color,
+            blend,
+        );
+    }
+
+    /// Draws the outline of a rectangle with corners rounded by `radius`.
+    pub fn draw_rounded_rect(
+        &mut self,
+        rect: Rect<i32, usize>,
+        radius: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        self.draw_rounded_rect_spans(rect, radius, false, color, blend);
+    }
+
+    /// Fills a rectangle with corners rounded by `radius`.
+    pub fn fill_rounded_rect(
+        &mut self,
+        rect: Rect<i32, usize>,
+        radius: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        self.draw_rounded_rect_spans(rect, radius, true, color, blend);
+    }
+
+    fn draw_rounded_rect_spans(
+        &mut self,
+        rect: Rect<i32, usize>,
+        radius: usize,
+        fill: bool,
+        color: Color,
+        blend: bool,
+    ) {
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+
+        let radius = radius.min((rect.w - 1) / 2).min((rect.h - 1) / 2) as i32;
+        let (right, bottom) = (
+            rect.x.saturating_add(rect.w as i32 - 1),
+            rect.y.saturating_add(rect.h as i32 - 1),
+        );
+        self.draw_spans(
+            rect.y..bottom.saturating_add(1),
+            |y| {
+                if y < rect.y || y > bottom {
+                    return None;
+                }
+
+                // Rows next to the top and bottom are inset by the corner's circle.
+                let corner = (rect.y + radius - y).max(y - (bottom - radius)).max(0);
+                let half = ellipse_half_width(Vec2::broadcast(radius as usize), corner)?;
+                let inset = radius - half;
+                Some((rect.x + inset, right - inset))
+            },
+            fill,
+            color,
+            blend,
+        );
+    }
+
+    /// Draws the outline of a closed polygon.
+    pub fn draw_polygon(&mut self, points: &[Vec2<i32>], color: Color, blend: bool) {
+        match points {
+            [point] => return self.draw_pixel(point.x, point.y, color, blend),
+            [from, to] => return self.draw_line(*from, *to, 1, color, blend),
+            _ => {}
+        }
+
+        // Every edge leaves out its' last pixel, which is the first pixel of the next edge.
+        let clip = self.clip_rect();
+        for (i, &from) in points.iter().enumerate() {
+            let to = points[(i + 1) % points.len()];
+            for_line(from, to, clip, |point| {
+                if point != to {
+                    self.draw_pixel(point.x, point.y, color, blend);
+                }
+            });
+        }
+    }
+
+    /// Fills a polygon with scanlines, with the even-odd rule, so the parts of a
+    /// self-intersecting polygon that are inside of it twice are left empty.
+    pub fn fill_polygon(&mut self, points: &[Vec2<i32>], color: Color, blend: bool) {
+        let points: Vec<Vec2<f32>> = points.iter().map(|point| point.as_()).collect();
+        self.fill_polygon_f32(&points, color, blend);
+    }
+
+    fn fill_polygon_f32(&mut self, points: &[Vec2<f32>], color: Color, blend: bool) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let (top, bottom) = points.iter().fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(top, bottom), point| (top.min(point.y), bottom.max(point.y)),
+        );
+        let clip = self.clip_rect();
+        let rows = top.ceil().max(clip.y as f32) as i32
+            ..bottom.ceil().min((clip.y + clip.h) as f32) as i32;
+
+        let mut crossings = Vec::new();
+        for y in rows {
+            let center = y as f32;
+            crossings.clear();
+            for (i, &a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                // Edges are half open, so a vertex on the scanline is only crossed once.
+                if (a.y <= center) != (b.y <= center) {
+                    crossings.push(((center - a.y) / (b.y - a.y)).mul_add(b.x - a.x, a.x));
+                }
+            }
+            crossings.sort_unstable_by(f32::total_cmp);
+
+            for pair in crossings.as_chunks::<2>().0 {
+                let (left, right) = (pair[0].ceil() as i32, pair[1].ceil() as i32 - 1);
+                self.draw_span(y, left, right, color, blend);
+            }
+        }
+    }
+
+    /// Fills a triangle with a single color.
+    pub fn fill_triangle(&mut self, points: [Vec2<i32>; 3], color: Color, blend: bool) {
+        self.fill_triangle_colored(points, [color; 3], blend);
+    }
+
+    /// Fills a triangle, interpolating the color of every vertex over it.
+    ///
+    /// Triangles sharing an edge never draw the same pixel twice, so meshes of triangles
+    /// blend correctly.
+    pub fn fill_triangle_colored(
+        &mut self,
+        points: [Vec2<i32>; 3],
+        colors: [Color; 3],
+        blend: bool,
+    ) {
+        let [mut a, mut b, c] = points.map(Vec2::as_::<i64>);
+        let mut colors = colors;
+        let mut area = edge(a, b, c);
+        if area < 0 {
+            std::mem::swap(&mut a, &mut b);
+            colors.swap(0, 1);
+            area = -area;
+        }
+        if area == 0 {
+            return;
+        }
+
+        let min = Vec2::new(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y));
+        let max = Vec2::new(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y));
+        let clip = self.clip_rect().map(i64::from, i64::from);
+        let (left, right) = (min.x.max(clip.x), max.x.min(clip.x + clip.w - 1));
+        let (top, bottom) = (min.y.max(clip.y), max.y.min(clip.y + clip.h - 1));
+
+        // Pixels exactly on an edge only belong to its' triangle if it's a top or left edge.
+        let bias = [(b, c), (c, a), (a, b)].map(|(from, to)| i64::from(!is_top_left(from, to)));
+        let flat = colors[0] == colors[1] && colors[1] == colors[2];
+        for y in top..=bottom {
+            for x in left..=right {
+                let point = Vec2::new(x, y);
+                let weights = [edge(b, c, point), edge(c, a, point), edge(a, b, point)];
+                if weights
+                    .iter()
+                    .zip(&bias)
+                    .any(|(&weight, &bias)| weight < bias)
+                {
+                    continue;
+                }
+
+                let color = if flat {
+                    colors[0]
+                } else {
+                    let weights = weights.map(|weight| weight as f32 / area as f32);
+                    let channel = |channel: fn(&Color) -> u8| {
+                        weights
+                            .iter()
+                            .zip(&colors)
+                            .fold(0.0, |sum, (&weight, color)| {
+                                weight.mul_add(f32::from(channel(color)), sum)
+                            })
+                            .round() as u8
+                    };
+                    Color::new(
+                        channel(|color| color.r),
+                        channel(|color| color.g),
+                        channel(|color| color.b),
+                        channel(|color| color.a),
+                    )
+                };
+                self.draw_pixel(x as i32, y as i32, color, blend);
+            }
+        }
+    }
+}
+
+impl Surface {
+    /// Draws a pixel, doing nothing if it's outside of the surface.
+    pub fn draw_pixel(&mut self, x: i32, y: i32, color: Color, blend: bool) {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return;
+        };
+        if let Some(index) = self.get_pixel_index(x, y)
+            && let Some(pixel) = self.rgba[index..].first_chunk_mut::<4>()
+        {
+            put(pixel, color, blend);
+        }
+    }
+
+    /// Draws a line from `from` to `to`, both inclusive, `thickness` pixels thick.
+    ///
+    /// Lines thicker than a pixel have square ends, reaching half the thickness past the ends.
+    pub fn draw_line(
+        &mut self,
+        from: Vec2<i32>,
+        to: Vec2<i32>,
+        thickness: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        self.as_view().draw_line(from, to, thickness, color, blend);
+    }
+
+    /// Draws the outline of a circle.
+    pub fn draw_circle(&mut self, center: Vec2<i32>, radius: usize, color: Color, blend: bool) {
+        self.as_view().draw_circle(center, radius, color, blend);
+    }
+
+    /// Fills a circle.
+    pub fn fill_circle(&mut self, center: Vec2<i32>, radius: usize, color: Color, blend: bool) {
+        self.as_view().fill_circle(center, radius, color, blend);
+    }
+
+    /// Draws the outline of an ellipse with the horizontal and vertical `radii`.
+    pub fn draw_ellipse(
+        &mut self,
+        center: Vec2<i32>,
+        radii: Vec2<usize>,
+        color: Color,
+        blend: bool,
+    ) {
+        self.as_view().draw_ellipse(center, radii, color, blend);
+    }
+
+    /// Fills an ellipse with the horizontal and vertical `radii`.
+    pub fn fill_ellipse(
+        &mut self,
+        center: Vec2<i32>,
+        radii: Vec2<usize>,
+        color: Color,
+        blend: bool,
+    ) {
+        self.as_view().fill_ellipse(center, radii, color, blend);
+    }
+
+    /// Draws the outline of a rectangle with corners rounded by `radius`.
+    pub fn draw_rounded_rect(
+        &mut self,
+        rect: Rect<i32, usize>,
+        radius: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        self.as_view().draw_rounded_rect(rect, radius, color, blend);
+    }
+
+    /// Fills a rectangle with corners rounded by `radius`.
+    pub fn fill_rounded_rect(
+        &mut self,
+        rect: Rect<i32, usize>,
+        radius: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        self.as_view().fill_rounded_rect(rect, radius, color, blend);
+    }
+
+    /// Draws the outline of a closed polygon.
+    pub fn draw_polygon(&mut self, points: &[Vec2<i32>], color: Color, blend: bool) {
+        self.as_view().draw_polygon(points, color, blend);
+    }
+
+    /// Fills a polygon with scanlines, with the even-odd rule.
+    pub fn fill_polygon(&mut self, points: &[Vec2<i32>], color: Color, blend: bool) {
+        self.as_view().fill_polygon(points, color, blend);
+    }
+
+    /// Fills a triangle with a single color.
+    pub fn fill_triangle(&mut self, points: [Vec2<i32>; 3], color: Color, blend: bool) {
+        self.as_view().fill_triangle(points, color, blend);
+    }
+
+    /// Fills a triangle, interpolating the color of every vertex over it.
+    pub fn fill_triangle_colored(
+        &mut self,
+        points: [Vec2<i32>; 3],
+        colors: [Color; 3],
+        blend: bool,
+    ) {
+        self.as_view().fill_triangle_colored(points, colors, blend);
+    }
+}
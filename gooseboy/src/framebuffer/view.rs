@@ -0,0 +1,316 @@
+//! Borrowed sub-surface views and their clip-rect stacks.
+//!
+//! A [`SurfaceView`] is a window into a surface with its own origin, so a panel can draw at
+//! `0, 0` without computing offsets, and nothing it draws can spill outside of it. Clip rects
+//! pushed onto a view restrict every drawing function of it until they're popped.
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+use crate::{
+    Rect, Vec2,
+    bindings::{self},
+    color::Color,
+    framebuffer::{Surface, pack_color, shapes::put},
+};
+
+/// Returns the part of `a` that's inside of `b`, with a size of zero if they don't overlap.
+fn intersect(a: Rect<i32, i32>, b: Rect<i32, i32>) -> Rect<i32, i32> {
+    let (x0, y0) = (a.x.max(b.x), a.y.max(b.y));
+    let x1 = (i64::from(a.x) + i64::from(a.w)).min(i64::from(b.x) + i64::from(b.w));
+    let y1 = (i64::from(a.y) + i64::from(a.h)).min(i64::from(b.y) + i64::from(b.h));
+    Rect::new(
+        x0,
+        y0,
+        (x1 - i64::from(x0)).max(0) as i32,
+        (y1 - i64::from(y0)).max(0) as i32,
+    )
+}
+
+/// Returns `true` if the point is inside of `rect`.
+const fn contains(rect: Rect<i32, i32>, x: i32, y: i32) -> bool {
+    x >= rect.x && y >= rect.y && x - rect.x < rect.w && y - rect.y < rect.h
+}
+
+/// Converts a size to an `i32`, saturating.
+fn size(value: usize) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+/// Blits premultiplied RGBA pixels onto the `dest` rect of `surface`, clipped to `visible`,
+/// with optional blending.
+pub(super) fn blit_clipped(
+    surface: &Surface,
+    visible: Rect<i32, i32>,
+    dest: Rect<i32, i32>,
+    src_rgba: &[u8],
+    blend: bool,
+) {
+    let rect = intersect(dest, visible);
+    if rect.w == 0 || rect.h == 0 {
+        return;
+    }
+
+    let (width, height) = (surface.width, surface.height);
+    let ptr = surface.rgba.as_ptr();
+    if rect == dest {
+        unsafe {
+            bindings::blit_premultiplied_clipped(
+                ptr,
+                width,
+                height,
+                rect.x,
+                rect.y,
+                dest.w as usize,
+                dest.h as usize,
+                src_rgba.as_ptr(),
+                blend,
+            );
+        }
+        return;
+    }
+
+    // The host can only blit whole sources, so clipped blits go a row at a time.
+    let (left, row_len) = ((rect.x - dest.x) as usize, rect.w as usize * 4);
+    for y in rect.y..rect.y + rect.h {
+        let start = (((y - dest.y) as usize) * dest.w as usize + left) * 4;
+        let Some(row) = src_rgba.get(start..start + row_len) else {
+            return;
+        };
+        unsafe {
+            bindings::blit_premultiplied_clipped(
+                ptr,
+                width,
+                height,
+                rect.x,
+                y,
+                rect.w as usize,
+                1,
+                row.as_ptr(),
+                blend,
+            );
+        }
+    }
+}
+
+/// Something that can be drawn onto, either a [`Surface`] or a [`SurfaceView`] of one.
+pub trait DrawTarget {
+    /// Returns a view of everything this can draw onto.
+    fn as_view(&mut self) -> SurfaceView<'_>;
+}
+
+impl DrawTarget for Surface {
+    fn as_view(&mut self) -> SurfaceView<'_> {
+        let bounds = Rect::new(0, 0, size(self.width), size(self.height));
+        SurfaceView {
+            surface: self,
+            bounds,
+            clip: bounds,
+            clips: Vec::new(),
+        }
+    }
+}
+
+impl DrawTarget for SurfaceView<'_> {
+    fn as_view(&mut self) -> SurfaceView<'_> {
+        SurfaceView {
+            surface: self.surface,
+            bounds: self.bounds,
+            clip: self.clip,
+            clips: Vec::new(),
+        }
+    }
+}
+
+/// A borrowed window into a [`Surface`], with its own origin.
+///
+/// Positions are relative to the view's origin, and drawing is clipped to the view, and to the
+/// clip rects pushed onto it.
+pub struct SurfaceView<'a> {
+    pub(super) surface: &'a mut Surface,
+    /// The rect of the view on the surface.
+    bounds: Rect<i32, i32>,
+    /// The part of the surface the view can draw onto, inside of every parent view and pushed
+    /// clip rect.
+    clip: Rect<i32, i32>,
+    /// The clip of the view before each pushed clip rect.
+    clips: Vec<Rect<i32, i32>>,
+}
+
+impl Surface {
+    /// Returns a view of the `rect` of the surface.
+    pub fn view_mut(&mut self, rect: Rect<i32, i32>) -> SurfaceView<'_> {
+        let bounds = Rect::new(0, 0, size(self.width), size(self.height));
+        SurfaceView {
+            surface: self,
+            bounds: rect,
+            clip: intersect(rect, bounds),
+            clips: Vec::new(),
+        }
+    }
+}
+
+impl SurfaceView<'_> {
+    /// Returns the width of the view.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.bounds.w as usize
+    }
+
+    /// Returns the height of the view.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.bounds.h as usize
+    }
+
+    /// Returns the position of the view on its' surface.
+    #[must_use]
+    pub const fn origin(&self) -> Vec2<i32> {
+        Vec2::new(self.bounds.x, self.bounds.y)
+    }
+
+    /// Returns the amount of pixels between two rows of the view.
+    #[must_use]
+    pub const fn stride(&self) -> usize {
+        self.surface.width
+    }
+
+    /// Returns the rect of the view that can currently be drawn onto, relative to the view.
+    #[must_use]
+    pub fn clip_rect(&self) -> Rect<i32, i32> {
+        let visible = self.visible();
+        Rect::new(
+            visible.x - self.bounds.x,
+            visible.y - self.bounds.y,
+            visible.w,
+            visible.h,
+        )
+    }
+
+    /// Returns a view of the `rect` of this view, which can't draw outside of this view.
+    pub fn view_mut(&mut self, rect: Rect<i32, i32>) -> SurfaceView<'_> {
+        let bounds = self.to_surface(rect);
+        SurfaceView {
+            surface: self.surface,
+            bounds,
+            clip: intersect(bounds, self.clip),
+            clips: Vec::new(),
+        }
+    }
+
+    /// Restricts drawing to `rect` of this view, inside of the current clip rect, until it's popped.
+    pub fn push_clip(&mut self, rect: Rect<i32, i32>) {
+        let rect = self.to_surface(rect);
+        self.clips.push(self.clip);
+        self.clip = intersect(rect, self.clip);
+    }
+
+    /// Removes the last pushed clip rect, returning what it restricted drawing to,
+    /// relative to this view.
+    pub fn pop_clip(&mut self) -> Option<Rect<i32, i32>> {
+        let popped = self.clip_rect();
+        self.clip = self.clips.pop()?;
+        Some(popped)
+    }
+
+    /// Moves a rect relative to the view onto the surface.
+    fn to_surface(&self, rect: Rect<i32, i32>) -> Rect<i32, i32> {
+        Rect::new(
+            rect.x.saturating_add(self.bounds.x),
+            rect.y.saturating_add(self.bounds.y),
+            rect.w,
+            rect.h,
+        )
+    }
+
+    /// Returns the rect of the surface that can currently be drawn onto.
+    const fn visible(&self) -> Rect<i32, i32> {
+        self.clip
+    }
+
+    /// Clears the view with the following `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.draw_rect(0, 0, self.width(), self.height(), color, false);
+    }
+
+    /// Sets a pixel to the following `color`.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let (x, y) = (
+            size(x).saturating_add(self.bounds.x),
+            size(y).saturating_add(self.bounds.y),
+        );
+        if contains(self.visible(), x, y)
+            && let Some(index) = self.surface.get_pixel_index(x as usize, y as usize)
+        {
+            unsafe {
+                color.blit_ex(self.surface, index);
+            }
+        }
+    }
+
+    /// Draws a pixel, doing nothing if it's outside of the view.
+    pub fn draw_pixel(&mut self, x: i32, y: i32, color: Color, blend: bool) {
+        let (x, y) = (
+            x.saturating_add(self.bounds.x),
+            y.saturating_add(self.bounds.y),
+        );
+        if contains(self.visible(), x, y)
+            && let Some(index) = self.surface.get_pixel_index(x as usize, y as usize)
+            && let Some(pixel) = self.surface.rgba[index..].first_chunk_mut::<4>()
+        {
+            put(pixel, color, blend);
+        }
+    }
+
+    /// Draws a rectangle to the following position with the following dimensions, to the following `color`,
+    /// with optional blending.
+    ///
+    /// The rectangle is clipped to the view, and blending treats `color` as straight alpha.
+    pub fn draw_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: usize,
+        height: usize,
+        color: Color,
+        blend: bool,
+    ) {
+        let rect = intersect(
+            self.to_surface(Rect::new(x, y, size(width), size(height))),
+            self.visible(),
+        );
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+
+        unsafe {
+            bindings::fill_rect_clipped(
+                self.surface.rgba.as_mut_ptr().cast_const(),
+                self.surface.width,
+                self.surface.height,
+                rect.x,
+                rect.y,
+                rect.w as usize,
+                rect.h as usize,
+                pack_color(color),
+                blend,
+            );
+        }
+    }
+
+    /// Blits premultiplied RGBA pixels to the following position, clipped to the view,
+    /// with optional blending.
+    pub fn blit_premultiplied_clipped(
+        &mut self,
+        dest_x: i32,
+        dest_y: i32,
+        src_w: usize,
+        src_h: usize,
+        src_rgba: &[u8],
+        blend: bool,
+    ) {
+        let dest = self.to_surface(Rect::new(dest_x, dest_y, size(src_w), size(src_h)));
+        blit_clipped(self.surface, self.visible(), dest, src_rgba, blend);
+    }
+}
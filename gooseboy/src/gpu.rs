@@ -14,14 +14,32 @@
 //! buffer.insert(&GpuCommand::Pop);
 //! let _ = buffer.upload();
 //! ```
+use std::{borrow::Cow, fmt};
+
 use crate::{
+    Mat4, Vec2, Vec3,
     bindings::{self, gpu_read, submit_gpu_commands},
+    color::Color,
     error::GooseboyError,
+    gpu::{
+        protocol::{HEADER_SIZE, Header, VERTEX_COLOR_SIZE, VERTEX_LIT_SIZE, VERTEX_SIZE},
+        validate::Validator,
+    },
     mem::alloc_bytes,
     sprite::Sprite,
+    texture::{EncodedTexture, TEXTURE_RLE, TextureFormat},
     unsafe_casts,
 };
 
+pub mod debug_draw;
+mod decode;
+pub mod protocol;
+pub mod software;
+mod validate;
+
+pub use decode::{GpuCommandReader, GpuDecodeError};
+pub use validate::GpuValidationError;
+
 /// `GooseGPU` virtual memory location of the last status.
 pub const GB_GPU_STATUS: u32 = 0;
 /// `GooseGPU` virtual memory location of the last record id.
@@ -30,6 +48,12 @@ pub const GB_GPU_RECORD_ID: u32 = 4;
 pub const GB_GPU_TEXTURE_ID: u32 = 8;
 /// `GooseGPU` virtual memory location of the last matrix depth.
 pub const GB_GPU_MATRIX_DEPTH: u32 = 12;
+/// The size of the `GooseGPU` virtual memory in bytes.
+pub const GB_GPU_MEMORY_SIZE: usize = 16;
+/// The maximum matrix depth of the `GooseGPU`, pushing past it fails.
+pub const GB_GPU_MAX_MATRIX_DEPTH: u32 = 64;
+/// The maximum width or height of a `GooseGPU` texture.
+pub const GB_GPU_MAX_TEXTURE_SIZE: u32 = 16384;
 /// `GooseGPU` status: OK
 pub const GB_STATUS_OK: u32 = 0;
 /// `GooseGPU` status for when the uploaded texture is too big.
@@ -42,10 +66,114 @@ pub const GB_STATUS_MATRIX_TOO_SMALL: u32 = 3;
 pub const GB_STATUS_MATRIX_TOO_BIG: u32 = 4;
 /// `GooseGPU` status for when failing to emit vertices because we never got a call to start recording.
 pub const GB_STATUS_NOT_RECORDING: u32 = 5;
+/// `GooseGPU` status for when a record id was never recorded, or was deleted.
+pub const GB_STATUS_UNKNOWN_RECORD: u32 = 6;
+/// `GooseGPU` status for when vertices don't match the vertex format of the record.
+pub const GB_STATUS_BAD_VERTEX_FORMAT: u32 = 7;
+
+/// A `GooseGPU` status, read from [`GB_GPU_STATUS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuStatus {
+    /// The last commands ran successfully.
+    Ok,
+    /// The uploaded texture is too big.
+    BadTextureSize,
+    /// The uploaded texture is malformed.
+    BadTexture,
+    /// The matrix is too small post-pop.
+    MatrixTooSmall,
+    /// The matrix is too big post-push.
+    MatrixTooBig,
+    /// Vertices were emitted without recording.
+    NotRecording,
+    /// A record id was never recorded, or was deleted.
+    UnknownRecord,
+    /// Vertices didn't match the vertex format of the record.
+    BadVertexFormat,
+    /// A status this version doesn't know about.
+    Unknown(u32),
+}
+
+impl GpuStatus {
+    /// Returns the [`GpuStatus`] of a raw status value.
+    #[must_use]
+    pub const fn from_raw(raw: u32) -> Self {
+        match raw {
+            GB_STATUS_OK => Self::Ok,
+            GB_STATUS_BAD_TEXTURE_SIZE => Self::BadTextureSize,
+            GB_STATUS_BAD_TEXTURE => Self::BadTexture,
+            GB_STATUS_MATRIX_TOO_SMALL => Self::MatrixTooSmall,
+            GB_STATUS_MATRIX_TOO_BIG => Self::MatrixTooBig,
+            GB_STATUS_NOT_RECORDING => Self::NotRecording,
+            GB_STATUS_UNKNOWN_RECORD => Self::UnknownRecord,
+            GB_STATUS_BAD_VERTEX_FORMAT => Self::BadVertexFormat,
+            raw => Self::Unknown(raw),
+        }
+    }
+
+    /// Returns the raw status value of this [`GpuStatus`].
+    #[must_use]
+    pub const fn raw(&self) -> u32 {
+        match self {
+            Self::Ok => GB_STATUS_OK,
+            Self::BadTextureSize => GB_STATUS_BAD_TEXTURE_SIZE,
+            Self::BadTexture => GB_STATUS_BAD_TEXTURE,
+            Self::MatrixTooSmall => GB_STATUS_MATRIX_TOO_SMALL,
+            Self::MatrixTooBig => GB_STATUS_MATRIX_TOO_BIG,
+            Self::NotRecording => GB_STATUS_NOT_RECORDING,
+            Self::UnknownRecord => GB_STATUS_UNKNOWN_RECORD,
+            Self::BadVertexFormat => GB_STATUS_BAD_VERTEX_FORMAT,
+            Self::Unknown(raw) => *raw,
+        }
+    }
+
+    /// Reads the last [`GpuStatus`] from the `GooseGPU` virtual memory.
+    /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+    #[must_use]
+    pub fn read() -> Self {
+        Self::from_raw(gpu_read_value(GB_GPU_STATUS))
+    }
+
+    /// Converts this [`GpuStatus`] into a result.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`GooseboyError::Gpu`] if the status isn't [`GpuStatus::Ok`].
+    pub const fn into_result(self) -> Result<(), GooseboyError> {
+        match self {
+            Self::Ok => Ok(()),
+            status => Err(GooseboyError::Gpu(status)),
+        }
+    }
+}
+
+impl fmt::Display for GpuStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok => write!(f, "OK"),
+            Self::BadTextureSize => write!(f, "bad texture size"),
+            Self::BadTexture => write!(f, "bad texture"),
+            Self::MatrixTooSmall => write!(f, "matrix too small"),
+            Self::MatrixTooBig => write!(f, "matrix too big"),
+            Self::NotRecording => write!(f, "not recording"),
+            Self::UnknownRecord => write!(f, "unknown record"),
+            Self::BadVertexFormat => write!(f, "bad vertex format"),
+            Self::Unknown(raw) => write!(f, "unknown status {raw}"),
+        }
+    }
+}
+
+/// The id of a record, returned by [`GpuCommandBuffer::record`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RecordId(pub u32);
+
+/// The id of a texture, returned by [`GpuCommandBuffer::register_texture`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureId(pub u32);
 
 /// A vertex, with a position and UV.
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vertex {
     /// The X position.
     pub x: f32,
@@ -69,8 +197,8 @@ impl Vertex {
 
     /// Returns the `Vertex` as a byte array.
     #[must_use]
-    pub fn as_bytes(&self) -> [u8; 20] {
-        let mut bytes = [0u8; 20];
+    pub fn as_bytes(&self) -> [u8; VERTEX_SIZE] {
+        let mut bytes = [0u8; VERTEX_SIZE];
         bytes[..4].copy_from_slice(&self.x.to_le_bytes());
         bytes[4..8].copy_from_slice(&self.y.to_le_bytes());
         bytes[8..12].copy_from_slice(&self.z.to_le_bytes());
@@ -80,13 +208,304 @@ impl Vertex {
     }
 }
 
+impl fmt::Display for Vertex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}, {}) uv ({}, {})",
+            self.x, self.y, self.z, self.u, self.v
+        )
+    }
+}
+
+/// A vertex, with a position, UV and color.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexColor {
+    /// The X position.
+    pub x: f32,
+    /// The Y position.
+    pub y: f32,
+    /// The Z position.
+    pub z: f32,
+    /// The UV's U component.
+    pub u: f32,
+    /// The UV's V component.
+    pub v: f32,
+    /// The color, multiplied with the texture.
+    pub color: Color,
+}
+
+impl VertexColor {
+    /// Creates a new [`VertexColor`].
+    #[must_use]
+    #[allow(clippy::many_single_char_names)]
+    pub const fn new(x: f32, y: f32, z: f32, u: f32, v: f32, color: Color) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            u,
+            v,
+            color,
+        }
+    }
+
+    /// Returns the `VertexColor` as a byte array.
+    #[must_use]
+    pub fn as_bytes(&self) -> [u8; VERTEX_COLOR_SIZE] {
+        let mut bytes = [0u8; VERTEX_COLOR_SIZE];
+        bytes[..VERTEX_SIZE]
+            .copy_from_slice(&Vertex::new(self.x, self.y, self.z, self.u, self.v).as_bytes());
+        bytes[VERTEX_SIZE..].copy_from_slice(&[
+            self.color.r,
+            self.color.g,
+            self.color.b,
+            self.color.a,
+        ]);
+        bytes
+    }
+}
+
+impl From<Vertex> for VertexColor {
+    fn from(v: Vertex) -> Self {
+        Self::new(v.x, v.y, v.z, v.u, v.v, Color::WHITE)
+    }
+}
+
+impl fmt::Display for VertexColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Color { r, g, b, a } = self.color;
+        write!(
+            f,
+            "({}, {}, {}) uv ({}, {}) color #{r:02x}{g:02x}{b:02x}{a:02x}",
+            self.x, self.y, self.z, self.u, self.v
+        )
+    }
+}
+
+/// A vertex, with a position, UV, normal and color, lit by the `GooseGPU`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexLit {
+    /// The X position.
+    pub x: f32,
+    /// The Y position.
+    pub y: f32,
+    /// The Z position.
+    pub z: f32,
+    /// The UV's U component.
+    pub u: f32,
+    /// The UV's V component.
+    pub v: f32,
+    /// The normal's X component.
+    pub nx: f32,
+    /// The normal's Y component.
+    pub ny: f32,
+    /// The normal's Z component.
+    pub nz: f32,
+    /// The color, multiplied with the texture.
+    pub color: Color,
+}
+
+impl VertexLit {
+    /// Creates a new [`VertexLit`].
+    #[must_use]
+    pub const fn new(position: Vec3<f32>, uv: Vec2<f32>, normal: Vec3<f32>, color: Color) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            u: uv.x,
+            v: uv.y,
+            nx: normal.x,
+            ny: normal.y,
+            nz: normal.z,
+            color,
+        }
+    }
+
+    /// Returns the `VertexLit` as a byte array.
+    #[must_use]
+    pub fn as_bytes(&self) -> [u8; VERTEX_LIT_SIZE] {
+        let mut bytes = [0u8; VERTEX_LIT_SIZE];
+        bytes[..VERTEX_SIZE]
+            .copy_from_slice(&Vertex::new(self.x, self.y, self.z, self.u, self.v).as_bytes());
+        bytes[20..24].copy_from_slice(&self.nx.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.ny.to_le_bytes());
+        bytes[28..32].copy_from_slice(&self.nz.to_le_bytes());
+        bytes[32..].copy_from_slice(&[self.color.r, self.color.g, self.color.b, self.color.a]);
+        bytes
+    }
+}
+
+impl From<Vertex> for VertexLit {
+    fn from(v: Vertex) -> Self {
+        VertexColor::from(v).into()
+    }
+}
+
+impl From<VertexColor> for VertexLit {
+    fn from(v: VertexColor) -> Self {
+        Self::new(
+            Vec3::new(v.x, v.y, v.z),
+            Vec2::new(v.u, v.v),
+            Vec3::zero(),
+            v.color,
+        )
+    }
+}
+
+impl fmt::Display for VertexLit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Color { r, g, b, a } = self.color;
+        write!(
+            f,
+            "({}, {}, {}) uv ({}, {}) normal ({}, {}, {}) color #{r:02x}{g:02x}{b:02x}{a:02x}",
+            self.x, self.y, self.z, self.u, self.v, self.nx, self.ny, self.nz
+        )
+    }
+}
+
+/// The vertex format of a record.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VertexFormat {
+    /// [`Vertex`], a position and UV.
+    #[default]
+    Standard,
+    /// [`VertexColor`], a position, UV and color.
+    Color,
+    /// [`VertexLit`], a position, UV, normal and color.
+    Lit,
+}
+
+impl VertexFormat {
+    /// Returns the representation of this [`VertexFormat`].
+    #[must_use]
+    pub const fn repr(&self) -> u8 {
+        match self {
+            Self::Standard => 0,
+            Self::Color => 1,
+            Self::Lit => 2,
+        }
+    }
+
+    /// Returns the [`VertexFormat`] of a representation, or `None` if unknown.
+    #[must_use]
+    pub const fn from_repr(repr: u8) -> Option<Self> {
+        match repr {
+            0 => Some(Self::Standard),
+            1 => Some(Self::Color),
+            2 => Some(Self::Lit),
+            _ => None,
+        }
+    }
+
+    /// Returns the size of a single vertex of this format in bytes.
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Standard => VERTEX_SIZE,
+            Self::Color => VERTEX_COLOR_SIZE,
+            Self::Lit => VERTEX_LIT_SIZE,
+        }
+    }
+}
+
+impl fmt::Display for VertexFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Standard => "standard",
+            Self::Color => "color",
+            Self::Lit => "lit",
+        })
+    }
+}
+
+/// An array of vertices of any [`VertexFormat`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum VertexData {
+    /// [`Vertex`]es.
+    Standard(Box<[Vertex]>),
+    /// [`VertexColor`]s.
+    Color(Box<[VertexColor]>),
+    /// [`VertexLit`]s.
+    Lit(Box<[VertexLit]>),
+}
+
+impl VertexData {
+    /// Returns the [`VertexFormat`] of the vertices.
+    #[must_use]
+    pub const fn format(&self) -> VertexFormat {
+        match self {
+            Self::Standard(_) => VertexFormat::Standard,
+            Self::Color(_) => VertexFormat::Color,
+            Self::Lit(_) => VertexFormat::Lit,
+        }
+    }
+
+    /// Returns the amount of vertices.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        match self {
+            Self::Standard(vertices) => vertices.len(),
+            Self::Color(vertices) => vertices.len(),
+            Self::Lit(vertices) => vertices.len(),
+        }
+    }
+
+    /// Returns `true` if there are no vertices.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Standard(vertices) => {
+                for v in vertices {
+                    buf.extend_from_slice(&v.as_bytes());
+                }
+            }
+            Self::Color(vertices) => {
+                for v in vertices {
+                    buf.extend_from_slice(&v.as_bytes());
+                }
+            }
+            Self::Lit(vertices) => {
+                for v in vertices {
+                    buf.extend_from_slice(&v.as_bytes());
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for VertexData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard(vertices) => vertices.iter().try_for_each(|v| write!(f, "\n    {v}")),
+            Self::Color(vertices) => vertices.iter().try_for_each(|v| write!(f, "\n    {v}")),
+            Self::Lit(vertices) => vertices.iter().try_for_each(|v| write!(f, "\n    {v}")),
+        }
+    }
+}
+
 /// The type of the primitive.
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PrimitiveType {
     /// Triangles.
     Triangles,
     /// Quads.
     Quads,
+    /// Lines between every pair of vertices, see [`GpuCommand::LineWidth`].
+    Lines,
+    /// Connected lines between every vertex and the next one, see [`GpuCommand::LineWidth`].
+    LineStrip,
+    /// A square point at every vertex, see [`GpuCommand::LineWidth`].
+    Points,
 }
 
 impl PrimitiveType {
@@ -96,12 +515,41 @@ impl PrimitiveType {
         match self {
             Self::Triangles => 0,
             Self::Quads => 1,
+            Self::Lines => 2,
+            Self::LineStrip => 3,
+            Self::Points => 4,
+        }
+    }
+
+    /// Returns the [`PrimitiveType`] of a representation, or `None` if unknown.
+    #[must_use]
+    pub const fn from_repr(repr: u8) -> Option<Self> {
+        match repr {
+            0 => Some(Self::Triangles),
+            1 => Some(Self::Quads),
+            2 => Some(Self::Lines),
+            3 => Some(Self::LineStrip),
+            4 => Some(Self::Points),
+            _ => None,
         }
     }
 }
 
+impl fmt::Display for PrimitiveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Triangles => "triangles",
+            Self::Quads => "quads",
+            Self::Lines => "lines",
+            Self::LineStrip => "line_strip",
+            Self::Points => "points",
+        })
+    }
+}
+
 /// A `GooseGPU` command.
 #[repr(u8)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum GpuCommand<'a> {
     /// Push onto the matrix.
     Push,
@@ -172,9 +620,101 @@ pub enum GpuCommand<'a> {
     Identity,
     /// Emits an array of vertices.
     EmitVertices(Box<[Vertex]>),
+    /// Deletes a record with an id, the id is never reused.
+    DeleteRecord(u32),
+    /// Deletes a texture with an id, the id is never reused.
+    DeleteTexture(u32),
+    /// Replaces a region of a texture.
+    UpdateTexture {
+        /// The texture id.
+        id: u32,
+        /// The X position of the region.
+        x: u32,
+        /// The Y position of the region.
+        y: u32,
+        /// The width of the region.
+        w: u32,
+        /// The height of the region.
+        h: u32,
+        /// The RGBA bytes of the region.
+        rgba: &'a [u8],
+    },
+    /// Starts recording over an existing record, replacing it once popped.
+    ReRecord {
+        /// The record id.
+        id: u32,
+        /// The new primitive type of the record.
+        primitive: PrimitiveType,
+    },
+    /// Push a label to start recording, with vertices of a [`VertexFormat`].
+    PushRecordEx {
+        /// The primitive type of the record.
+        primitive: PrimitiveType,
+        /// The vertex format of the record.
+        format: VertexFormat,
+    },
+    /// Emits an array of vertices of any [`VertexFormat`],
+    /// which must match the format of the record.
+    EmitVerticesEx(VertexData),
+    /// Sets the width in pixels of the lines, or the size of the points,
+    /// of the record being recorded. Records start with a width of 1.
+    LineWidth(f32),
+    /// Draws a recording with an id once for every transform,
+    /// each multiplied with the current matrix.
+    DrawRecordedInstanced {
+        /// The record id.
+        id: u32,
+        /// The column-major transform of every instance.
+        transforms: Cow<'a, [[f32; 16]]>,
+    },
+    /// Draws a recording with an id once for every offset, each translating the current matrix.
+    ///
+    /// This is the compact version of
+    /// [`DrawRecordedInstanced`](GpuCommand::DrawRecordedInstanced), using 12 bytes per instance
+    /// instead of 64.
+    DrawRecordedTranslated {
+        /// The record id.
+        id: u32,
+        /// The X, Y and Z offset of every instance.
+        offsets: Cow<'a, [[f32; 3]]>,
+    },
+    /// Registers a texture in a compact [`TextureFormat`], see [`texture`](crate::texture)
+    /// for the formats.
+    RegisterTextureEx {
+        /// The width of the texture.
+        w: u32,
+        /// The height of the texture.
+        h: u32,
+        /// The pixel format of the data.
+        format: TextureFormat,
+        /// Whether the data is run-length encoded.
+        rle: bool,
+        /// The encoded pixels.
+        data: &'a [u8],
+    },
+    /// Starts recording over an existing record with vertices of a [`VertexFormat`],
+    /// replacing it once popped.
+    ReRecordEx {
+        /// The record id.
+        id: u32,
+        /// The new primitive type of the record.
+        primitive: PrimitiveType,
+        /// The new vertex format of the record.
+        format: VertexFormat,
+    },
 }
 
-impl GpuCommand<'_> {
+impl<'a> GpuCommand<'a> {
+    /// Deserializes a single GPU command from the start of `bytes`,
+    /// returning it along with the amount of bytes it took up.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `bytes` doesn't start with a valid command.
+    pub fn deserialize(bytes: &'a [u8]) -> Result<(Self, usize), GpuDecodeError> {
+        decode::decode(bytes)
+    }
+
     /// Returns the representation of this [`GpuCommand`].
     #[must_use]
     pub const fn repr(&self) -> u8 {
@@ -195,15 +735,36 @@ impl GpuCommand<'_> {
             GpuCommand::MulMatrix(_) => 0x0D,
             GpuCommand::Identity => 0x0E,
             GpuCommand::EmitVertices { .. } => 0x0F,
+            GpuCommand::DeleteRecord(_) => 0x10,
+            GpuCommand::DeleteTexture(_) => 0x11,
+            GpuCommand::UpdateTexture { .. } => 0x12,
+            GpuCommand::ReRecord { .. } => 0x13,
+            GpuCommand::PushRecordEx { .. } => 0x14,
+            GpuCommand::EmitVerticesEx(_) => 0x15,
+            GpuCommand::LineWidth(_) => 0x16,
+            GpuCommand::DrawRecordedInstanced { .. } => 0x17,
+            GpuCommand::DrawRecordedTranslated { .. } => 0x18,
+            GpuCommand::RegisterTextureEx { .. } => 0x19,
+            GpuCommand::ReRecordEx { .. } => 0x1A,
         }
     }
 
     /// Serializes the GPU command onto a buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`EmitVertices`](GpuCommand::EmitVertices) or
+    /// [`EmitVerticesEx`](GpuCommand::EmitVerticesEx) has more than `u32::MAX` vertices,
+    /// an instanced draw has more than `u32::MAX` instances,
+    /// or [`RegisterTextureEx`](GpuCommand::RegisterTextureEx) has more than `u32::MAX` bytes.
     pub fn serialize(&self, buf: &mut Vec<u8>) {
         buf.push(self.repr());
         match self {
             GpuCommand::PushRecord(p) => buf.extend_from_slice(&p.repr().to_le_bytes()),
-            GpuCommand::DrawRecorded(id) | GpuCommand::BindTexture(id) => {
+            GpuCommand::DrawRecorded(id)
+            | GpuCommand::BindTexture(id)
+            | GpuCommand::DeleteRecord(id)
+            | GpuCommand::DeleteTexture(id) => {
                 buf.extend_from_slice(&id.to_le_bytes());
             }
             GpuCommand::EmitVertex(v) => buf.extend_from_slice(&v.as_bytes()),
@@ -234,54 +795,462 @@ impl GpuCommand<'_> {
                 }
             }
             GpuCommand::EmitVertices(vertices) => {
-                buf.extend_from_slice(&vertices.len().to_le_bytes());
+                // Always 4 bytes, `usize` differs between wasm32 and native.
+                let len = u32::try_from(vertices.len()).expect("too many vertices");
+                buf.extend_from_slice(&len.to_le_bytes());
                 for v in vertices {
                     buf.extend_from_slice(&v.as_bytes());
                 }
             }
+            GpuCommand::UpdateTexture {
+                id,
+                x,
+                y,
+                w,
+                h,
+                rgba,
+            } => {
+                for value in [id, x, y, w, h] {
+                    buf.extend_from_slice(&value.to_le_bytes());
+                }
+                buf.extend_from_slice(rgba);
+            }
+            GpuCommand::ReRecord { id, primitive }
+            | GpuCommand::ReRecordEx { id, primitive, .. } => {
+                buf.extend_from_slice(&id.to_le_bytes());
+                buf.push(primitive.repr());
+                if let GpuCommand::ReRecordEx { format, .. } = self {
+                    buf.push(format.repr());
+                }
+            }
+            GpuCommand::PushRecordEx { primitive, format } => {
+                buf.push(primitive.repr());
+                buf.push(format.repr());
+            }
+            GpuCommand::EmitVerticesEx(data) => {
+                buf.push(data.format().repr());
+                let len = u32::try_from(data.len()).expect("too many vertices");
+                buf.extend_from_slice(&len.to_le_bytes());
+                data.serialize(buf);
+            }
+            GpuCommand::LineWidth(width) => buf.extend_from_slice(&width.to_le_bytes()),
+            GpuCommand::DrawRecordedInstanced { id, transforms } => {
+                serialize_instances(buf, *id, transforms);
+            }
+            GpuCommand::DrawRecordedTranslated { id, offsets } => {
+                serialize_instances(buf, *id, offsets);
+            }
+            GpuCommand::RegisterTextureEx {
+                w,
+                h,
+                format,
+                rle,
+                data,
+            } => {
+                let len = u32::try_from(data.len()).expect("texture is too big");
+                buf.extend_from_slice(&w.to_le_bytes());
+                buf.extend_from_slice(&h.to_le_bytes());
+                buf.push(format.repr() | if *rle { TEXTURE_RLE } else { 0 });
+                buf.extend_from_slice(&len.to_le_bytes());
+                buf.extend_from_slice(data);
+            }
             _ => {}
         }
     }
 }
 
+/// Serializes the id and instance count of an instanced draw, followed by the instances.
+fn serialize_instances<const N: usize>(buf: &mut Vec<u8>, id: u32, instances: &[[f32; N]]) {
+    let len = u32::try_from(instances.len()).expect("too many instances");
+    buf.extend_from_slice(&id.to_le_bytes());
+    buf.extend_from_slice(&len.to_le_bytes());
+    for value in instances.iter().flatten() {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+impl fmt::Display for GpuCommand<'_> {
+    /// Formats the command as a single line of disassembly.
+    /// The alternate flag (`{:#}`) also lists every vertex of `emit_vertices`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuCommand::Push => write!(f, "push"),
+            GpuCommand::Pop => write!(f, "pop"),
+            GpuCommand::PushRecord(primitive) => write!(f, "push_record {primitive}"),
+            GpuCommand::PopRecord => write!(f, "pop_record"),
+            GpuCommand::DrawRecorded(id) => write!(f, "draw_recorded {id}"),
+            GpuCommand::EmitVertex(v) => write!(f, "emit_vertex {v}"),
+            GpuCommand::BindTexture(id) => write!(f, "bind_texture {id}"),
+            GpuCommand::RegisterTexture { w, h, rgba } => {
+                write!(f, "register_texture {w}x{h} ({} bytes)", rgba.len())
+            }
+            GpuCommand::Translate { x, y, z } => write!(f, "translate {x} {y} {z}"),
+            GpuCommand::RotateAxis { x, y, z, angle } => {
+                write!(f, "rotate_axis {x} {y} {z} {angle}")
+            }
+            GpuCommand::RotateEuler { yaw, pitch, roll } => {
+                write!(f, "rotate_euler {yaw} {pitch} {roll}")
+            }
+            GpuCommand::Scale { x, y, z } => write!(f, "scale {x} {y} {z}"),
+            GpuCommand::LoadMatrix(mat) => write!(f, "load_matrix {mat:?}"),
+            GpuCommand::MulMatrix(mat) => write!(f, "mul_matrix {mat:?}"),
+            GpuCommand::Identity => write!(f, "identity"),
+            GpuCommand::EmitVertices(vertices) => {
+                write!(f, "emit_vertices {}", vertices.len())?;
+                if f.alternate() {
+                    for v in vertices {
+                        write!(f, "\n    {v}")?;
+                    }
+                }
+                Ok(())
+            }
+            GpuCommand::DeleteRecord(id) => write!(f, "delete_record {id}"),
+            GpuCommand::DeleteTexture(id) => write!(f, "delete_texture {id}"),
+            GpuCommand::UpdateTexture {
+                id,
+                x,
+                y,
+                w,
+                h,
+                rgba,
+            } => write!(
+                f,
+                "update_texture {id} {x},{y} {w}x{h} ({} bytes)",
+                rgba.len()
+            ),
+            GpuCommand::ReRecord { id, primitive } => write!(f, "re_record {id} {primitive}"),
+            GpuCommand::PushRecordEx { primitive, format } => {
+                write!(f, "push_record_ex {primitive} {format}")
+            }
+            GpuCommand::EmitVerticesEx(data) => {
+                write!(f, "emit_vertices_ex {} {}", data.format(), data.len())?;
+                if f.alternate() {
+                    write!(f, "{data}")?;
+                }
+                Ok(())
+            }
+            GpuCommand::LineWidth(width) => write!(f, "line_width {width}"),
+            GpuCommand::DrawRecordedInstanced { id, transforms } => {
+                write!(f, "draw_recorded_instanced {id} {}", transforms.len())?;
+                if f.alternate() {
+                    for transform in transforms.iter() {
+                        write!(f, "\n    {transform:?}")?;
+                    }
+                }
+                Ok(())
+            }
+            GpuCommand::DrawRecordedTranslated { id, offsets } => {
+                write!(f, "draw_recorded_translated {id} {}", offsets.len())?;
+                if f.alternate() {
+                    for [x, y, z] in offsets.iter() {
+                        write!(f, "\n    ({x}, {y}, {z})")?;
+                    }
+                }
+                Ok(())
+            }
+            GpuCommand::RegisterTextureEx {
+                w,
+                h,
+                format,
+                rle,
+                data,
+            } => write!(
+                f,
+                "register_texture_ex {w}x{h} {format}{} ({} bytes)",
+                if *rle { " rle" } else { "" },
+                data.len()
+            ),
+            GpuCommand::ReRecordEx {
+                id,
+                primitive,
+                format,
+            } => write!(f, "re_record_ex {id} {primitive} {format}"),
+        }
+    }
+}
+
+/// The stream of a [`GpuCommandBuffer`] without any commands.
+const EMPTY_STREAM: [u8; HEADER_SIZE] = Header::new(0).to_bytes();
+
 /// A GPU command buffer to hold all GPU commands to be sent to the `GooseGPU`.
+/// The buffer always holds a complete stream, see [`protocol`] for the format.
 pub struct GpuCommandBuffer {
     buffer: Vec<u8>,
+    command_count: u32,
+    validator: Validator,
 }
 
 impl GpuCommandBuffer {
     /// Creates a new [`GpuCommandBuffer`].
     #[must_use]
     pub const fn new() -> Self {
-        Self { buffer: Vec::new() }
+        Self {
+            buffer: Vec::new(),
+            command_count: 0,
+            validator: Validator::new(),
+        }
     }
 
     /// Inserts a command onto the buffer.
     pub fn insert(&mut self, cmd: &GpuCommand) -> &mut Self {
+        self.validator.track(cmd);
+        if self.buffer.is_empty() {
+            self.buffer.extend_from_slice(&EMPTY_STREAM);
+        }
         cmd.serialize(&mut self.buffer);
+        self.command_count += 1;
+        self.buffer[..HEADER_SIZE].copy_from_slice(&Header::new(self.command_count).to_bytes());
         self
     }
 
+    /// Returns the serialized stream of this [`GpuCommandBuffer`], including the header.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        // The header is only written once a command is inserted, so `new` can be `const`.
+        if self.buffer.is_empty() {
+            &EMPTY_STREAM
+        } else {
+            &self.buffer
+        }
+    }
+
+    /// Returns the amount of commands in this [`GpuCommandBuffer`].
+    #[must_use]
+    pub const fn command_count(&self) -> u32 {
+        self.command_count
+    }
+
+    /// Returns an iterator decoding the commands of this [`GpuCommandBuffer`].
+    #[must_use]
+    pub fn commands(&self) -> GpuCommandReader<'_> {
+        GpuCommandReader::new(&self.as_bytes()[HEADER_SIZE..])
+    }
+
     /// Inserts a `GpuCommand` for registering a `Sprite`.
     pub fn insert_register_sprite(&mut self, sprite: &Sprite) -> &mut Self {
-        (GpuCommand::RegisterTexture {
-            w: unsafe { unsafe_casts::usize_as_u32(sprite.width) },
-            h: unsafe { unsafe_casts::usize_as_u32(sprite.height) },
-            rgba: &sprite.rgba,
-        })
-        .serialize(&mut self.buffer);
+        self.register_sprite(sprite);
         self
     }
 
-    /// Uploads the [`GpuCommandBuffer`] to the GPU.
+    /// Registers a texture of `w`x`h` RGBA pixels, returning the id it will get.
+    pub fn register_texture(&mut self, w: u32, h: u32, rgba: &[u8]) -> TextureId {
+        let id = TextureId(self.validator.next_texture_id());
+        self.insert(&GpuCommand::RegisterTexture { w, h, rgba });
+        id
+    }
+
+    /// Registers a `Sprite` as a texture, returning the id it will get.
+    pub fn register_sprite(&mut self, sprite: &Sprite) -> TextureId {
+        self.register_texture(
+            unsafe { unsafe_casts::usize_as_u32(sprite.width) },
+            unsafe { unsafe_casts::usize_as_u32(sprite.height) },
+            &sprite.rgba,
+        )
+    }
+
+    /// Registers an [`EncodedTexture`] as a texture, returning the id it will get.
+    pub fn register_encoded(&mut self, texture: &EncodedTexture) -> TextureId {
+        let id = TextureId(self.validator.next_texture_id());
+        self.insert(&GpuCommand::RegisterTextureEx {
+            w: texture.width,
+            h: texture.height,
+            format: texture.format,
+            rle: texture.rle,
+            data: &texture.data,
+        });
+        id
+    }
+
+    /// Records the commands inserted by `f` as `primitive`s, returning the id the record will get.
+    ///
+    /// The id is predicted from the buffers uploaded so far, [`upload`](Self::upload) checks it
+    /// against the one the host assigned.
+    ///
+    /// Example:
+    /// ```rs
+    /// let quad = buffer.record(PrimitiveType::Quads, |r| {
+    ///     r.bind_texture(texture);
+    ///     r.insert(&GpuCommand::EmitVertices(vertices.into()));
+    /// });
+    /// ```
+    pub fn record(&mut self, primitive: PrimitiveType, f: impl FnOnce(&mut Self)) -> RecordId {
+        self.insert(&GpuCommand::PushRecord(primitive));
+        f(self);
+        let id = RecordId(self.validator.next_record_id());
+        self.insert(&GpuCommand::PopRecord);
+        id
+    }
+
+    /// Records the commands inserted by `f` into a new record with vertices of `format`,
+    /// which must be emitted with [`EmitVerticesEx`](GpuCommand::EmitVerticesEx).
+    ///
+    /// Example:
+    /// ```rs
+    /// let triangle = buffer.record_ex(PrimitiveType::Triangles, VertexFormat::Color, |r| {
+    ///     r.insert(&GpuCommand::EmitVerticesEx(VertexData::Color(vertices.into())));
+    /// });
+    /// ```
+    pub fn record_ex(
+        &mut self,
+        primitive: PrimitiveType,
+        format: VertexFormat,
+        f: impl FnOnce(&mut Self),
+    ) -> RecordId {
+        self.insert(&GpuCommand::PushRecordEx { primitive, format });
+        f(self);
+        let id = RecordId(self.validator.next_record_id());
+        self.insert(&GpuCommand::PopRecord);
+        id
+    }
+
+    /// Records the commands inserted by `f` over an existing record, replacing its' vertices,
+    /// primitive type, vertex format and texture.
+    ///
+    /// Records with vertices of any format other than [`VertexFormat::Standard`] must be emitted
+    /// with [`EmitVerticesEx`](GpuCommand::EmitVerticesEx), like [`record_ex`](Self::record_ex).
+    pub fn rerecord(
+        &mut self,
+        record: RecordId,
+        primitive: PrimitiveType,
+        format: VertexFormat,
+        f: impl FnOnce(&mut Self),
+    ) -> &mut Self {
+        let id = record.0;
+        if format == VertexFormat::Standard {
+            self.insert(&GpuCommand::ReRecord { id, primitive });
+        } else {
+            self.insert(&GpuCommand::ReRecordEx {
+                id,
+                primitive,
+                format,
+            });
+        }
+        f(self);
+        self.insert(&GpuCommand::PopRecord)
+    }
+
+    /// Deletes a record, freeing it on the host.
+    pub fn delete_record(&mut self, record: RecordId) -> &mut Self {
+        self.insert(&GpuCommand::DeleteRecord(record.0))
+    }
+
+    /// Deletes a texture, freeing it on the host.
+    pub fn delete_texture(&mut self, texture: TextureId) -> &mut Self {
+        self.insert(&GpuCommand::DeleteTexture(texture.0))
+    }
+
+    /// Replaces the `w`x`h` region of a texture at `x`, `y` with `rgba`.
+    pub fn update_texture(
+        &mut self,
+        texture: TextureId,
+        x: u32,
+        y: u32,
+        w: u32,
+        h: u32,
+        rgba: &[u8],
+    ) -> &mut Self {
+        self.insert(&GpuCommand::UpdateTexture {
+            id: texture.0,
+            x,
+            y,
+            w,
+            h,
+            rgba,
+        })
+    }
+
+    /// Binds a texture, to the current record if recording.
+    pub fn bind_texture(&mut self, texture: TextureId) -> &mut Self {
+        self.insert(&GpuCommand::BindTexture(texture.0))
+    }
+
+    /// Draws a record with the current matrix.
+    pub fn draw_recorded(&mut self, record: RecordId) -> &mut Self {
+        self.insert(&GpuCommand::DrawRecorded(record.0))
+    }
+
+    /// Draws a record once for every transform, each multiplied with the current matrix.
+    ///
+    /// Example:
+    /// ```rs
+    /// let trees: Vec<Mat4<f32>> = positions
+    ///     .iter()
+    ///     .map(|&p| Mat4::translation_3d(p) * Mat4::rotation_y(rand_f32() * TAU))
+    ///     .collect();
+    /// buffer.draw_recorded_instanced(tree, &trees);
+    /// ```
+    pub fn draw_recorded_instanced(
+        &mut self,
+        record: RecordId,
+        transforms: &[Mat4<f32>],
+    ) -> &mut Self {
+        self.insert(&GpuCommand::DrawRecordedInstanced {
+            id: record.0,
+            transforms: transforms.iter().map(|m| m.into_col_array()).collect(),
+        })
+    }
+
+    /// Draws a record once for every offset, each translating the current matrix.
+    pub fn draw_recorded_translated(
+        &mut self,
+        record: RecordId,
+        offsets: &[Vec3<f32>],
+    ) -> &mut Self {
+        self.insert(&GpuCommand::DrawRecordedTranslated {
+            id: record.0,
+            offsets: offsets.iter().map(|&o| o.into_array()).collect(),
+        })
+    }
+
+    /// Validates the commands of this [`GpuCommandBuffer`], assuming the matrix stack
+    /// is empty when it runs, and that every previously uploaded buffer ran successfully.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first mistake found, with the index of the command.
+    pub fn validate(&self) -> Result<(), GpuValidationError> {
+        self.validator.validate()
+    }
+
+    /// Uploads the [`GpuCommandBuffer`] to the GPU, waits for it to run and checks the [`GpuStatus`].
     /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
     ///
     /// # Errors
     ///
-    /// This function will return an error if we aren't authorized to use the GPU
+    /// This function will return an error if we aren't authorized to use the GPU,
+    /// if the commands failed with a [`GpuStatus`],
+    /// if the host gave the records or textures of this buffer different ids than the returned
+    /// ones, or in debug builds, if [`validate`](Self::validate) fails.
     pub fn upload(&mut self) -> Result<(), GooseboyError> {
-        if unsafe { submit_gpu_commands(self.buffer.as_ptr(), unsafe_casts::arr_len(&self.buffer)) }
-        {
+        self.submit()?;
+        defer_gpu();
+        let in_sync = self.validator.reconcile(
+            gpu_read_value(GB_GPU_RECORD_ID),
+            gpu_read_value(GB_GPU_TEXTURE_ID),
+        );
+        GpuStatus::read().into_result()?;
+        if in_sync {
+            Ok(())
+        } else {
+            Err(GooseboyError::GpuIdsOutOfSync)
+        }
+    }
+
+    /// Submits the [`GpuCommandBuffer`] to the GPU without waiting for it to run,
+    /// use this for per-frame commands.
+    /// Requires [`Gpu`](crate::system::Permission::Gpu) permission
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if we aren't authorized to use the GPU,
+    /// or in debug builds, if [`validate`](Self::validate) fails.
+    pub fn submit(&mut self) -> Result<(), GooseboyError> {
+        #[cfg(debug_assertions)]
+        self.validate()?;
+
+        let bytes = self.as_bytes();
+        if unsafe { submit_gpu_commands(bytes.as_ptr(), unsafe_casts::arr_len(bytes)) } {
+            self.validator.commit();
             Ok(())
         } else {
             Err(GooseboyError::Unauthorized)
@@ -291,6 +1260,31 @@ impl GpuCommandBuffer {
     /// Clears this [`GpuCommandBuffer`].
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.command_count = 0;
+        self.validator = Validator::new();
+    }
+}
+
+impl fmt::Display for GpuCommandBuffer {
+    /// Formats the buffer as a disassembly, one command per line prefixed with its' byte offset
+    /// after the header.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "; protocol v{}, {} commands",
+            protocol::PROTOCOL_VERSION,
+            self.command_count
+        )?;
+        let mut commands = self.commands();
+        loop {
+            let offset = commands.offset();
+            match commands.next() {
+                Some(Ok(command)) if f.alternate() => writeln!(f, "{offset:06x}  {command:#}")?,
+                Some(Ok(command)) => writeln!(f, "{offset:06x}  {command}")?,
+                Some(Err(e)) => writeln!(f, "{offset:06x}  error: {e}")?,
+                None => return Ok(()),
+            }
+        }
     }
 }
 
@@ -324,7 +1318,10 @@ pub fn defer_gpu() {
 
 /// Do note; this method is very slow compared to creating your own mesh
 /// file format, it's only here for quick testing, Only supports [v, f, vf]
-/// in the `obj_data` string
+/// in the `obj_data` string.
+///
+/// The buildscript's `convert_models` imports models into [`Mesh`](crate::mesh::Mesh)es
+/// at build time instead.
 #[must_use]
 #[allow(clippy::similar_names)]
 pub fn load_obj(obj_data: &str, flip_v: bool) -> Vec<Vertex> {
@@ -0,0 +1,189 @@
+//! This is used to draw debug geometry, like collision boxes, rays and gizmos, with the `GooseGPU`.
+//!
+//! Every shape is batched into a single line record, which is recorded over on every flush, so
+//! drawing every frame doesn't create a new record each time.
+//!
+//! Example:
+//! ```rs
+//! let mut debug = DebugDraw::new();
+//! debug.aabb(&player.bounds(), Color::GREEN);
+//! debug.ray(eye, look * 4.0, Color::YELLOW);
+//! debug.axes(Vec3::zero(), 1.0);
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! debug.flush(&mut buffer);
+//! let _ = buffer.submit();
+//! ```
+#![allow(clippy::cast_precision_loss)]
+use std::f32::consts::TAU;
+
+use crate::{
+    Aabb, Vec3,
+    color::Color,
+    gpu::{
+        GpuCommand, GpuCommandBuffer, PrimitiveType, RecordId, VertexColor, VertexData,
+        VertexFormat,
+    },
+};
+
+/// The amount of segments in every circle of a sphere.
+pub const SPHERE_SEGMENTS: u32 = 24;
+
+/// A batch of debug lines, drawn with a single record.
+#[derive(Clone, Debug)]
+pub struct DebugDraw {
+    /// The width of the lines in pixels.
+    pub line_width: f32,
+    vertices: Vec<VertexColor>,
+    record: Option<RecordId>,
+}
+
+impl DebugDraw {
+    /// Creates a new empty [`DebugDraw`] with 1 pixel wide lines.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            line_width: 1.0,
+            vertices: Vec::new(),
+            record: None,
+        }
+    }
+
+    /// Returns the amount of lines queued since the last flush.
+    #[must_use]
+    pub const fn line_count(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    /// Returns `true` if no lines were queued since the last flush.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Queues a line from `a` to `b`.
+    pub fn line(&mut self, a: Vec3<f32>, b: Vec3<f32>, color: Color) -> &mut Self {
+        for p in [a, b] {
+            self.vertices
+                .push(VertexColor::new(p.x, p.y, p.z, 0.0, 0.0, color));
+        }
+        self
+    }
+
+    /// Queues a ray from `origin` to `origin + direction`.
+    pub fn ray(&mut self, origin: Vec3<f32>, direction: Vec3<f32>, color: Color) -> &mut Self {
+        self.line(origin, origin + direction, color)
+    }
+
+    /// Queues the 12 edges of a box.
+    pub fn aabb(&mut self, aabb: &Aabb<f32>, color: Color) -> &mut Self {
+        let corner = |i: u32| {
+            Vec3::new(
+                if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+            )
+        };
+
+        for i in 0..8 {
+            // Connect every corner to the corners one axis further along.
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    self.line(corner(i), corner(i | axis), color);
+                }
+            }
+        }
+        self
+    }
+
+    /// Queues the X, Y and Z axes from `origin`, in red, green and blue.
+    pub fn axes(&mut self, origin: Vec3<f32>, size: f32) -> &mut Self {
+        self.ray(origin, Vec3::unit_x() * size, Color::RED)
+            .ray(origin, Vec3::unit_y() * size, Color::GREEN)
+            .ray(origin, Vec3::unit_z() * size, Color::BLUE)
+    }
+
+    /// Queues a grid on the XZ plane centered on `center`, `size` wide with `cells` cells
+    /// on each side.
+    pub fn grid(&mut self, center: Vec3<f32>, size: f32, cells: u32, color: Color) -> &mut Self {
+        let cells = cells.max(1);
+        let half = size * 0.5;
+        for i in 0..=cells {
+            let offset = (i as f32 / cells as f32).mul_add(size, -half);
+            self.line(
+                center + Vec3::new(offset, 0.0, -half),
+                center + Vec3::new(offset, 0.0, half),
+                color,
+            );
+            self.line(
+                center + Vec3::new(-half, 0.0, offset),
+                center + Vec3::new(half, 0.0, offset),
+                color,
+            );
+        }
+        self
+    }
+
+    /// Queues a wire sphere, as a circle around each axis.
+    pub fn sphere(&mut self, center: Vec3<f32>, radius: f32, color: Color) -> &mut Self {
+        let point = |i: u32| {
+            let angle = i as f32 / SPHERE_SEGMENTS as f32 * TAU;
+            (angle.cos() * radius, angle.sin() * radius)
+        };
+
+        for i in 0..SPHERE_SEGMENTS {
+            let (ax, ay) = point(i);
+            let (bx, by) = point(i + 1);
+            self.line(
+                center + Vec3::new(ax, ay, 0.0),
+                center + Vec3::new(bx, by, 0.0),
+                color,
+            );
+            self.line(
+                center + Vec3::new(ax, 0.0, ay),
+                center + Vec3::new(bx, 0.0, by),
+                color,
+            );
+            self.line(
+                center + Vec3::new(0.0, ax, ay),
+                center + Vec3::new(0.0, bx, by),
+                color,
+            );
+        }
+        self
+    }
+
+    /// Records the queued lines into a single record and draws it with the current matrix,
+    /// clearing the queue. The record of the previous flush is recorded over.
+    ///
+    /// Returns the record, or `None` if nothing was queued, in which case nothing is drawn.
+    pub fn flush(&mut self, buffer: &mut GpuCommandBuffer) -> Option<RecordId> {
+        if self.vertices.is_empty() {
+            return None;
+        }
+
+        let (line_width, vertices) = (self.line_width, std::mem::take(&mut self.vertices));
+        let emit = |r: &mut GpuCommandBuffer| {
+            r.insert(&GpuCommand::LineWidth(line_width));
+            r.insert(&GpuCommand::EmitVerticesEx(VertexData::Color(
+                vertices.into(),
+            )));
+        };
+        let record = match self.record {
+            Some(record) => {
+                buffer.rerecord(record, PrimitiveType::Lines, VertexFormat::Color, emit);
+                record
+            }
+            None => buffer.record_ex(PrimitiveType::Lines, VertexFormat::Color, emit),
+        };
+        buffer.draw_recorded(record);
+        self.record = Some(record);
+        Some(record)
+    }
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self::new()
+    }
+}
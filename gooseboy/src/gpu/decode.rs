@@ -0,0 +1,419 @@
+//! This is used to decode serialized `GooseGPU` command streams back into [`GpuCommand`]s.
+//!
+//! Example:
+//! ```rs
+//! for command in buffer.commands() {
+//!     log!("{}", command?);
+//! }
+//! ```
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::{
+    color::Color,
+    gpu::{
+        GpuCommand, PrimitiveType, Vertex, VertexColor, VertexData, VertexFormat, VertexLit,
+        protocol::VERTEX_SIZE,
+    },
+    texture::{EncodedTexture, TextureError, TextureFormat},
+};
+
+/// An error returned when decoding a malformed command stream.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GpuDecodeError {
+    /// The stream ended in the middle of a command.
+    #[error("unexpected end of stream at byte {offset}")]
+    UnexpectedEnd {
+        /// The offset of the truncated command.
+        offset: usize,
+    },
+    /// The opcode doesn't belong to any command.
+    #[error("unknown opcode {opcode:#04x} at byte {offset}")]
+    UnknownOpcode {
+        /// The offset of the opcode.
+        offset: usize,
+        /// The opcode.
+        opcode: u8,
+    },
+    /// A [`PushRecord`](GpuCommand::PushRecord) has an unknown primitive type.
+    #[error("unknown primitive type {repr} at byte {offset}")]
+    UnknownPrimitiveType {
+        /// The offset of the command.
+        offset: usize,
+        /// The primitive type representation.
+        repr: u8,
+    },
+    /// A [`PushRecordEx`](GpuCommand::PushRecordEx) or
+    /// [`EmitVerticesEx`](GpuCommand::EmitVerticesEx) has an unknown vertex format.
+    #[error("unknown vertex format {repr} at byte {offset}")]
+    UnknownVertexFormat {
+        /// The offset of the command.
+        offset: usize,
+        /// The vertex format representation.
+        repr: u8,
+    },
+    /// A [`RegisterTextureEx`](GpuCommand::RegisterTextureEx) has an unknown texture format.
+    #[error("unknown texture format {repr} at byte {offset}")]
+    UnknownTextureFormat {
+        /// The offset of the command.
+        offset: usize,
+        /// The texture format representation, without the run-length bit.
+        repr: u8,
+    },
+    /// The stream doesn't start with the protocol magic bytes.
+    #[error("missing GooseGPU stream header")]
+    BadMagic,
+    /// The stream was written with another protocol version.
+    #[error("unsupported GooseGPU protocol version {version}")]
+    UnsupportedVersion {
+        /// The version in the header.
+        version: u16,
+    },
+    /// The stream doesn't contain as many commands as its' header says.
+    #[error("expected {expected} commands, got {actual}")]
+    CommandCountMismatch {
+        /// The command count in the header.
+        expected: u32,
+        /// The amount of commands in the stream.
+        actual: u32,
+    },
+}
+
+impl GpuDecodeError {
+    /// Returns the byte offset of the command that failed to decode,
+    /// header errors are at offset 0.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        match self {
+            Self::UnexpectedEnd { offset }
+            | Self::UnknownOpcode { offset, .. }
+            | Self::UnknownPrimitiveType { offset, .. }
+            | Self::UnknownVertexFormat { offset, .. }
+            | Self::UnknownTextureFormat { offset, .. } => *offset,
+            Self::BadMagic
+            | Self::UnsupportedVersion { .. }
+            | Self::CommandCountMismatch { .. } => 0,
+        }
+    }
+
+    const fn shifted(self, by: usize) -> Self {
+        match self {
+            Self::UnexpectedEnd { offset } => Self::UnexpectedEnd {
+                offset: offset + by,
+            },
+            Self::UnknownOpcode { offset, opcode } => Self::UnknownOpcode {
+                offset: offset + by,
+                opcode,
+            },
+            Self::UnknownPrimitiveType { offset, repr } => Self::UnknownPrimitiveType {
+                offset: offset + by,
+                repr,
+            },
+            Self::UnknownVertexFormat { offset, repr } => Self::UnknownVertexFormat {
+                offset: offset + by,
+                repr,
+            },
+            Self::UnknownTextureFormat { offset, repr } => Self::UnknownTextureFormat {
+                offset: offset + by,
+                repr,
+            },
+            other => other,
+        }
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], GpuDecodeError> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or(GpuDecodeError::UnexpectedEnd { offset: 0 })?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, GpuDecodeError> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, GpuDecodeError> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, GpuDecodeError> {
+        self.u32().map(f32::from_bits)
+    }
+
+    fn vertex(&mut self) -> Result<Vertex, GpuDecodeError> {
+        Ok(Vertex::new(
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+        ))
+    }
+
+    fn color(&mut self) -> Result<Color, GpuDecodeError> {
+        self.take(4).map(|b| Color::new(b[0], b[1], b[2], b[3]))
+    }
+
+    fn vertex_color(&mut self) -> Result<VertexColor, GpuDecodeError> {
+        let Vertex { x, y, z, u, v } = self.vertex()?;
+        Ok(VertexColor::new(x, y, z, u, v, self.color()?))
+    }
+
+    fn vertex_lit(&mut self) -> Result<VertexLit, GpuDecodeError> {
+        let Vertex { x, y, z, u, v } = self.vertex()?;
+        let normal = [self.f32()?, self.f32()?, self.f32()?];
+        Ok(VertexLit::new(
+            [x, y, z].into(),
+            [u, v].into(),
+            normal.into(),
+            self.color()?,
+        ))
+    }
+
+    fn vertex_format(&mut self) -> Result<VertexFormat, GpuDecodeError> {
+        let repr = self.u8()?;
+        VertexFormat::from_repr(repr).ok_or(GpuDecodeError::UnknownVertexFormat { offset: 0, repr })
+    }
+
+    /// Reads `len` values of `size` bytes, without trusting `len` for the allocation
+    /// since the stream might be truncated.
+    fn array<T>(
+        &mut self,
+        len: usize,
+        size: usize,
+        read: impl Fn(&mut Self) -> Result<T, GpuDecodeError>,
+    ) -> Result<Box<[T]>, GpuDecodeError> {
+        let remaining = self.bytes.len() - self.pos;
+        let mut values = Vec::with_capacity(len.min(remaining / size));
+        for _ in 0..len {
+            values.push(read(self)?);
+        }
+        Ok(values.into_boxed_slice())
+    }
+
+    fn vertex_data(&mut self) -> Result<VertexData, GpuDecodeError> {
+        let format = self.vertex_format()?;
+        let len = self.u32()? as usize;
+        let size = format.size();
+        Ok(match format {
+            VertexFormat::Standard => VertexData::Standard(self.array(len, size, Self::vertex)?),
+            VertexFormat::Color => VertexData::Color(self.array(len, size, Self::vertex_color)?),
+            VertexFormat::Lit => VertexData::Lit(self.array(len, size, Self::vertex_lit)?),
+        })
+    }
+
+    /// Reads the id and instances of an instanced draw.
+    fn instances<T: Clone>(
+        &mut self,
+        size: usize,
+        read: impl Fn(&mut Self) -> Result<T, GpuDecodeError>,
+    ) -> Result<(u32, Cow<'a, [T]>), GpuDecodeError> {
+        let id = self.u32()?;
+        let len = self.u32()? as usize;
+        Ok((id, self.array(len, size, read)?.into_vec().into()))
+    }
+
+    fn matrix(&mut self) -> Result<[f32; 16], GpuDecodeError> {
+        let mut mat = [0.0; 16];
+        for f in &mut mat {
+            *f = self.f32()?;
+        }
+        Ok(mat)
+    }
+
+    fn primitive(&mut self) -> Result<PrimitiveType, GpuDecodeError> {
+        let repr = self.u8()?;
+        PrimitiveType::from_repr(repr)
+            .ok_or(GpuDecodeError::UnknownPrimitiveType { offset: 0, repr })
+    }
+
+    fn texture_format(&mut self) -> Result<(TextureFormat, bool), GpuDecodeError> {
+        EncodedTexture::parse_format_byte(self.u8()?).map_err(|e| match e {
+            TextureError::UnknownFormat { repr } => {
+                GpuDecodeError::UnknownTextureFormat { offset: 0, repr }
+            }
+            _ => GpuDecodeError::UnexpectedEnd { offset: 0 },
+        })
+    }
+
+    fn texture_len(w: u32, h: u32) -> Result<usize, GpuDecodeError> {
+        (w as usize)
+            .checked_mul(h as usize)
+            .and_then(|len| len.checked_mul(4))
+            .ok_or(GpuDecodeError::UnexpectedEnd { offset: 0 })
+    }
+
+    /// Reads the payload of a [`RegisterTextureEx`](GpuCommand::RegisterTextureEx).
+    fn encoded_texture(&mut self) -> Result<GpuCommand<'a>, GpuDecodeError> {
+        let w = self.u32()?;
+        let h = self.u32()?;
+        let (format, rle) = self.texture_format()?;
+        let len = self.u32()? as usize;
+        Ok(GpuCommand::RegisterTextureEx {
+            w,
+            h,
+            format,
+            rle,
+            data: self.take(len)?,
+        })
+    }
+
+    fn command(&mut self) -> Result<GpuCommand<'a>, GpuDecodeError> {
+        let opcode = self.u8()?;
+        Ok(match opcode {
+            0x00 => GpuCommand::Push,
+            0x01 => GpuCommand::Pop,
+            0x02 => GpuCommand::PushRecord(self.primitive()?),
+            0x03 => GpuCommand::PopRecord,
+            0x04 => GpuCommand::DrawRecorded(self.u32()?),
+            0x05 => GpuCommand::EmitVertex(self.vertex()?),
+            0x06 => GpuCommand::BindTexture(self.u32()?),
+            0x07 => {
+                let w = self.u32()?;
+                let h = self.u32()?;
+                GpuCommand::RegisterTexture {
+                    w,
+                    h,
+                    rgba: self.take(Self::texture_len(w, h)?)?,
+                }
+            }
+            0x08 => GpuCommand::Translate {
+                x: self.f32()?,
+                y: self.f32()?,
+                z: self.f32()?,
+            },
+            0x09 => GpuCommand::RotateAxis {
+                x: self.f32()?,
+                y: self.f32()?,
+                z: self.f32()?,
+                angle: self.f32()?,
+            },
+            0x0A => GpuCommand::RotateEuler {
+                yaw: self.f32()?,
+                pitch: self.f32()?,
+                roll: self.f32()?,
+            },
+            0x0B => GpuCommand::Scale {
+                x: self.f32()?,
+                y: self.f32()?,
+                z: self.f32()?,
+            },
+            0x0C => GpuCommand::LoadMatrix(self.matrix()?),
+            0x0D => GpuCommand::MulMatrix(self.matrix()?),
+            0x0E => GpuCommand::Identity,
+            0x0F => {
+                let len = self.u32()? as usize;
+                GpuCommand::EmitVertices(self.array(len, VERTEX_SIZE, Self::vertex)?)
+            }
+            0x10 => GpuCommand::DeleteRecord(self.u32()?),
+            0x11 => GpuCommand::DeleteTexture(self.u32()?),
+            0x12 => {
+                let id = self.u32()?;
+                let x = self.u32()?;
+                let y = self.u32()?;
+                let w = self.u32()?;
+                let h = self.u32()?;
+                GpuCommand::UpdateTexture {
+                    id,
+                    x,
+                    y,
+                    w,
+                    h,
+                    rgba: self.take(Self::texture_len(w, h)?)?,
+                }
+            }
+            0x13 => GpuCommand::ReRecord {
+                id: self.u32()?,
+                primitive: self.primitive()?,
+            },
+            0x14 => GpuCommand::PushRecordEx {
+                primitive: self.primitive()?,
+                format: self.vertex_format()?,
+            },
+            0x15 => GpuCommand::EmitVerticesEx(self.vertex_data()?),
+            0x16 => GpuCommand::LineWidth(self.f32()?),
+            0x17 => {
+                let (id, transforms) = self.instances(64, Self::matrix)?;
+                GpuCommand::DrawRecordedInstanced { id, transforms }
+            }
+            0x18 => {
+                let (id, offsets) = self.instances(12, |c| Ok([c.f32()?, c.f32()?, c.f32()?]))?;
+                GpuCommand::DrawRecordedTranslated { id, offsets }
+            }
+            0x19 => self.encoded_texture()?,
+            0x1A => GpuCommand::ReRecordEx {
+                id: self.u32()?,
+                primitive: self.primitive()?,
+                format: self.vertex_format()?,
+            },
+            opcode => return Err(GpuDecodeError::UnknownOpcode { offset: 0, opcode }),
+        })
+    }
+}
+
+/// Decodes a single command from the start of `bytes`, returning it and its' size in bytes.
+pub fn decode(bytes: &[u8]) -> Result<(GpuCommand<'_>, usize), GpuDecodeError> {
+    let mut cursor = Cursor { bytes, pos: 0 };
+    let command = cursor.command()?;
+    Ok((command, cursor.pos))
+}
+
+/// An iterator over serialized commands, without the stream header.
+/// It stops after the first error.
+pub struct GpuCommandReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    failed: bool,
+}
+
+impl<'a> GpuCommandReader<'a> {
+    /// Creates a new [`GpuCommandReader`] over serialized commands,
+    /// use [`split_stream`](crate::gpu::protocol::split_stream) to skip the stream header.
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// Returns the byte offset of the next command.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for GpuCommandReader<'a> {
+    type Item = Result<GpuCommand<'a>, GpuDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.offset >= self.bytes.len() {
+            return None;
+        }
+
+        match decode(&self.bytes[self.offset..]) {
+            Ok((command, len)) => {
+                self.offset += len;
+                Some(Ok(command))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e.shifted(self.offset)))
+            }
+        }
+    }
+}
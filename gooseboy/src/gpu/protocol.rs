@@ -0,0 +1,235 @@
+//! This is used to describe the `GooseGPU` wire format.
+//!
+//! A command stream starts with a [`Header`], followed by `command_count` commands.
+//! Every command is a single opcode byte followed by its' payload, all values are little-endian
+//! and fixed-width no matter the platform the stream was serialized on:
+//!
+//! | Opcode | Command            | Payload                                     |
+//! |--------|--------------------|---------------------------------------------|
+//! | `0x00` | `Push`             | -                                           |
+//! | `0x01` | `Pop`              | -                                           |
+//! | `0x02` | `PushRecord`       | primitive `u8`                              |
+//! | `0x03` | `PopRecord`        | -                                           |
+//! | `0x04` | `DrawRecorded`     | id `u32`                                    |
+//! | `0x05` | `EmitVertex`       | x, y, z, u, v `f32`                         |
+//! | `0x06` | `BindTexture`      | id `u32`                                    |
+//! | `0x07` | `RegisterTexture`  | w `u32`, h `u32`, `w * h * 4` RGBA bytes    |
+//! | `0x08` | `Translate`        | x, y, z `f32`                               |
+//! | `0x09` | `RotateAxis`       | x, y, z, angle `f32`                        |
+//! | `0x0A` | `RotateEuler`      | yaw, pitch, roll `f32`                      |
+//! | `0x0B` | `Scale`            | x, y, z `f32`                               |
+//! | `0x0C` | `LoadMatrix`       | 16 column-major `f32`                       |
+//! | `0x0D` | `MulMatrix`        | 16 column-major `f32`                       |
+//! | `0x0E` | `Identity`         | -                                           |
+//! | `0x0F` | `EmitVertices`     | count `u32`, `count` vertices               |
+//! | `0x10` | `DeleteRecord`     | id `u32`                                    |
+//! | `0x11` | `DeleteTexture`    | id `u32`                                    |
+//! | `0x12` | `UpdateTexture`    | id, x, y, w, h `u32`, `w * h * 4` bytes     |
+//! | `0x13` | `ReRecord`         | id `u32`, primitive `u8`                    |
+//! | `0x14` | `PushRecordEx`     | primitive `u8`, vertex format `u8`          |
+//! | `0x15` | `EmitVerticesEx`   | format `u8`, count `u32`, `count` vertices  |
+//! | `0x16` | `LineWidth`        | width `f32`                                 |
+//! | `0x17` | `DrawRecordedInstanced`  | id, count `u32`, `count` matrices     |
+//! | `0x18` | `DrawRecordedTranslated` | id, count `u32`, `count` x, y, z `f32` |
+//! | `0x19` | `RegisterTextureEx` | w, h `u32`, format `u8`, len `u32`, `len` bytes |
+//! | `0x1A` | `ReRecordEx`       | id `u32`, primitive `u8`, vertex format `u8` |
+//!
+//! The format of `RegisterTextureEx` is a [`TextureFormat`](crate::texture::TextureFormat)
+//! with [`TEXTURE_RLE`](crate::texture::TEXTURE_RLE) set if the data is run-length encoded.
+//!
+//! Vertices are 20 bytes in the standard format (x, y, z, u, v `f32`), 24 bytes in the color
+//! format (followed by RGBA bytes) and 36 bytes in the lit format (x, y, z, u, v, nx, ny, nz
+//! `f32` followed by RGBA bytes).
+//!
+//! Hosts accept streams of their [`PROTOCOL_VERSION`] or older. New opcodes, primitives, vertex
+//! formats and texture formats are additive, since older hosts stop at an unknown one instead of
+//! misreading it, so they keep the version as is. Only bump [`PROTOCOL_VERSION`] when an existing
+//! command changes, or is removed.
+use crate::gpu::{GpuDecodeError, VertexFormat};
+
+/// The magic bytes every command stream starts with.
+pub const MAGIC: [u8; 4] = *b"GGPU";
+/// The current version of the wire format.
+pub const PROTOCOL_VERSION: u16 = 1;
+/// The size of the [`Header`] in bytes.
+pub const HEADER_SIZE: usize = 12;
+/// The size of a serialized vertex in bytes.
+pub const VERTEX_SIZE: usize = 20;
+/// The size of a serialized [`VertexColor`](crate::gpu::VertexColor) in bytes.
+pub const VERTEX_COLOR_SIZE: usize = 24;
+/// The size of a serialized [`VertexLit`](crate::gpu::VertexLit) in bytes.
+pub const VERTEX_LIT_SIZE: usize = 36;
+
+/// The header of a command stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The version of the wire format the stream was written with.
+    pub version: u16,
+    /// The amount of commands in the stream.
+    pub command_count: u32,
+}
+
+impl Header {
+    /// Creates a new [`Header`] with the current protocol version.
+    #[must_use]
+    pub const fn new(command_count: u32) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            command_count,
+        }
+    }
+
+    /// Returns the [`Header`] as a byte array,
+    /// the magic bytes, the version, 2 reserved bytes and the command count.
+    #[must_use]
+    pub const fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let [v0, v1] = self.version.to_le_bytes();
+        let [c0, c1, c2, c3] = self.command_count.to_le_bytes();
+        [
+            MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], v0, v1, 0, 0, c0, c1, c2, c3,
+        ]
+    }
+
+    /// Parses the [`Header`] at the start of a command stream.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stream is too short, doesn't start with
+    /// [`MAGIC`] or was written with a newer protocol version.
+    pub fn parse(bytes: &[u8]) -> Result<Self, GpuDecodeError> {
+        let bytes = bytes
+            .get(..HEADER_SIZE)
+            .ok_or(GpuDecodeError::UnexpectedEnd { offset: 0 })?;
+        if bytes[..4] != MAGIC {
+            return Err(GpuDecodeError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version > PROTOCOL_VERSION {
+            return Err(GpuDecodeError::UnsupportedVersion { version });
+        }
+
+        Ok(Self {
+            version,
+            command_count: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+        })
+    }
+}
+
+/// The size of a command's payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// Always this many bytes.
+    Fixed(usize),
+    /// A `u32` width and height, followed by `w * h * 4` bytes.
+    Texture,
+    /// A `u32` count, followed by `count` vertices.
+    Vertices,
+    /// A `u32` id, X, Y, width and height, followed by `w * h * 4` bytes.
+    TextureRegion,
+    /// A `u8` vertex format and a `u32` count, followed by `count` vertices of that format.
+    FormattedVertices,
+    /// A `u32` id and count, followed by `count` instances of this many bytes.
+    Instances(usize),
+    /// A `u32` width and height, a `u8` format and a `u32` length,
+    /// followed by `length` bytes.
+    EncodedTexture,
+}
+
+impl Payload {
+    /// Returns the size in bytes of the payload starting at `bytes`,
+    /// or `None` if `bytes` is too short to tell.
+    #[must_use]
+    pub fn size(&self, bytes: &[u8]) -> Option<usize> {
+        let u32_at = |offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        };
+
+        match self {
+            Self::Fixed(size) => Some(*size),
+            Self::Texture => u32_at(0)?
+                .checked_mul(u32_at(4)?)?
+                .checked_mul(4)?
+                .checked_add(8),
+            Self::Vertices => u32_at(0)?.checked_mul(VERTEX_SIZE)?.checked_add(4),
+            Self::TextureRegion => u32_at(12)?
+                .checked_mul(u32_at(16)?)?
+                .checked_mul(4)?
+                .checked_add(20),
+            Self::FormattedVertices => {
+                let format = VertexFormat::from_repr(*bytes.first()?)?;
+                u32_at(1)?.checked_mul(format.size())?.checked_add(5)
+            }
+            Self::Instances(size) => u32_at(4)?.checked_mul(*size)?.checked_add(8),
+            Self::EncodedTexture => u32_at(9)?.checked_add(13),
+        }
+    }
+}
+
+/// An entry of the opcode table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opcode {
+    /// The opcode byte.
+    pub code: u8,
+    /// The name of the command, as shown in disassembly.
+    pub name: &'static str,
+    /// The size of the command's payload.
+    pub payload: Payload,
+}
+
+const fn op(code: u8, name: &'static str, payload: Payload) -> Opcode {
+    Opcode {
+        code,
+        name,
+        payload,
+    }
+}
+
+/// Every opcode of the wire format, indexed by opcode.
+pub const OPCODES: [Opcode; 27] = [
+    op(0x00, "push", Payload::Fixed(0)),
+    op(0x01, "pop", Payload::Fixed(0)),
+    op(0x02, "push_record", Payload::Fixed(1)),
+    op(0x03, "pop_record", Payload::Fixed(0)),
+    op(0x04, "draw_recorded", Payload::Fixed(4)),
+    op(0x05, "emit_vertex", Payload::Fixed(VERTEX_SIZE)),
+    op(0x06, "bind_texture", Payload::Fixed(4)),
+    op(0x07, "register_texture", Payload::Texture),
+    op(0x08, "translate", Payload::Fixed(12)),
+    op(0x09, "rotate_axis", Payload::Fixed(16)),
+    op(0x0A, "rotate_euler", Payload::Fixed(12)),
+    op(0x0B, "scale", Payload::Fixed(12)),
+    op(0x0C, "load_matrix", Payload::Fixed(64)),
+    op(0x0D, "mul_matrix", Payload::Fixed(64)),
+    op(0x0E, "identity", Payload::Fixed(0)),
+    op(0x0F, "emit_vertices", Payload::Vertices),
+    op(0x10, "delete_record", Payload::Fixed(4)),
+    op(0x11, "delete_texture", Payload::Fixed(4)),
+    op(0x12, "update_texture", Payload::TextureRegion),
+    op(0x13, "re_record", Payload::Fixed(5)),
+    op(0x14, "push_record_ex", Payload::Fixed(2)),
+    op(0x15, "emit_vertices_ex", Payload::FormattedVertices),
+    op(0x16, "line_width", Payload::Fixed(4)),
+    op(0x17, "draw_recorded_instanced", Payload::Instances(64)),
+    op(0x18, "draw_recorded_translated", Payload::Instances(12)),
+    op(0x19, "register_texture_ex", Payload::EncodedTexture),
+    op(0x1A, "re_record_ex", Payload::Fixed(6)),
+];
+
+/// Returns the opcode table entry of `code`, or `None` if it's unknown.
+#[must_use]
+pub fn opcode(code: u8) -> Option<&'static Opcode> {
+    OPCODES.get(code as usize)
+}
+
+/// Splits a command stream into its' [`Header`] and the serialized commands.
+///
+/// # Errors
+///
+/// This function will return an error if the header is invalid.
+pub fn split_stream(bytes: &[u8]) -> Result<(Header, &[u8]), GpuDecodeError> {
+    let header = Header::parse(bytes)?;
+    Ok((header, &bytes[HEADER_SIZE..]))
+}
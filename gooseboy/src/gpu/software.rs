@@ -0,0 +1,817 @@
+//! A CPU reference implementation of the `GooseGPU`, used to render command streams
+//! onto a [`Surface`] without the mod, e.g. for headless hosts and golden tests.
+//!
+//! Example:
+//! ```rs
+//! let mut gpu = SoftwareGpu::new();
+//! let mut target = Surface::new_empty(320, 240);
+//!
+//! // Records and textures persist between streams, like on the real GooseGPU.
+//! gpu.execute(setup.as_bytes(), &mut target);
+//!
+//! gpu.begin_frame(&mut target, Color::BLACK);
+//! gpu.camera = get_camera_transform();
+//! gpu.execute(frame.as_bytes(), &mut target);
+//! ```
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::many_single_char_names)]
+use crate::{
+    Mat4, Vec2, Vec3, Vec4,
+    camera::{Camera, CameraTransform},
+    color::Color,
+    framebuffer::Surface,
+    gpu::{
+        GB_GPU_MATRIX_DEPTH, GB_GPU_MAX_MATRIX_DEPTH, GB_GPU_MAX_TEXTURE_SIZE, GB_GPU_MEMORY_SIZE,
+        GB_GPU_RECORD_ID, GB_GPU_STATUS, GB_GPU_TEXTURE_ID, GB_STATUS_BAD_TEXTURE,
+        GB_STATUS_BAD_TEXTURE_SIZE, GB_STATUS_BAD_VERTEX_FORMAT, GB_STATUS_MATRIX_TOO_BIG,
+        GB_STATUS_MATRIX_TOO_SMALL, GB_STATUS_NOT_RECORDING, GB_STATUS_OK,
+        GB_STATUS_UNKNOWN_RECORD, GpuCommand, GpuCommandReader, GpuDecodeError, PrimitiveType,
+        VertexData, VertexFormat, VertexLit, protocol,
+    },
+    texture::{self, TextureFormat},
+};
+
+pub use crate::camera::{DEFAULT_FAR, DEFAULT_FOV, DEFAULT_NEAR};
+
+/// The default direction light travels in, lighting lit records from above.
+pub const DEFAULT_LIGHT_DIRECTION: Vec3<f32> = Vec3::new(-0.3, -1.0, -0.5);
+/// The default ambient light of lit records.
+pub const DEFAULT_AMBIENT: f32 = 0.4;
+
+/// A record, its' vertices are stored as [`VertexLit`] no matter the format.
+struct Record {
+    primitive: PrimitiveType,
+    format: VertexFormat,
+    texture: Option<u32>,
+    width: f32,
+    vertices: Vec<VertexLit>,
+}
+
+impl Record {
+    const fn new(primitive: PrimitiveType, format: VertexFormat) -> Self {
+        Self {
+            primitive,
+            format,
+            texture: None,
+            width: 1.0,
+            vertices: Vec::new(),
+        }
+    }
+}
+
+struct Texture {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+impl Texture {
+    fn sample(&self, u: f32, v: f32) -> [u8; 4] {
+        let x = ((u * self.width as f32).floor() as i64).rem_euclid(self.width as i64) as usize;
+        let y = ((v * self.height as f32).floor() as i64).rem_euclid(self.height as i64) as usize;
+        let i = (y * self.width + x) * 4;
+        [
+            self.rgba[i],
+            self.rgba[i + 1],
+            self.rgba[i + 2],
+            self.rgba[i + 3],
+        ]
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    pos: Vec4<f32>,
+    u: f32,
+    v: f32,
+    /// The lit vertex color, from 0 to 1.
+    color: Vec4<f32>,
+}
+
+impl ClipVertex {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            pos: self.pos + (other.pos - self.pos) * t,
+            u: (other.u - self.u).mul_add(t, self.u),
+            v: (other.v - self.v).mul_add(t, self.v),
+            color: self.color + (other.color - self.color) * t,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    u_w: f32,
+    v_w: f32,
+    color_w: Vec4<f32>,
+}
+
+/// A software `GooseGPU`, holding records, textures, the matrix stack and the virtual memory.
+pub struct SoftwareGpu {
+    /// The camera transform that draws are projected from.
+    pub camera: CameraTransform,
+    /// The camera roll in radians.
+    pub roll: f32,
+    /// The vertical field of view in degrees.
+    pub fov: f32,
+    /// The near plane distance.
+    pub near: f32,
+    /// The far plane distance.
+    pub far: f32,
+    /// The direction light travels in, used to shade records with [`VertexFormat::Lit`].
+    pub light_direction: Vec3<f32>,
+    /// The light every lit vertex gets, from 0 to 1.
+    pub ambient: f32,
+    records: Vec<Option<Record>>,
+    textures: Vec<Option<Texture>>,
+    recording: Option<Record>,
+    rerecording: Option<u32>,
+    matrices: Vec<Mat4<f32>>,
+    bound_texture: Option<u32>,
+    memory: [u8; GB_GPU_MEMORY_SIZE],
+    depth: Vec<f32>,
+}
+
+impl SoftwareGpu {
+    /// Creates a new [`SoftwareGpu`] with no records or textures.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            camera: CameraTransform::default(),
+            roll: 0.0,
+            fov: DEFAULT_FOV,
+            near: DEFAULT_NEAR,
+            far: DEFAULT_FAR,
+            light_direction: DEFAULT_LIGHT_DIRECTION,
+            ambient: DEFAULT_AMBIENT,
+            records: Vec::new(),
+            textures: Vec::new(),
+            recording: None,
+            rerecording: None,
+            matrices: vec![Mat4::identity()],
+            bound_texture: None,
+            memory: [0; GB_GPU_MEMORY_SIZE],
+            depth: Vec::new(),
+        }
+    }
+
+    /// Clears `target` with `color` and resets the depth buffer, call this before every frame.
+    pub fn begin_frame(&mut self, target: &mut Surface, color: Color) {
+        for pixel in target.rgba.as_chunks_mut::<4>().0 {
+            *pixel = [color.r, color.g, color.b, color.a];
+        }
+        self.depth.clear();
+        self.depth
+            .resize(target.width * target.height, f32::INFINITY);
+    }
+
+    /// Executes a serialized command stream, drawing onto `target`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stream is malformed,
+    /// the commands before the malformed one still run.
+    pub fn execute(&mut self, stream: &[u8], target: &mut Surface) -> Result<(), GpuDecodeError> {
+        self.write_memory(GB_GPU_STATUS, GB_STATUS_OK);
+
+        let (header, commands) = protocol::split_stream(stream)?;
+        let mut count = 0;
+        for command in GpuCommandReader::new(commands) {
+            self.apply(&command?, target);
+            count += 1;
+        }
+
+        if count == header.command_count {
+            Ok(())
+        } else {
+            Err(GpuDecodeError::CommandCountMismatch {
+                expected: header.command_count,
+                actual: count,
+            })
+        }
+    }
+
+    /// Returns the `GooseGPU` virtual memory.
+    #[must_use]
+    pub const fn memory(&self) -> &[u8; GB_GPU_MEMORY_SIZE] {
+        &self.memory
+    }
+
+    /// Reads a value from the `GooseGPU` virtual memory at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value does not fit in the virtual memory.
+    #[must_use]
+    pub fn read_value(&self, offset: u32) -> u32 {
+        let i = offset as usize;
+        let b = &self.memory[i..i + 4];
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+    }
+
+    /// Returns the amount of records that weren't deleted.
+    #[must_use]
+    pub fn record_count(&self) -> usize {
+        self.records.iter().flatten().count()
+    }
+
+    /// Returns the amount of registered textures that weren't deleted.
+    #[must_use]
+    pub fn texture_count(&self) -> usize {
+        self.textures.iter().flatten().count()
+    }
+
+    /// Returns the current matrix depth.
+    #[must_use]
+    pub const fn matrix_depth(&self) -> usize {
+        self.matrices.len() - 1
+    }
+
+    /// Returns the view-projection matrix of the camera for a target of `width`x`height`.
+    #[must_use]
+    pub fn view_projection(&self, width: usize, height: usize) -> Mat4<f32> {
+        self.full_camera()
+            .view_projection(width.max(1) as f32 / height.max(1) as f32)
+    }
+
+    /// Returns the camera draws are projected from, with the roll and projection.
+    #[must_use]
+    pub const fn full_camera(&self) -> Camera {
+        Camera {
+            transform: self.camera,
+            roll: self.roll,
+            fov: self.fov,
+            near: self.near,
+            far: self.far,
+        }
+    }
+
+    fn write_memory(&mut self, offset: u32, value: u32) {
+        let i = offset as usize;
+        self.memory[i..i + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn top(&mut self) -> &mut Mat4<f32> {
+        self.matrices
+            .last_mut()
+            .expect("the matrix stack is never empty")
+    }
+
+    fn apply(&mut self, command: &GpuCommand, target: &mut Surface) {
+        match command {
+            GpuCommand::Push => {
+                if self.matrix_depth() >= GB_GPU_MAX_MATRIX_DEPTH as usize {
+                    self.write_memory(GB_GPU_STATUS, GB_STATUS_MATRIX_TOO_BIG);
+                } else {
+                    let top = *self.top();
+                    self.matrices.push(top);
+                }
+                self.write_memory(GB_GPU_MATRIX_DEPTH, self.matrix_depth() as u32);
+            }
+            GpuCommand::Pop => {
+                if self.matrix_depth() == 0 {
+                    self.write_memory(GB_GPU_STATUS, GB_STATUS_MATRIX_TOO_SMALL);
+                } else {
+                    self.matrices.pop();
+                }
+                self.write_memory(GB_GPU_MATRIX_DEPTH, self.matrix_depth() as u32);
+            }
+            GpuCommand::PushRecord(primitive) => {
+                self.recording = Some(Record::new(*primitive, VertexFormat::Standard));
+                self.rerecording = None;
+            }
+            GpuCommand::PushRecordEx { primitive, format } => {
+                self.recording = Some(Record::new(*primitive, *format));
+                self.rerecording = None;
+            }
+            GpuCommand::ReRecord { id, primitive } => {
+                self.rerecord(*id, *primitive, VertexFormat::Standard);
+            }
+            GpuCommand::ReRecordEx {
+                id,
+                primitive,
+                format,
+            } => self.rerecord(*id, *primitive, *format),
+            GpuCommand::PopRecord => self.pop_record(),
+            GpuCommand::DrawRecorded(id) => self.draw_instances(*id, [Mat4::identity()], target),
+            GpuCommand::DrawRecordedInstanced { id, transforms } => {
+                let instances = transforms.iter().map(|m| Mat4::from_col_array(*m));
+                self.draw_instances(*id, instances, target);
+            }
+            GpuCommand::DrawRecordedTranslated { id, offsets } => {
+                let instances = offsets.iter().map(|&o| Mat4::translation_3d(Vec3::from(o)));
+                self.draw_instances(*id, instances, target);
+            }
+            GpuCommand::DeleteRecord(id) => {
+                if self.record(*id).is_some() {
+                    self.records[*id as usize] = None;
+                } else {
+                    self.write_memory(GB_GPU_STATUS, GB_STATUS_UNKNOWN_RECORD);
+                }
+            }
+            GpuCommand::DeleteTexture(id) => self.delete_texture(*id),
+            GpuCommand::UpdateTexture {
+                id,
+                x,
+                y,
+                w,
+                h,
+                rgba,
+            } => self.update_texture(*id, (*x, *y), (*w, *h), rgba),
+            GpuCommand::EmitVertex(vertex) => {
+                self.emit(VertexFormat::Standard, [VertexLit::from(*vertex)]);
+            }
+            GpuCommand::EmitVertices(vertices) => {
+                self.emit(VertexFormat::Standard, vertices.iter().map(|&v| v.into()));
+            }
+            GpuCommand::EmitVerticesEx(data) => self.emit_data(data),
+            GpuCommand::LineWidth(width) => self.line_width(*width),
+            GpuCommand::BindTexture(id) => self.bind_texture(*id),
+            GpuCommand::RegisterTexture { w, h, rgba } => self.register_texture(*w, *h, rgba),
+            GpuCommand::RegisterTextureEx {
+                w,
+                h,
+                format,
+                rle,
+                data,
+            } => self.register_encoded_texture(*w, *h, *format, *rle, data),
+            GpuCommand::Translate { x, y, z } => {
+                *self.top() *= Mat4::translation_3d(Vec3::new(*x, *y, *z));
+            }
+            GpuCommand::RotateAxis { x, y, z, angle } => {
+                let axis = Vec3::new(*x, *y, *z);
+                if axis.magnitude_squared() > 0.0 {
+                    *self.top() *= Mat4::rotation_3d(*angle, axis);
+                }
+            }
+            GpuCommand::RotateEuler { yaw, pitch, roll } => {
+                *self.top() *=
+                    Mat4::rotation_y(*yaw) * Mat4::rotation_x(*pitch) * Mat4::rotation_z(*roll);
+            }
+            GpuCommand::Scale { x, y, z } => {
+                *self.top() *= Mat4::scaling_3d(Vec3::new(*x, *y, *z));
+            }
+            GpuCommand::LoadMatrix(mat) => *self.top() = Mat4::from_col_array(*mat),
+            GpuCommand::MulMatrix(mat) => *self.top() *= Mat4::from_col_array(*mat),
+            GpuCommand::Identity => *self.top() = Mat4::identity(),
+        }
+    }
+
+    fn rerecord(&mut self, id: u32, primitive: PrimitiveType, format: VertexFormat) {
+        if self.record(id).is_none() {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_UNKNOWN_RECORD);
+            return;
+        }
+        self.recording = Some(Record::new(primitive, format));
+        self.rerecording = Some(id);
+    }
+
+    fn pop_record(&mut self) {
+        let Some(record) = self.recording.take() else {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_NOT_RECORDING);
+            return;
+        };
+
+        let id = if let Some(id) = self.rerecording.take() {
+            self.records[id as usize] = Some(record);
+            id
+        } else {
+            self.records.push(Some(record));
+            self.records.len() as u32 - 1
+        };
+        self.write_memory(GB_GPU_RECORD_ID, id);
+    }
+
+    fn emit_data(&mut self, data: &VertexData) {
+        match data {
+            VertexData::Standard(vertices) => {
+                self.emit(data.format(), vertices.iter().map(|&v| v.into()));
+            }
+            VertexData::Color(vertices) => {
+                self.emit(data.format(), vertices.iter().map(|&v| v.into()));
+            }
+            VertexData::Lit(vertices) => self.emit(data.format(), vertices.iter().copied()),
+        }
+    }
+
+    fn emit(&mut self, format: VertexFormat, vertices: impl IntoIterator<Item = VertexLit>) {
+        match &mut self.recording {
+            Some(record) if record.format == format => record.vertices.extend(vertices),
+            Some(_) => self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_VERTEX_FORMAT),
+            None => self.write_memory(GB_GPU_STATUS, GB_STATUS_NOT_RECORDING),
+        }
+    }
+
+    fn line_width(&mut self, width: f32) {
+        if let Some(record) = &mut self.recording {
+            record.width = width;
+        } else {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_NOT_RECORDING);
+        }
+    }
+
+    fn bind_texture(&mut self, id: u32) {
+        if self.texture(id).is_none() {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE);
+        } else if let Some(record) = &mut self.recording {
+            record.texture = Some(id);
+        } else {
+            self.bound_texture = Some(id);
+        }
+    }
+
+    /// Returns `true` if a texture can be `w`x`h`, setting the status otherwise.
+    fn check_texture_size(&mut self, w: u32, h: u32) -> bool {
+        if w == 0 || h == 0 || w > GB_GPU_MAX_TEXTURE_SIZE || h > GB_GPU_MAX_TEXTURE_SIZE {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE_SIZE);
+            return false;
+        }
+        true
+    }
+
+    fn register_texture(&mut self, w: u32, h: u32, rgba: &[u8]) {
+        if !self.check_texture_size(w, h) {
+            return;
+        }
+        if rgba.len() != w as usize * h as usize * 4 {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE);
+            return;
+        }
+
+        self.textures.push(Some(Texture {
+            width: w as usize,
+            height: h as usize,
+            rgba: rgba.to_vec(),
+        }));
+        self.write_memory(GB_GPU_TEXTURE_ID, self.textures.len() as u32 - 1);
+    }
+
+    fn register_encoded_texture(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: TextureFormat,
+        rle: bool,
+        data: &[u8],
+    ) {
+        if !self.check_texture_size(w, h) {
+            return;
+        }
+        match texture::decode(w, h, format, rle, data) {
+            Ok(rgba) => self.register_texture(w, h, &rgba),
+            Err(_) => self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE),
+        }
+    }
+
+    fn delete_texture(&mut self, id: u32) {
+        if self.texture(id).is_none() {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE);
+            return;
+        }
+
+        self.textures[id as usize] = None;
+        if self.bound_texture == Some(id) {
+            self.bound_texture = None;
+        }
+    }
+
+    fn update_texture(&mut self, id: u32, (x, y): (u32, u32), (w, h): (u32, u32), rgba: &[u8]) {
+        let Some(Some(texture)) = self.textures.get_mut(id as usize) else {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE);
+            return;
+        };
+
+        let (x, y, w, h) = (x as usize, y as usize, w as usize, h as usize);
+        if w == 0
+            || h == 0
+            || x.checked_add(w).is_none_or(|end| end > texture.width)
+            || y.checked_add(h).is_none_or(|end| end > texture.height)
+        {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE_SIZE);
+            return;
+        }
+        if rgba.len() != w * h * 4 {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_BAD_TEXTURE);
+            return;
+        }
+
+        for (row, src) in rgba.chunks(w * 4).enumerate() {
+            let start = ((y + row) * texture.width + x) * 4;
+            texture.rgba[start..start + w * 4].copy_from_slice(src);
+        }
+    }
+
+    fn record(&self, id: u32) -> Option<&Record> {
+        self.records.get(id as usize).and_then(Option::as_ref)
+    }
+
+    fn texture(&self, id: u32) -> Option<&Texture> {
+        self.textures.get(id as usize).and_then(Option::as_ref)
+    }
+
+    fn draw_instances(
+        &mut self,
+        id: u32,
+        instances: impl IntoIterator<Item = Mat4<f32>>,
+        target: &mut Surface,
+    ) {
+        if self.record(id).is_none() {
+            self.write_memory(GB_GPU_STATUS, GB_STATUS_UNKNOWN_RECORD);
+            return;
+        }
+
+        let top = *self
+            .matrices
+            .last()
+            .expect("the matrix stack is never empty");
+        for instance in instances {
+            self.draw(id, top * instance, target);
+        }
+    }
+
+    fn draw(&mut self, id: u32, model: Mat4<f32>, target: &mut Surface) {
+        let Some(Some(record)) = self.records.get(id as usize) else {
+            return;
+        };
+
+        let texture = record
+            .texture
+            .or(self.bound_texture)
+            .and_then(|t| self.textures.get(t as usize))
+            .and_then(Option::as_ref);
+        let mvp = self.view_projection(target.width, target.height) * model;
+        let light = (-self.light_direction).try_normalized().unwrap_or_default();
+        let lit = record.format == VertexFormat::Lit;
+
+        if self.depth.len() != target.width * target.height {
+            self.depth.clear();
+            self.depth
+                .resize(target.width * target.height, f32::INFINITY);
+        }
+
+        let clip: Vec<ClipVertex> = record
+            .vertices
+            .iter()
+            .map(|v| {
+                let mut color = Vec4::new(v.color.r, v.color.g, v.color.b, v.color.a)
+                    .map(|c| f32::from(c) / 255.0);
+                if lit {
+                    let normal = Vec3::from(model * Vec4::new(v.nx, v.ny, v.nz, 0.0));
+                    let diffuse = normal
+                        .try_normalized()
+                        .map_or(0.0, |n| n.dot(light).max(0.0));
+                    let intensity = diffuse.mul_add(1.0 - self.ambient, self.ambient);
+                    color = Vec4::from((Vec3::from(color) * intensity, color.w));
+                }
+                ClipVertex {
+                    pos: mvp * Vec4::new(v.x, v.y, v.z, 1.0),
+                    u: v.u,
+                    v: v.v,
+                    color,
+                }
+            })
+            .collect();
+
+        match record.primitive {
+            PrimitiveType::Triangles => {
+                for tri in clip.as_chunks::<3>().0 {
+                    draw_triangle(*tri, texture, &mut self.depth, target);
+                }
+            }
+            PrimitiveType::Quads => {
+                for quad in clip.as_chunks::<4>().0 {
+                    draw_triangle(
+                        [quad[0], quad[1], quad[2]],
+                        texture,
+                        &mut self.depth,
+                        target,
+                    );
+                    draw_triangle(
+                        [quad[0], quad[2], quad[3]],
+                        texture,
+                        &mut self.depth,
+                        target,
+                    );
+                }
+            }
+            PrimitiveType::Lines => {
+                for line in clip.as_chunks::<2>().0 {
+                    draw_line(*line, record.width, texture, &mut self.depth, target);
+                }
+            }
+            PrimitiveType::LineStrip => {
+                for line in clip.windows(2) {
+                    draw_line(
+                        [line[0], line[1]],
+                        record.width,
+                        texture,
+                        &mut self.depth,
+                        target,
+                    );
+                }
+            }
+            PrimitiveType::Points => {
+                for &point in &clip {
+                    draw_point(point, record.width, texture, &mut self.depth, target);
+                }
+            }
+        }
+    }
+}
+
+impl Default for SoftwareGpu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Signed distance to the near plane, in OpenGL-style clip space.
+fn near_distance(v: &ClipVertex) -> f32 {
+    v.pos.z + v.pos.w
+}
+
+fn to_screen(v: &ClipVertex, target: &Surface) -> ScreenVertex {
+    let inv_w = 1.0 / v.pos.w;
+    ScreenVertex {
+        x: (v.pos.x * inv_w).mul_add(0.5, 0.5) * target.width as f32,
+        y: (v.pos.y * inv_w).mul_add(-0.5, 0.5) * target.height as f32,
+        z: v.pos.z * inv_w,
+        inv_w,
+        u_w: v.u * inv_w,
+        v_w: v.v * inv_w,
+        color_w: v.color * inv_w,
+    }
+}
+
+/// Clips a line against the near plane and rasterizes it as a screen space quad
+/// `width` pixels wide.
+fn draw_line(
+    [a, b]: [ClipVertex; 2],
+    width: f32,
+    texture: Option<&Texture>,
+    depth: &mut [f32],
+    target: &mut Surface,
+) {
+    let (da, db) = (near_distance(&a), near_distance(&b));
+    let (a, b) = match (da >= 0.0, db >= 0.0) {
+        (true, true) => (a, b),
+        (true, false) => (a, a.lerp(b, da / (da - db))),
+        (false, true) => (b.lerp(a, db / (db - da)), b),
+        (false, false) => return,
+    };
+
+    let (a, b) = (to_screen(&a, target), to_screen(&b, target));
+    let Some(direction) = Vec2::new(b.x - a.x, b.y - a.y).try_normalized() else {
+        return;
+    };
+    let offset = Vec2::new(-direction.y, direction.x) * (width.max(1.0) * 0.5);
+    let corner = |v: ScreenVertex, sign: f32| ScreenVertex {
+        x: offset.x.mul_add(sign, v.x),
+        y: offset.y.mul_add(sign, v.y),
+        ..v
+    };
+
+    let quad = [
+        corner(a, 1.0),
+        corner(b, 1.0),
+        corner(b, -1.0),
+        corner(a, -1.0),
+    ];
+    fill_triangle([quad[0], quad[1], quad[2]], texture, depth, target);
+    fill_triangle([quad[0], quad[2], quad[3]], texture, depth, target);
+}
+
+/// Rasterizes a point as a screen space square `size` pixels wide,
+/// unless it's behind the near plane.
+fn draw_point(
+    point: ClipVertex,
+    size: f32,
+    texture: Option<&Texture>,
+    depth: &mut [f32],
+    target: &mut Surface,
+) {
+    if near_distance(&point) < 0.0 {
+        return;
+    }
+
+    let center = to_screen(&point, target);
+    let half = size.max(1.0) * 0.5;
+    let corner = |x: f32, y: f32| ScreenVertex {
+        x: x.mul_add(half, center.x),
+        y: y.mul_add(half, center.y),
+        ..center
+    };
+
+    let quad = [
+        corner(-1.0, -1.0),
+        corner(1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, 1.0),
+    ];
+    fill_triangle([quad[0], quad[1], quad[2]], texture, depth, target);
+    fill_triangle([quad[0], quad[2], quad[3]], texture, depth, target);
+}
+
+/// Clips a triangle against the near plane and rasterizes the result.
+fn draw_triangle(
+    tri: [ClipVertex; 3],
+    texture: Option<&Texture>,
+    depth: &mut [f32],
+    target: &mut Surface,
+) {
+    let mut polygon: Vec<ClipVertex> = Vec::with_capacity(4);
+    for i in 0..3 {
+        let a = tri[i];
+        let b = tri[(i + 1) % 3];
+        let (da, db) = (near_distance(&a), near_distance(&b));
+
+        if da >= 0.0 {
+            polygon.push(a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            polygon.push(a.lerp(b, da / (da - db)));
+        }
+    }
+
+    let screen: Vec<ScreenVertex> = polygon.iter().map(|v| to_screen(v, target)).collect();
+    for i in 1..screen.len().saturating_sub(1) {
+        fill_triangle(
+            [screen[0], screen[i], screen[i + 1]],
+            texture,
+            depth,
+            target,
+        );
+    }
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (b.x - a.x).mul_add(y - a.y, -((b.y - a.y) * (x - a.x)))
+}
+
+/// Rasterizes a screen space triangle with perspective correct UVs and colors,
+/// and depth testing.
+fn fill_triangle(
+    [a, b, c]: [ScreenVertex; 3],
+    texture: Option<&Texture>,
+    depth: &mut [f32],
+    target: &mut Surface,
+) {
+    if target.width == 0 || target.height == 0 {
+        return;
+    }
+
+    let area = edge(&a, &b, c.x, c.y);
+    if area.abs() < f32::EPSILON || !area.is_finite() {
+        return;
+    }
+
+    let max_x = target.width as f32 - 1.0;
+    let max_y = target.height as f32 - 1.0;
+    let min_x = a.x.min(b.x).min(c.x).floor().clamp(0.0, max_x) as usize;
+    let min_y = a.y.min(b.y).min(c.y).floor().clamp(0.0, max_y) as usize;
+    let end_x = a.x.max(b.x).max(c.x).ceil().clamp(0.0, max_x) as usize;
+    let end_y = a.y.max(b.y).max(c.y).ceil().clamp(0.0, max_y) as usize;
+
+    for y in min_y..=end_y {
+        let py = y as f32 + 0.5;
+        for x in min_x..=end_x {
+            let px = x as f32 + 0.5;
+            let w0 = edge(&b, &c, px, py) / area;
+            let w1 = edge(&c, &a, px, py) / area;
+            let w2 = edge(&a, &b, px, py) / area;
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+
+            let z = w2.mul_add(c.z, w0.mul_add(a.z, w1 * b.z));
+            let i = y * target.width + x;
+            if !(-1.0..=1.0).contains(&z) || z >= depth[i] {
+                continue;
+            }
+
+            let inv_w = w2.mul_add(c.inv_w, w0.mul_add(a.inv_w, w1 * b.inv_w));
+            let sample = texture.map_or([255; 4], |texture| {
+                let u = w2.mul_add(c.u_w, w0.mul_add(a.u_w, w1 * b.u_w)) / inv_w;
+                let v = w2.mul_add(c.v_w, w0.mul_add(a.v_w, w1 * b.v_w)) / inv_w;
+                texture.sample(u, v)
+            });
+            let tint = (a.color_w * w0 + b.color_w * w1 + c.color_w * w2) / inv_w;
+            let color = Vec4::from(sample.map(f32::from)) * tint;
+            if color.w < 127.5 {
+                continue;
+            }
+
+            depth[i] = z;
+            let [red, green, blue] =
+                [color.x, color.y, color.z].map(|c: f32| c.round().clamp(0.0, 255.0) as u8);
+            target.rgba[i * 4..i * 4 + 4].copy_from_slice(&[red, green, blue, 255]);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! This is used to catch `GooseGPU` mistakes while commands are inserted,
+//! instead of finding them as a status in the virtual memory later on.
+use std::{
+    collections::BTreeSet,
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use thiserror::Error;
+
+use crate::{
+    gpu::{GB_GPU_MAX_MATRIX_DEPTH, GB_GPU_MAX_TEXTURE_SIZE, GpuCommand, VertexFormat},
+    texture::{self, TextureError},
+};
+
+/// The amount of records allocated by uploaded buffers.
+static RECORDS: AtomicU32 = AtomicU32::new(0);
+/// The amount of textures allocated by uploaded buffers.
+static TEXTURES: AtomicU32 = AtomicU32::new(0);
+/// The records deleted by uploaded buffers.
+static DELETED_RECORDS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+/// The textures deleted by uploaded buffers.
+static DELETED_TEXTURES: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Locks a set of deleted ids, even if a thread panicked while holding it.
+fn deleted(ids: &'static Mutex<BTreeSet<u32>>) -> MutexGuard<'static, BTreeSet<u32>> {
+    ids.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// An error found while validating a [`GpuCommandBuffer`](crate::gpu::GpuCommandBuffer),
+/// with the index of the offending command.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GpuValidationError {
+    /// A `Push` went past the maximum matrix depth, mirrors `GB_STATUS_MATRIX_TOO_BIG`.
+    #[error("command {index}: matrix stack is too big")]
+    MatrixTooBig {
+        /// The index of the command.
+        index: usize,
+    },
+    /// A `Pop` had nothing to pop, mirrors `GB_STATUS_MATRIX_TOO_SMALL`.
+    #[error("command {index}: matrix stack is too small")]
+    MatrixTooSmall {
+        /// The index of the command.
+        index: usize,
+    },
+    /// A `Push` was never popped.
+    #[error("command {index}: push is never popped")]
+    UnbalancedPush {
+        /// The index of the command.
+        index: usize,
+    },
+    /// Vertices were emitted, a line width was set or a record was popped outside of a record,
+    /// mirrors `GB_STATUS_NOT_RECORDING`.
+    #[error("command {index}: not recording")]
+    NotRecording {
+        /// The index of the command.
+        index: usize,
+    },
+    /// A `PushRecord` happened while already recording.
+    #[error("command {index}: already recording")]
+    AlreadyRecording {
+        /// The index of the command.
+        index: usize,
+    },
+    /// A `PushRecord` was never popped.
+    #[error("command {index}: record is never popped")]
+    UnfinishedRecord {
+        /// The index of the command.
+        index: usize,
+    },
+    /// A texture is empty or too big, mirrors `GB_STATUS_BAD_TEXTURE_SIZE`.
+    #[error("command {index}: bad texture size {w}x{h}")]
+    BadTextureSize {
+        /// The index of the command.
+        index: usize,
+        /// The width of the texture.
+        w: u32,
+        /// The height of the texture.
+        h: u32,
+    },
+    /// A texture's RGBA doesn't match its' size, mirrors `GB_STATUS_BAD_TEXTURE`.
+    #[error("command {index}: texture has {len} bytes, expected {expected}")]
+    BadTexture {
+        /// The index of the command.
+        index: usize,
+        /// The length of the RGBA bytes.
+        len: usize,
+        /// The expected length of the RGBA bytes.
+        expected: usize,
+    },
+    /// An encoded texture's data is malformed, mirrors `GB_STATUS_BAD_TEXTURE`.
+    #[error("command {index}: {error}")]
+    BadEncodedTexture {
+        /// The index of the command.
+        index: usize,
+        /// The error from decoding the texture.
+        error: TextureError,
+    },
+    /// A record command used an id that was never recorded, or was deleted,
+    /// mirrors `GB_STATUS_UNKNOWN_RECORD`.
+    #[error("command {index}: unknown record {id}")]
+    UnknownRecord {
+        /// The index of the command.
+        index: usize,
+        /// The record id.
+        id: u32,
+    },
+    /// A texture command used an id that was never registered, or was deleted.
+    #[error("command {index}: unknown texture {id}")]
+    UnknownTexture {
+        /// The index of the command.
+        index: usize,
+        /// The texture id.
+        id: u32,
+    },
+    /// Vertices were emitted in another format than the record's,
+    /// mirrors `GB_STATUS_BAD_VERTEX_FORMAT`.
+    #[error("command {index}: expected {expected} vertices, got {actual}")]
+    VertexFormatMismatch {
+        /// The index of the command.
+        index: usize,
+        /// The vertex format of the record.
+        expected: VertexFormat,
+        /// The vertex format of the emitted vertices.
+        actual: VertexFormat,
+    },
+}
+
+impl GpuValidationError {
+    /// Returns the index of the offending command.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        match self {
+            Self::MatrixTooBig { index }
+            | Self::MatrixTooSmall { index }
+            | Self::UnbalancedPush { index }
+            | Self::NotRecording { index }
+            | Self::AlreadyRecording { index }
+            | Self::UnfinishedRecord { index }
+            | Self::BadTextureSize { index, .. }
+            | Self::BadTexture { index, .. }
+            | Self::BadEncodedTexture { index, .. }
+            | Self::UnknownRecord { index, .. }
+            | Self::UnknownTexture { index, .. }
+            | Self::VertexFormatMismatch { index, .. } => *index,
+        }
+    }
+}
+
+/// Tracks the state of a buffer as commands are inserted, keeping the first error.
+#[derive(Default)]
+pub struct Validator {
+    index: usize,
+    pushes: Vec<usize>,
+    recording: Option<usize>,
+    rerecording: Option<u32>,
+    format: VertexFormat,
+    records: u32,
+    textures: u32,
+    last_record: Option<u32>,
+    last_texture: Option<u32>,
+    deleted_records: Vec<u32>,
+    deleted_textures: Vec<u32>,
+    error: Option<GpuValidationError>,
+}
+
+impl Validator {
+    /// Creates a new [`Validator`] with nothing tracked.
+    pub const fn new() -> Self {
+        Self {
+            index: 0,
+            pushes: Vec::new(),
+            recording: None,
+            rerecording: None,
+            format: VertexFormat::Standard,
+            records: 0,
+            textures: 0,
+            last_record: None,
+            last_texture: None,
+            deleted_records: Vec::new(),
+            deleted_textures: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Tracks the next inserted command.
+    pub fn track(&mut self, cmd: &GpuCommand) {
+        let index = self.index;
+        self.index += 1;
+
+        if let Err(e) = self.check(index, cmd)
+            && self.error.is_none()
+        {
+            self.error = Some(e);
+        }
+    }
+
+    fn check(&mut self, index: usize, cmd: &GpuCommand) -> Result<(), GpuValidationError> {
+        match cmd {
+            GpuCommand::Push => {
+                if self.pushes.len() >= GB_GPU_MAX_MATRIX_DEPTH as usize {
+                    return Err(GpuValidationError::MatrixTooBig { index });
+                }
+                self.pushes.push(index);
+            }
+            GpuCommand::Pop => {
+                if self.pushes.pop().is_none() {
+                    return Err(GpuValidationError::MatrixTooSmall { index });
+                }
+            }
+            GpuCommand::PushRecord(_) => self.start_record(index, VertexFormat::Standard)?,
+            GpuCommand::PushRecordEx { format, .. } => self.start_record(index, *format)?,
+            GpuCommand::ReRecord { id, .. } => {
+                self.check_record(index, *id)?;
+                self.start_record(index, VertexFormat::Standard)?;
+                self.rerecording = Some(*id);
+            }
+            GpuCommand::ReRecordEx { id, format, .. } => {
+                self.check_record(index, *id)?;
+                self.start_record(index, *format)?;
+                self.rerecording = Some(*id);
+            }
+            GpuCommand::PopRecord => {
+                if self.recording.take().is_none() {
+                    return Err(GpuValidationError::NotRecording { index });
+                }
+                self.last_record = Some(self.rerecording.unwrap_or_else(|| self.next_record_id()));
+                if self.rerecording.is_none() {
+                    self.records += 1;
+                }
+            }
+            GpuCommand::EmitVertex(_) | GpuCommand::EmitVertices(_) => {
+                self.check_vertices(index, VertexFormat::Standard)?;
+            }
+            GpuCommand::EmitVerticesEx(data) => self.check_vertices(index, data.format())?,
+            GpuCommand::LineWidth(_) => {
+                if self.recording.is_none() {
+                    return Err(GpuValidationError::NotRecording { index });
+                }
+            }
+            GpuCommand::DrawRecorded(id)
+            | GpuCommand::DrawRecordedInstanced { id, .. }
+            | GpuCommand::DrawRecordedTranslated { id, .. } => self.check_record(index, *id)?,
+            GpuCommand::BindTexture(id) => self.check_texture(index, *id)?,
+            GpuCommand::DeleteRecord(id) => {
+                self.check_record(index, *id)?;
+                self.deleted_records.push(*id);
+            }
+            GpuCommand::DeleteTexture(id) => {
+                self.check_texture(index, *id)?;
+                self.deleted_textures.push(*id);
+            }
+            GpuCommand::UpdateTexture { id, w, h, rgba, .. } => {
+                self.check_texture(index, *id)?;
+                Self::check_texture_data(index, *w, *h, rgba)?;
+            }
+            GpuCommand::RegisterTexture { w, h, rgba } => {
+                Self::check_texture_data(index, *w, *h, rgba)?;
+                self.last_texture = Some(self.next_texture_id());
+                self.textures += 1;
+            }
+            GpuCommand::RegisterTextureEx {
+                w,
+                h,
+                format,
+                rle,
+                data,
+            } => {
+                Self::check_texture_size(index, *w, *h)?;
+                texture::decode(*w, *h, *format, *rle, data)
+                    .map_err(|error| GpuValidationError::BadEncodedTexture { index, error })?;
+                self.last_texture = Some(self.next_texture_id());
+                self.textures += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    const fn start_record(
+        &mut self,
+        index: usize,
+        format: VertexFormat,
+    ) -> Result<(), GpuValidationError> {
+        if self.recording.replace(index).is_some() {
+            return Err(GpuValidationError::AlreadyRecording { index });
+        }
+        self.rerecording = None;
+        self.format = format;
+        Ok(())
+    }
+
+    fn check_vertices(&self, index: usize, format: VertexFormat) -> Result<(), GpuValidationError> {
+        if self.recording.is_none() {
+            return Err(GpuValidationError::NotRecording { index });
+        }
+        if self.format != format {
+            return Err(GpuValidationError::VertexFormatMismatch {
+                index,
+                expected: self.format,
+                actual: format,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_record(&self, index: usize, id: u32) -> Result<(), GpuValidationError> {
+        if id >= self.next_record_id()
+            || self.deleted_records.contains(&id)
+            || deleted(&DELETED_RECORDS).contains(&id)
+        {
+            return Err(GpuValidationError::UnknownRecord { index, id });
+        }
+        Ok(())
+    }
+
+    fn check_texture(&self, index: usize, id: u32) -> Result<(), GpuValidationError> {
+        if id >= self.next_texture_id()
+            || self.deleted_textures.contains(&id)
+            || deleted(&DELETED_TEXTURES).contains(&id)
+        {
+            return Err(GpuValidationError::UnknownTexture { index, id });
+        }
+        Ok(())
+    }
+
+    const fn check_texture_size(index: usize, w: u32, h: u32) -> Result<(), GpuValidationError> {
+        if w == 0 || h == 0 || w > GB_GPU_MAX_TEXTURE_SIZE || h > GB_GPU_MAX_TEXTURE_SIZE {
+            return Err(GpuValidationError::BadTextureSize { index, w, h });
+        }
+        Ok(())
+    }
+
+    const fn check_texture_data(
+        index: usize,
+        w: u32,
+        h: u32,
+        rgba: &[u8],
+    ) -> Result<(), GpuValidationError> {
+        if let Err(e) = Self::check_texture_size(index, w, h) {
+            return Err(e);
+        }
+
+        let expected = w as usize * h as usize * 4;
+        if rgba.len() != expected {
+            return Err(GpuValidationError::BadTexture {
+                index,
+                len: rgba.len(),
+                expected,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the first error, or an error for the first push or record that was left open.
+    pub fn validate(&self) -> Result<(), GpuValidationError> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        if let Some(index) = self.recording {
+            return Err(GpuValidationError::UnfinishedRecord { index });
+        }
+        if let Some(&index) = self.pushes.first() {
+            return Err(GpuValidationError::UnbalancedPush { index });
+        }
+        Ok(())
+    }
+
+    /// Returns the id the next popped record will get.
+    pub fn next_record_id(&self) -> u32 {
+        RECORDS.load(Ordering::Relaxed) + self.records
+    }
+
+    /// Returns the id the next registered texture will get.
+    pub fn next_texture_id(&self) -> u32 {
+        TEXTURES.load(Ordering::Relaxed) + self.textures
+    }
+
+    /// Marks the records and textures of this buffer as allocated, and the ones it deleted as
+    /// gone, after it's uploaded.
+    pub fn commit(&self) {
+        RECORDS.fetch_add(self.records, Ordering::Relaxed);
+        TEXTURES.fetch_add(self.textures, Ordering::Relaxed);
+        deleted(&DELETED_RECORDS).extend(&self.deleted_records);
+        deleted(&DELETED_TEXTURES).extend(&self.deleted_textures);
+    }
+
+    /// Checks the ids the host gave to the last record and texture of this buffer, read back
+    /// after it ran, against the predicted ones, returning `false` if they differ.
+    ///
+    /// If they differ, every id predicted afterwards continues from the host's instead.
+    pub fn reconcile(&self, record_id: u32, texture_id: u32) -> bool {
+        let mut in_sync = true;
+        if let Some(expected) = self.last_record
+            && expected != record_id
+        {
+            in_sync = false;
+            // A re-recorded id says nothing about how many records were allocated.
+            if self.rerecording.is_none() {
+                RECORDS.store(record_id.saturating_add(1), Ordering::Relaxed);
+            }
+        }
+        if let Some(expected) = self.last_texture
+            && expected != texture_id
+        {
+            in_sync = false;
+            TEXTURES.store(texture_id.saturating_add(1), Ordering::Relaxed);
+        }
+        in_sync
+    }
+}
@@ -1,6 +1,10 @@
 //! The Gooseboy standard library, used for making Gooseboy crates.
 #![warn(missing_docs)]
 
+#[cfg(feature = "gpu")]
+pub mod animation;
+#[cfg(feature = "framebuffer")]
+pub mod atlas;
 #[cfg(feature = "audio")]
 pub mod audio;
 pub mod bindings;
@@ -20,10 +24,16 @@ pub mod input;
 #[cfg(feature = "input")]
 pub mod keys;
 pub mod mem;
+#[cfg(feature = "gpu")]
+pub mod mesh;
+#[cfg(feature = "mock-host")]
+pub mod mock;
 mod panic;
 #[cfg(feature = "rand")]
 pub mod rand;
 pub mod runtime;
+#[cfg(feature = "gpu")]
+pub mod scene;
 #[cfg(feature = "framebuffer")]
 pub mod sprite;
 #[cfg(feature = "storage")]
@@ -31,12 +41,18 @@ pub mod storage;
 pub mod system;
 #[cfg(feature = "framebuffer")]
 pub mod text;
+#[cfg(feature = "framebuffer")]
+pub mod texture;
 pub mod timer;
 pub mod unsafe_casts;
+#[cfg(feature = "framebuffer")]
+pub mod vector;
+#[cfg(feature = "gpu")]
+pub mod voxel;
 
 pub use gooseboy_macros::{gpu_main, main, update};
 #[cfg(any(feature = "gpu", feature = "framebuffer"))]
-pub use vek::{self, Aabb, Mat3, Mat4, Rect, Vec2, Vec3, Vec4};
+pub use vek::{self, Aabb, Mat3, Mat4, Ray, Rect, Vec2, Vec3, Vec4};
 
 /// Called pre-main
 #[doc(hidden)]
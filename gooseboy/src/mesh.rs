@@ -0,0 +1,713 @@
+//! This is used to build indexed triangle meshes, and record them on the `GooseGPU`.
+//!
+//! Example:
+//! ```rs
+//! let mut cube = Mesh::cube(1.0);
+//! cube.translate(Vec3::new(0.0, 0.5, 0.0));
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! let record = cube.record(&mut buffer);
+//! buffer.draw_recorded(record);
+//! ```
+//!
+//! Meshes can also be stored in a compact binary format, which is what the buildscript's
+//! `convert_models` generates. All values are little-endian:
+//!
+//! | Size                 | Field                                               |
+//! |----------------------|-----------------------------------------------------|
+//! | 4                    | [`MESH_MAGIC`]                                      |
+//! | 2                    | [`MESH_VERSION`]                                    |
+//! | 2                    | flags, see [`MESH_HAS_NORMALS`] and the others      |
+//! | 4                    | vertex count `u32`                                  |
+//! | 4                    | index count `u32`                                   |
+//! | `vertices * 12`      | positions, x, y, z `f32`                            |
+//! | `vertices * 8`       | UVs, u, v `f32`                                     |
+//! | `vertices * 12`      | normals, x, y, z `f32`, if [`MESH_HAS_NORMALS`]     |
+//! | `vertices * 4`       | colors, r, g, b, a `u8`, if [`MESH_HAS_COLORS`]     |
+//! | `indices * 2` or `4` | indices, `u16` if [`MESH_SHORT_INDICES`], else `u32`|
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+use std::{collections::HashMap, f32::consts::PI, f32::consts::TAU};
+
+use thiserror::Error;
+
+use crate::{
+    Aabb, Mat4, Vec2, Vec3, Vec4,
+    color::Color,
+    gpu::{
+        GpuCommand, GpuCommandBuffer, PrimitiveType, RecordId, Vertex, VertexColor, VertexData,
+        VertexFormat, VertexLit, load_obj,
+    },
+};
+
+/// The magic bytes every binary mesh starts with.
+pub const MESH_MAGIC: [u8; 4] = *b"GMSH";
+/// The current version of the binary mesh format.
+pub const MESH_VERSION: u16 = 1;
+/// The binary mesh flag for when normals are present.
+pub const MESH_HAS_NORMALS: u16 = 1 << 0;
+/// The binary mesh flag for when colors are present.
+pub const MESH_HAS_COLORS: u16 = 1 << 1;
+/// The binary mesh flag for when indices are `u16`s instead of `u32`s.
+pub const MESH_SHORT_INDICES: u16 = 1 << 2;
+
+const MESH_HEADER_SIZE: usize = 16;
+
+/// An error returned when decoding a malformed binary mesh.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MeshDecodeError {
+    /// The data doesn't start with [`MESH_MAGIC`].
+    #[error("missing mesh header")]
+    BadMagic,
+    /// The mesh was written with another format version.
+    #[error("unsupported mesh version {version}")]
+    UnsupportedVersion {
+        /// The version in the header.
+        version: u16,
+    },
+    /// The data ended before all of the vertices and indices.
+    #[error("unexpected end of mesh data")]
+    UnexpectedEnd,
+    /// An index points past the vertices.
+    #[error("index {index} is out of bounds for {vertex_count} vertices")]
+    IndexOutOfBounds {
+        /// The index.
+        index: u32,
+        /// The amount of vertices.
+        vertex_count: u32,
+    },
+}
+
+/// An indexed triangle mesh.
+///
+/// Every vertex has a position and a UV, normals and colors are optional but
+/// must have one entry per vertex when present.
+/// Front faces are wound counter-clockwise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    /// The vertex positions.
+    pub positions: Vec<Vec3<f32>>,
+    /// The vertex UVs.
+    pub uvs: Vec<Vec2<f32>>,
+    /// The vertex normals.
+    pub normals: Option<Vec<Vec3<f32>>>,
+    /// The vertex colors.
+    pub colors: Option<Vec<Color>>,
+    /// The triangle list, three indices per triangle.
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Creates a new empty [`Mesh`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            positions: Vec::new(),
+            uvs: Vec::new(),
+            normals: None,
+            colors: None,
+            indices: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`Mesh`] from a triangle list of vertices, merging the duplicated ones.
+    #[must_use]
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        let mut mesh = Self {
+            positions: vertices.iter().map(|v| Vec3::new(v.x, v.y, v.z)).collect(),
+            uvs: vertices.iter().map(|v| Vec2::new(v.u, v.v)).collect(),
+            indices: (0..vertices.len() as u32).collect(),
+            ..Self::new()
+        };
+        mesh.dedup();
+        mesh
+    }
+
+    /// Creates a new [`Mesh`] from an OBJ file, see [`load_obj`].
+    #[must_use]
+    pub fn from_obj(obj_data: &str, flip_v: bool) -> Self {
+        Self::from_vertices(&load_obj(obj_data, flip_v))
+    }
+
+    /// Decodes a mesh from the binary mesh format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MeshDecodeError> {
+        let header = bytes
+            .get(..MESH_HEADER_SIZE)
+            .ok_or(MeshDecodeError::UnexpectedEnd)?;
+        if header[..4] != MESH_MAGIC {
+            return Err(MeshDecodeError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != MESH_VERSION {
+            return Err(MeshDecodeError::UnsupportedVersion { version });
+        }
+
+        let flags = u16::from_le_bytes([header[6], header[7]]);
+        let vertex_count = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        let index_count = u32::from_le_bytes([header[12], header[13], header[14], header[15]]);
+        let (vertices, indices) = (vertex_count as usize, index_count as usize);
+
+        let mut rest = &bytes[MESH_HEADER_SIZE..];
+        let mut take = |len: usize| {
+            if rest.len() < len {
+                return Err(MeshDecodeError::UnexpectedEnd);
+            }
+            let (taken, remaining) = rest.split_at(len);
+            rest = remaining;
+            Ok(taken)
+        };
+        let floats = |bytes: &[u8]| -> Vec<f32> {
+            bytes
+                .as_chunks::<4>()
+                .0
+                .iter()
+                .map(|b| f32::from_le_bytes(*b))
+                .collect()
+        };
+
+        let mut mesh = Self::new();
+        let positions = floats(take(vertices.saturating_mul(12))?);
+        mesh.positions = positions
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .map(|&p| Vec3::from(p))
+            .collect();
+        let uvs = floats(take(vertices.saturating_mul(8))?);
+        mesh.uvs = uvs
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|&uv| Vec2::from(uv))
+            .collect();
+        if flags & MESH_HAS_NORMALS != 0 {
+            let normals = floats(take(vertices.saturating_mul(12))?);
+            mesh.normals = Some(
+                normals
+                    .as_chunks::<3>()
+                    .0
+                    .iter()
+                    .map(|&n| Vec3::from(n))
+                    .collect(),
+            );
+        }
+        if flags & MESH_HAS_COLORS != 0 {
+            let colors = take(vertices.saturating_mul(4))?;
+            mesh.colors = Some(
+                colors
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|&[r, g, b, a]| Color::new(r, g, b, a))
+                    .collect(),
+            );
+        }
+        mesh.indices = if flags & MESH_SHORT_INDICES != 0 {
+            take(indices.saturating_mul(2))?
+                .as_chunks::<2>()
+                .0
+                .iter()
+                .map(|&b| u32::from(u16::from_le_bytes(b)))
+                .collect()
+        } else {
+            take(indices.saturating_mul(4))?
+                .as_chunks::<4>()
+                .0
+                .iter()
+                .map(|&b| u32::from_le_bytes(b))
+                .collect()
+        };
+
+        if let Some(&index) = mesh.indices.iter().find(|&&index| index >= vertex_count) {
+            return Err(MeshDecodeError::IndexOutOfBounds {
+                index,
+                vertex_count,
+            });
+        }
+        Ok(mesh)
+    }
+
+    /// Encodes the mesh in the binary mesh format.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let short = self.positions.len() <= usize::from(u16::MAX) + 1;
+        let mut flags = 0;
+        if self.normals.is_some() {
+            flags |= MESH_HAS_NORMALS;
+        }
+        if self.colors.is_some() {
+            flags |= MESH_HAS_COLORS;
+        }
+        if short {
+            flags |= MESH_SHORT_INDICES;
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MESH_MAGIC);
+        bytes.extend_from_slice(&MESH_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(&(self.positions.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.indices.len() as u32).to_le_bytes());
+
+        let mut floats = |values: &[f32]| {
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        };
+        for p in &self.positions {
+            floats(&[p.x, p.y, p.z]);
+        }
+        for uv in &self.uvs {
+            floats(&[uv.x, uv.y]);
+        }
+        for n in self.normals.iter().flatten() {
+            floats(&[n.x, n.y, n.z]);
+        }
+        for c in self.colors.iter().flatten() {
+            bytes.extend_from_slice(&[c.r, c.g, c.b, c.a]);
+        }
+        for &index in &self.indices {
+            if short {
+                bytes.extend_from_slice(&(index as u16).to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Returns the amount of vertices.
+    #[must_use]
+    pub const fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the amount of triangles.
+    #[must_use]
+    pub const fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns `true` if the mesh has no triangles.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.indices.len() < 3
+    }
+
+    /// Adds a vertex and returns its' index.
+    /// If the mesh has normals or colors, the vertex gets a zero normal and a white color.
+    pub fn push_vertex(&mut self, position: Vec3<f32>, uv: Vec2<f32>) -> u32 {
+        let index = self.positions.len() as u32;
+        self.positions.push(position);
+        self.uvs.push(uv);
+        if let Some(normals) = &mut self.normals {
+            normals.push(Vec3::zero());
+        }
+        if let Some(colors) = &mut self.colors {
+            colors.push(Color::WHITE);
+        }
+        index
+    }
+
+    pub(crate) fn push_lit_vertex(
+        &mut self,
+        position: Vec3<f32>,
+        uv: Vec2<f32>,
+        normal: Vec3<f32>,
+    ) -> u32 {
+        let index = self.push_vertex(position, uv);
+        if let Some(normals) = &mut self.normals {
+            normals[index as usize] = normal;
+        }
+        index
+    }
+
+    /// Adds a triangle of vertex indices.
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) -> &mut Self {
+        self.indices.extend_from_slice(&[a, b, c]);
+        self
+    }
+
+    /// Appends the vertices and triangles of another mesh.
+    /// Normals and colors are kept only if both meshes have them.
+    pub fn append(&mut self, other: &Self) -> &mut Self {
+        let offset = self.positions.len() as u32;
+        self.positions.extend_from_slice(&other.positions);
+        self.uvs.extend_from_slice(&other.uvs);
+        self.normals = match (self.normals.take(), &other.normals) {
+            (Some(mut normals), Some(other)) => {
+                normals.extend_from_slice(other);
+                Some(normals)
+            }
+            _ => None,
+        };
+        self.colors = match (self.colors.take(), &other.colors) {
+            (Some(mut colors), Some(other)) => {
+                colors.extend_from_slice(other);
+                Some(colors)
+            }
+            _ => None,
+        };
+        self.indices
+            .extend(other.indices.iter().map(|index| index + offset));
+        self
+    }
+
+    /// Merges vertices with identical attributes, and remaps the indices to them.
+    pub fn dedup(&mut self) -> &mut Self {
+        let mut remap = Vec::with_capacity(self.positions.len());
+        let mut seen: HashMap<[u32; 9], u32> = HashMap::new();
+        let mut kept = Vec::new();
+
+        for i in 0..self.positions.len() {
+            let index = *seen.entry(self.key(i)).or_insert_with(|| {
+                kept.push(i);
+                kept.len() as u32 - 1
+            });
+            remap.push(index);
+        }
+
+        self.positions = kept.iter().map(|&i| self.positions[i]).collect();
+        self.uvs = kept.iter().map(|&i| self.uvs[i]).collect();
+        if let Some(normals) = &mut self.normals {
+            *normals = kept.iter().map(|&i| normals[i]).collect();
+        }
+        if let Some(colors) = &mut self.colors {
+            *colors = kept.iter().map(|&i| colors[i]).collect();
+        }
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+        self
+    }
+
+    fn key(&self, i: usize) -> [u32; 9] {
+        let p = self.positions[i];
+        let uv = self.uvs[i];
+        let n = self.normals.as_ref().map_or_else(Vec3::zero, |n| n[i]);
+        let c = self.colors.as_ref().map_or(Color::WHITE, |c| c[i]);
+        [
+            p.x.to_bits(),
+            p.y.to_bits(),
+            p.z.to_bits(),
+            uv.x.to_bits(),
+            uv.y.to_bits(),
+            n.x.to_bits(),
+            n.y.to_bits(),
+            n.z.to_bits(),
+            u32::from_le_bytes([c.r, c.g, c.b, c.a]),
+        ]
+    }
+
+    /// Returns the bounding box of the vertices, or `None` if there are none.
+    #[must_use]
+    pub fn bounds(&self) -> Option<Aabb<f32>> {
+        let (first, rest) = self.positions.split_first()?;
+        let mut bounds = Aabb::new_empty(*first);
+        for position in rest {
+            bounds.expand_to_contain_point(*position);
+        }
+        Some(bounds)
+    }
+
+    /// Transforms the positions by `mat`, and the normals by its' inverse transpose.
+    pub fn transform(&mut self, mat: &Mat4<f32>) -> &mut Self {
+        for position in &mut self.positions {
+            *position = mat.mul_point(*position);
+        }
+        if let Some(normals) = &mut self.normals {
+            let normal_mat = mat.inverted().transposed();
+            for normal in normals {
+                *normal = Vec3::from(normal_mat * Vec4::from_direction(*normal)).normalized();
+            }
+        }
+        self
+    }
+
+    /// Moves the positions by `offset`.
+    pub fn translate(&mut self, offset: Vec3<f32>) -> &mut Self {
+        self.transform(&Mat4::translation_3d(offset))
+    }
+
+    /// Scales the positions by `scale`.
+    pub fn scale(&mut self, scale: Vec3<f32>) -> &mut Self {
+        self.transform(&Mat4::scaling_3d(scale))
+    }
+
+    /// Computes smooth normals, weighting every triangle by its' area.
+    pub fn compute_normals(&mut self) -> &mut Self {
+        let mut normals = vec![Vec3::zero(); self.positions.len()];
+        for tri in self.indices.as_chunks::<3>().0 {
+            let [a, b, c] = tri.map(|i| self.positions[i as usize]);
+            let normal = (b - a).cross(c - a);
+            for &i in tri {
+                normals[i as usize] += normal;
+            }
+        }
+        for normal in &mut normals {
+            *normal = normal.try_normalized().unwrap_or_else(Vec3::zero);
+        }
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Returns the [`VertexFormat`] that keeps every attribute of the mesh,
+    /// [`VertexFormat::Lit`] if it has normals, otherwise [`VertexFormat::Color`] if it has colors.
+    #[must_use]
+    pub const fn vertex_format(&self) -> VertexFormat {
+        if self.normals.is_some() {
+            VertexFormat::Lit
+        } else if self.colors.is_some() {
+            VertexFormat::Color
+        } else {
+            VertexFormat::Standard
+        }
+    }
+
+    /// Returns the triangles as a flat list of vertices, as expected by
+    /// [`PrimitiveType::Triangles`] records.
+    ///
+    /// Do note; [`Vertex`] has no normal or color, so they're dropped,
+    /// use [`Mesh::to_vertex_data`] to keep them.
+    #[must_use]
+    pub fn to_vertices(&self) -> Vec<Vertex> {
+        self.indices
+            .iter()
+            .map(|&i| {
+                let p = self.positions[i as usize];
+                let uv = self.uvs[i as usize];
+                Vertex::new(p.x, p.y, p.z, uv.x, uv.y)
+            })
+            .collect()
+    }
+
+    /// Returns the triangles as a flat list of vertices in the mesh's [`Mesh::vertex_format`].
+    /// Missing colors are white.
+    #[must_use]
+    pub fn to_vertex_data(&self) -> VertexData {
+        let color = |i: usize| {
+            self.colors
+                .as_ref()
+                .map_or(Color::WHITE, |colors| colors[i])
+        };
+        let indices = self.indices.iter().map(|&i| i as usize);
+        match (self.vertex_format(), &self.normals) {
+            (VertexFormat::Lit, Some(normals)) => VertexData::Lit(
+                indices
+                    .map(|i| VertexLit::new(self.positions[i], self.uvs[i], normals[i], color(i)))
+                    .collect(),
+            ),
+            (VertexFormat::Color, _) => VertexData::Color(
+                indices
+                    .map(|i| {
+                        let (p, uv) = (self.positions[i], self.uvs[i]);
+                        VertexColor::new(p.x, p.y, p.z, uv.x, uv.y, color(i))
+                    })
+                    .collect(),
+            ),
+            _ => VertexData::Standard(self.to_vertices().into()),
+        }
+    }
+
+    /// Emits the triangles into the record currently being recorded in `buffer`,
+    /// which must have been started with the mesh's [`Mesh::vertex_format`].
+    pub fn emit(&self, buffer: &mut GpuCommandBuffer) {
+        match self.to_vertex_data() {
+            VertexData::Standard(vertices) => buffer.insert(&GpuCommand::EmitVertices(vertices)),
+            data => buffer.insert(&GpuCommand::EmitVerticesEx(data)),
+        };
+    }
+
+    /// Records the triangles into a new record, keeping the normals and colors.
+    pub fn record(&self, buffer: &mut GpuCommandBuffer) -> RecordId {
+        buffer.record_ex(PrimitiveType::Triangles, self.vertex_format(), |buffer| {
+            self.emit(buffer);
+        })
+    }
+
+    /// Records the triangles over `previous` if there's one, e.g. the record of the last frame,
+    /// returning the record. The record keeps its' id, so calling this every frame doesn't
+    /// use up new records.
+    pub fn record_over(
+        &self,
+        buffer: &mut GpuCommandBuffer,
+        previous: Option<RecordId>,
+    ) -> RecordId {
+        let Some(record) = previous else {
+            return self.record(buffer);
+        };
+
+        buffer.rerecord(
+            record,
+            PrimitiveType::Triangles,
+            self.vertex_format(),
+            |buffer| self.emit(buffer),
+        );
+        record
+    }
+
+    /// Creates a cube centered on the origin, with a full UV and a normal on every face.
+    #[must_use]
+    pub fn cube(size: f32) -> Self {
+        // The normal, and the axes the face's U and V go along.
+        const FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        const CORNERS: [(f32, f32); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+        let half = size / 2.0;
+        let mut mesh = Self {
+            normals: Some(Vec::new()),
+            ..Self::new()
+        };
+        for (normal, u, v) in FACES {
+            let (normal, u, v) = (Vec3::from(normal), Vec3::from(u), Vec3::from(v));
+            let first = mesh.positions.len() as u32;
+            for (cu, cv) in CORNERS {
+                mesh.push_lit_vertex(
+                    (normal + u * cu + v * cv) * half,
+                    Vec2::new(cu.mul_add(0.5, 0.5), cv.mul_add(-0.5, 0.5)),
+                    normal,
+                );
+            }
+            mesh.push_triangle(first, first + 1, first + 2)
+                .push_triangle(first, first + 2, first + 3);
+        }
+        mesh
+    }
+
+    /// Creates a plane on the XZ axes centered on the origin, facing up.
+    #[must_use]
+    pub fn plane(width: f32, depth: f32) -> Self {
+        Self::grid(width, depth, 1, 1)
+    }
+
+    /// Creates a plane on the XZ axes centered on the origin, facing up,
+    /// split into `columns`x`rows` cells that share vertices.
+    #[must_use]
+    pub fn grid(width: f32, depth: f32, columns: u32, rows: u32) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let mut mesh = Self {
+            normals: Some(Vec::new()),
+            ..Self::new()
+        };
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let (u, v) = (column as f32 / columns as f32, row as f32 / rows as f32);
+                mesh.push_vertex(
+                    Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth),
+                    Vec2::new(u, v),
+                );
+            }
+        }
+        mesh.normals = Some(vec![Vec3::unit_y(); mesh.positions.len()]);
+
+        let stride = columns + 1;
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = row * stride + column;
+                let (b, c, d) = (a + 1, a + stride + 1, a + stride);
+                mesh.push_triangle(a, d, c).push_triangle(a, c, b);
+            }
+        }
+        mesh
+    }
+
+    /// Creates a sphere centered on the origin, made of `segments` around the Y axis
+    /// and `rings` from the top to the bottom.
+    #[must_use]
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut mesh = Self {
+            normals: Some(Vec::new()),
+            ..Self::new()
+        };
+        for ring in 0..=rings {
+            let v = ring as f32 / rings as f32;
+            let (sin_theta, cos_theta) = (v * PI).sin_cos();
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+                let normal = Vec3::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi);
+                mesh.push_lit_vertex(normal * radius, Vec2::new(u, v), normal);
+            }
+        }
+
+        let stride = segments + 1;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let a = ring * stride + segment;
+                let b = a + stride;
+                if ring != 0 {
+                    mesh.push_triangle(a, a + 1, b);
+                }
+                if ring != rings - 1 {
+                    mesh.push_triangle(a + 1, b + 1, b);
+                }
+            }
+        }
+        mesh
+    }
+
+    /// Creates a capped cylinder along the Y axis centered on the origin,
+    /// made of `segments` around the Y axis.
+    #[must_use]
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let half = height / 2.0;
+        let mut mesh = Self {
+            normals: Some(Vec::new()),
+            ..Self::new()
+        };
+        // The side, the top ring followed by the bottom ring.
+        for (y, v) in [(half, 0.0), (-half, 1.0)] {
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+                let normal = Vec3::new(cos_phi, 0.0, sin_phi);
+                mesh.push_lit_vertex(
+                    Vec3::new(normal.x * radius, y, normal.z * radius),
+                    Vec2::new(u, v),
+                    normal,
+                );
+            }
+        }
+        let stride = segments + 1;
+        for a in 0..segments {
+            let b = a + stride;
+            mesh.push_triangle(a, a + 1, b)
+                .push_triangle(a + 1, b + 1, b);
+        }
+
+        // The caps, as triangle fans around their centers.
+        for (y, normal) in [(half, Vec3::unit_y()), (-half, -Vec3::unit_y())] {
+            let center = mesh.push_lit_vertex(Vec3::new(0.0, y, 0.0), Vec2::broadcast(0.5), normal);
+            for segment in 0..=segments {
+                let (sin_phi, cos_phi) = (segment as f32 / segments as f32 * TAU).sin_cos();
+                mesh.push_lit_vertex(
+                    Vec3::new(cos_phi * radius, y, sin_phi * radius),
+                    Vec2::new(cos_phi.mul_add(0.5, 0.5), sin_phi.mul_add(0.5, 0.5)),
+                    normal,
+                );
+            }
+            for segment in 0..segments {
+                let (a, b) = (center + 1 + segment, center + 2 + segment);
+                if y > 0.0 {
+                    mesh.push_triangle(center, b, a);
+                } else {
+                    mesh.push_triangle(center, a, b);
+                }
+            }
+        }
+        mesh
+    }
+}
@@ -0,0 +1,718 @@
+//! An in-process mock of the Gooseboy host, enabled with the `mock-host` feature.
+//!
+//! When enabled, every function in [`bindings`](crate::bindings) is backed by a pure Rust
+//! implementation instead of a WASM import, so crates can be tested natively with `cargo test`.
+//! The host state is thread-local, so every test thread gets its own host.
+//!
+//! Example:
+//! ```rs
+//! mock::with_host(|host| {
+//!     host.set_framebuffer_size(64, 32);
+//!     host.press_key(KEY_SPACE);
+//! });
+//!
+//! init_fb();
+//! assert!(is_key_down(KEY_SPACE));
+//! ```
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::system::Permission;
+#[cfg(feature = "gpu")]
+use crate::{
+    camera::CameraTransform,
+    framebuffer::Surface,
+    gpu::{GpuDecodeError, software::SoftwareGpu},
+};
+
+/// The default framebuffer width of the mock host.
+pub const DEFAULT_FRAMEBUFFER_WIDTH: usize = 320;
+/// The default framebuffer height of the mock host.
+pub const DEFAULT_FRAMEBUFFER_HEIGHT: usize = 240;
+/// The default storage size of the mock host in bytes.
+pub const DEFAULT_STORAGE_SIZE: usize = 4096;
+
+/// A call made to the audio host functions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioCall {
+    /// `play_audio` was called.
+    Play {
+        /// The instance id that was returned.
+        id: i64,
+        /// The audio data.
+        data: Vec<u8>,
+        /// The sample rate.
+        sample_rate: i32,
+        /// The format, as `AudioFormat::repr`.
+        format: i32,
+    },
+    /// `stop_audio` was called.
+    Stop(i64),
+    /// `stop_all_audio` was called.
+    StopAll,
+    /// `set_audio_volume` was called.
+    SetVolume {
+        /// The instance id.
+        id: i64,
+        /// The new volume.
+        volume: f32,
+    },
+    /// `set_audio_pitch` was called.
+    SetPitch {
+        /// The instance id.
+        id: i64,
+        /// The new pitch.
+        pitch: f32,
+    },
+}
+
+/// The state of the mock host.
+pub struct MockHost {
+    /// The framebuffer width returned to the crate.
+    pub framebuffer_width: usize,
+    /// The framebuffer height returned to the crate.
+    pub framebuffer_height: usize,
+    /// Every line written to the console.
+    pub logs: Vec<String>,
+    /// The crate storage.
+    pub storage: Vec<u8>,
+    /// The key returned by `get_key_code`, or -1 if none.
+    pub key_code: i32,
+    /// The keys currently held down.
+    pub keys: HashSet<i32>,
+    /// The mouse buttons currently held down.
+    pub mouse_buttons: HashSet<i32>,
+    /// The mouse X position.
+    pub mouse_x: i32,
+    /// The mouse Y position.
+    pub mouse_y: i32,
+    /// The mouse accumulated delta X.
+    pub mouse_accumulated_dx: f64,
+    /// The mouse accumulated delta Y.
+    pub mouse_accumulated_dy: f64,
+    /// Is the mouse grabbed?
+    pub mouse_grabbed: bool,
+    /// Every call made to the audio host functions, in order.
+    pub audio_calls: Vec<AudioCall>,
+    /// The audio instances that are currently playing.
+    pub playing_audio: HashSet<i64>,
+    /// The maximum amount of audio instances that can play at once.
+    pub max_playing_audio: usize,
+    /// The time returned by `get_time_nanos`, or `None` to use the system clock.
+    pub time_nanos: Option<i64>,
+    /// The permissions granted to the crate.
+    pub permissions: HashSet<i32>,
+    /// The platform name returned by `get_platform_name`.
+    pub platform_name: String,
+    /// The camera transform, as X, Y, Z, yaw and pitch.
+    pub camera_transform: [f32; 5],
+    /// The camera field of view in degrees, 70 by default like Minecraft's.
+    pub camera_fov: f32,
+    /// The camera roll in radians.
+    pub camera_roll: f32,
+    /// Every GPU command stream submitted with `submit_gpu_commands`, in order.
+    pub gpu_submissions: Vec<Vec<u8>>,
+    /// The software `GooseGPU` that submitted command streams are executed on.
+    #[cfg(feature = "gpu")]
+    pub gpu: SoftwareGpu,
+    /// The surface the software `GooseGPU` draws onto, sized like the framebuffer.
+    #[cfg(feature = "gpu")]
+    pub gpu_surface: Surface,
+    #[cfg(feature = "audio")]
+    next_audio_id: i64,
+}
+
+impl MockHost {
+    /// Creates a new [`MockHost`] with every permission granted.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            framebuffer_width: DEFAULT_FRAMEBUFFER_WIDTH,
+            framebuffer_height: DEFAULT_FRAMEBUFFER_HEIGHT,
+            logs: Vec::new(),
+            storage: vec![0; DEFAULT_STORAGE_SIZE],
+            key_code: -1,
+            keys: HashSet::new(),
+            mouse_buttons: HashSet::new(),
+            mouse_x: 0,
+            mouse_y: 0,
+            mouse_accumulated_dx: 0.0,
+            mouse_accumulated_dy: 0.0,
+            mouse_grabbed: false,
+            audio_calls: Vec::new(),
+            playing_audio: HashSet::new(),
+            max_playing_audio: 32,
+            time_nanos: None,
+            permissions: (0..=Permission::Gpu as i32).collect(),
+            platform_name: "mock".to_owned(),
+            camera_transform: [0.0; 5],
+            camera_fov: 70.0,
+            camera_roll: 0.0,
+            gpu_submissions: Vec::new(),
+            #[cfg(feature = "gpu")]
+            gpu: SoftwareGpu::new(),
+            #[cfg(feature = "gpu")]
+            gpu_surface: Surface::new_empty(DEFAULT_FRAMEBUFFER_WIDTH, DEFAULT_FRAMEBUFFER_HEIGHT),
+            #[cfg(feature = "audio")]
+            next_audio_id: 0,
+        }
+    }
+
+    /// Sets the framebuffer size, this has to be called before `init_fb`.
+    pub const fn set_framebuffer_size(&mut self, width: usize, height: usize) {
+        self.framebuffer_width = width;
+        self.framebuffer_height = height;
+    }
+
+    /// Resizes the crate storage to `size` bytes, clearing it.
+    pub fn set_storage_size(&mut self, size: usize) {
+        self.storage = vec![0; size];
+    }
+
+    /// Holds `key` down, making it the current key code.
+    pub fn press_key(&mut self, key: i32) {
+        self.keys.insert(key);
+        self.key_code = key;
+    }
+
+    /// Releases `key`.
+    pub fn release_key(&mut self, key: i32) {
+        self.keys.remove(&key);
+        if self.key_code == key {
+            self.key_code = self.keys.iter().next().copied().unwrap_or(-1);
+        }
+    }
+
+    /// Holds the mouse `button` down.
+    pub fn press_mouse_button(&mut self, button: i32) {
+        self.mouse_buttons.insert(button);
+    }
+
+    /// Releases the mouse `button`.
+    pub fn release_mouse_button(&mut self, button: i32) {
+        self.mouse_buttons.remove(&button);
+    }
+
+    /// Moves the mouse to a position.
+    pub const fn set_mouse_position(&mut self, x: i32, y: i32) {
+        self.mouse_x = x;
+        self.mouse_y = y;
+    }
+
+    /// Adds to the mouse accumulated delta.
+    pub fn move_mouse(&mut self, dx: f64, dy: f64) {
+        self.mouse_accumulated_dx += dx;
+        self.mouse_accumulated_dy += dy;
+    }
+
+    /// Grants `permission` to the crate.
+    pub fn grant(&mut self, permission: Permission) {
+        self.permissions.insert(permission as i32);
+    }
+
+    /// Revokes `permission` from the crate.
+    pub fn revoke(&mut self, permission: Permission) {
+        self.permissions.remove(&(permission as i32));
+    }
+
+    /// Marks an audio instance as finished playing.
+    pub fn finish_audio(&mut self, id: i64) {
+        self.playing_audio.remove(&id);
+    }
+
+    /// Executes a GPU command stream on the software `GooseGPU`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stream is malformed.
+    #[cfg(feature = "gpu")]
+    pub fn execute_gpu_commands(&mut self, commands: &[u8]) -> Result<(), GpuDecodeError> {
+        if self.gpu_surface.width != self.framebuffer_width
+            || self.gpu_surface.height != self.framebuffer_height
+        {
+            self.gpu_surface = Surface::new_empty(self.framebuffer_width, self.framebuffer_height);
+        }
+
+        let [x, y, z, yaw, pitch] = self.camera_transform;
+        self.gpu.camera = CameraTransform {
+            x,
+            y,
+            z,
+            yaw,
+            pitch,
+        };
+        self.gpu.fov = self.camera_fov;
+        self.gpu.roll = self.camera_roll;
+        self.gpu.execute(commands, &mut self.gpu_surface)
+    }
+
+    #[cfg(any(feature = "storage", feature = "gpu"))]
+    fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&(permission as i32))
+    }
+}
+
+impl Default for MockHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static HOST: RefCell<MockHost> = RefCell::new(MockHost::new());
+}
+
+/// Runs `f` with the mock host of the current thread.
+///
+/// # Panics
+///
+/// Panics if called from inside `f`.
+pub fn with_host<R>(f: impl FnOnce(&mut MockHost) -> R) -> R {
+    HOST.with(|host| f(&mut host.borrow_mut()))
+}
+
+/// Resets the mock host of the current thread back to its defaults.
+pub fn reset() {
+    with_host(|host| *host = MockHost::new());
+}
+
+/// The mock implementations of every host function, re-exported by [`bindings`](crate::bindings).
+#[allow(clippy::missing_safety_doc)]
+#[allow(clippy::missing_panics_doc)]
+#[allow(clippy::missing_const_for_fn)]
+#[allow(clippy::must_use_candidate)]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_possible_wrap)]
+#[allow(clippy::cast_sign_loss)]
+pub mod bindings {
+    use std::slice;
+
+    #[cfg(feature = "storage")]
+    use super::MockHost;
+    use super::with_host;
+    use crate::bindings::{Pointer, PointerMut};
+    #[cfg(feature = "framebuffer")]
+    use crate::framebuffer::raster::{blit_rgba, clear_rgba, fill_rect_rgba};
+    #[cfg(any(feature = "storage", feature = "gpu"))]
+    use crate::system::Permission;
+
+    unsafe fn bytes<'a>(ptr: Pointer, len: i32) -> &'a [u8] {
+        if ptr.is_null() || len <= 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(ptr, len as usize) }
+    }
+
+    unsafe fn bytes_mut<'a>(ptr: PointerMut, len: i32) -> &'a mut [u8] {
+        if ptr.is_null() || len <= 0 {
+            return &mut [];
+        }
+        unsafe { slice::from_raw_parts_mut(ptr, len as usize) }
+    }
+
+    #[cfg(feature = "storage")]
+    fn storage_range(host: &MockHost, offset: i32, len: i32) -> Option<(usize, usize)> {
+        let offset = usize::try_from(offset).ok()?;
+        let len = usize::try_from(len).ok()?;
+        if offset > host.storage.len() {
+            return None;
+        }
+        Some((offset, len.min(host.storage.len() - offset)))
+    }
+
+    /// Writes to the console.
+    pub unsafe fn log(ptr: Pointer, len: i32) {
+        let text = String::from_utf8_lossy(unsafe { bytes(ptr, len) }).into_owned();
+        with_host(|host| host.logs.push(text));
+    }
+
+    /// Returns the framebuffer width.
+    #[cfg(feature = "framebuffer")]
+    pub unsafe fn get_framebuffer_width() -> usize {
+        with_host(|host| host.framebuffer_width)
+    }
+
+    /// Returns the framebuffer height.
+    #[cfg(feature = "framebuffer")]
+    pub unsafe fn get_framebuffer_height() -> usize {
+        with_host(|host| host.framebuffer_height)
+    }
+
+    /// Clears a surface with `color`, with size being the size of the surface struct.
+    #[cfg(feature = "framebuffer")]
+    pub unsafe fn clear_surface(ptr: Pointer, size: i32, color: i32) {
+        clear_rgba(
+            unsafe { bytes_mut(ptr.cast_mut(), size) },
+            size as usize,
+            color,
+        );
+    }
+
+    /// Blits a Surface onto another Surface, with optional blending.
+    #[cfg(feature = "framebuffer")]
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn blit_premultiplied_clipped(
+        dest_ptr: Pointer,
+        dest_w: usize,
+        dest_h: usize,
+        dest_x: i32,
+        dest_y: i32,
+        src_w: usize,
+        src_h: usize,
+        src_ptr: Pointer,
+        blend: bool,
+    ) {
+        let dest = unsafe { bytes_mut(dest_ptr.cast_mut(), (dest_w * dest_h * 4) as i32) };
+        let src = unsafe { bytes(src_ptr, (src_w * src_h * 4) as i32) };
+        blit_rgba(
+            dest, dest_w, dest_h, dest_x, dest_y, src, src_w, src_h, blend,
+        );
+    }
+
+    /// Fills a rectangle of a Surface with a color, with optional blending.
+    #[cfg(feature = "framebuffer")]
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn fill_rect_clipped(
+        dest_ptr: Pointer,
+        dest_w: usize,
+        dest_h: usize,
+        x: i32,
+        y: i32,
+        w: usize,
+        h: usize,
+        color: i32,
+        blend: bool,
+    ) {
+        let dest = unsafe { bytes_mut(dest_ptr.cast_mut(), (dest_w * dest_h * 4) as i32) };
+        fill_rect_rgba(dest, dest_w, dest_h, x, y, w, h, color, blend);
+    }
+
+    /// Fills a region of memory.
+    pub unsafe fn mem_fill(addr: PointerMut, len: i32, value: i32) {
+        unsafe { bytes_mut(addr, len) }.fill(value as u8);
+    }
+
+    /// Copies a region of memory.
+    pub unsafe fn mem_copy(dst: PointerMut, src: Pointer, len: i32) {
+        if len > 0 {
+            unsafe { std::ptr::copy(src, dst, len as usize) };
+        }
+    }
+
+    /// Returns the current key being held down.
+    #[cfg(feature = "input")]
+    pub unsafe fn get_key_code() -> i32 {
+        with_host(|host| host.key_code)
+    }
+
+    /// Is `key` held down?
+    #[cfg(feature = "input")]
+    pub unsafe fn get_key(key: i32) -> bool {
+        with_host(|host| host.keys.contains(&key))
+    }
+
+    /// Is `btn` held down?
+    #[cfg(feature = "input")]
+    pub unsafe fn get_mouse_button(btn: i32) -> bool {
+        with_host(|host| host.mouse_buttons.contains(&btn))
+    }
+
+    /// Returns the mouse X position.
+    #[cfg(feature = "input")]
+    pub unsafe fn get_mouse_x() -> i32 {
+        with_host(|host| host.mouse_x)
+    }
+
+    /// Returns the mouse Y position.
+    #[cfg(feature = "input")]
+    pub unsafe fn get_mouse_y() -> i32 {
+        with_host(|host| host.mouse_y)
+    }
+
+    /// Returns the mouse accumulated delta X, helpful for first-person games.
+    #[cfg(feature = "input")]
+    pub unsafe fn get_mouse_accumulated_dx() -> f64 {
+        with_host(|host| host.mouse_accumulated_dx)
+    }
+
+    /// Returns the mouse accumulated delta Y, helpful for first-person games.
+    #[cfg(feature = "input")]
+    pub unsafe fn get_mouse_accumulated_dy() -> f64 {
+        with_host(|host| host.mouse_accumulated_dy)
+    }
+
+    /// Is the mouse grabbed?
+    #[cfg(feature = "input")]
+    pub unsafe fn is_mouse_grabbed() -> bool {
+        with_host(|host| host.mouse_grabbed)
+    }
+
+    /// Grabs the mouse.
+    #[cfg(feature = "input")]
+    pub unsafe fn grab_mouse() {
+        with_host(|host| host.mouse_grabbed = true);
+    }
+
+    /// Releases the mouse.
+    #[cfg(feature = "input")]
+    pub unsafe fn release_mouse() {
+        with_host(|host| host.mouse_grabbed = false);
+    }
+
+    /// Plays an audio, with format being `AudioFormat::repr`, returning the audio instance id.
+    #[cfg(feature = "audio")]
+    pub unsafe fn play_audio(ptr: Pointer, len: i32, sample_rate: i32, format: i32) -> i64 {
+        let data = unsafe { bytes(ptr, len) }.to_vec();
+        with_host(|host| {
+            if host.playing_audio.len() >= host.max_playing_audio {
+                return -1;
+            }
+
+            let id = host.next_audio_id;
+            host.next_audio_id += 1;
+            host.playing_audio.insert(id);
+            host.audio_calls.push(super::AudioCall::Play {
+                id,
+                data,
+                sample_rate,
+                format,
+            });
+            id
+        })
+    }
+
+    /// Stops an audio instance.
+    #[cfg(feature = "audio")]
+    pub unsafe fn stop_audio(id: i64) {
+        with_host(|host| {
+            host.playing_audio.remove(&id);
+            host.audio_calls.push(super::AudioCall::Stop(id));
+        });
+    }
+
+    /// Stops all running audio instances.
+    #[cfg(feature = "audio")]
+    pub unsafe fn stop_all_audio() {
+        with_host(|host| {
+            host.playing_audio.clear();
+            host.audio_calls.push(super::AudioCall::StopAll);
+        });
+    }
+
+    /// Sets the volume of an audio instance.
+    #[cfg(feature = "audio")]
+    pub unsafe fn set_audio_volume(id: i64, volume: f32) {
+        with_host(|host| {
+            host.audio_calls
+                .push(super::AudioCall::SetVolume { id, volume });
+        });
+    }
+
+    /// Sets the pitch of an audio instance.
+    #[cfg(feature = "audio")]
+    pub unsafe fn set_audio_pitch(id: i64, pitch: f32) {
+        with_host(|host| {
+            host.audio_calls
+                .push(super::AudioCall::SetPitch { id, pitch });
+        });
+    }
+
+    /// Is this audio instance currently playing?
+    #[cfg(feature = "audio")]
+    pub unsafe fn is_audio_playing(id: i64) -> bool {
+        with_host(|host| host.playing_audio.contains(&id))
+    }
+
+    /// Reads from the crate storage, and returns the amount of read bytes.
+    #[cfg(feature = "storage")]
+    pub unsafe fn storage_read(offset: i32, ptr: PointerMut, len: i32) -> i32 {
+        let buf = unsafe { bytes_mut(ptr, len) };
+        with_host(|host| {
+            if !host.has_permission(Permission::StorageRead) {
+                return 0;
+            }
+            let Some((offset, len)) = storage_range(host, offset, len) else {
+                return 0;
+            };
+            buf[..len].copy_from_slice(&host.storage[offset..offset + len]);
+            len as i32
+        })
+    }
+
+    /// Writes to the crate storage, and returns the amount of written bytes.
+    #[cfg(feature = "storage")]
+    pub unsafe fn storage_write(offset: i32, ptr: Pointer, len: i32) -> i32 {
+        let buf = unsafe { bytes(ptr, len) };
+        with_host(|host| {
+            if !host.has_permission(Permission::StorageWrite) {
+                return 0;
+            }
+            let Some((offset, len)) = storage_range(host, offset, len) else {
+                return 0;
+            };
+            host.storage[offset..offset + len].copy_from_slice(&buf[..len]);
+            len as i32
+        })
+    }
+
+    /// Returns the size of the crate storage in bytes.
+    #[cfg(feature = "storage")]
+    pub unsafe fn storage_size() -> u32 {
+        with_host(|host| host.storage.len() as u32)
+    }
+
+    /// Clears the crate storage entirely.
+    #[cfg(feature = "storage")]
+    pub unsafe fn storage_clear() {
+        with_host(|host| {
+            if host.has_permission(Permission::StorageWrite) {
+                host.storage.fill(0);
+            }
+        });
+    }
+
+    /// Returns the time in nanoseconds since the Unix Epoch.
+    pub unsafe fn get_time_nanos() -> i64 {
+        with_host(|host| {
+            host.time_nanos.unwrap_or_else(|| {
+                super::SystemTime::now()
+                    .duration_since(super::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as i64)
+            })
+        })
+    }
+
+    /// Does this crate have that permission?
+    pub unsafe fn has_permission(permission: i32) -> bool {
+        with_host(|host| host.permissions.contains(&permission))
+    }
+
+    /// Gets the platform name, and returns the length of the string.
+    pub unsafe fn get_platform_name(ptr: PointerMut) -> u32 {
+        with_host(|host| {
+            let name = host.platform_name.as_bytes();
+            let len = name.len().min(256);
+            unsafe { bytes_mut(ptr, len as i32) }.copy_from_slice(&name[..len]);
+            len as u32
+        })
+    }
+
+    /// Gets the current camera transform, and returns true if successful.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn get_camera_transform(ptr: PointerMut) -> bool {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return false;
+            }
+            let out = unsafe { bytes_mut(ptr, 20) };
+            for (chunk, value) in out
+                .as_chunks_mut::<4>()
+                .0
+                .iter_mut()
+                .zip(host.camera_transform)
+            {
+                *chunk = value.to_ne_bytes();
+            }
+            true
+        })
+    }
+
+    /// Sets the current camera transform, and returns true if successful.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn set_camera_transform(x: f32, y: f32, z: f32, yaw: f32, pitch: f32) -> bool {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return false;
+            }
+            host.camera_transform = [x, y, z, yaw, pitch];
+            true
+        })
+    }
+
+    /// Returns the current camera field of view in degrees.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn get_camera_fov() -> f32 {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return 0.0;
+            }
+            host.camera_fov
+        })
+    }
+
+    /// Sets the current camera field of view in degrees, and returns true if successful.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn set_camera_fov(fov: f32) -> bool {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return false;
+            }
+            host.camera_fov = fov;
+            true
+        })
+    }
+
+    /// Returns the current camera roll in radians.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn get_camera_roll() -> f32 {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return 0.0;
+            }
+            host.camera_roll
+        })
+    }
+
+    /// Sets the current camera roll in radians, and returns true if successful.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn set_camera_roll(roll: f32) -> bool {
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return false;
+            }
+            host.camera_roll = roll;
+            true
+        })
+    }
+
+    /// Submits a group of GPU commands, and returns true if successful.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn submit_gpu_commands(ptr: Pointer, count: i32) -> bool {
+        let commands = unsafe { bytes(ptr, count) }.to_vec();
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return false;
+            }
+            // Malformed streams are still recorded, so tests can inspect them.
+            let _ = host.execute_gpu_commands(&commands);
+            host.gpu_submissions.push(commands);
+            true
+        })
+    }
+
+    /// Defers until the queued GPU commands run.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn defer_gpu() {}
+
+    /// Reads from the GPU memory, and returns the amount of read bytes.
+    #[cfg(feature = "gpu")]
+    pub unsafe fn gpu_read(offset: i32, ptr: Pointer, len: i32) -> u32 {
+        let out = unsafe { bytes_mut(ptr.cast_mut(), len) };
+        with_host(|host| {
+            if !host.has_permission(Permission::Gpu) {
+                return 0;
+            }
+            let Ok(offset) = usize::try_from(offset) else {
+                return 0;
+            };
+            let memory = host.gpu.memory();
+            let offset = offset.min(memory.len());
+            let len = out.len().min(memory.len() - offset);
+            out[..len].copy_from_slice(&memory[offset..offset + len]);
+            len as u32
+        })
+    }
+}
@@ -0,0 +1,365 @@
+//! This is used to draw a hierarchy of records with the `GooseGPU`, without pushing and
+//! popping the matrices by hand.
+//!
+//! Every node has a local transform relative to its' parent, and optionally a record to draw
+//! and the bounds of that record, which are used to skip nodes outside of the camera's view.
+//!
+//! Example:
+//! ```rs
+//! let mut scene = Scene::new();
+//! let car = scene.add(Node::with_record(body, models::CAR.bounds()));
+//! let wheel = scene.add_child(car, Node::with_record(wheel, models::WHEEL.bounds()));
+//! scene.node_mut(wheel).unwrap().transform.position = Vec3::new(1.0, 0.3, 1.5);
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! scene.render(&Camera::read(), &mut buffer);
+//! let _ = buffer.submit();
+//! ```
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+use crate::{
+    Aabb, Mat4, Vec3,
+    animation::transform_matrix,
+    camera::{Camera, Frustum},
+    framebuffer::{get_framebuffer_height, get_framebuffer_width},
+    gpu::{GpuCommand, GpuCommandBuffer, RecordId},
+    vek::{Quaternion, Transform},
+};
+
+/// The id of a node in a [`Scene`], never reused after the node is removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+/// A node in a [`Scene`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The position, orientation and scale relative to the parent.
+    pub transform: Transform<f32, f32, f32>,
+    /// The record drawn at this node, if any.
+    pub record: Option<RecordId>,
+    /// The bounds of the record in local space, or `None` to never cull it.
+    pub bounds: Option<Aabb<f32>>,
+    /// Whether this node and its' children are drawn.
+    pub visible: bool,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl Node {
+    /// Creates a new empty [`Node`], with an identity transform.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            record: None,
+            bounds: None,
+            visible: true,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`Node`] drawing `record`, culled with `bounds` if there are any.
+    #[must_use]
+    pub fn with_record(record: RecordId, bounds: Option<Aabb<f32>>) -> Self {
+        Self {
+            record: Some(record),
+            bounds,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the parent of this node, or `None` if it's a root.
+    #[must_use]
+    pub const fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// Returns the children of this node.
+    #[must_use]
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Returns the local transform as a matrix, see [`transform_matrix`].
+    #[must_use]
+    pub fn local_matrix(&self) -> Mat4<f32> {
+        transform_matrix(&self.transform)
+    }
+
+    fn has_identity_transform(&self) -> bool {
+        self.transform.position == Vec3::zero()
+            && self.transform.orientation == Quaternion::identity()
+            && self.transform.scale == Vec3::one()
+    }
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The amount of nodes drawn and culled by a [`Scene::render`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The amount of records drawn.
+    pub drawn: usize,
+    /// The amount of records skipped for being outside of the frustum.
+    pub culled: usize,
+}
+
+/// A hierarchy of [`Node`]s.
+#[derive(Clone, Debug, Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    /// Creates a new empty [`Scene`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Returns the amount of nodes in the scene.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.iter().flatten().count()
+    }
+
+    /// Returns `true` if the scene has no nodes.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the nodes without a parent.
+    #[must_use]
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Returns a node, or `None` if it was removed.
+    #[must_use]
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0 as usize)?.as_ref()
+    }
+
+    /// Returns a node mutably, or `None` if it was removed.
+    #[must_use]
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    /// Adds a node without a parent.
+    pub fn add(&mut self, node: Node) -> NodeId {
+        let id = self.insert(node, None);
+        self.roots.push(id);
+        id
+    }
+
+    /// Adds a node as the last child of `parent`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` was removed.
+    pub fn add_child(&mut self, parent: NodeId, node: Node) -> NodeId {
+        assert!(self.node(parent).is_some(), "{parent:?} was removed");
+        let id = self.insert(node, Some(parent));
+        self.nodes[parent.0 as usize]
+            .as_mut()
+            .unwrap()
+            .children
+            .push(id);
+        id
+    }
+
+    fn insert(&mut self, mut node: Node, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        node.parent = parent;
+        node.children.clear();
+        self.nodes.push(Some(node));
+        id
+    }
+
+    /// Removes a node and all of its' children, returning the node.
+    pub fn remove(&mut self, id: NodeId) -> Option<Node> {
+        let node = self.nodes.get_mut(id.0 as usize)?.take()?;
+        self.siblings_mut(node.parent)
+            .retain(|&sibling| sibling != id);
+
+        let mut stack = node.children.clone();
+        while let Some(child) = stack.pop() {
+            if let Some(child) = self.nodes[child.0 as usize].take() {
+                stack.extend(child.children);
+            }
+        }
+        Some(node)
+    }
+
+    /// Moves a node under a new parent, or to the roots if `parent` is `None`,
+    /// keeping its' local transform.
+    ///
+    /// Returns `false` without moving it if either node was removed, or if `parent` is the node
+    /// or one of its' children.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> bool {
+        let Some(node) = self.node(id) else {
+            return false;
+        };
+        let old_parent = node.parent;
+        if let Some(parent) = parent
+            && (self.node(parent).is_none() || self.ancestors(parent).any(|a| a == id))
+        {
+            return false;
+        }
+
+        self.siblings_mut(old_parent)
+            .retain(|&sibling| sibling != id);
+        self.siblings_mut(parent).push(id);
+        if let Some(node) = self.node_mut(id) {
+            node.parent = parent;
+        }
+        true
+    }
+
+    fn siblings_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent.and_then(|parent| self.nodes[parent.0 as usize].as_mut()) {
+            Some(parent) => &mut parent.children,
+            None => &mut self.roots,
+        }
+    }
+
+    /// Returns the node and every parent above it, starting with the node.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.node(id).map(|_| id), |&id| self.node(id)?.parent)
+    }
+
+    /// Returns the transform of a node relative to the world, or `None` if it was removed.
+    #[must_use]
+    pub fn world_matrix(&self, id: NodeId) -> Option<Mat4<f32>> {
+        self.node(id)?;
+        Some(
+            self.ancestors(id)
+                .filter_map(|id| self.node(id))
+                .fold(Mat4::identity(), |child, parent| {
+                    parent.local_matrix() * child
+                }),
+        )
+    }
+
+    /// Draws every visible node in front of the camera, with the aspect ratio of the framebuffer.
+    pub fn render(&self, camera: &Camera, buffer: &mut GpuCommandBuffer) -> RenderStats {
+        let aspect = get_framebuffer_width() as f32 / get_framebuffer_height() as f32;
+        self.render_frustum(&camera.frustum(aspect), buffer)
+    }
+
+    /// Draws every visible node inside a frustum.
+    ///
+    /// Nodes are only pushed and multiplied onto the matrix if they, or one of their children,
+    /// are drawn, and nodes with an identity transform are never pushed.
+    pub fn render_frustum(&self, frustum: &Frustum, buffer: &mut GpuCommandBuffer) -> RenderStats {
+        let mut stats = RenderStats::default();
+        let mut draws = vec![Draw::default(); self.nodes.len()];
+        for &root in &self.roots {
+            self.cull(root, Mat4::identity(), frustum, &mut draws, &mut stats);
+        }
+        for &root in &self.roots {
+            self.emit(root, &draws, buffer);
+        }
+        stats
+    }
+
+    /// Marks the nodes to draw, returning `true` if anything in the subtree is drawn.
+    fn cull(
+        &self,
+        id: NodeId,
+        parent: Mat4<f32>,
+        frustum: &Frustum,
+        draws: &mut [Draw],
+        stats: &mut RenderStats,
+    ) -> bool {
+        let node = self.node(id).unwrap();
+        if !node.visible {
+            return false;
+        }
+
+        let world = parent * node.local_matrix();
+        let mut subtree = false;
+        if node.record.is_some() {
+            if node
+                .bounds
+                .is_none_or(|bounds| frustum.intersects_aabb(&transform_aabb(&world, &bounds)))
+            {
+                draws[id.0 as usize].record = true;
+                subtree = true;
+                stats.drawn += 1;
+            } else {
+                stats.culled += 1;
+            }
+        }
+
+        for &child in &node.children {
+            subtree |= self.cull(child, world, frustum, draws, stats);
+        }
+        draws[id.0 as usize].subtree = subtree;
+        subtree
+    }
+
+    /// Emits the commands of a subtree, if anything in it is drawn.
+    fn emit(&self, id: NodeId, draws: &[Draw], buffer: &mut GpuCommandBuffer) {
+        let node = self.node(id).unwrap();
+        let draw = draws[id.0 as usize];
+        if !draw.subtree {
+            return;
+        }
+
+        let push = !node.has_identity_transform();
+        if push {
+            buffer.insert(&GpuCommand::Push);
+            buffer.insert(&GpuCommand::MulMatrix(node.local_matrix().into_col_array()));
+        }
+        if let Some(record) = node.record
+            && draw.record
+        {
+            buffer.draw_recorded(record);
+        }
+        for &child in &node.children {
+            self.emit(child, draws, buffer);
+        }
+        if push {
+            buffer.insert(&GpuCommand::Pop);
+        }
+    }
+}
+
+/// What [`Scene::render_frustum`] draws of a node.
+#[derive(Clone, Copy, Default)]
+struct Draw {
+    /// Whether the record of the node is drawn.
+    record: bool,
+    /// Whether the record of the node, or of any of its' children, is drawn.
+    subtree: bool,
+}
+
+/// Returns the box around a transformed box.
+fn transform_aabb(matrix: &Mat4<f32>, aabb: &Aabb<f32>) -> Aabb<f32> {
+    let corner = |i: u32| {
+        let point = Vec3::new(
+            if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+            if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+            if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+        );
+        matrix.mul_point(point)
+    };
+
+    (1..8)
+        .map(corner)
+        .fold(Aabb::new_empty(corner(0)), |aabb, point| {
+            aabb.expanded_to_contain_point(point)
+        })
+}
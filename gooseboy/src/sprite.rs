@@ -9,8 +9,114 @@
 //!
 //! // Renders the sprite onto 0,0 of the framebuffer
 //! sprites::MY_SPRITE.blit(0, 0);
+//!
+//! // Renders the sprite twice as big, rotated around its' center, onto 32,32
+//! let options = BlitOptions {
+//!     scale: Vec2::broadcast(2.0),
+//!     rotation: 0.5,
+//!     origin: Vec2::new(8.0, 8.0),
+//!     filter: Filter::Bilinear,
+//!     ..BlitOptions::default()
+//! };
+//! sprites::MY_SPRITE.blit_ex(32, 32, &options);
 //! ```
-use crate::framebuffer::get_framebuffer_surface_mut;
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+use crate::{
+    Rect, Vec2,
+    color::Color,
+    framebuffer::{DrawTarget, SurfaceView, get_framebuffer_surface_mut},
+};
+
+/// How a sprite is sampled when it's scaled or rotated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// Uses the closest pixel, keeping pixel art sharp.
+    #[default]
+    Nearest,
+    /// Blends the 4 closest pixels, smoothing the sprite.
+    Bilinear,
+}
+
+/// How [`Sprite::blit_ex`] draws a sprite.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlitOptions {
+    /// The scale of the sprite, around the origin.
+    pub scale: Vec2<f32>,
+    /// The clockwise rotation of the sprite around the origin, in radians.
+    pub rotation: f32,
+    /// The point of the sprite that's drawn onto the blit position, in pixels.
+    pub origin: Vec2<f32>,
+    /// Mirrors the sprite horizontally.
+    pub flip_x: bool,
+    /// Mirrors the sprite vertically.
+    pub flip_y: bool,
+    /// The color every pixel is multiplied with.
+    pub tint: Color,
+    /// How the sprite is sampled when it's scaled or rotated.
+    pub filter: Filter,
+    /// The opacity of the sprite.
+    pub alpha: u8,
+}
+
+impl Default for BlitOptions {
+    fn default() -> Self {
+        Self {
+            scale: Vec2::one(),
+            rotation: 0.0,
+            origin: Vec2::zero(),
+            flip_x: false,
+            flip_y: false,
+            tint: Color::WHITE,
+            filter: Filter::Nearest,
+            alpha: 255,
+        }
+    }
+}
+
+/// Returns `value` if it's a whole number of at least 1.
+fn whole(value: f32) -> Option<usize> {
+    let rounded = value.round();
+    ((value - rounded).abs() < f32::EPSILON && rounded >= 1.0).then_some(rounded as usize)
+}
+
+/// Returns what every channel of a premultiplied pixel is multiplied with, out of `255 * 255`,
+/// or `None` if the options don't tint the sprite.
+fn tint_factors(options: &BlitOptions) -> Option<[u32; 4]> {
+    if options.tint == Color::WHITE && options.alpha == 255 {
+        return None;
+    }
+
+    let tint = options.tint;
+    let opacity = (u32::from(tint.a) * u32::from(options.alpha) + 127) / 255;
+    Some([tint.r, tint.g, tint.b, 255].map(|channel| u32::from(channel) * opacity))
+}
+
+/// Multiplies a premultiplied pixel with the factors of [`tint_factors`].
+fn tint(pixel: [u8; 4], factors: Option<[u32; 4]>) -> [u8; 4] {
+    let Some(factors) = factors else {
+        return pixel;
+    };
+
+    let mut tinted = [0; 4];
+    for ((tinted, &channel), factor) in tinted.iter_mut().zip(&pixel).zip(factors) {
+        *tinted = ((u32::from(channel) * factor + 32512) / 65025) as u8;
+    }
+    tinted
+}
+
+/// Returns the part of `rect` that can be drawn onto a view, or `None` if there's none.
+fn visible(view: &SurfaceView, rect: Rect<i32, i32>) -> Option<Rect<i32, i32>> {
+    let clip = view.clip_rect();
+    let (left, top) = (rect.x.max(clip.x), rect.y.max(clip.y));
+    let right = rect.x.saturating_add(rect.w).min(clip.x + clip.w);
+    let bottom = rect.y.saturating_add(rect.h).min(clip.y + clip.h);
+    (left < right && top < bottom).then(|| Rect::new(left, top, right - left, bottom - top))
+}
 
 /// A sprite.
 #[derive(Clone, Debug)]
@@ -50,13 +156,229 @@ impl Sprite {
 
     /// Blits the sprite onto a position.
     pub fn blit(&self, x: usize, y: usize) {
-        get_framebuffer_surface_mut().blit_premultiplied_clipped(
+        self.blit_to(
+            get_framebuffer_surface_mut(),
             unsafe { crate::unsafe_casts::usize_as_i32(x) },
             unsafe { crate::unsafe_casts::usize_as_i32(y) },
+        );
+    }
+
+    /// Blits the sprite onto a position of a surface or a view of one.
+    pub fn blit_to(&self, surface: &mut impl DrawTarget, x: i32, y: i32) {
+        surface.as_view().blit_premultiplied_clipped(
+            x,
+            y,
             self.width,
             self.height,
             &self.rgba,
             self.blend,
         );
     }
+
+    /// Blits the sprite onto a position, scaled, rotated, flipped and tinted.
+    pub fn blit_ex(&self, x: i32, y: i32, options: &BlitOptions) {
+        self.blit_ex_to(get_framebuffer_surface_mut(), x, y, options);
+    }
+
+    /// Blits the sprite onto a position of a surface or a view of one, scaled, rotated, flipped
+    /// and tinted.
+    ///
+    /// Like [`Sprite::blit`], the pixels are premultiplied. Rotated and fractionally scaled
+    /// sprites are always blended, so the pixels around them are kept.
+    pub fn blit_ex_to(&self, surface: &mut impl DrawTarget, x: i32, y: i32, options: &BlitOptions) {
+        let mut view = surface.as_view();
+        let factors = tint_factors(options);
+        let blend = self.blend || factors.is_some_and(|factors| factors[3] < 65025);
+        let rotated = options.rotation.abs() >= f32::EPSILON;
+
+        if !rotated
+            && let (Some(scale_x), Some(scale_y)) = (whole(options.scale.x), whole(options.scale.y))
+            && (options.filter == Filter::Nearest || scale_x * scale_y == 1)
+        {
+            let scale = Vec2::new(scale_x, scale_y);
+            let position = (Vec2::new(x, y).as_::<f32>() - options.origin * scale.as_::<f32>())
+                .map(|value| value.round() as i32);
+            if scale == Vec2::one() && !options.flip_x && !options.flip_y && factors.is_none() {
+                view.blit_premultiplied_clipped(
+                    position.x,
+                    position.y,
+                    self.width,
+                    self.height,
+                    &self.rgba,
+                    self.blend,
+                );
+            } else {
+                self.blit_scaled(&mut view, position, scale, options, factors, blend);
+            }
+            return;
+        }
+
+        self.blit_transformed(&mut view, Vec2::new(x, y), options, factors);
+    }
+
+    /// Returns a pixel of the sprite, or transparent if it's outside of it.
+    fn texel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.rgba
+            .get((y * self.width + x) * 4..)
+            .and_then(|pixels| pixels.first_chunk::<4>())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Samples the sprite at a point inside of it, in pixels.
+    fn sample(&self, point: Vec2<f32>, filter: Filter) -> [u8; 4] {
+        if filter == Filter::Nearest {
+            return self.texel(point.x as usize, point.y as usize);
+        }
+
+        let point = point - 0.5;
+        let base = point.map(f32::floor);
+        let fraction = point - base;
+        let clamp = |value: f32, size: usize| (value.max(0.0) as usize).min(size - 1);
+        let (left, top) = (clamp(base.x, self.width), clamp(base.y, self.height));
+        let (right, bottom) = (
+            clamp(base.x + 1.0, self.width),
+            clamp(base.y + 1.0, self.height),
+        );
+        let [top_left, top_right, bottom_left, bottom_right] =
+            [(left, top), (right, top), (left, bottom), (right, bottom)]
+                .map(|(x, y)| self.texel(x, y).map(f32::from));
+
+        let mut sampled = [0; 4];
+        for (channel, sampled) in sampled.iter_mut().enumerate() {
+            let upper =
+                (top_right[channel] - top_left[channel]).mul_add(fraction.x, top_left[channel]);
+            let lower = (bottom_right[channel] - bottom_left[channel])
+                .mul_add(fraction.x, bottom_left[channel]);
+            *sampled = (lower - upper).mul_add(fraction.y, upper).round() as u8;
+        }
+        sampled
+    }
+
+    /// Blits the sprite scaled up by whole numbers and flipped, without any resampling.
+    fn blit_scaled(
+        &self,
+        view: &mut SurfaceView,
+        position: Vec2<i32>,
+        scale: Vec2<usize>,
+        options: &BlitOptions,
+        factors: Option<[u32; 4]>,
+        blend: bool,
+    ) {
+        let size = Vec2::new(self.width * scale.x, self.height * scale.y).as_::<i32>();
+        let Some(rect) = visible(view, Rect::new(position.x, position.y, size.x, size.y)) else {
+            return;
+        };
+
+        let mut rgba = Vec::with_capacity(rect.w as usize * rect.h as usize * 4);
+        for y in rect.y..rect.y + rect.h {
+            let row = (y - position.y) as usize / scale.y;
+            let row = if options.flip_y {
+                self.height - 1 - row
+            } else {
+                row
+            };
+            for x in rect.x..rect.x + rect.w {
+                let column = (x - position.x) as usize / scale.x;
+                let column = if options.flip_x {
+                    self.width - 1 - column
+                } else {
+                    column
+                };
+                rgba.extend(tint(self.texel(column, row), factors));
+            }
+        }
+        view.blit_premultiplied_clipped(
+            rect.x,
+            rect.y,
+            rect.w as usize,
+            rect.h as usize,
+            &rgba,
+            blend,
+        );
+    }
+
+    /// Blits the sprite with an affine transform, sampling it at the center of every pixel.
+    fn blit_transformed(
+        &self,
+        view: &mut SurfaceView,
+        position: Vec2<i32>,
+        options: &BlitOptions,
+        factors: Option<[u32; 4]>,
+    ) {
+        let scale = options.scale;
+        if scale.x.abs() < f32::EPSILON || scale.y.abs() < f32::EPSILON || self.rgba.is_empty() {
+            return;
+        }
+
+        let (sin, cos) = options.rotation.sin_cos();
+        let position = position.as_::<f32>();
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let corners = [
+            Vec2::zero(),
+            Vec2::new(size.x, 0.0),
+            Vec2::new(0.0, size.y),
+            size,
+        ]
+        .map(|corner| {
+            let local = (corner - options.origin) * scale;
+            position
+                + Vec2::new(
+                    cos.mul_add(local.x, -sin * local.y),
+                    sin.mul_add(local.x, cos * local.y),
+                )
+        });
+        let (min, max) = corners.iter().fold(
+            (
+                Vec2::broadcast(f32::INFINITY),
+                Vec2::broadcast(f32::NEG_INFINITY),
+            ),
+            |(min, max), corner| {
+                (
+                    Vec2::new(min.x.min(corner.x), min.y.min(corner.y)),
+                    Vec2::new(max.x.max(corner.x), max.y.max(corner.y)),
+                )
+            },
+        );
+        let (min, max) = (min.map(f32::floor), max.map(f32::ceil));
+        let bounds = Rect::new(
+            min.x as i32,
+            min.y as i32,
+            (max.x - min.x) as i32,
+            (max.y - min.y) as i32,
+        );
+        let Some(rect) = visible(view, bounds) else {
+            return;
+        };
+
+        let mut rgba = vec![0; rect.w as usize * rect.h as usize * 4];
+        let rows = rgba.as_chunks_mut::<4>().0.chunks_mut(rect.w as usize);
+        for (row, y) in rows.zip(rect.y..) {
+            for (pixel, x) in row.iter_mut().zip(rect.x..) {
+                let offset = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - position;
+                let local = Vec2::new(
+                    cos.mul_add(offset.x, sin * offset.y),
+                    cos.mul_add(offset.y, -sin * offset.x),
+                ) / scale;
+                let mut point = local + options.origin;
+                if options.flip_x {
+                    point.x = size.x - point.x;
+                }
+                if options.flip_y {
+                    point.y = size.y - point.y;
+                }
+                if point.x >= 0.0 && point.y >= 0.0 && point.x < size.x && point.y < size.y {
+                    *pixel = tint(self.sample(point, options.filter), factors);
+                }
+            }
+        }
+        view.blit_premultiplied_clipped(
+            rect.x,
+            rect.y,
+            rect.w as usize,
+            rect.h as usize,
+            &rgba,
+            true,
+        );
+    }
 }
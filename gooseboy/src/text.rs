@@ -2,7 +2,7 @@
 use crate::{
     color::Color,
     font::FONT,
-    framebuffer::{Surface, get_framebuffer_surface_mut, get_framebuffer_width},
+    framebuffer::{DrawTarget, get_framebuffer_surface_mut, get_framebuffer_width},
 };
 
 /// Draws a single character onto the global framebuffer surface.
@@ -10,8 +10,9 @@ pub fn draw_char(x: usize, y: usize, c: u8, color: Color) {
     draw_char_ex(get_framebuffer_surface_mut(), x, y, c, color);
 }
 
-/// Draws a single character onto a surface.
-pub fn draw_char_ex(surface: &mut Surface, x: usize, y: usize, c: u8, color: Color) {
+/// Draws a single character onto a surface or a view of one.
+pub fn draw_char_ex(surface: &mut impl DrawTarget, x: usize, y: usize, c: u8, color: Color) {
+    let mut surface = surface.as_view();
     for (row, &bits) in FONT[c as usize].iter().take(8).enumerate() {
         for col in 0..8 {
             if bits & (1 << (7 - col)) != 0 {
@@ -21,9 +22,9 @@ pub fn draw_char_ex(surface: &mut Surface, x: usize, y: usize, c: u8, color: Col
     }
 }
 
-/// Draws text that wraps around a surface.
+/// Draws text that wraps around a surface or a view of one.
 pub fn draw_text_wrapped_ex<S: AsRef<str>>(
-    surface: &mut Surface,
+    surface: &mut impl DrawTarget,
     x: usize,
     mut y: usize,
     text: S,
@@ -63,6 +64,17 @@ pub fn draw_text<S: AsRef<str>>(x: usize, y: usize, text: S, color: Color) {
     );
 }
 
+/// Draws text onto a surface or a view of one.
+pub fn draw_text_ex<S: AsRef<str>>(
+    surface: &mut impl DrawTarget,
+    x: usize,
+    y: usize,
+    text: S,
+    color: Color,
+) {
+    draw_text_wrapped_ex(surface, x, y, text, color, None);
+}
+
 /// Draws wrapped text onto the global framebuffer surface.
 pub fn draw_text_wrapped<S: AsRef<str>>(x: usize, y: usize, text: S, color: Color) {
     draw_text_wrapped_ex(
@@ -75,6 +87,38 @@ pub fn draw_text_wrapped<S: AsRef<str>>(x: usize, y: usize, text: S, color: Colo
     );
 }
 
+/// Draws text over a `background` rectangle the size of the text onto a surface or a view of one.
+pub fn draw_text_background_ex<S: AsRef<str>>(
+    surface: &mut impl DrawTarget,
+    x: usize,
+    y: usize,
+    text: S,
+    color: Color,
+    background: Color,
+) {
+    let text = text.as_ref();
+    surface.as_view().draw_rect(
+        i32::try_from(x).unwrap_or(i32::MAX),
+        i32::try_from(y).unwrap_or(i32::MAX),
+        get_text_width(text),
+        get_text_height(text),
+        background,
+        true,
+    );
+    draw_text_wrapped_ex(surface, x, y, text, color, None);
+}
+
+/// Draws text over a `background` rectangle the size of the text onto the global framebuffer surface.
+pub fn draw_text_background<S: AsRef<str>>(
+    x: usize,
+    y: usize,
+    text: S,
+    color: Color,
+    background: Color,
+) {
+    draw_text_background_ex(get_framebuffer_surface_mut(), x, y, text, color, background);
+}
+
 /// Returns a color from its' name.
 #[must_use]
 pub fn color_from_name(name: &str) -> Option<Color> {
@@ -128,9 +172,9 @@ pub fn draw_text_formatted_wrapped<S: AsRef<str>>(
     );
 }
 
-/// Draws formatted text onto the surface.
+/// Draws formatted text onto a surface or a view of one.
 pub fn draw_text_formatted_ex(
-    surface: &mut Surface,
+    surface: &mut impl DrawTarget,
     x: usize,
     mut y: usize,
     text: &str,
@@ -153,7 +197,7 @@ pub fn draw_text_formatted_ex(
                     cx = x;
                     y += 8;
                 }
-                draw_char(cx, y, b'[', color);
+                draw_char_ex(surface, cx, y, b'[', color);
                 cx += 8;
                 i += 2;
                 continue;
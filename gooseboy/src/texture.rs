@@ -0,0 +1,575 @@
+//! This is used to store textures in less bytes than RGBA8, for registering them with the
+//! `GooseGPU` and for embedding them into the crate.
+//!
+//! Every format stores the pixels row by row, starting from the top left:
+//!
+//! | Format     | Pixel                                                                  |
+//! |------------|------------------------------------------------------------------------|
+//! | `Rgba8`    | R, G, B, A `u8`                                                        |
+//! | `Rgb565`   | `u16`, 5 bits of red, 6 bits of green and 5 bits of blue, fully opaque |
+//! | `Rgba4444` | `u16`, 4 bits of red, green, blue and alpha                            |
+//! | `Palette8` | `u8` index into the palette                                            |
+//!
+//! `Palette8` data starts with the amount of colors minus one as a `u8`, followed by every
+//! color as R, G, B, A `u8`, and then the indices.
+//!
+//! Run-length encoded data is made of packets starting with a `u8`, which is followed by
+//! that many plus one pixels if it's below `0x80`, or by a single pixel repeated
+//! `byte - 0x80 + 1` times otherwise. Only the pixels are encoded, not the palette.
+//!
+//! [`EncodedTexture::to_bytes`] stores a texture as:
+//!
+//! | Size | Field                                                    |
+//! |------|----------------------------------------------------------|
+//! | 4    | magic, [`TEXTURE_MAGIC`]                                 |
+//! | 2    | version `u16`, [`TEXTURE_VERSION`]                       |
+//! | 4    | width `u32`                                              |
+//! | 4    | height `u32`                                             |
+//! | 1    | format `u8`, with [`TEXTURE_RLE`] if run-length encoded  |
+//! | ..   | data                                                     |
+//!
+//! Example:
+//! ```rs
+//! let texture = EncodedTexture::encode_best(16, 16, &sprite.rgba, false)?;
+//! let id = buffer.register_encoded(&texture);
+//! ```
+#![allow(clippy::cast_possible_truncation)]
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::sprite::Sprite;
+
+/// The magic bytes of an encoded texture file.
+pub const TEXTURE_MAGIC: [u8; 4] = *b"GTEX";
+/// The current version of the encoded texture file format.
+pub const TEXTURE_VERSION: u16 = 1;
+/// The bit of a serialized format that marks run-length encoded data.
+pub const TEXTURE_RLE: u8 = 0x80;
+/// The size of the header of an encoded texture file in bytes.
+pub const TEXTURE_HEADER_SIZE: usize = 15;
+
+/// The longest run or literal packet of run-length encoded data.
+const MAX_PACKET: usize = 0x80;
+
+/// An error from encoding or decoding a texture.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TextureError {
+    /// The file doesn't start with [`TEXTURE_MAGIC`].
+    #[error("not an encoded texture")]
+    BadMagic,
+    /// The file was written with another version.
+    #[error("unsupported encoded texture version {version}")]
+    UnsupportedVersion {
+        /// The version of the file.
+        version: u16,
+    },
+    /// The format byte is unknown.
+    #[error("unknown texture format {repr}")]
+    UnknownFormat {
+        /// The format byte, without [`TEXTURE_RLE`].
+        repr: u8,
+    },
+    /// The data ends before every pixel was read.
+    #[error("texture data ends early")]
+    UnexpectedEnd,
+    /// The data continues after every pixel was read.
+    #[error("texture data has {len} trailing bytes")]
+    TrailingBytes {
+        /// The amount of bytes left over.
+        len: usize,
+    },
+    /// A run-length packet goes past the last pixel.
+    #[error("run-length packet overflows the texture")]
+    BadRun,
+    /// A pixel uses an index outside of the palette.
+    #[error("palette index {index} is out of bounds")]
+    BadPaletteIndex {
+        /// The index.
+        index: u8,
+    },
+    /// A texture has more than 256 colors, so it can't be palettized.
+    #[error("texture has more than 256 colors")]
+    TooManyColors,
+    /// The RGBA doesn't match the size of the texture, or the texture is too big to address.
+    #[error("texture has {len} RGBA bytes, expected {expected}")]
+    BadSize {
+        /// The length of the RGBA bytes, or of the encoded data.
+        len: usize,
+        /// The expected length of the RGBA bytes, `usize::MAX` if it doesn't fit in a `usize`.
+        expected: usize,
+    },
+}
+
+/// The pixel format of an [`EncodedTexture`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// 4 bytes per pixel, lossless.
+    #[default]
+    Rgba8,
+    /// 2 bytes per pixel, without alpha.
+    Rgb565,
+    /// 2 bytes per pixel.
+    Rgba4444,
+    /// 1 byte per pixel indexing a palette of up to 256 colors, lossless.
+    Palette8,
+}
+
+impl TextureFormat {
+    /// Returns the representation of this [`TextureFormat`].
+    #[must_use]
+    pub const fn repr(&self) -> u8 {
+        match self {
+            Self::Rgba8 => 0,
+            Self::Rgb565 => 1,
+            Self::Rgba4444 => 2,
+            Self::Palette8 => 3,
+        }
+    }
+
+    /// Returns the [`TextureFormat`] of a representation, or `None` if unknown.
+    #[must_use]
+    pub const fn from_repr(repr: u8) -> Option<Self> {
+        match repr {
+            0 => Some(Self::Rgba8),
+            1 => Some(Self::Rgb565),
+            2 => Some(Self::Rgba4444),
+            3 => Some(Self::Palette8),
+            _ => None,
+        }
+    }
+
+    /// Returns the size of a single pixel of this format in bytes.
+    #[must_use]
+    pub const fn pixel_size(&self) -> usize {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Rgb565 | Self::Rgba4444 => 2,
+            Self::Palette8 => 1,
+        }
+    }
+
+    fn encode_pixel(self, [r, g, b, a]: [u8; 4], out: &mut Vec<u8>) {
+        match self {
+            Self::Rgba8 => out.extend_from_slice(&[r, g, b, a]),
+            Self::Rgb565 => {
+                let value = (u16::from(quantize(r, 5)) << 11)
+                    | (u16::from(quantize(g, 6)) << 5)
+                    | u16::from(quantize(b, 5));
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Self::Rgba4444 => {
+                let value = (u16::from(quantize(r, 4)) << 12)
+                    | (u16::from(quantize(g, 4)) << 8)
+                    | (u16::from(quantize(b, 4)) << 4)
+                    | u16::from(quantize(a, 4));
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Self::Palette8 => unreachable!("palettized pixels are indices"),
+        }
+    }
+
+    fn decode_pixel(self, pixel: &[u8]) -> [u8; 4] {
+        match self {
+            Self::Rgba8 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            Self::Rgb565 => {
+                let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+                [
+                    expand(value >> 11, 5),
+                    expand(value >> 5, 6),
+                    expand(value, 5),
+                    255,
+                ]
+            }
+            Self::Rgba4444 => {
+                let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+                [
+                    expand(value >> 12, 4),
+                    expand(value >> 8, 4),
+                    expand(value >> 4, 4),
+                    expand(value, 4),
+                ]
+            }
+            Self::Palette8 => unreachable!("palettized pixels are indices"),
+        }
+    }
+}
+
+impl fmt::Display for TextureFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rgba8 => "rgba8",
+            Self::Rgb565 => "rgb565",
+            Self::Rgba4444 => "rgba4444",
+            Self::Palette8 => "palette8",
+        })
+    }
+}
+
+/// Rounds an 8 bit channel to `bits` bits.
+const fn quantize(value: u8, bits: u32) -> u8 {
+    let max = (1u32 << bits) - 1;
+    ((value as u32 * max + 127) / 255) as u8
+}
+
+/// Expands the low `bits` bits of `value` to an 8 bit channel.
+const fn expand(value: u16, bits: u32) -> u8 {
+    let max = (1u32 << bits) - 1;
+    (((value as u32 & max) * 255 + max / 2) / max) as u8
+}
+
+/// A texture in a [`TextureFormat`], optionally run-length encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedTexture {
+    /// The width.
+    pub width: u32,
+    /// The height.
+    pub height: u32,
+    /// The pixel format.
+    pub format: TextureFormat,
+    /// Whether the pixels are run-length encoded.
+    pub rle: bool,
+    /// The encoded pixels, see the [module documentation](self) for the layout.
+    pub data: Vec<u8>,
+}
+
+impl EncodedTexture {
+    /// Encodes `width`x`height` RGBA pixels into a format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rgba` doesn't match the size, or if it has
+    /// more than 256 colors and `format` is [`TextureFormat::Palette8`].
+    pub fn encode(
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        format: TextureFormat,
+        rle: bool,
+    ) -> Result<Self, TextureError> {
+        let expected = pixels_len(width, height, 4, rgba.len())?;
+        if rgba.len() != expected {
+            return Err(TextureError::BadSize {
+                len: rgba.len(),
+                expected,
+            });
+        }
+
+        let pixels = rgba.as_chunks::<4>().0;
+        let mut data = Vec::new();
+        let encoded = if format == TextureFormat::Palette8 {
+            let palette = palette(pixels).ok_or(TextureError::TooManyColors)?;
+            data.push((palette.len() - 1) as u8);
+            data.extend(palette.iter().flatten());
+            pixels
+                .iter()
+                .map(|pixel| palette.binary_search(pixel).unwrap_or_default() as u8)
+                .collect()
+        } else {
+            let mut encoded = Vec::with_capacity(pixels.len() * format.pixel_size());
+            for &pixel in pixels {
+                format.encode_pixel(pixel, &mut encoded);
+            }
+            encoded
+        };
+
+        if rle {
+            encode_rle(&encoded, format.pixel_size(), &mut data);
+        } else {
+            data.extend_from_slice(&encoded);
+        }
+        Ok(Self {
+            width,
+            height,
+            format,
+            rle,
+            data,
+        })
+    }
+
+    /// Encodes `width`x`height` RGBA pixels into the smallest format that keeps every pixel,
+    /// or that loses some precision if `lossy` is set, run-length encoding it if that's smaller.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rgba` doesn't match the size.
+    pub fn encode_best(
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        lossy: bool,
+    ) -> Result<Self, TextureError> {
+        let format = choose_format(rgba, lossy);
+        let raw = Self::encode(width, height, rgba, format, false)?;
+        let rle = Self::encode(width, height, rgba, format, true)?;
+        Ok(if rle.data.len() < raw.data.len() {
+            rle
+        } else {
+            raw
+        })
+    }
+
+    /// Decodes the texture into RGBA pixels.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed.
+    pub fn decode(&self) -> Result<Vec<u8>, TextureError> {
+        decode(self.width, self.height, self.format, self.rle, &self.data)
+    }
+
+    /// Decodes the texture into a blended [`Sprite`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed.
+    pub fn to_sprite(&self) -> Result<Sprite, TextureError> {
+        let rgba = self.decode()?;
+        Ok(Sprite {
+            width: self.width as usize,
+            height: self.height as usize,
+            rgba,
+            blend: true,
+        })
+    }
+
+    /// Returns the serialized format byte, the format's representation with
+    /// [`TEXTURE_RLE`] if it's run-length encoded.
+    #[must_use]
+    pub const fn format_byte(&self) -> u8 {
+        self.format.repr() | if self.rle { TEXTURE_RLE } else { 0 }
+    }
+
+    /// Parses a serialized format byte into the format and whether it's run-length encoded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format is unknown.
+    pub const fn parse_format_byte(byte: u8) -> Result<(TextureFormat, bool), TextureError> {
+        let repr = byte & !TEXTURE_RLE;
+        match TextureFormat::from_repr(repr) {
+            Some(format) => Ok((format, byte & TEXTURE_RLE != 0)),
+            None => Err(TextureError::UnknownFormat { repr }),
+        }
+    }
+
+    /// Serializes the texture, see the [module documentation](self) for the layout.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(TEXTURE_HEADER_SIZE + self.data.len());
+        bytes.extend_from_slice(&TEXTURE_MAGIC);
+        bytes.extend_from_slice(&TEXTURE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.push(self.format_byte());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Parses a texture serialized with [`EncodedTexture::to_bytes`].
+    ///
+    /// The data is only checked once it's decoded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the header is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TextureError> {
+        let header = bytes
+            .get(..TEXTURE_HEADER_SIZE)
+            .ok_or(TextureError::UnexpectedEnd)?;
+        if header[..4] != TEXTURE_MAGIC {
+            return Err(TextureError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != TEXTURE_VERSION {
+            return Err(TextureError::UnsupportedVersion { version });
+        }
+
+        let u32_at = |at: usize| {
+            u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+        };
+        let (format, rle) = Self::parse_format_byte(header[14])?;
+        Ok(Self {
+            width: u32_at(6),
+            height: u32_at(10),
+            format,
+            rle,
+            data: bytes[TEXTURE_HEADER_SIZE..].to_vec(),
+        })
+    }
+}
+
+/// Returns the sorted colors of the pixels, or `None` if there are more than 256.
+fn palette(pixels: &[[u8; 4]]) -> Option<Vec<[u8; 4]>> {
+    let mut palette = vec![*pixels.first().unwrap_or(&[0; 4])];
+    for pixel in pixels {
+        if let Err(index) = palette.binary_search(pixel) {
+            if palette.len() == 256 {
+                return None;
+            }
+            palette.insert(index, *pixel);
+        }
+    }
+    Some(palette)
+}
+
+/// Returns the smallest format for RGBA pixels, keeping every pixel unless `lossy` is set.
+#[must_use]
+pub fn choose_format(rgba: &[u8], lossy: bool) -> TextureFormat {
+    let pixels = rgba.as_chunks::<4>().0;
+    if palette(pixels).is_some() {
+        return TextureFormat::Palette8;
+    }
+
+    let keeps = |format: TextureFormat| {
+        pixels.iter().all(|&pixel| {
+            let mut encoded = Vec::with_capacity(2);
+            format.encode_pixel(pixel, &mut encoded);
+            format.decode_pixel(&encoded) == pixel
+        })
+    };
+    let opaque = pixels.iter().all(|pixel| pixel[3] == 255);
+    if opaque && (lossy || keeps(TextureFormat::Rgb565)) {
+        TextureFormat::Rgb565
+    } else if lossy || keeps(TextureFormat::Rgba4444) {
+        TextureFormat::Rgba4444
+    } else {
+        TextureFormat::Rgba8
+    }
+}
+
+/// Run-length encodes pixels of `size` bytes onto `out`.
+fn encode_rle(pixels: &[u8], size: usize, out: &mut Vec<u8>) {
+    let pixels: Vec<&[u8]> = pixels.chunks_exact(size).collect();
+    let mut i = 0;
+    while i < pixels.len() {
+        let run = pixels[i..]
+            .iter()
+            .take(MAX_PACKET)
+            .take_while(|&&pixel| pixel == pixels[i])
+            .count();
+        if run > 1 {
+            out.push((0x80 + run - 1) as u8);
+            out.extend_from_slice(pixels[i]);
+            i += run;
+            continue;
+        }
+
+        // Literals last until the next run of at least 2 pixels.
+        let start = i;
+        while i < pixels.len()
+            && i - start < MAX_PACKET
+            && pixels.get(i + 1).is_none_or(|&next| next != pixels[i])
+        {
+            i += 1;
+        }
+        out.push((i - start - 1) as u8);
+        for pixel in &pixels[start..i] {
+            out.extend_from_slice(pixel);
+        }
+    }
+}
+
+/// Returns the length of `width`x`height` pixels of `size` bytes, or [`TextureError::BadSize`]
+/// if it doesn't fit in a `usize`, with `len` being the length of the data it's for.
+fn pixels_len(width: u32, height: u32, size: usize, len: usize) -> Result<usize, TextureError> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|count| count.checked_mul(size))
+        .ok_or(TextureError::BadSize {
+            len,
+            expected: usize::MAX,
+        })
+}
+
+/// Reads exactly `count` pixels of `size` bytes from `data`, run-length decoding them
+/// if `rle` is set.
+fn read_pixels(data: &[u8], count: usize, size: usize, rle: bool) -> Result<Vec<u8>, TextureError> {
+    let len = count * size;
+    if !rle {
+        return match data.len().cmp(&len) {
+            std::cmp::Ordering::Less => Err(TextureError::UnexpectedEnd),
+            std::cmp::Ordering::Greater => Err(TextureError::TrailingBytes {
+                len: data.len() - len,
+            }),
+            std::cmp::Ordering::Equal => Ok(data.to_vec()),
+        };
+    }
+
+    // A run of a pixel is at most 128 pixels, so the data caps the size of a bogus texture.
+    let mut pixels = Vec::with_capacity(len.min(data.len().saturating_mul(128)));
+    let mut pos = 0;
+    while pixels.len() < len {
+        let control = *data.get(pos).ok_or(TextureError::UnexpectedEnd)? as usize;
+        pos += 1;
+        let (repeat, literal) = if control >= 0x80 {
+            (control - 0x80 + 1, 1)
+        } else {
+            (1, control + 1)
+        };
+        let bytes = data
+            .get(pos..pos + literal * size)
+            .ok_or(TextureError::UnexpectedEnd)?;
+        pos += literal * size;
+        if pixels.len() + repeat * bytes.len() > len {
+            return Err(TextureError::BadRun);
+        }
+        for _ in 0..repeat {
+            pixels.extend_from_slice(bytes);
+        }
+    }
+
+    if pos < data.len() {
+        return Err(TextureError::TrailingBytes {
+            len: data.len() - pos,
+        });
+    }
+    Ok(pixels)
+}
+
+/// Decodes `width`x`height` pixels of a format into RGBA pixels.
+///
+/// # Errors
+///
+/// This function will return an error if the data is malformed.
+pub fn decode(
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    rle: bool,
+    data: &[u8],
+) -> Result<Vec<u8>, TextureError> {
+    let count = pixels_len(width, height, 1, data.len())?;
+    let rgba_len = pixels_len(width, height, 4, data.len())?;
+    if format == TextureFormat::Palette8 {
+        let colors = *data.first().ok_or(TextureError::UnexpectedEnd)? as usize + 1;
+        let palette = data
+            .get(1..1 + colors * 4)
+            .ok_or(TextureError::UnexpectedEnd)?
+            .as_chunks::<4>()
+            .0;
+        let indices = read_pixels(&data[1 + colors * 4..], count, 1, rle)?;
+
+        let mut rgba = Vec::with_capacity(rgba_len);
+        for &index in &indices {
+            let color = palette
+                .get(index as usize)
+                .ok_or(TextureError::BadPaletteIndex { index })?;
+            rgba.extend_from_slice(color);
+        }
+        return Ok(rgba);
+    }
+
+    let size = format.pixel_size();
+    let pixels = read_pixels(data, count, size, rle)?;
+    if format == TextureFormat::Rgba8 {
+        return Ok(pixels);
+    }
+
+    let mut rgba = Vec::with_capacity(rgba_len);
+    for pixel in pixels.chunks_exact(size) {
+        rgba.extend_from_slice(&format.decode_pixel(pixel));
+    }
+    Ok(rgba)
+}
@@ -0,0 +1,752 @@
+//! This is used to draw smooth, anti-aliased shapes made of lines and curves, for UIs and charts.
+//!
+//! A [`Path`] is built out of lines, quadratic and cubic Béziers and arcs, and is filled with
+//! [`fill_path`] by a [`Paint`], either a solid color or a gradient. Strokes are turned into
+//! paths with [`Path::stroke`], and drawn with [`stroke_path`].
+//!
+//! Positions are in pixels, where the pixel at `x`, `y` covers the area from `x`, `y` to
+//! `x + 1`, `y + 1`, so the center of the top left pixel is at 0.5, 0.5. Every pixel is drawn
+//! with the amount of its' area covered by the path, blended over the surface.
+//!
+//! Example:
+//! ```rs
+//! let mut path = Path::new();
+//! path.move_to(Vec2::new(10.0, 10.0))
+//!     .quad_to(Vec2::new(40.0, 0.0), Vec2::new(70.0, 10.0))
+//!     .line_to(Vec2::new(70.0, 60.0))
+//!     .close();
+//!
+//! let surface = get_framebuffer_surface_mut();
+//! fill_path(surface, &path, &Paint::Solid(Color::RED), FillRule::NonZero);
+//! stroke_path(surface, &path, &Paint::Solid(Color::WHITE), &Stroke::new(2.0));
+//! ```
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::{Mat3, Rect, Vec2, Vec3, color::Color, framebuffer::DrawTarget};
+
+/// The furthest a flattened curve gets from the real curve, in pixels.
+pub const TOLERANCE: f32 = 0.1;
+/// The amount of scanlines sampled per row of pixels.
+const SUBSAMPLES: usize = 5;
+/// The most lines a single curve is flattened into.
+const MAX_CURVE_LINES: usize = 256;
+
+/// A command of a [`Path`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new subpath at a point.
+    MoveTo(Vec2<f32>),
+    /// A line to a point.
+    LineTo(Vec2<f32>),
+    /// A quadratic Bézier through a control point to a point.
+    QuadTo(Vec2<f32>, Vec2<f32>),
+    /// A cubic Bézier through two control points to a point.
+    CubicTo(Vec2<f32>, Vec2<f32>, Vec2<f32>),
+    /// A line back to the start of the subpath, closing it.
+    Close,
+}
+
+/// A flattened subpath of a [`Path`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contour {
+    /// The points, without the first point repeated at the end when it's closed.
+    pub points: Vec<Vec2<f32>>,
+    /// Is the last point connected back to the first point?
+    pub closed: bool,
+}
+
+/// A shape made of subpaths of lines and curves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+    /// The start of the current subpath.
+    start: Vec2<f32>,
+    /// The end of the last command, or `None` if there's no subpath yet.
+    current: Option<Vec2<f32>>,
+}
+
+impl Path {
+    /// Creates a new empty [`Path`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a rectangle.
+    #[must_use]
+    pub fn rect(rect: Rect<f32, f32>) -> Self {
+        let mut path = Self::new();
+        path.move_to(Vec2::new(rect.x, rect.y))
+            .line_to(Vec2::new(rect.x + rect.w, rect.y))
+            .line_to(Vec2::new(rect.x + rect.w, rect.y + rect.h))
+            .line_to(Vec2::new(rect.x, rect.y + rect.h))
+            .close();
+        path
+    }
+
+    /// Creates a rectangle with corners rounded by `radius`.
+    #[must_use]
+    pub fn rounded_rect(rect: Rect<f32, f32>, radius: f32) -> Self {
+        let radius = radius.min(rect.w / 2.0).min(rect.h / 2.0).max(0.0);
+        let (left, top) = (rect.x + radius, rect.y + radius);
+        let (right, bottom) = (rect.x + rect.w - radius, rect.y + rect.h - radius);
+
+        let mut path = Self::new();
+        path.arc(Vec2::new(right, top), radius, -FRAC_PI_2, 0.0)
+            .arc(Vec2::new(right, bottom), radius, 0.0, FRAC_PI_2)
+            .arc(Vec2::new(left, bottom), radius, FRAC_PI_2, PI)
+            .arc(Vec2::new(left, top), radius, PI, PI + FRAC_PI_2)
+            .close();
+        path
+    }
+
+    /// Creates a circle.
+    #[must_use]
+    pub fn circle(center: Vec2<f32>, radius: f32) -> Self {
+        let mut path = Self::new();
+        path.arc(center, radius, 0.0, PI * 2.0).close();
+        path
+    }
+
+    /// Returns the commands of the path.
+    #[must_use]
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    /// Returns `true` if the path has no commands.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Starts a new subpath at `point`.
+    pub fn move_to(&mut self, point: Vec2<f32>) -> &mut Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self.start = point;
+        self.current = Some(point);
+        self
+    }
+
+    /// Starts a subpath at `point` if there isn't one yet.
+    fn ensure_subpath(&mut self, point: Vec2<f32>) {
+        if self.current.is_none() {
+            self.move_to(point);
+        }
+    }
+
+    /// Adds a line to `point`.
+    pub fn line_to(&mut self, point: Vec2<f32>) -> &mut Self {
+        self.ensure_subpath(point);
+        self.commands.push(PathCommand::LineTo(point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Adds a quadratic Bézier through `control` to `point`.
+    pub fn quad_to(&mut self, control: Vec2<f32>, point: Vec2<f32>) -> &mut Self {
+        self.ensure_subpath(control);
+        self.commands.push(PathCommand::QuadTo(control, point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Adds a cubic Bézier through `control1` and `control2` to `point`.
+    pub fn cubic_to(
+        &mut self,
+        control1: Vec2<f32>,
+        control2: Vec2<f32>,
+        point: Vec2<f32>,
+    ) -> &mut Self {
+        self.ensure_subpath(control1);
+        self.commands
+            .push(PathCommand::CubicTo(control1, control2, point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Adds an arc of a circle from `start_angle` to `end_angle`, in radians, with a line to the
+    /// start of the arc if there's already a subpath.
+    ///
+    /// Angles start at the right of the circle and grow clockwise on the screen, so an arc
+    /// from 0 to π/2 goes from the right to the bottom of the circle.
+    pub fn arc(
+        &mut self,
+        center: Vec2<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    ) -> &mut Self {
+        let point_at = |angle: f32| center + Vec2::new(angle.cos(), angle.sin()) * radius;
+        self.line_to(point_at(start_angle));
+
+        // Every cubic covers at most a quarter of the circle, which keeps it within 0.03% of it.
+        let sweep = end_angle - start_angle;
+        let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / segments as f32;
+        let handle = radius * 4.0 / 3.0 * (step / 4.0).tan();
+        for i in 0..segments {
+            let from = (i as f32).mul_add(step, start_angle);
+            let to = from + step;
+            let tangent = |angle: f32| Vec2::new(-angle.sin(), angle.cos()) * handle;
+            self.cubic_to(
+                point_at(from) + tangent(from),
+                point_at(to) - tangent(to),
+                point_at(to),
+            );
+        }
+        self
+    }
+
+    /// Closes the current subpath with a line back to its' start.
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.commands.push(PathCommand::Close);
+            self.current = Some(self.start);
+        }
+        self
+    }
+
+    /// Transforms every point of the path by a 2D homogeneous matrix.
+    pub fn transform(&mut self, mat: &Mat3<f32>) -> &mut Self {
+        let apply = |point: Vec2<f32>| (*mat * Vec3::new(point.x, point.y, 1.0)).xy();
+        for command in &mut self.commands {
+            *command = match *command {
+                PathCommand::MoveTo(point) => PathCommand::MoveTo(apply(point)),
+                PathCommand::LineTo(point) => PathCommand::LineTo(apply(point)),
+                PathCommand::QuadTo(control, point) => {
+                    PathCommand::QuadTo(apply(control), apply(point))
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    PathCommand::CubicTo(apply(control1), apply(control2), apply(point))
+                }
+                PathCommand::Close => PathCommand::Close,
+            };
+        }
+        self.start = apply(self.start);
+        self.current = self.current.map(apply);
+        self
+    }
+
+    /// Flattens the curves of the path into lines, at most [`TOLERANCE`] pixels away from them.
+    #[must_use]
+    pub fn flatten(&self) -> Vec<Contour> {
+        fn push(points: &mut Vec<Vec2<f32>>, last: Vec2<f32>, point: Vec2<f32>) {
+            if points.is_empty() {
+                points.push(last);
+            }
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+
+        let mut contours = Vec::new();
+        let mut finish = |points: &mut Vec<Vec2<f32>>, closed: bool| {
+            if closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            if !points.is_empty() {
+                contours.push(Contour {
+                    points: std::mem::take(points),
+                    closed,
+                });
+            }
+        };
+
+        let mut points = Vec::new();
+        let (mut start, mut last) = (Vec2::zero(), Vec2::zero());
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    finish(&mut points, false);
+                    points.push(point);
+                    start = point;
+                    last = point;
+                }
+                PathCommand::LineTo(point) => {
+                    push(&mut points, last, point);
+                    last = point;
+                }
+                PathCommand::QuadTo(control, point) => {
+                    let lines = curve_lines((last - control * 2.0 + point).magnitude() / 4.0);
+                    for i in 1..=lines {
+                        let t = i as f32 / lines as f32;
+                        let u = 1.0 - t;
+                        let at = last * (u * u) + control * (2.0 * u * t) + point * (t * t);
+                        push(&mut points, last, at);
+                    }
+                    last = point;
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    let bend = (last - control1 * 2.0 + control2)
+                        .magnitude()
+                        .max((control1 - control2 * 2.0 + point).magnitude());
+                    let lines = curve_lines(bend * 0.75);
+                    for i in 1..=lines {
+                        let t = i as f32 / lines as f32;
+                        let u = 1.0 - t;
+                        let at = last * (u * u * u)
+                            + control1 * (3.0 * u * u * t)
+                            + control2 * (3.0 * u * t * t)
+                            + point * (t * t * t);
+                        push(&mut points, last, at);
+                    }
+                    last = point;
+                }
+                PathCommand::Close => {
+                    finish(&mut points, true);
+                    last = start;
+                }
+            }
+        }
+        finish(&mut points, false);
+        contours
+    }
+
+    /// Returns the outline of a stroke along the path, as a path to fill with
+    /// [`FillRule::NonZero`].
+    #[must_use]
+    pub fn stroke(&self, stroke: &Stroke) -> Self {
+        let mut outline = Self::new();
+        let half = stroke.width / 2.0;
+        if half <= 0.0 {
+            return outline;
+        }
+
+        for contour in self.flatten() {
+            let points = &contour.points;
+            if let [point] = points[..] {
+                outline.add_cap(point, Vec2::unit_x(), half, stroke.cap);
+                outline.add_cap(point, -Vec2::unit_x(), half, stroke.cap);
+                continue;
+            }
+
+            let count = if contour.closed && points.len() > 2 {
+                points.len()
+            } else {
+                points.len() - 1
+            };
+            let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+            for i in 0..count {
+                let (from, to) = segment(i);
+                let across = perpendicular((to - from).normalized()) * half;
+                outline.add_polygon(&[from + across, to + across, to - across, from - across]);
+            }
+
+            for i in 1..count {
+                outline.add_join(segment(i - 1), segment(i), half, stroke);
+            }
+            if count == points.len() {
+                outline.add_join(segment(count - 1), segment(0), half, stroke);
+            } else {
+                let (first, second) = segment(0);
+                let (before, last) = segment(count - 1);
+                outline.add_cap(first, (first - second).normalized(), half, stroke.cap);
+                outline.add_cap(last, (last - before).normalized(), half, stroke.cap);
+            }
+        }
+        outline
+    }
+
+    /// Adds a closed polygon, wound clockwise, so overlapping polygons never cancel out.
+    fn add_polygon(&mut self, points: &[Vec2<f32>]) {
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x.mul_add(b.y, -(b.x * a.y)))
+            .sum();
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let mut points = points.to_vec();
+        if area < 0.0 {
+            points.reverse();
+        }
+        self.move_to(points[0]);
+        for &point in &points[1..] {
+            self.line_to(point);
+        }
+        self.close();
+    }
+
+    /// Adds the join between two segments meeting at the end of `before`.
+    fn add_join(
+        &mut self,
+        before: (Vec2<f32>, Vec2<f32>),
+        after: (Vec2<f32>, Vec2<f32>),
+        half: f32,
+        stroke: &Stroke,
+    ) {
+        let point = before.1;
+        let (incoming, outgoing) = (
+            (before.1 - before.0).normalized(),
+            (after.1 - after.0).normalized(),
+        );
+        let cross = incoming.x.mul_add(outgoing.y, -(incoming.y * outgoing.x));
+        if cross.abs() < 1e-6 && incoming.dot(outgoing) > 0.0 {
+            return;
+        }
+
+        // The join fills the gap on the outer side of the turn.
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let (normal_in, normal_out) = (
+            perpendicular(incoming) * side,
+            perpendicular(outgoing) * side,
+        );
+        let (a, b) = (point + normal_in * half, point + normal_out * half);
+        match stroke.join {
+            LineJoin::Round => self.add_circle(point, half),
+            LineJoin::Bevel => self.add_polygon(&[point, a, b]),
+            LineJoin::Miter => {
+                // The miter's length over the half width is 1 / cos(θ / 2) = 2 / |sum|.
+                let sum = normal_in + normal_out;
+                let length_squared = sum.magnitude_squared();
+                if length_squared > 1e-6 && 2.0 / length_squared.sqrt() <= stroke.miter_limit {
+                    let tip = point + sum * (2.0 * half / length_squared);
+                    self.add_polygon(&[point, a, tip, b]);
+                } else {
+                    self.add_polygon(&[point, a, b]);
+                }
+            }
+        }
+    }
+
+    /// Adds a cap to the end of a stroke at `point`, facing `direction`.
+    fn add_cap(&mut self, point: Vec2<f32>, direction: Vec2<f32>, half: f32, cap: LineCap) {
+        match cap {
+            LineCap::Butt => {}
+            LineCap::Round => self.add_circle(point, half),
+            LineCap::Square => {
+                let (along, across) = (direction * half, perpendicular(direction) * half);
+                self.add_polygon(&[
+                    point + across,
+                    point + along + across,
+                    point + along - across,
+                    point - across,
+                ]);
+            }
+        }
+    }
+
+    fn add_circle(&mut self, center: Vec2<f32>, radius: f32) {
+        self.current = None;
+        self.arc(center, radius, 0.0, PI * 2.0).close();
+    }
+}
+
+/// Returns `direction` rotated by a quarter turn clockwise on the screen.
+const fn perpendicular(direction: Vec2<f32>) -> Vec2<f32> {
+    Vec2::new(-direction.y, direction.x)
+}
+
+/// Returns the amount of lines a curve has to be flattened into, with `bend` being the
+/// largest distance between its' control points and their neighbours' midpoint.
+fn curve_lines(bend: f32) -> usize {
+    ((bend / TOLERANCE).sqrt().ceil() as usize).clamp(1, MAX_CURVE_LINES)
+}
+
+/// How the inside of a path is decided from the amount of times its' edges wind around a point.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Points are inside if the edges wind around them at all.
+    #[default]
+    NonZero,
+    /// Points are inside if the edges wind around them an odd amount of times, which leaves
+    /// holes where subpaths overlap.
+    EvenOdd,
+}
+
+impl FillRule {
+    const fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// The shape of the corners of a stroke.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corners, beveled past [`Stroke::miter_limit`].
+    #[default]
+    Miter,
+    /// Round corners.
+    Round,
+    /// Cut off corners.
+    Bevel,
+}
+
+/// The shape of the ends of an open stroke.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end points.
+    #[default]
+    Butt,
+    /// Ends with a half circle past the end points.
+    Round,
+    /// Ends with half a square past the end points.
+    Square,
+}
+
+/// The style of a stroke.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    /// The width in pixels.
+    pub width: f32,
+    /// The shape of the corners.
+    pub join: LineJoin,
+    /// The shape of the ends of open subpaths.
+    pub cap: LineCap,
+    /// The longest a miter can be, relative to half the width, before it's beveled.
+    pub miter_limit: f32,
+}
+
+impl Stroke {
+    /// Creates a new [`Stroke`] of `width`, with miter joins and butt caps.
+    #[must_use]
+    pub const fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// A color at an offset of a gradient.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    /// The offset along the gradient, from 0 to 1.
+    pub offset: f32,
+    /// The color.
+    pub color: Color,
+}
+
+impl GradientStop {
+    /// Creates a new [`GradientStop`].
+    #[must_use]
+    pub const fn new(offset: f32, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+/// What a path is filled with.
+///
+/// Gradients keep the colors of their first and last stops past their ends, and their stops
+/// must be sorted by offset.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    /// A single color.
+    Solid(Color),
+    /// Colors along the line from `start` to `end`.
+    LinearGradient {
+        /// The position of offset 0.
+        start: Vec2<f32>,
+        /// The position of offset 1.
+        end: Vec2<f32>,
+        /// The colors.
+        stops: Vec<GradientStop>,
+    },
+    /// Colors by the distance from `center`, reaching offset 1 at `radius`.
+    RadialGradient {
+        /// The position of offset 0.
+        center: Vec2<f32>,
+        /// The distance of offset 1.
+        radius: f32,
+        /// The colors.
+        stops: Vec<GradientStop>,
+    },
+}
+
+impl Paint {
+    /// Returns the color of the paint at a point.
+    #[must_use]
+    pub fn color_at(&self, point: Vec2<f32>) -> Color {
+        match self {
+            Self::Solid(color) => *color,
+            Self::LinearGradient { start, end, stops } => {
+                let axis = *end - *start;
+                let length_squared = axis.magnitude_squared().max(f32::EPSILON);
+                gradient_color(stops, (point - *start).dot(axis) / length_squared)
+            }
+            Self::RadialGradient {
+                center,
+                radius,
+                stops,
+            } => gradient_color(stops, point.distance(*center) / radius.max(f32::EPSILON)),
+        }
+    }
+}
+
+/// Returns the color of a gradient's stops at `offset`.
+fn gradient_color(stops: &[GradientStop], offset: f32) -> Color {
+    let after = stops.partition_point(|stop| stop.offset <= offset);
+    match (
+        after.checked_sub(1).map(|i| stops[i]),
+        stops.get(after).copied(),
+    ) {
+        (Some(before), Some(after)) => {
+            let t = (offset - before.offset) / (after.offset - before.offset);
+            let mix = |a: u8, b: u8| {
+                (f32::from(b) - f32::from(a))
+                    .mul_add(t, f32::from(a))
+                    .round() as u8
+            };
+            Color::new(
+                mix(before.color.r, after.color.r),
+                mix(before.color.g, after.color.g),
+                mix(before.color.b, after.color.b),
+                mix(before.color.a, after.color.a),
+            )
+        }
+        (Some(stop), None) | (None, Some(stop)) => stop.color,
+        (None, None) => Color::TRANSPARENT,
+    }
+}
+
+/// A non-horizontal line of a flattened path, from its' top to its' bottom.
+struct Edge {
+    top: Vec2<f32>,
+    bottom: Vec2<f32>,
+    /// 1 if the line goes down, -1 if it goes up.
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        t.mul_add(self.bottom.x - self.top.x, self.top.x)
+    }
+}
+
+/// Returns the edges of every contour, closing the open ones.
+fn edges(contours: &[Contour]) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for contour in contours {
+        let points = &contour.points;
+        for (i, &from) in points.iter().enumerate() {
+            let to = points[(i + 1) % points.len()];
+            if (from.y - to.y).abs() < f32::EPSILON || !(from.y.is_finite() && to.y.is_finite()) {
+                continue;
+            }
+            edges.push(if from.y < to.y {
+                Edge {
+                    top: from,
+                    bottom: to,
+                    winding: 1,
+                }
+            } else {
+                Edge {
+                    top: to,
+                    bottom: from,
+                    winding: -1,
+                }
+            });
+        }
+    }
+    edges
+}
+
+/// Adds `weight` times the part of every pixel covered by the span from `left` to `right`.
+fn cover(coverage: &mut [f32], left: f32, right: f32, weight: f32) {
+    let width = coverage.len() as f32;
+    let (left, right) = (left.clamp(0.0, width), right.clamp(0.0, width));
+    if left >= right {
+        return;
+    }
+
+    let (first, last) = (left as usize, right as usize);
+    if first == last {
+        coverage[first] = (right - left).mul_add(weight, coverage[first]);
+        return;
+    }
+    coverage[first] = (first as f32 + 1.0 - left).mul_add(weight, coverage[first]);
+    for pixel in &mut coverage[first + 1..last] {
+        *pixel += weight;
+    }
+    if let Some(pixel) = coverage.get_mut(last) {
+        *pixel = (right - last as f32).mul_add(weight, *pixel);
+    }
+}
+
+/// Fills a path, anti-aliased, blending the paint over the surface.
+pub fn fill_path(surface: &mut impl DrawTarget, path: &Path, paint: &Paint, rule: FillRule) {
+    let mut surface = surface.as_view();
+    let edges = edges(&path.flatten());
+    let (top, bottom) = edges
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(top, bottom), edge| {
+            (top.min(edge.top.y), bottom.max(edge.bottom.y))
+        });
+    if edges.is_empty() || surface.width() == 0 {
+        return;
+    }
+
+    let rows =
+        top.floor().max(0.0) as usize..(bottom.ceil().max(0.0) as usize).min(surface.height());
+    let mut coverage = vec![0.0; surface.width()];
+    let mut row_edges = Vec::new();
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for y in rows {
+        let (row_top, row_bottom) = (y as f32, y as f32 + 1.0);
+        row_edges.clear();
+        row_edges.extend(
+            edges
+                .iter()
+                .filter(|edge| edge.top.y < row_bottom && edge.bottom.y > row_top),
+        );
+        if row_edges.is_empty() {
+            continue;
+        }
+
+        coverage.fill(0.0);
+        for sample in 0..SUBSAMPLES {
+            let scanline = row_top + (sample as f32 + 0.5) / SUBSAMPLES as f32;
+            crossings.clear();
+            crossings.extend(
+                row_edges
+                    .iter()
+                    .filter(|edge| edge.top.y <= scanline && scanline < edge.bottom.y)
+                    .map(|edge| (edge.x_at(scanline), edge.winding)),
+            );
+            crossings.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if rule.is_inside(winding) {
+                    cover(&mut coverage, pair[0].0, pair[1].0, 1.0 / SUBSAMPLES as f32);
+                }
+            }
+        }
+
+        for (x, &covered) in coverage.iter().enumerate() {
+            if covered <= 0.0 {
+                continue;
+            }
+            let mut color = paint.color_at(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+            color.a = (f32::from(color.a) * covered.min(1.0)).round() as u8;
+            surface.draw_pixel(x as i32, y as i32, color, true);
+        }
+    }
+}
+
+/// Strokes a path, anti-aliased, blending the paint over the surface.
+pub fn stroke_path(surface: &mut impl DrawTarget, path: &Path, paint: &Paint, stroke: &Stroke) {
+    fill_path(surface, &path.stroke(stroke), paint, FillRule::NonZero);
+}
@@ -0,0 +1,553 @@
+//! This is used to build worlds out of blocks, meshed into a record per chunk.
+//!
+//! Only the faces between a block and air are meshed, including the faces between
+//! neighbouring chunks, and faces next to each other with the same texture are merged
+//! into larger quads. Changing blocks marks their chunk dirty, and [`VoxelWorld::remesh`]
+//! re-records a limited amount of dirty chunks per call.
+//!
+//! Example:
+//! ```rs
+//! fn textures(block: Block, face: Face) -> u32 {
+//!     match (block, face) {
+//!         (GRASS, Face::Up) => 0,
+//!         (GRASS, Face::Down) | (DIRT, _) => 2,
+//!         (GRASS, _) => 1,
+//!         _ => 3,
+//!     }
+//! }
+//!
+//! let mut world = VoxelWorld::new(Mesher::new(Vec2::new(4, 4), textures));
+//! world.set_block(Vec3::new(0, 0, 0), GRASS);
+//!
+//! let mut buffer = GpuCommandBuffer::new();
+//! buffer.bind_texture(atlas);
+//! world.remesh(&mut buffer, 4);
+//! world.draw(&mut buffer, None);
+//! ```
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_sign_loss)]
+use std::collections::HashMap;
+
+use crate::{
+    Aabb, Vec2, Vec3,
+    camera::Frustum,
+    gpu::{GpuCommandBuffer, RecordId},
+    mesh::Mesh,
+};
+
+/// A block type, where [`AIR`] is empty and every other block is opaque.
+pub type Block = u16;
+
+/// The empty block.
+pub const AIR: Block = 0;
+/// The amount of blocks along every side of a chunk.
+pub const CHUNK_SIZE: usize = 16;
+/// The amount of blocks in a chunk.
+pub const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// A side of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Face {
+    /// Facing positive X.
+    East,
+    /// Facing negative X.
+    West,
+    /// Facing positive Y.
+    Up,
+    /// Facing negative Y.
+    Down,
+    /// Facing positive Z.
+    South,
+    /// Facing negative Z.
+    North,
+}
+
+impl Face {
+    /// Every face.
+    pub const ALL: [Self; 6] = [
+        Self::East,
+        Self::West,
+        Self::Up,
+        Self::Down,
+        Self::South,
+        Self::North,
+    ];
+
+    /// Returns the axis the face is along, 0 for X, 1 for Y and 2 for Z.
+    #[must_use]
+    pub const fn axis(&self) -> usize {
+        match self {
+            Self::East | Self::West => 0,
+            Self::Up | Self::Down => 1,
+            Self::South | Self::North => 2,
+        }
+    }
+
+    /// Returns `true` if the face points towards the positive end of its' axis.
+    #[must_use]
+    pub const fn is_positive(&self) -> bool {
+        matches!(self, Self::East | Self::Up | Self::South)
+    }
+
+    /// Returns the direction the face points to.
+    #[must_use]
+    pub fn normal(&self) -> Vec3<i32> {
+        let mut normal = Vec3::zero();
+        normal[self.axis()] = if self.is_positive() { 1 } else { -1 };
+        normal
+    }
+}
+
+/// A cube of [`CHUNK_SIZE`] blocks on every side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    blocks: Box<[Block]>,
+}
+
+impl Chunk {
+    /// Creates a new [`Chunk`] full of air.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::filled(AIR)
+    }
+
+    /// Creates a new [`Chunk`] full of `block`.
+    #[must_use]
+    pub fn filled(block: Block) -> Self {
+        Self {
+            blocks: vec![block; CHUNK_VOLUME].into_boxed_slice(),
+        }
+    }
+
+    const fn index(x: usize, y: usize, z: usize) -> usize {
+        (y * CHUNK_SIZE + z) * CHUNK_SIZE + x
+    }
+
+    /// Returns the block at a position in the chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the chunk.
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Block {
+        assert!(x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE);
+        self.blocks[Self::index(x, y, z)]
+    }
+
+    /// Replaces the block at a position in the chunk, returning the previous block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the chunk.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block: Block) -> Block {
+        assert!(x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE);
+        std::mem::replace(&mut self.blocks[Self::index(x, y, z)], block)
+    }
+
+    /// Returns `true` if the chunk is full of air.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&block| block == AIR)
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds the meshes of chunks, with the texture of every block face in a grid atlas.
+#[derive(Clone, Copy, Debug)]
+pub struct Mesher {
+    /// The amount of tiles across and down the atlas.
+    pub atlas_size: Vec2<u32>,
+    /// Returns the tile of a block face, counting from the top left tile, row by row.
+    pub textures: fn(Block, Face) -> u32,
+    /// Whether to merge neighbouring faces with the same tile.
+    ///
+    /// Merged faces stretch their tile over the whole quad, as an atlas can't repeat a
+    /// single tile, so turn this off for tiles that need to stay one block big.
+    pub greedy: bool,
+}
+
+impl Mesher {
+    /// Creates a new greedy [`Mesher`].
+    #[must_use]
+    pub const fn new(atlas_size: Vec2<u32>, textures: fn(Block, Face) -> u32) -> Self {
+        Self {
+            atlas_size,
+            textures,
+            greedy: true,
+        }
+    }
+
+    /// Returns the top left and bottom right UVs of a tile.
+    #[must_use]
+    pub fn tile_uvs(&self, tile: u32) -> (Vec2<f32>, Vec2<f32>) {
+        let size = self.atlas_size.map(|size| size.max(1));
+        let (column, row) = (tile % size.x, tile / size.x);
+        let tile_size = Vec2::one() / size.as_::<f32>();
+        let min = Vec2::new(column as f32, row as f32) * tile_size;
+        (min, min + tile_size)
+    }
+
+    /// Meshes a chunk at `origin` in the world, with `block` returning the blocks around it
+    /// from a position relative to the chunk.
+    #[must_use]
+    pub fn mesh(
+        &self,
+        chunk: &Chunk,
+        origin: Vec3<f32>,
+        block: impl Fn(Vec3<i32>) -> Block,
+    ) -> Mesh {
+        let mut mesh = Mesh {
+            normals: Some(Vec::new()),
+            ..Mesh::new()
+        };
+        let block_at = |p: Vec3<i32>| {
+            if p.iter().all(|&c| (0..CHUNK_SIZE as i32).contains(&c)) {
+                chunk.get(p.x as usize, p.y as usize, p.z as usize)
+            } else {
+                block(p)
+            }
+        };
+
+        let mut mask = vec![None; CHUNK_SIZE * CHUNK_SIZE];
+        for face in Face::ALL {
+            let axis = face.axis();
+            let (u, v) = tangent_axes(axis);
+            let normal = face.normal();
+
+            for slice in 0..CHUNK_SIZE {
+                for b in 0..CHUNK_SIZE {
+                    for a in 0..CHUNK_SIZE {
+                        let mut p = Vec3::zero();
+                        p[axis] = slice as i32;
+                        p[u] = a as i32;
+                        p[v] = b as i32;
+                        let block = chunk.get(p.x as usize, p.y as usize, p.z as usize);
+                        mask[b * CHUNK_SIZE + a] = (block != AIR && block_at(p + normal) == AIR)
+                            .then(|| (self.textures)(block, face));
+                    }
+                }
+                self.mesh_slice(&mut mesh, &mut mask, face, slice, origin);
+            }
+        }
+        mesh
+    }
+
+    /// Turns the visible faces of a slice into quads, clearing the mask.
+    fn mesh_slice(
+        &self,
+        mesh: &mut Mesh,
+        mask: &mut [Option<u32>],
+        face: Face,
+        slice: usize,
+        origin: Vec3<f32>,
+    ) {
+        for b in 0..CHUNK_SIZE {
+            let mut a = 0;
+            while a < CHUNK_SIZE {
+                let Some(tile) = mask[b * CHUNK_SIZE + a] else {
+                    a += 1;
+                    continue;
+                };
+
+                let (mut width, mut height) = (1, 1);
+                if self.greedy {
+                    while a + width < CHUNK_SIZE && mask[b * CHUNK_SIZE + a + width] == Some(tile) {
+                        width += 1;
+                    }
+                    while b + height < CHUNK_SIZE
+                        && mask[(b + height) * CHUNK_SIZE + a..][..width]
+                            .iter()
+                            .all(|&other| other == Some(tile))
+                    {
+                        height += 1;
+                    }
+                }
+                for row in b..b + height {
+                    mask[row * CHUNK_SIZE + a..][..width].fill(None);
+                }
+
+                self.push_quad(mesh, face, slice, [a, b, width, height], tile, origin);
+                a += width;
+            }
+        }
+    }
+
+    fn push_quad(
+        &self,
+        mesh: &mut Mesh,
+        face: Face,
+        slice: usize,
+        [a, b, width, height]: [usize; 4],
+        tile: u32,
+        origin: Vec3<f32>,
+    ) {
+        let axis = face.axis();
+        let (u, v) = tangent_axes(axis);
+        let (uv_min, uv_max) = self.tile_uvs(tile);
+        let normal = face.normal().as_::<f32>();
+
+        let first = mesh.positions.len() as u32;
+        for (du, dv) in [(0, 0), (width, 0), (width, height), (0, height)] {
+            let mut position = Vec3::zero();
+            position[axis] = (slice + usize::from(face.is_positive())) as f32;
+            position[u] = (a + du) as f32;
+            position[v] = (b + dv) as f32;
+
+            // The tile's V goes down, and the world's Y goes up.
+            let uv = Vec2::new(
+                if du == 0 { uv_min.x } else { uv_max.x },
+                if (dv == 0) == (v == 1) {
+                    uv_max.y
+                } else {
+                    uv_min.y
+                },
+            );
+            mesh.push_lit_vertex(origin + position, uv, normal);
+        }
+
+        // Wind the quad counter-clockwise as seen from the side the face points to.
+        if face.is_positive() == (u == (axis + 1) % 3) {
+            mesh.push_triangle(first, first + 1, first + 2)
+                .push_triangle(first, first + 2, first + 3);
+        } else {
+            mesh.push_triangle(first, first + 2, first + 1)
+                .push_triangle(first, first + 3, first + 2);
+        }
+    }
+}
+
+/// Returns the two axes along a face of `axis`, Y always being the second for the sides.
+const fn tangent_axes(axis: usize) -> (usize, usize) {
+    match axis {
+        0 => (2, 1),
+        1 => (0, 2),
+        _ => (0, 1),
+    }
+}
+
+/// A chunk in a [`VoxelWorld`], with its' record.
+#[derive(Clone, Debug)]
+struct ChunkEntry {
+    chunk: Chunk,
+    record: Option<RecordId>,
+    dirty: bool,
+}
+
+/// A world of chunks, each with its' own record.
+#[derive(Clone, Debug)]
+pub struct VoxelWorld {
+    /// The mesher of the chunks.
+    pub mesher: Mesher,
+    chunks: HashMap<Vec3<i32>, ChunkEntry>,
+    removed: Vec<RecordId>,
+}
+
+impl VoxelWorld {
+    /// Creates a new empty [`VoxelWorld`].
+    #[must_use]
+    pub fn new(mesher: Mesher) -> Self {
+        Self {
+            mesher,
+            chunks: HashMap::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    /// Returns the chunk containing a block, and the block's position in it.
+    #[must_use]
+    pub fn split_position(position: Vec3<i32>) -> (Vec3<i32>, Vec3<usize>) {
+        let size = CHUNK_SIZE as i32;
+        (
+            position.map(|c| c.div_euclid(size)),
+            position.map(|c| c.rem_euclid(size) as usize),
+        )
+    }
+
+    /// Returns the amount of chunks.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns `true` if the world has no chunks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns the amount of chunks waiting to be remeshed.
+    #[must_use]
+    pub fn dirty_count(&self) -> usize {
+        self.chunks.values().filter(|entry| entry.dirty).count()
+    }
+
+    /// Returns a chunk.
+    #[must_use]
+    pub fn chunk(&self, position: Vec3<i32>) -> Option<&Chunk> {
+        self.chunks.get(&position).map(|entry| &entry.chunk)
+    }
+
+    /// Returns the record of a chunk, if it was meshed and has any faces.
+    #[must_use]
+    pub fn record(&self, position: Vec3<i32>) -> Option<RecordId> {
+        self.chunks.get(&position)?.record
+    }
+
+    /// Inserts or replaces a chunk, marking it and its' neighbours dirty.
+    pub fn insert_chunk(&mut self, position: Vec3<i32>, chunk: Chunk) {
+        let entry = self.chunks.entry(position).or_insert_with(|| ChunkEntry {
+            chunk: Chunk::new(),
+            record: None,
+            dirty: true,
+        });
+        entry.chunk = chunk;
+        entry.dirty = true;
+        self.mark_neighbours_dirty(position);
+    }
+
+    /// Removes a chunk, marking its' neighbours dirty. The record is deleted on the next
+    /// [`VoxelWorld::remesh`].
+    pub fn remove_chunk(&mut self, position: Vec3<i32>) -> Option<Chunk> {
+        let entry = self.chunks.remove(&position)?;
+        self.removed.extend(entry.record);
+        self.mark_neighbours_dirty(position);
+        Some(entry.chunk)
+    }
+
+    /// Returns a chunk mutably, marking it and its' neighbours dirty.
+    pub fn chunk_mut(&mut self, position: Vec3<i32>) -> Option<&mut Chunk> {
+        self.chunks.contains_key(&position).then_some(())?;
+        self.mark_neighbours_dirty(position);
+        let entry = self.chunks.get_mut(&position)?;
+        entry.dirty = true;
+        Some(&mut entry.chunk)
+    }
+
+    fn mark_neighbours_dirty(&mut self, position: Vec3<i32>) {
+        for face in Face::ALL {
+            if let Some(entry) = self.chunks.get_mut(&(position + face.normal())) {
+                entry.dirty = true;
+            }
+        }
+    }
+
+    /// Returns the block at a position in the world, [`AIR`] in missing chunks.
+    #[must_use]
+    pub fn block(&self, position: Vec3<i32>) -> Block {
+        let (chunk, local) = Self::split_position(position);
+        self.chunk(chunk)
+            .map_or(AIR, |chunk| chunk.get(local.x, local.y, local.z))
+    }
+
+    /// Replaces the block at a position in the world, returning the previous block.
+    ///
+    /// Missing chunks are created, and the chunk is marked dirty along with the neighbours
+    /// the block touches.
+    pub fn set_block(&mut self, position: Vec3<i32>, block: Block) -> Block {
+        let (chunk_position, local) = Self::split_position(position);
+        if block == AIR && !self.chunks.contains_key(&chunk_position) {
+            return AIR;
+        }
+
+        let entry = self
+            .chunks
+            .entry(chunk_position)
+            .or_insert_with(|| ChunkEntry {
+                chunk: Chunk::new(),
+                record: None,
+                dirty: true,
+            });
+        let previous = entry.chunk.set(local.x, local.y, local.z, block);
+        if previous == block {
+            return previous;
+        }
+        entry.dirty = true;
+
+        for face in Face::ALL {
+            let edge = if face.is_positive() {
+                CHUNK_SIZE - 1
+            } else {
+                0
+            };
+            if local[face.axis()] == edge
+                && let Some(entry) = self.chunks.get_mut(&(chunk_position + face.normal()))
+            {
+                entry.dirty = true;
+            }
+        }
+        previous
+    }
+
+    /// Meshes a chunk, culling the faces against its' neighbours.
+    #[must_use]
+    pub fn mesh_chunk(&self, position: Vec3<i32>) -> Option<Mesh> {
+        let chunk = self.chunk(position)?;
+        let origin = position * CHUNK_SIZE as i32;
+        Some(
+            self.mesher
+                .mesh(chunk, origin.as_::<f32>(), |p| self.block(origin + p)),
+        )
+    }
+
+    /// Re-records up to `limit` dirty chunks, deleting the records of removed and
+    /// emptied chunks. Returns the amount of chunks remeshed.
+    pub fn remesh(&mut self, buffer: &mut GpuCommandBuffer, limit: usize) -> usize {
+        for record in self.removed.drain(..) {
+            buffer.delete_record(record);
+        }
+
+        let mut dirty: Vec<Vec3<i32>> = self
+            .chunks
+            .iter()
+            .filter(|(_, entry)| entry.dirty)
+            .map(|(&position, _)| position)
+            .collect();
+        dirty.sort_unstable_by_key(|p| (p.y, p.z, p.x));
+        dirty.truncate(limit);
+
+        for &position in &dirty {
+            let mesh = self.mesh_chunk(position).unwrap_or_default();
+            let Some(entry) = self.chunks.get_mut(&position) else {
+                continue;
+            };
+            entry.dirty = false;
+            entry.record = if mesh.is_empty() {
+                if let Some(record) = entry.record {
+                    buffer.delete_record(record);
+                }
+                None
+            } else {
+                Some(mesh.record_over(buffer, entry.record))
+            };
+        }
+        dirty.len()
+    }
+
+    /// Draws the recorded chunks, skipping the ones outside of `frustum` if there's one.
+    /// Returns the amount of chunks drawn.
+    pub fn draw(&self, buffer: &mut GpuCommandBuffer, frustum: Option<&Frustum>) -> usize {
+        let mut drawn = 0;
+        for (&position, entry) in &self.chunks {
+            let Some(record) = entry.record else {
+                continue;
+            };
+            let min = (position * CHUNK_SIZE as i32).as_::<f32>();
+            let bounds = Aabb {
+                min,
+                max: min + CHUNK_SIZE as f32,
+            };
+            if frustum.is_none_or(|frustum| frustum.intersects_aabb(&bounds)) {
+                buffer.draw_recorded(record);
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+}
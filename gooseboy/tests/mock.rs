@@ -0,0 +1,197 @@
+//! Native tests of the mock host, run with `cargo test -p gooseboy --features mock-host`.
+use gooseboy::{
+    audio::{Audio, AudioFormat},
+    camera::{get_camera_fov, get_camera_roll, set_camera_fov, set_camera_roll},
+    color::Color,
+    error::GooseboyError,
+    framebuffer::{
+        Surface, clear_framebuffer, get_framebuffer_height, get_framebuffer_surface_ref,
+        get_framebuffer_width, init_fb,
+    },
+    gpu::{
+        GB_GPU_RECORD_ID, GpuCommand, GpuCommandBuffer, GpuValidationError, PrimitiveType, Vertex,
+        gpu_read_value,
+    },
+    input::{get_key, grab_mouse, is_key_down, is_mouse_grabbed},
+    keys::{KEY_A, KEY_SPACE},
+    log,
+    mock::{AudioCall, with_host},
+    storage::{storage_clear, storage_read_slice, storage_read_value, storage_write_value},
+    system::{Permission, get_platform_name, get_time_nanos, has_permission},
+};
+
+// The framebuffer surface is global, so this is the only test that initializes it.
+#[test]
+fn framebuffer() {
+    with_host(|host| host.set_framebuffer_size(8, 4));
+    init_fb();
+    assert_eq!((get_framebuffer_width(), get_framebuffer_height()), (8, 4));
+
+    clear_framebuffer(Color::RED);
+    let rgba = &get_framebuffer_surface_ref().rgba;
+    assert_eq!(rgba.len(), 8 * 4 * 4);
+    assert!(
+        rgba.as_chunks::<4>()
+            .0
+            .iter()
+            .all(|p| *p == [255, 0, 0, 255])
+    );
+}
+
+#[test]
+fn surface_blit() {
+    let surface = Surface::new_empty(4, 4);
+    let src = [255; 2 * 2 * 4];
+    surface.blit_premultiplied_clipped(-1, -1, 2, 2, &src, false);
+
+    assert_eq!(surface.rgba[..4], [255; 4]);
+    assert_eq!(surface.rgba[4..8], [0; 4]);
+    assert_eq!(surface.rgba[16..20], [0; 4]);
+}
+
+#[test]
+fn input() {
+    with_host(|host| host.press_key(KEY_SPACE));
+    assert!(is_key_down(KEY_SPACE));
+    assert!(!is_key_down(KEY_A));
+    assert_eq!(get_key(), Some(KEY_SPACE));
+
+    with_host(|host| host.release_key(KEY_SPACE));
+    assert!(!is_key_down(KEY_SPACE));
+    assert_eq!(get_key(), None);
+
+    grab_mouse();
+    assert!(is_mouse_grabbed());
+}
+
+#[test]
+fn storage() {
+    storage_write_value(4, 0xDEAD_BEEF_u32);
+    assert_eq!(storage_read_value::<u32>(4), 0xDEAD_BEEF);
+    assert_eq!(
+        with_host(|host| host.storage[4..8].to_vec()),
+        0xDEAD_BEEF_u32.to_ne_bytes()
+    );
+
+    with_host(|host| host.revoke(Permission::StorageWrite));
+    storage_write_value(4, 1_u32);
+    storage_clear();
+    assert_eq!(storage_read_value::<u32>(4), 0xDEAD_BEEF);
+
+    with_host(|host| host.revoke(Permission::StorageRead));
+    assert_eq!(storage_read_slice(4, &mut [0; 4]), 0);
+}
+
+#[test]
+fn audio() {
+    let mut audio = Audio::new(vec![1, 2, 3, 4], 44100, AudioFormat::Mono8);
+    let mut instance = audio.play().expect("audio should play");
+    assert!(instance.is_playing());
+    instance.set_volume(0.5);
+    instance.stop();
+
+    with_host(|host| {
+        assert_eq!(
+            host.audio_calls,
+            [
+                AudioCall::Play {
+                    id: 0,
+                    data: vec![1, 2, 3, 4],
+                    sample_rate: 44100,
+                    format: AudioFormat::Mono8.repr(),
+                },
+                AudioCall::SetVolume { id: 0, volume: 0.5 },
+                AudioCall::Stop(0),
+            ]
+        );
+        host.max_playing_audio = 0;
+    });
+    assert!(audio.play().is_none());
+}
+
+#[test]
+fn system() {
+    with_host(|host| {
+        host.time_nanos = Some(1_000);
+        host.revoke(Permission::Audio);
+    });
+    assert_eq!(get_time_nanos(), 1_000);
+    assert_eq!(get_platform_name(), "mock");
+    assert!(has_permission(Permission::Console));
+    assert!(!has_permission(Permission::Audio));
+
+    log!("hello {}", 1);
+    assert_eq!(with_host(|host| host.logs.clone()), ["hello 1"]);
+}
+
+#[test]
+fn camera() {
+    set_camera_fov(90.0);
+    set_camera_roll(0.5);
+    assert_eq!(get_camera_fov(), 90.0);
+    assert_eq!(get_camera_roll(), 0.5);
+
+    with_host(|host| host.revoke(Permission::Gpu));
+    set_camera_fov(60.0);
+    set_camera_roll(1.0);
+    assert_eq!(get_camera_fov(), 0.0);
+    assert_eq!(get_camera_roll(), 0.0);
+    with_host(|host| assert_eq!((host.camera_fov, host.camera_roll), (90.0, 0.5)));
+}
+
+// The validator's ids are global, so this is the only test that uploads GPU commands.
+#[test]
+fn gpu() {
+    let point = |r: &mut GpuCommandBuffer| {
+        r.insert(&GpuCommand::EmitVertex(Vertex::new(
+            0.0, 0.0, 0.0, 0.0, 0.0,
+        )));
+    };
+
+    let mut buffer = GpuCommandBuffer::new();
+    let record = buffer.record(PrimitiveType::Points, point);
+    buffer.upload().expect("upload should succeed");
+
+    assert_eq!(with_host(|host| host.gpu_submissions.len()), 1);
+    assert_eq!(gpu_read_value::<u32>(GB_GPU_RECORD_ID), record.0);
+    assert_eq!(with_host(|host| host.gpu.record_count()), 1);
+
+    let mut delete = GpuCommandBuffer::new();
+    delete.delete_record(record);
+    delete.submit().expect("submit should succeed");
+    let mut draw = GpuCommandBuffer::new();
+    draw.draw_recorded(record);
+    assert_eq!(
+        draw.validate(),
+        Err(GpuValidationError::UnknownRecord {
+            index: 0,
+            id: record.0
+        })
+    );
+
+    // A record the validator never saw shifts every id the host gives out after it.
+    let mut hidden = GpuCommandBuffer::new();
+    hidden.record(PrimitiveType::Points, point);
+    with_host(|host| host.execute_gpu_commands(hidden.as_bytes())).expect("stream is valid");
+
+    let mut buffer = GpuCommandBuffer::new();
+    let predicted = buffer.record(PrimitiveType::Points, point);
+    assert!(matches!(
+        buffer.upload(),
+        Err(GooseboyError::GpuIdsOutOfSync)
+    ));
+    let actual = gpu_read_value::<u32>(GB_GPU_RECORD_ID);
+    assert_eq!(actual, predicted.0 + 1);
+
+    let mut buffer = GpuCommandBuffer::new();
+    let next = buffer.record(PrimitiveType::Points, point);
+    buffer.upload().expect("ids should be back in sync");
+    assert_eq!(next.0, actual + 1);
+
+    with_host(|host| host.revoke(Permission::Gpu));
+    buffer.clear();
+    buffer.insert(&GpuCommand::Push);
+    buffer.insert(&GpuCommand::Pop);
+    assert!(matches!(buffer.submit(), Err(GooseboyError::Unauthorized)));
+    assert_eq!(with_host(|host| host.gpu_submissions.len()), 4);
+}
@@ -0,0 +1,535 @@
+//! Used to define every import module declared in `gooseboy::bindings`.
+//!
+//! `usize`, pointers and `bool` are all passed as `i32` on `wasm32`.
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::needless_pass_by_value)]
+use gooseboy::{
+    camera::CameraTransform,
+    framebuffer::raster::{blit_rgba, clear_rgba, fill_rect_rgba},
+    system::Permission,
+};
+use wasmi::{Caller, Error, Extern, Linker, Memory};
+
+use crate::state::{HostState, MAX_PLAYING_AUDIO};
+
+type Ctx<'a> = Caller<'a, HostState>;
+
+const CONSOLE: i32 = Permission::Console as i32;
+const AUDIO: i32 = Permission::Audio as i32;
+const INPUT_KEYBOARD: i32 = Permission::InputKeyboard as i32;
+const INPUT_MOUSE: i32 = Permission::InputMouse as i32;
+const INPUT_MOUSE_POS: i32 = Permission::InputMousePos as i32;
+const INPUT_GRAB_MOUSE: i32 = Permission::InputGrabMouse as i32;
+const STORAGE_READ: i32 = Permission::StorageRead as i32;
+const STORAGE_WRITE: i32 = Permission::StorageWrite as i32;
+const GPU: i32 = Permission::Gpu as i32;
+
+fn memory(caller: &Ctx<'_>) -> Result<Memory, Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::new("crate does not export its memory"))
+}
+
+fn span(memory: &[u8], ptr: i32, len: usize) -> Result<std::ops::Range<usize>, Error> {
+    let start = ptr as u32 as usize;
+    let end = start
+        .checked_add(len)
+        .filter(|&end| end <= memory.len())
+        .ok_or_else(|| Error::new(format!("out of bounds access at {start:#x}+{len}")))?;
+    Ok(start..end)
+}
+
+fn read_bytes(caller: &Ctx<'_>, ptr: i32, len: i32) -> Result<Vec<u8>, Error> {
+    let memory = memory(caller)?;
+    let data = memory.data(caller);
+    Ok(data[span(data, ptr, len.max(0) as usize)?].to_vec())
+}
+
+fn write_bytes(caller: &mut Ctx<'_>, ptr: i32, bytes: &[u8]) -> Result<(), Error> {
+    let memory = memory(caller)?;
+    let data = memory.data_mut(caller);
+    let range = span(data, ptr, bytes.len())?;
+    data[range].copy_from_slice(bytes);
+    Ok(())
+}
+
+fn storage_span(state: &HostState, offset: i32, len: i32) -> Option<(usize, usize)> {
+    let offset = usize::try_from(offset).ok()?;
+    let len = usize::try_from(len).ok()?;
+    if offset > state.storage.len() {
+        return None;
+    }
+    Some((offset, len.min(state.storage.len() - offset)))
+}
+
+/// Defines every import module onto `linker`.
+///
+/// # Errors
+///
+/// This function will return an error if an import was defined twice.
+pub fn define(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    define_console(linker)?;
+    define_framebuffer(linker)?;
+    define_memory(linker)?;
+    define_input(linker)?;
+    define_audio(linker)?;
+    define_storage(linker)?;
+    define_system(linker)?;
+    define_camera(linker)?;
+    define_gpu(linker)?;
+    Ok(())
+}
+
+fn define_console(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "console",
+        "log",
+        |mut caller: Ctx<'_>, ptr: i32, len: i32| -> Result<(), Error> {
+            let text = String::from_utf8_lossy(&read_bytes(&caller, ptr, len)?).into_owned();
+            let state = caller.data_mut();
+            if state.check(CONSOLE) && !state.quiet {
+                println!("[{}] {text}", state.name);
+            }
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn define_framebuffer(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap("framebuffer", "get_framebuffer_width", |caller: Ctx<'_>| {
+        caller.data().width as i32
+    })?;
+    linker.func_wrap(
+        "framebuffer",
+        "get_framebuffer_height",
+        |caller: Ctx<'_>| caller.data().height as i32,
+    )?;
+    linker.func_wrap(
+        "framebuffer",
+        "clear_surface",
+        |mut caller: Ctx<'_>, ptr: i32, size: i32, color: i32| -> Result<(), Error> {
+            let memory = memory(&caller)?;
+            let data = memory.data_mut(&mut caller);
+            let range = span(data, ptr, size.max(0) as usize)?;
+            clear_rgba(&mut data[range], size as usize, color);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "framebuffer",
+        "blit_premultiplied_clipped",
+        |mut caller: Ctx<'_>,
+         dest_ptr: i32,
+         dest_w: i32,
+         dest_h: i32,
+         dest_x: i32,
+         dest_y: i32,
+         src_w: i32,
+         src_h: i32,
+         src_ptr: i32,
+         blend: i32|
+         -> Result<(), Error> {
+            let (dest_w, dest_h) = (dest_w as u32 as usize, dest_h as u32 as usize);
+            let (src_w, src_h) = (src_w as u32 as usize, src_h as u32 as usize);
+            let src = read_bytes(&caller, src_ptr, (src_w * src_h * 4) as i32)?;
+            let memory = memory(&caller)?;
+            let data = memory.data_mut(&mut caller);
+            let range = span(data, dest_ptr, dest_w * dest_h * 4)?;
+            blit_rgba(
+                &mut data[range],
+                dest_w,
+                dest_h,
+                dest_x,
+                dest_y,
+                &src,
+                src_w,
+                src_h,
+                blend != 0,
+            );
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "framebuffer",
+        "fill_rect_clipped",
+        |mut caller: Ctx<'_>,
+         dest_ptr: i32,
+         dest_w: i32,
+         dest_h: i32,
+         x: i32,
+         y: i32,
+         w: i32,
+         h: i32,
+         color: i32,
+         blend: i32|
+         -> Result<(), Error> {
+            let (dest_w, dest_h) = (dest_w as u32 as usize, dest_h as u32 as usize);
+            let (w, h) = (w as u32 as usize, h as u32 as usize);
+            let memory = memory(&caller)?;
+            let data = memory.data_mut(&mut caller);
+            let range = span(data, dest_ptr, dest_w * dest_h * 4)?;
+            fill_rect_rgba(
+                &mut data[range],
+                dest_w,
+                dest_h,
+                x,
+                y,
+                w,
+                h,
+                color,
+                blend != 0,
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn define_memory(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "memory",
+        "mem_fill",
+        |mut caller: Ctx<'_>, addr: i32, len: i32, value: i32| -> Result<(), Error> {
+            let memory = memory(&caller)?;
+            let data = memory.data_mut(&mut caller);
+            let range = span(data, addr, len.max(0) as usize)?;
+            data[range].fill(value as u8);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "memory",
+        "mem_copy",
+        |mut caller: Ctx<'_>, dst: i32, src: i32, len: i32| -> Result<(), Error> {
+            let memory = memory(&caller)?;
+            let data = memory.data_mut(&mut caller);
+            let len = len.max(0) as usize;
+            let src = span(data, src, len)?;
+            let dst = span(data, dst, len)?;
+            data.copy_within(src, dst.start);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn define_input(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap("input", "get_key_code", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(INPUT_KEYBOARD) {
+            state.input.key_code()
+        } else {
+            -1
+        }
+    })?;
+    linker.func_wrap("input", "get_key", |mut caller: Ctx<'_>, key: i32| {
+        let state = caller.data_mut();
+        i32::from(state.check(INPUT_KEYBOARD) && state.input.keys.contains(&key))
+    })?;
+    linker.func_wrap(
+        "input",
+        "get_mouse_button",
+        |mut caller: Ctx<'_>, btn: i32| {
+            let state = caller.data_mut();
+            i32::from(state.check(INPUT_MOUSE) && state.input.mouse_buttons.contains(&btn))
+        },
+    )?;
+    linker.func_wrap("input", "get_mouse_x", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(INPUT_MOUSE_POS) {
+            state.input.mouse_x
+        } else {
+            0
+        }
+    })?;
+    linker.func_wrap("input", "get_mouse_y", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(INPUT_MOUSE_POS) {
+            state.input.mouse_y
+        } else {
+            0
+        }
+    })?;
+    linker.func_wrap(
+        "input",
+        "get_mouse_accumulated_dx",
+        |mut caller: Ctx<'_>| {
+            let state = caller.data_mut();
+            if state.check(INPUT_MOUSE_POS) {
+                state.input.mouse_accumulated_dx
+            } else {
+                0.0
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "input",
+        "get_mouse_accumulated_dy",
+        |mut caller: Ctx<'_>| {
+            let state = caller.data_mut();
+            if state.check(INPUT_MOUSE_POS) {
+                state.input.mouse_accumulated_dy
+            } else {
+                0.0
+            }
+        },
+    )?;
+    linker.func_wrap("input", "is_mouse_grabbed", |caller: Ctx<'_>| {
+        i32::from(caller.data().input.mouse_grabbed)
+    })?;
+    linker.func_wrap("input", "grab_mouse", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(INPUT_GRAB_MOUSE) {
+            state.input.mouse_grabbed = true;
+        }
+    })?;
+    linker.func_wrap("input", "release_mouse", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(INPUT_GRAB_MOUSE) {
+            state.input.mouse_grabbed = false;
+        }
+    })?;
+    Ok(())
+}
+
+fn define_audio(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "audio",
+        "play_audio",
+        |mut caller: Ctx<'_>, ptr: i32, len: i32, _rate: i32, _format: i32| {
+            // Audio is never actually played, only validated and tracked.
+            read_bytes(&caller, ptr, len)?;
+            let state = caller.data_mut();
+            if !state.check(AUDIO) || state.playing_audio.len() >= MAX_PLAYING_AUDIO {
+                return Ok(-1i64);
+            }
+            let id = state.next_audio_id;
+            state.next_audio_id += 1;
+            state.playing_audio.insert(id);
+            Ok::<_, Error>(id)
+        },
+    )?;
+    linker.func_wrap("audio", "stop_audio", |mut caller: Ctx<'_>, id: i64| {
+        let state = caller.data_mut();
+        if state.check(AUDIO) {
+            state.playing_audio.remove(&id);
+        }
+    })?;
+    linker.func_wrap("audio", "stop_all_audio", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(AUDIO) {
+            state.playing_audio.clear();
+        }
+    })?;
+    linker.func_wrap(
+        "audio",
+        "set_audio_volume",
+        |mut caller: Ctx<'_>, _id: i64, _volume: f32| {
+            caller.data_mut().check(AUDIO);
+        },
+    )?;
+    linker.func_wrap(
+        "audio",
+        "set_audio_pitch",
+        |mut caller: Ctx<'_>, _id: i64, _pitch: f32| {
+            caller.data_mut().check(AUDIO);
+        },
+    )?;
+    linker.func_wrap(
+        "audio",
+        "is_audio_playing",
+        |mut caller: Ctx<'_>, id: i64| {
+            let state = caller.data_mut();
+            i32::from(state.check(AUDIO) && state.playing_audio.contains(&id))
+        },
+    )?;
+    Ok(())
+}
+
+fn define_storage(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "storage",
+        "storage_read",
+        |mut caller: Ctx<'_>, offset: i32, ptr: i32, len: i32| -> Result<i32, Error> {
+            let state = caller.data_mut();
+            if !state.check(STORAGE_READ) {
+                return Ok(0);
+            }
+            let Some((offset, len)) = storage_span(state, offset, len) else {
+                return Ok(0);
+            };
+            let bytes = state.storage[offset..offset + len].to_vec();
+            write_bytes(&mut caller, ptr, &bytes)?;
+            Ok(len as i32)
+        },
+    )?;
+    linker.func_wrap(
+        "storage",
+        "storage_write",
+        |mut caller: Ctx<'_>, offset: i32, ptr: i32, len: i32| -> Result<i32, Error> {
+            let bytes = read_bytes(&caller, ptr, len)?;
+            let state = caller.data_mut();
+            if !state.check(STORAGE_WRITE) {
+                return Ok(0);
+            }
+            let Some((offset, len)) = storage_span(state, offset, len) else {
+                return Ok(0);
+            };
+            state.storage[offset..offset + len].copy_from_slice(&bytes[..len]);
+            Ok(len as i32)
+        },
+    )?;
+    linker.func_wrap("storage", "storage_size", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(STORAGE_READ) {
+            state.storage.len() as i32
+        } else {
+            0
+        }
+    })?;
+    linker.func_wrap("storage", "storage_clear", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if state.check(STORAGE_WRITE) {
+            state.storage.fill(0);
+        }
+    })?;
+    Ok(())
+}
+
+fn define_system(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap("system", "get_time_nanos", |caller: Ctx<'_>| {
+        caller.data().time_nanos
+    })?;
+    linker.func_wrap(
+        "system",
+        "has_permission",
+        |caller: Ctx<'_>, permission: i32| {
+            i32::from(caller.data().permissions.contains(&permission))
+        },
+    )?;
+    linker.func_wrap(
+        "system",
+        "get_platform_name",
+        |mut caller: Ctx<'_>, ptr: i32| -> Result<i32, Error> {
+            let name = b"headless";
+            write_bytes(&mut caller, ptr, name)?;
+            Ok(name.len() as i32)
+        },
+    )?;
+    Ok(())
+}
+
+fn define_camera(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "gpu",
+        "get_camera_transform",
+        |mut caller: Ctx<'_>, ptr: i32| -> Result<i32, Error> {
+            let state = caller.data_mut();
+            if !state.check(GPU) {
+                return Ok(0);
+            }
+            let camera = state.gpu.camera;
+            let bytes: Vec<u8> = [camera.x, camera.y, camera.z, camera.yaw, camera.pitch]
+                .iter()
+                .flat_map(|f| f.to_le_bytes())
+                .collect();
+            write_bytes(&mut caller, ptr, &bytes)?;
+            Ok(1)
+        },
+    )?;
+    linker.func_wrap(
+        "gpu",
+        "set_camera_transform",
+        |mut caller: Ctx<'_>, x: f32, y: f32, z: f32, yaw: f32, pitch: f32| {
+            let state = caller.data_mut();
+            if !state.check(GPU) {
+                return 0;
+            }
+            state.gpu.camera = CameraTransform {
+                x,
+                y,
+                z,
+                yaw,
+                pitch,
+            };
+            1
+        },
+    )?;
+    linker.func_wrap("gpu", "get_camera_fov", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if !state.check(GPU) {
+            return 0.0;
+        }
+        state.gpu.fov
+    })?;
+    linker.func_wrap("gpu", "set_camera_fov", |mut caller: Ctx<'_>, fov: f32| {
+        let state = caller.data_mut();
+        if !state.check(GPU) {
+            return 0;
+        }
+        state.gpu.fov = fov;
+        1
+    })?;
+    linker.func_wrap("gpu", "get_camera_roll", |mut caller: Ctx<'_>| {
+        let state = caller.data_mut();
+        if !state.check(GPU) {
+            return 0.0;
+        }
+        state.gpu.roll
+    })?;
+    linker.func_wrap(
+        "gpu",
+        "set_camera_roll",
+        |mut caller: Ctx<'_>, roll: f32| {
+            let state = caller.data_mut();
+            if !state.check(GPU) {
+                return 0;
+            }
+            state.gpu.roll = roll;
+            1
+        },
+    )?;
+    Ok(())
+}
+
+fn define_gpu(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    linker.func_wrap(
+        "gpu",
+        "submit_gpu_commands",
+        |mut caller: Ctx<'_>, ptr: i32, count: i32| -> Result<i32, Error> {
+            let commands = read_bytes(&caller, ptr, count)?;
+            let state = caller.data_mut();
+            if !state.check(GPU) {
+                return Ok(0);
+            }
+            state.gpu_submissions += 1;
+            // Commands run right away, so `defer_gpu` has nothing to wait for.
+            if let Err(e) = state.gpu.execute(&commands, &mut state.gpu_surface) {
+                eprintln!("[{}] malformed GPU command stream: {e}", state.name);
+            }
+            Ok(1)
+        },
+    )?;
+    linker.func_wrap("gpu", "defer_gpu", |_caller: Ctx<'_>| {})?;
+    linker.func_wrap(
+        "gpu",
+        "gpu_read",
+        |mut caller: Ctx<'_>, offset: i32, ptr: i32, len: i32| -> Result<i32, Error> {
+            let state = caller.data_mut();
+            if !state.check(GPU) {
+                return Ok(0);
+            }
+            let memory = state.gpu.memory();
+            let offset = (offset.max(0) as usize).min(memory.len());
+            let len = (len.max(0) as usize).min(memory.len() - offset);
+            let bytes = memory[offset..offset + len].to_vec();
+            write_bytes(&mut caller, ptr, &bytes)?;
+            Ok(len as i32)
+        },
+    )?;
+    Ok(())
+}
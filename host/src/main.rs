@@ -0,0 +1,313 @@
+//! A headless reference host, used to run Gooseboy crates outside of Minecraft.
+//!
+//! Example:
+//! ```sh
+//! cargo host examples/tests --wasm target/wasm32-unknown-unknown/release/tests.wasm \
+//!     --frames 120 --input input.txt --screenshot tests.png
+//! ```
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use gooseboy::camera::CameraTransform;
+
+use crate::{manifest::Manifest, runner::Runner, state::HostState};
+
+mod imports;
+mod manifest;
+mod runner;
+mod script;
+mod state;
+
+const USAGE: &str = "usage: gooseboy_host <crate.json or crate folder> [options]
+    --wasm <path>          use this module instead of the manifest's entrypoint
+    --frames <n>           amount of frames to run (default: 60)
+    --fps <n>              simulated frames per second (default: 60)
+    --size <w>x<h>         framebuffer size (default: 320x240)
+    --input <path>         scripted input to feed the crate
+    --dump <folder>        write frames as PNGs into this folder
+    --dump-every <n>       only write every nth frame (default: 1)
+    --screenshot <path>    write the last frame as a PNG
+    --storage <path>       load the crate storage from this file, and save it on exit
+    --storage-size <n>     crate storage size in bytes (default: 65536)
+    --min-gpu-submissions <n>
+                           fail unless the crate submitted at least this many GPU buffers
+    --camera <x>,<y>,<z>,<yaw>,<pitch>
+                           where the camera starts (default: 0,0,0,0,0)
+    --expect <path>        fail unless the last frame matches this PNG exactly
+    --quiet                don't print console output";
+
+struct Options {
+    manifest: PathBuf,
+    wasm: Option<PathBuf>,
+    frames: u64,
+    fps: u32,
+    width: usize,
+    height: usize,
+    input: Option<PathBuf>,
+    dump: Option<PathBuf>,
+    dump_every: u64,
+    screenshot: Option<PathBuf>,
+    storage: Option<PathBuf>,
+    storage_size: usize,
+    min_gpu_submissions: usize,
+    camera: CameraTransform,
+    expect: Option<PathBuf>,
+    quiet: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        fn value<T: std::str::FromStr>(
+            args: &mut impl Iterator<Item = String>,
+            flag: &str,
+        ) -> Result<T, String> {
+            args.next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| format!("{flag} expects a valid value"))
+        }
+
+        let mut manifest = None;
+        let mut options = Self {
+            manifest: PathBuf::new(),
+            wasm: None,
+            frames: 60,
+            fps: 60,
+            width: 320,
+            height: 240,
+            input: None,
+            dump: None,
+            dump_every: 1,
+            screenshot: None,
+            storage: None,
+            storage_size: 65536,
+            min_gpu_submissions: 0,
+            camera: CameraTransform::default(),
+            expect: None,
+            quiet: false,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--wasm" => options.wasm = Some(value(&mut args, &arg)?),
+                "--frames" => options.frames = value(&mut args, &arg)?,
+                "--fps" => options.fps = value(&mut args, &arg)?,
+                "--size" => {
+                    let size: String = value(&mut args, &arg)?;
+                    let (w, h) = size
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .ok_or("--size expects <w>x<h>")?;
+                    options.width = w;
+                    options.height = h;
+                }
+                "--input" => options.input = Some(value(&mut args, &arg)?),
+                "--dump" => options.dump = Some(value(&mut args, &arg)?),
+                "--dump-every" => options.dump_every = value::<u64>(&mut args, &arg)?.max(1),
+                "--screenshot" => options.screenshot = Some(value(&mut args, &arg)?),
+                "--storage" => options.storage = Some(value(&mut args, &arg)?),
+                "--storage-size" => options.storage_size = value(&mut args, &arg)?,
+                "--min-gpu-submissions" => options.min_gpu_submissions = value(&mut args, &arg)?,
+                "--camera" => {
+                    let camera: String = value(&mut args, &arg)?;
+                    let parts: Vec<f32> = camera
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| "--camera expects <x>,<y>,<z>,<yaw>,<pitch>")?;
+                    let [x, y, z, yaw, pitch] = parts[..] else {
+                        return Err("--camera expects <x>,<y>,<z>,<yaw>,<pitch>".to_owned());
+                    };
+                    options.camera = CameraTransform {
+                        x,
+                        y,
+                        z,
+                        yaw,
+                        pitch,
+                    };
+                }
+                "--expect" => options.expect = Some(value(&mut args, &arg)?),
+                "--quiet" => options.quiet = true,
+                "-h" | "--help" => return Err(USAGE.to_owned()),
+                _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`")),
+                _ => manifest = Some(PathBuf::from(arg)),
+            }
+        }
+
+        let manifest = manifest.ok_or(USAGE)?;
+        options.manifest = if manifest.is_dir() {
+            manifest.join("crate.json")
+        } else {
+            manifest
+        };
+        Ok(options)
+    }
+}
+
+fn write_png(path: &Path, width: usize, height: usize, rgba: &[u8]) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        u32::try_from(width).map_err(|e| e.to_string())?,
+        u32::try_from(height).map_err(|e| e.to_string())?,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut w| w.write_image_data(rgba))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+fn read_png(path: &Path) -> Result<(usize, usize, Vec<u8>), String> {
+    let file = File::open(path).map_err(|e| format!("failed to open {}: {e}", path.display()))?;
+    let mut reader = png::Decoder::new(BufReader::new(file))
+        .read_info()
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let mut rgba = vec![0; reader.output_buffer_size().unwrap_or_default()];
+    let info = reader
+        .next_frame(&mut rgba)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err(format!("{} is not an 8-bit RGBA PNG", path.display()));
+    }
+    rgba.truncate(info.buffer_size());
+    Ok((info.width as usize, info.height as usize, rgba))
+}
+
+fn compare_png(path: &Path, width: usize, height: usize, rgba: &[u8]) -> Result<(), String> {
+    let (expected_width, expected_height, expected) = read_png(path)?;
+    if (expected_width, expected_height) != (width, height) {
+        return Err(format!(
+            "{} is {expected_width}x{expected_height}, but the frame is {width}x{height}",
+            path.display()
+        ));
+    }
+
+    let mismatched = rgba
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .zip(expected.as_chunks::<4>().0)
+        .filter(|(a, b)| a != b)
+        .count();
+    if mismatched > 0 {
+        return Err(format!(
+            "{mismatched} pixels of the last frame differ from {}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let manifest = Manifest::load(&options.manifest)?;
+    let wasm_path = options.wasm.clone().unwrap_or_else(|| {
+        options
+            .manifest
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&manifest.entrypoint)
+    });
+    let wasm =
+        fs::read(&wasm_path).map_err(|e| format!("failed to read {}: {e}", wasm_path.display()))?;
+
+    let mut storage = vec![0; options.storage_size];
+    if let Some(path) = &options.storage
+        && let Ok(saved) = fs::read(path)
+    {
+        let len = saved.len().min(storage.len());
+        storage[..len].copy_from_slice(&saved[..len]);
+    }
+
+    let events = match &options.input {
+        Some(path) => script::load(path)?,
+        None => Vec::new(),
+    };
+
+    let mut state = HostState::new(
+        manifest.name.clone(),
+        manifest.permission_codes()?,
+        options.width,
+        options.height,
+        storage,
+    );
+    state.quiet = options.quiet;
+    state.gpu.camera = options.camera;
+
+    if let Some(dir) = &options.dump {
+        fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+    }
+
+    let mut runner = Runner::new(&wasm, state)?;
+    runner.start()?;
+
+    let frame_duration = Duration::from_secs(1) / options.fps.max(1);
+    let mut events = events.iter().peekable();
+
+    for frame in 0..options.frames {
+        while let Some(event) = events.next_if(|e| e.frame <= frame) {
+            runner.state_mut().input.apply(&event.action);
+        }
+
+        runner.frame(frame_duration * u32::try_from(frame).unwrap_or(u32::MAX))?;
+
+        if let Some(dir) = &options.dump
+            && frame % options.dump_every == 0
+            && let Some(rgba) = runner.framebuffer()
+        {
+            write_png(
+                &dir.join(format!("frame_{frame:05}.png")),
+                options.width,
+                options.height,
+                &rgba,
+            )?;
+        }
+    }
+
+    if let Some(path) = &options.screenshot {
+        let rgba = runner
+            .framebuffer()
+            .ok_or("crate does not export get_framebuffer_ptr")?;
+        write_png(path, options.width, options.height, &rgba)?;
+    }
+
+    if let Some(path) = &options.storage {
+        fs::write(path, &runner.state().storage)
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    }
+
+    println!(
+        "[{}] ran {} frames, {} GPU submissions",
+        manifest.name,
+        options.frames,
+        runner.state().gpu_submissions
+    );
+    if runner.state().gpu_submissions < options.min_gpu_submissions {
+        return Err(format!(
+            "expected at least {} GPU submissions",
+            options.min_gpu_submissions
+        ));
+    }
+
+    if let Some(path) = &options.expect {
+        let rgba = runner
+            .framebuffer()
+            .ok_or("crate does not export get_framebuffer_ptr")?;
+        compare_png(path, options.width, options.height, &rgba)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = Options::parse(std::env::args().skip(1)).and_then(|options| run(&options));
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
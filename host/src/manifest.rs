@@ -0,0 +1,97 @@
+//! Used to parse a crate's `crate.json` manifest.
+use std::{collections::HashSet, fs, path::Path};
+
+use gooseboy::system::Permission;
+use serde::Deserialize;
+
+/// The only manifest version this host understands.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// A crate manifest, as written in `crate.json`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// The manifest version.
+    pub version: u32,
+    /// The display name of the crate.
+    pub name: String,
+    /// The path of the WASM module, relative to the manifest.
+    pub entrypoint: String,
+    /// The permissions requested by the crate.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl Manifest {
+    /// Reads a manifest from a `crate.json` file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be read, isn't a valid manifest
+    /// or has an unsupported version.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let manifest: Self = serde_json::from_str(&text)
+            .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+
+        if manifest.version != MANIFEST_VERSION {
+            return Err(format!(
+                "{}: unsupported manifest version {}",
+                path.display(),
+                manifest.version
+            ));
+        }
+        Ok(manifest)
+    }
+
+    /// Returns the granted permissions as `Permission` codes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a permission name is unknown.
+    pub fn permission_codes(&self) -> Result<HashSet<i32>, String> {
+        self.permissions
+            .iter()
+            .map(|name| {
+                permission_from_name(name)
+                    .map(|p| p as i32)
+                    .ok_or_else(|| format!("unknown permission `{name}`"))
+            })
+            .collect()
+    }
+}
+
+/// Returns a permission from its' manifest name.
+#[must_use]
+pub fn permission_from_name(name: &str) -> Option<Permission> {
+    match name {
+        "CONSOLE" => Some(Permission::Console),
+        "AUDIO" => Some(Permission::Audio),
+        "INPUT_KEYBOARD" => Some(Permission::InputKeyboard),
+        "INPUT_MOUSE" => Some(Permission::InputMouse),
+        "INPUT_MOUSE_POS" => Some(Permission::InputMousePos),
+        "INPUT_GRAB_MOUSE" => Some(Permission::InputGrabMouse),
+        "STORAGE_READ" => Some(Permission::StorageRead),
+        "STORAGE_WRITE" => Some(Permission::StorageWrite),
+        "GPU" => Some(Permission::Gpu),
+        _ => None,
+    }
+}
+
+/// Returns the manifest name of a permission code.
+#[must_use]
+pub const fn permission_name(code: i32) -> &'static str {
+    match code {
+        0 => "CONSOLE",
+        1 => "AUDIO",
+        2 => "INPUT_KEYBOARD",
+        3 => "INPUT_MOUSE",
+        4 => "INPUT_MOUSE_POS",
+        5 => "INPUT_GRAB_MOUSE",
+        6 => "STORAGE_READ",
+        7 => "STORAGE_WRITE",
+        8 => "GPU",
+        _ => "UNKNOWN",
+    }
+}
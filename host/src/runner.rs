@@ -0,0 +1,133 @@
+//! Used to instantiate a crate and drive its exported entrypoints.
+use std::time::Duration;
+
+use gooseboy::color::Color;
+use wasmi::{Engine, Instance, Linker, Module, Store, TypedFunc};
+
+use crate::{imports, state::HostState};
+
+/// A running crate instance.
+pub struct Runner {
+    store: Store<HostState>,
+    instance: Instance,
+    update: Option<TypedFunc<i64, ()>>,
+    framebuffer_ptr: Option<u32>,
+}
+
+impl Runner {
+    /// Instantiates the `wasm` module with the following host `state`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the module is invalid or imports something unknown.
+    pub fn new(wasm: &[u8], state: HostState) -> Result<Self, String> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm).map_err(|e| format!("invalid module: {e}"))?;
+        let mut store = Store::new(&engine, state);
+        let mut linker = Linker::new(&engine);
+        imports::define(&mut linker).map_err(|e| format!("failed to define imports: {e}"))?;
+
+        let instance = linker
+            .instantiate_and_start(&mut store, &module)
+            .map_err(|e| format!("failed to instantiate: {e}"))?;
+        let update = instance.get_typed_func(&store, "update").ok();
+
+        Ok(Self {
+            store,
+            instance,
+            update,
+            framebuffer_ptr: None,
+        })
+    }
+
+    /// Calls `main`, grabs the framebuffer pointer and then calls `gpu_main`, like the mod does.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the crate traps.
+    pub fn start(&mut self) -> Result<(), String> {
+        self.call_optional("main")?;
+
+        if let Ok(func) = self
+            .instance
+            .get_typed_func::<(), i32>(&self.store, "get_framebuffer_ptr")
+        {
+            let ptr = func
+                .call(&mut self.store, ())
+                .map_err(|e| format!("get_framebuffer_ptr trapped: {e}"))?;
+            self.framebuffer_ptr = Some(ptr.cast_unsigned());
+        }
+
+        self.call_optional("gpu_main")
+    }
+
+    /// Runs a single frame at `time` since the crate started.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the crate traps.
+    pub fn frame(&mut self, time: Duration) -> Result<(), String> {
+        let nano_time = i64::try_from(time.as_nanos()).unwrap_or(i64::MAX);
+        let state = self.store.data_mut();
+        state.time_nanos = nano_time;
+        state.gpu.begin_frame(&mut state.gpu_surface, Color::BLACK);
+
+        if let Some(update) = &self.update {
+            update
+                .call(&mut self.store, nano_time)
+                .map_err(|e| format!("update trapped: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the host state.
+    #[must_use]
+    pub fn state(&self) -> &HostState {
+        self.store.data()
+    }
+
+    /// Returns the host state as a mutable reference.
+    pub fn state_mut(&mut self) -> &mut HostState {
+        self.store.data_mut()
+    }
+
+    /// Returns a copy of the crate's RGBA framebuffer, or `None` if the crate doesn't export one.
+    /// If the crate used the `GooseGPU`, the framebuffer is composited over what it drew.
+    #[must_use]
+    pub fn framebuffer(&self) -> Option<Vec<u8>> {
+        let ptr = self.framebuffer_ptr? as usize;
+        let state = self.store.data();
+        let len = state.width * state.height * 4;
+        let memory = self.instance.get_memory(&self.store, "memory")?;
+        let framebuffer = memory.data(&self.store).get(ptr..ptr + len)?;
+
+        if state.gpu_submissions == 0 {
+            return Some(framebuffer.to_vec());
+        }
+
+        let mut rgba = state.gpu_surface.rgba.clone();
+        for (dest, src) in rgba
+            .as_chunks_mut::<4>()
+            .0
+            .iter_mut()
+            .zip(framebuffer.as_chunks::<4>().0)
+        {
+            let alpha = u32::from(src[3]);
+            for c in 0..3 {
+                let blended =
+                    (u32::from(src[c]) * alpha + u32::from(dest[c]) * (255 - alpha)) / 255;
+                dest[c] = u8::try_from(blended).unwrap_or(u8::MAX);
+            }
+            dest[3] = 255;
+        }
+        Some(rgba)
+    }
+
+    fn call_optional(&mut self, name: &str) -> Result<(), String> {
+        if let Ok(func) = self.instance.get_typed_func::<(), ()>(&self.store, name) {
+            func.call(&mut self.store, ())
+                .map_err(|e| format!("{name} trapped: {e}"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! Used to parse scripted input, fed to the crate frame by frame.
+//!
+//! Every non-empty line is `<frame> <action> [args...]`, with `#` starting a comment:
+//! ```text
+//! # hold W for a second
+//! 0 key_down 87
+//! 60 key_up 87
+//! 10 mouse_move 40 20
+//! 10 mouse_down 0
+//! 11 mouse_up 0
+//! 20 mouse_delta 12.5 -3
+//! ```
+use std::{fs, path::Path};
+
+/// A single input action.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Holds a key down.
+    KeyDown(i32),
+    /// Releases a key.
+    KeyUp(i32),
+    /// Holds a mouse button down.
+    MouseDown(i32),
+    /// Releases a mouse button.
+    MouseUp(i32),
+    /// Moves the mouse to a position.
+    MouseMove(i32, i32),
+    /// Adds to the mouse accumulated delta.
+    MouseDelta(f64, f64),
+}
+
+/// An input action scheduled at a frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptEvent {
+    /// The frame the action runs before.
+    pub frame: u64,
+    /// The action.
+    pub action: Action,
+}
+
+/// Parses an input script.
+///
+/// # Errors
+///
+/// This function will return an error with the line number if a line is malformed.
+pub fn parse(text: &str) -> Result<Vec<ScriptEvent>, String> {
+    fn arg<T: std::str::FromStr>(parts: &[&str], i: usize, line: usize) -> Result<T, String> {
+        parts
+            .get(i)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("line {line}: missing or invalid argument {i}"))
+    }
+
+    let mut events = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let frame = arg(&parts, 0, line_no)?;
+        let action = match parts.get(1).copied() {
+            Some("key_down") => Action::KeyDown(arg(&parts, 2, line_no)?),
+            Some("key_up") => Action::KeyUp(arg(&parts, 2, line_no)?),
+            Some("mouse_down") => Action::MouseDown(arg(&parts, 2, line_no)?),
+            Some("mouse_up") => Action::MouseUp(arg(&parts, 2, line_no)?),
+            Some("mouse_move") => {
+                Action::MouseMove(arg(&parts, 2, line_no)?, arg(&parts, 3, line_no)?)
+            }
+            Some("mouse_delta") => {
+                Action::MouseDelta(arg(&parts, 2, line_no)?, arg(&parts, 3, line_no)?)
+            }
+            Some(other) => return Err(format!("line {line_no}: unknown action `{other}`")),
+            None => return Err(format!("line {line_no}: missing action")),
+        };
+
+        events.push(ScriptEvent { frame, action });
+    }
+
+    events.sort_by_key(|e| e.frame);
+    Ok(events)
+}
+
+/// Reads and parses an input script file.
+///
+/// # Errors
+///
+/// This function will return an error if the file can't be read or is malformed.
+pub fn load(path: &Path) -> Result<Vec<ScriptEvent>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
@@ -0,0 +1,138 @@
+//! Used to hold the host state shared by every import module.
+use std::collections::HashSet;
+
+use gooseboy::{framebuffer::Surface, gpu::software::SoftwareGpu};
+
+use crate::{manifest::permission_name, script::Action};
+
+/// The maximum amount of audio instances that can play at once.
+pub const MAX_PLAYING_AUDIO: usize = 32;
+
+/// The keyboard and mouse state.
+#[derive(Default)]
+pub struct InputState {
+    /// The keys currently held down, in the order they were pressed.
+    pub keys: Vec<i32>,
+    /// The mouse buttons currently held down.
+    pub mouse_buttons: HashSet<i32>,
+    /// The mouse X position.
+    pub mouse_x: i32,
+    /// The mouse Y position.
+    pub mouse_y: i32,
+    /// The mouse accumulated delta X.
+    pub mouse_accumulated_dx: f64,
+    /// The mouse accumulated delta Y.
+    pub mouse_accumulated_dy: f64,
+    /// Is the mouse grabbed?
+    pub mouse_grabbed: bool,
+}
+
+impl InputState {
+    /// Applies a scripted input action.
+    pub fn apply(&mut self, action: &Action) {
+        match *action {
+            Action::KeyDown(key) => {
+                if !self.keys.contains(&key) {
+                    self.keys.push(key);
+                }
+            }
+            Action::KeyUp(key) => self.keys.retain(|&k| k != key),
+            Action::MouseDown(button) => {
+                self.mouse_buttons.insert(button);
+            }
+            Action::MouseUp(button) => {
+                self.mouse_buttons.remove(&button);
+            }
+            Action::MouseMove(x, y) => {
+                self.mouse_x = x;
+                self.mouse_y = y;
+            }
+            Action::MouseDelta(dx, dy) => {
+                self.mouse_accumulated_dx += dx;
+                self.mouse_accumulated_dy += dy;
+            }
+        }
+    }
+
+    /// Returns the most recently pressed key that is still held down, or -1 if none.
+    #[must_use]
+    pub fn key_code(&self) -> i32 {
+        self.keys.last().copied().unwrap_or(-1)
+    }
+}
+
+/// The state of the host, stored inside the WASM store.
+pub struct HostState {
+    /// The crate name, used to prefix console output.
+    pub name: String,
+    /// The granted permission codes.
+    pub permissions: HashSet<i32>,
+    /// The framebuffer width.
+    pub width: usize,
+    /// The framebuffer height.
+    pub height: usize,
+    /// The crate storage.
+    pub storage: Vec<u8>,
+    /// The input state.
+    pub input: InputState,
+    /// The audio instances that are currently playing.
+    pub playing_audio: HashSet<i64>,
+    /// The next audio instance id.
+    pub next_audio_id: i64,
+    /// The simulated time in nanoseconds.
+    pub time_nanos: i64,
+    /// The amount of GPU command streams submitted.
+    pub gpu_submissions: usize,
+    /// The software `GooseGPU`, which also holds the camera transform.
+    pub gpu: SoftwareGpu,
+    /// The surface the `GooseGPU` draws onto, the framebuffer is composited over it.
+    pub gpu_surface: Surface,
+    /// Should console output be printed?
+    pub quiet: bool,
+    denied: HashSet<i32>,
+}
+
+impl HostState {
+    /// Creates a new [`HostState`].
+    #[must_use]
+    pub fn new(
+        name: String,
+        permissions: HashSet<i32>,
+        width: usize,
+        height: usize,
+        storage: Vec<u8>,
+    ) -> Self {
+        Self {
+            name,
+            permissions,
+            width,
+            height,
+            storage,
+            input: InputState::default(),
+            playing_audio: HashSet::new(),
+            next_audio_id: 0,
+            time_nanos: 0,
+            gpu_submissions: 0,
+            gpu: SoftwareGpu::new(),
+            gpu_surface: Surface::new_empty(width, height),
+            quiet: false,
+            denied: HashSet::new(),
+        }
+    }
+
+    /// Returns true if the crate has `permission`, warning once per permission if it doesn't.
+    pub fn check(&mut self, permission: i32) -> bool {
+        if self.permissions.contains(&permission) {
+            return true;
+        }
+
+        if self.denied.insert(permission) {
+            eprintln!(
+                "[{}] denied: crate.json does not request {}",
+                self.name,
+                permission_name(permission)
+            );
+        }
+        false
+    }
+}
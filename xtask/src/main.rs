@@ -9,16 +9,34 @@ fn main() {
 
     if project == "all" {
         build_all();
+    } else if project == "smoke" {
+        smoke_all();
     } else {
         do_project(&project);
     }
 }
 
-fn build_all() {
+/// Examples whose last smoke frame is compared against `examples/<name>/golden.png`,
+/// with the extra host arguments that put something worth comparing on screen.
+const GOLDEN: &[(&str, &[&str])] = &[
+    (
+        "goosegpu",
+        &[
+            "--camera",
+            "5,3,14,0,-0.2",
+            "--input",
+            "examples/goosegpu/golden.txt",
+        ],
+    ),
+    ("bsprenderer", &["--camera", "480,-352,88,0,1.2"]),
+];
+
+fn example_names() -> Vec<String> {
     let entries = fs::read_dir("examples").unwrap_or_else(|_| {
         eprintln!("failed to read examples directory");
         std::process::exit(1);
     });
+    let mut names = Vec::new();
 
     for entry in entries {
         let entry = match entry {
@@ -42,10 +60,65 @@ fn build_all() {
             continue;
         }
 
+        names.push(name);
+    }
+
+    names.sort();
+    names
+}
+
+fn build_all() {
+    for name in example_names() {
         do_project(&name);
     }
 }
 
+fn smoke_all() {
+    let is_release = std::env::args().any(|a| a == "--release");
+    let bless = std::env::args().any(|a| a == "--bless");
+    let profile = if is_release { "release" } else { "debug" };
+    let out_dir = format!("target/smoke/{profile}");
+    fs::create_dir_all(&out_dir).expect("failed to create the smoke output directory");
+
+    for name in example_names() {
+        let wasm = format!("target/wasm32-unknown-unknown/{profile}/{name}.wasm");
+        assert!(
+            fs::exists(&wasm).unwrap_or(false),
+            "{wasm} was not built, build every example first"
+        );
+
+        let mut cmd = Command::new("cargo");
+        cmd.args(["run", "-p", "gooseboy_host", "--release", "--"]);
+        cmd.arg(format!("examples/{name}"));
+        cmd.args(["--wasm", &wasm, "--frames", "60", "--quiet"]);
+        if uses_gpu(&name) {
+            cmd.args(["--min-gpu-submissions", "1"]);
+        }
+
+        let golden = GOLDEN.iter().find(|(golden, _)| *golden == name);
+        let golden_path = format!("examples/{name}/golden.png");
+        if let Some((_, args)) = golden {
+            cmd.args(*args);
+        }
+        if golden.is_some() && bless {
+            cmd.args(["--screenshot", &golden_path]);
+        } else {
+            cmd.args(["--screenshot", &format!("{out_dir}/{name}.png")]);
+            if golden.is_some() {
+                cmd.args(["--expect", &golden_path]);
+            }
+        }
+
+        let status = cmd.status().expect("failed to run the headless host");
+        assert!(status.success(), "smoke test failed for {name}");
+    }
+}
+
+fn uses_gpu(name: &str) -> bool {
+    fs::read_to_string(format!("examples/{name}/crate.json"))
+        .is_ok_and(|manifest| manifest.contains("\"GPU\""))
+}
+
 fn do_project(project: &str) {
     let is_release = std::env::args().any(|a| a == "--release");
     let should_copy = !std::env::args().any(|a| a == "--no-copy");